@@ -0,0 +1,275 @@
+// Command line interface of the backend binary. Running it without a
+// subcommand starts the web server, as before. The global flags override the
+// matching environment variables (which may also come from `.env`).
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use frontend::fixtures::{DEFAULT_FIXTURE_SET, FIXTURE_SETS};
+use frontend::synthetic::SyntheticConfig;
+
+#[derive(Debug, Parser)]
+#[command(name = "backend", version, about = "Tech graph web server and database tools")]
+#[command(after_help = EXIT_CODES_HELP)]
+pub struct Cli {
+    /// Database to use (sqlite:... or postgres://...)
+    #[arg(long, global = true, env = "DATABASE_URL")]
+    pub database_url: Option<String>,
+
+    /// Address the server listens on [default: site-addr from Cargo.toml or LEPTOS_SITE_ADDR]
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub bind: Option<SocketAddr>,
+
+    /// Environment; DEV seeds demo items on startup
+    #[arg(long, global = true, env = "LEPTOS_ENV", ignore_case = true, default_value = "prod")]
+    pub env: AppEnv,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand, PartialEq)]
+pub enum Command {
+    /// Start the web server (the default)
+    Serve,
+    /// Apply pending database migrations
+    Migrate {
+        #[command(subcommand)]
+        action: Option<MigrateAction>,
+    },
+    /// Apply a fixture set (items and graph rows); rows already present are left alone
+    Seed {
+        /// Named fixture set compiled into the binary
+        #[arg(long, default_value = DEFAULT_FIXTURE_SET, value_parser = PossibleValuesParser::new(FIXTURE_SETS))]
+        fixture: String,
+        /// A YAML or JSON fixture file to apply instead of a named set
+        #[arg(long, conflicts_with = "fixture")]
+        file: Option<PathBuf>,
+    },
+    /// Replace the stored graph with a dataset and record it as a new graph version
+    Import {
+        /// A JSON file as written by `export`, or a directory with books.csv, technologies.csv and book_tech_links.csv
+        path: PathBuf,
+        /// Label of the graph version created for the import
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Write the stored graph to a JSON file that `import` accepts
+    Export {
+        path: PathBuf,
+    },
+    /// Check a dataset (or, without a path, the stored graph) for dangling links and duplicates
+    Validate {
+        /// A JSON file or CSV directory, as accepted by `import`
+        path: Option<PathBuf>,
+    },
+    /// Generate a synthetic dataset for load testing, as CSV files or straight into the database
+    Generate(GenerateArgs),
+    /// Show row counts for the main tables
+    Stats,
+    /// Print a shell completion script
+    Completions {
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Debug, Args, PartialEq)]
+pub struct GenerateArgs {
+    /// Number of books
+    #[arg(long, default_value_t = 10_000)]
+    pub books: usize,
+    /// Number of technologies
+    #[arg(long, default_value_t = 2_000)]
+    pub technologies: usize,
+    /// Number of technology categories
+    #[arg(long, default_value_t = 8)]
+    pub categories: usize,
+    /// Mean number of technologies per book
+    #[arg(long, default_value_t = 4.0)]
+    pub links_per_book: f64,
+    /// Power-law exponent of the link degree distribution; lower is more skewed
+    #[arg(long, default_value_t = 2.5)]
+    pub exponent: f64,
+    /// Random seed; the same seed and sizes give the same dataset
+    #[arg(long, default_value_t = 42)]
+    pub seed: u64,
+    /// Directory to write books.csv, technologies.csv and book_tech_links.csv to
+    #[arg(long, value_name = "DIR", required_unless_present = "into_db", conflicts_with = "into_db")]
+    pub out: Option<PathBuf>,
+    /// Replace the stored graph with the generated one and record it as a new graph version
+    #[arg(long)]
+    pub into_db: bool,
+    /// Label of the graph version created by --into-db
+    #[arg(long, conflicts_with = "out")]
+    pub label: Option<String>,
+}
+
+impl GenerateArgs {
+    pub fn config(&self) -> SyntheticConfig {
+        SyntheticConfig {
+            books: self.books,
+            technologies: self.technologies,
+            categories: self.categories,
+            links_per_book: self.links_per_book,
+            exponent: self.exponent,
+            seed: self.seed,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand, PartialEq, Eq)]
+pub enum MigrateAction {
+    /// List the migrations and whether each has been applied
+    Status,
+    /// Revert the most recently applied migrations using their down scripts
+    Rollback {
+        /// How many migrations to revert
+        #[arg(default_value_t = 1)]
+        steps: usize,
+    },
+    /// Create an empty up/down migration pair for SQLite and PostgreSQL
+    New {
+        /// What the migration does, e.g. add_book_year
+        name: String,
+        /// The frontend crate, which holds migrations/ and migrations_postgres/
+        #[arg(long, default_value = DEFAULT_MIGRATIONS_CRATE)]
+        dir: PathBuf,
+    },
+}
+
+// Migrations are embedded from the frontend crate at compile time; new ones
+// are written to the source tree this binary was built from.
+const DEFAULT_MIGRATIONS_CRATE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../frontend");
+
+// The values LEPTOS_ENV already used, accepted in any case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AppEnv {
+    #[value(alias = "development")]
+    Dev,
+    #[value(alias = "production")]
+    Prod,
+}
+
+impl AppEnv {
+    pub fn as_env_value(self) -> &'static str {
+        match self {
+            AppEnv::Dev => "DEV",
+            AppEnv::Prod => "PROD",
+        }
+    }
+}
+
+// Exit codes shared by every command, so scripts can tell failures apart.
+// Usage errors (2) are reported by clap itself.
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_DATABASE: u8 = 3;
+pub const EXIT_INVALID_DATA: u8 = 4;
+pub const EXIT_IO: u8 = 5;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  success
+  1  the command failed
+  2  invalid arguments
+  3  database unreachable, migrations failed, or the schema is newer than this binary
+  4  dataset failed validation
+  5  file could not be read or written";
+
+// Why a command failed; decides the exit code.
+#[derive(Debug)]
+pub enum CommandError {
+    Failed(String),
+    Database(String),
+    InvalidData(Vec<String>),
+    Io(String),
+}
+
+impl CommandError {
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            CommandError::Failed(_) => EXIT_FAILURE,
+            CommandError::Database(_) => EXIT_DATABASE,
+            CommandError::InvalidData(_) => EXIT_INVALID_DATA,
+            CommandError::Io(_) => EXIT_IO,
+        })
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Failed(msg) | CommandError::Database(msg) | CommandError::Io(msg) => f.write_str(msg),
+            CommandError::InvalidData(problems) => {
+                write!(f, "dataset has {} problem(s):", problems.len())?;
+                for problem in problems {
+                    write!(f, "\n   - {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<frontend::errors::AppError> for CommandError {
+    fn from(err: frontend::errors::AppError) -> Self {
+        CommandError::Failed(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("backend").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_subcommands_and_global_flags() {
+        let cli = parse(&[]).unwrap();
+        assert_eq!(cli.command, None, "no subcommand means serve");
+
+        let cli = parse(&["migrate", "status", "--database-url", "sqlite::memory:"]).unwrap();
+        assert_eq!(cli.command, Some(Command::Migrate { action: Some(MigrateAction::Status) }));
+        assert_eq!(cli.database_url.as_deref(), Some("sqlite::memory:"));
+
+        let cli = parse(&["--env", "DEV", "--bind", "0.0.0.0:8080", "serve"]).unwrap();
+        assert_eq!(cli.env, AppEnv::Dev);
+        assert_eq!(cli.bind, Some("0.0.0.0:8080".parse().unwrap()));
+
+        assert_eq!(
+            parse(&["seed", "--fixture", "large"]).unwrap().command,
+            Some(Command::Seed { fixture: "large".to_string(), file: None })
+        );
+        let Some(Command::Generate(args)) = parse(&["generate", "--books", "50000", "--out", "data"]).unwrap().command else {
+            panic!("expected generate");
+        };
+        assert_eq!(args.config(), SyntheticConfig { books: 50_000, ..Default::default() });
+        assert_eq!(args.out, Some(PathBuf::from("data")));
+        assert_eq!(
+            parse(&["migrate", "rollback"]).unwrap().command,
+            Some(Command::Migrate { action: Some(MigrateAction::Rollback { steps: 1 }) })
+        );
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        // The old `--seed` flag is gone; clap exits with 2 on usage errors.
+        assert_eq!(parse(&["--seed"]).unwrap_err().exit_code(), 2);
+        assert!(parse(&["import"]).is_err(), "import needs a path");
+        assert!(parse(&["seed", "--fixture", "huge"]).is_err());
+        assert!(parse(&["seed", "--fixture", "test", "--file", "seed.yaml"]).is_err());
+        assert!(parse(&["--bind", "not-an-address"]).is_err());
+        assert!(parse(&["generate"]).is_err(), "generate needs --out or --into-db");
+        assert!(parse(&["generate", "--out", "data", "--into-db"]).is_err());
+        assert!(parse(&["generate", "--out", "data", "--label", "x"]).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+// The database subcommands of the backend binary (everything but `serve`).
+// Progress goes to stdout with the same emoji markers the server uses at
+// startup; failures are returned as `CommandError`s and reported by `main`.
+
+use std::path::{Path, PathBuf};
+
+use frontend::database::{DbPool, MigrationState, SYSTEM_ACTOR};
+use frontend::dataset::{graph_to_csv, parse_graph_csv, validate_graph};
+use frontend::errors::AppError;
+use frontend::fixtures::{named_fixture, parse_fixture, FixtureFormat};
+use frontend::repository::{FixtureReport, Repositories};
+use frontend::synthetic::generate_graph;
+use shared::GraphData;
+
+use crate::cli::{Cli, CommandError, GenerateArgs};
+
+pub async fn connect(database_url: Option<&str>) -> Result<DbPool, CommandError> {
+    let url = database_url.ok_or_else(|| {
+        CommandError::Database("DATABASE_URL is not set (use --database-url or put it in .env)".to_string())
+    })?;
+    frontend::database::init_pool(url)
+        .await
+        .map_err(|e| CommandError::Database(format!("failed to connect to the database: {}", e)))
+}
+
+pub async fn migrate(pool: &DbPool) -> Result<(), CommandError> {
+    println!("🔧 Running database migrations...");
+    frontend::database::run_migrations(pool)
+        .await
+        .map_err(|e| CommandError::Database(format!("database migrations failed: {}", e)))?;
+    println!("✅ Database migrations completed successfully!");
+    Ok(())
+}
+
+// Commands that write data migrate first when the binary is built with
+// DATABASE_AUTO_MIGRATE, like the server does on startup.
+pub async fn auto_migrate(pool: &DbPool) -> Result<(), CommandError> {
+    #[cfg(feature = "DATABASE_AUTO_MIGRATE")]
+    migrate(pool).await?;
+    #[cfg(not(feature = "DATABASE_AUTO_MIGRATE"))]
+    let _ = pool;
+    Ok(())
+}
+
+pub async fn migrate_status(pool: &DbPool) -> Result<(), CommandError> {
+    let migrations = frontend::database::migration_status(pool)
+        .await
+        .map_err(|e| CommandError::Database(format!("failed to read the migration history: {}", e)))?;
+    println!("📋 Migrations ({:?} database):", pool.backend());
+    for m in &migrations {
+        let marker = match m.state {
+            MigrationState::Applied => "✅",
+            MigrationState::Pending => "⏳",
+            MigrationState::Unknown => "⚠️ ",
+        };
+        println!("   {} {:04} {}", marker, m.version, m.description);
+    }
+    let count = |state| migrations.iter().filter(|m| m.state == state).count();
+    println!("   {} applied, {} pending", count(MigrationState::Applied), count(MigrationState::Pending));
+    if count(MigrationState::Unknown) > 0 {
+        println!("⚠️  The database was migrated by a newer build; this binary will not serve it.");
+    }
+    Ok(())
+}
+
+pub async fn rollback(pool: &DbPool, steps: usize) -> Result<(), CommandError> {
+    println!("⏪ Rolling back {} migration(s)...", steps);
+    let reverted = frontend::database::rollback_migrations(pool, steps)
+        .await
+        .map_err(|e| CommandError::Database(format!("rollback failed: {}", e)))?;
+    for m in &reverted {
+        println!("   ↩️  {:04} {}", m.version, m.description);
+    }
+    println!("✅ Rolled back {} migration(s).", reverted.len());
+    Ok(())
+}
+
+// The server must not run against a schema it does not know: columns it
+// never writes may be NOT NULL, and its queries may reference dropped ones.
+pub async fn ensure_schema_not_ahead(pool: &DbPool) -> Result<(), CommandError> {
+    let migrations = frontend::database::migration_status(pool)
+        .await
+        .map_err(|e| CommandError::Database(format!("failed to read the migration history: {}", e)))?;
+    let unknown: Vec<String> = migrations
+        .iter()
+        .filter(|m| m.state == MigrationState::Unknown)
+        .map(|m| format!("{:04}", m.version))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(CommandError::Database(format!(
+            "the database schema is ahead of this binary (migration {} applied by a newer build); \
+             deploy that build, or roll back with it before downgrading",
+            unknown.join(", ")
+        )));
+    }
+    let pending = migrations.iter().filter(|m| m.state == MigrationState::Pending).count();
+    if pending > 0 && !cfg!(feature = "DATABASE_AUTO_MIGRATE") {
+        println!("⚠️  {} pending migration(s); run `backend migrate` before serving.", pending);
+    }
+    Ok(())
+}
+
+// Writes `NNNN_<name>.up.sql` and `.down.sql` into both migration directories
+// under `crate_dir`, numbered after the highest existing version.
+pub fn new_migration(crate_dir: &Path, name: &str) -> Result<Vec<PathBuf>, CommandError> {
+    let name = name.trim().to_lowercase().replace([' ', '-'], "_");
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(CommandError::Failed(format!(
+            "invalid migration name `{}` (use letters, digits and underscores)",
+            name
+        )));
+    }
+    let io_err = |path: &Path, e: std::io::Error| CommandError::Io(format!("{}: {}", path.display(), e));
+
+    let dirs = [crate_dir.join("migrations"), crate_dir.join("migrations_postgres")];
+    let mut latest = 0;
+    for dir in &dirs {
+        for entry in std::fs::read_dir(dir).map_err(|e| io_err(dir, e))? {
+            let file_name = entry.map_err(|e| io_err(dir, e))?.file_name();
+            let version = file_name.to_string_lossy().split('_').next().and_then(|v| v.parse::<u32>().ok());
+            latest = latest.max(version.unwrap_or(0));
+        }
+    }
+    let stem = format!("{:04}_{}", latest + 1, name);
+
+    let mut created = Vec::new();
+    for (dir, dialect) in dirs.iter().zip(["SQLite", "PostgreSQL"]) {
+        for (suffix, header) in [
+            ("up", format!("-- {} ({})", name.replace('_', " "), dialect)),
+            ("down", format!("-- Reverts {}.up.sql ({})", stem, dialect)),
+        ] {
+            let path = dir.join(format!("{}.{}.sql", stem, suffix));
+            std::fs::write(&path, format!("{}\n", header)).map_err(|e| io_err(&path, e))?;
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+pub fn print_new_migration(crate_dir: &Path, name: &str) -> Result<(), CommandError> {
+    for path in new_migration(crate_dir, name)? {
+        println!("📝 Created {}", path.display());
+    }
+    println!("   Fill in both dialects, then rebuild: migrations are embedded at compile time.");
+    Ok(())
+}
+
+// Applies a named fixture set, or the fixture file at `file`.
+pub async fn seed(repos: &Repositories, fixture_set: &str, file: Option<&Path>) -> Result<(), CommandError> {
+    let invalid = |e: AppError| CommandError::InvalidData(vec![e.to_string()]);
+    let (name, fixture) = match file {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| CommandError::Io(format!("failed to read {}: {}", path.display(), e)))?;
+            let name = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+            (name, parse_fixture(&text, FixtureFormat::from_path(path)).map_err(invalid)?)
+        }
+        None => (fixture_set.to_string(), named_fixture(fixture_set).map_err(invalid)?),
+    };
+
+    println!("🌱 Applying fixture '{}'...", name);
+    let report = frontend::database::seed_fixture(repos, &name, &fixture).await?;
+    if report == FixtureReport::default() {
+        println!("✅ Fixture '{}' was already applied; nothing changed.", name);
+    } else {
+        println!(
+            "✅ Fixture '{}' applied: {} items added, {} books, {} technologies, {} links and {} taxonomy nodes added or updated.",
+            name, report.items, report.books, report.technologies, report.links, report.taxonomy
+        );
+    }
+    Ok(())
+}
+
+// A dataset as accepted by `import` and `validate`: the JSON written by
+// `export`, or a directory laid out like `public/data/` (where `taxonomy.csv`
+// may be missing).
+pub fn read_dataset(path: &Path) -> Result<GraphData, CommandError> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).map_err(|e| CommandError::Io(format!("failed to read {}: {}", path.display(), e)))
+    };
+    let parsed = if path.is_dir() {
+        let taxonomy = path.join("taxonomy.csv");
+        let taxonomy = if taxonomy.exists() { Some(read(&taxonomy)?) } else { None };
+        parse_graph_csv(
+            &read(&path.join("books.csv"))?,
+            &read(&path.join("technologies.csv"))?,
+            &read(&path.join("book_tech_links.csv"))?,
+            taxonomy.as_deref(),
+        )
+        .map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str::<GraphData>(&read(path)?).map_err(|e| format!("invalid JSON: {}", e))
+    };
+    parsed.map_err(|problem| CommandError::InvalidData(vec![problem]))
+}
+
+fn check(data: &GraphData) -> Result<(), CommandError> {
+    let problems = validate_graph(data);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(CommandError::InvalidData(problems))
+    }
+}
+
+fn describe(data: &GraphData) -> String {
+    format!("{} books, {} technologies, {} links", data.books.len(), data.technologies.len(), data.links.len())
+}
+
+pub async fn import(repos: &Repositories, path: &Path, label: Option<String>) -> Result<(), CommandError> {
+    println!("📥 Importing {}...", path.display());
+    let data = read_dataset(path)?;
+    check(&data)?;
+    let summary = describe(&data);
+    let label = label.unwrap_or_else(|| format!("Imported {}", path.file_name().unwrap_or(path.as_os_str()).to_string_lossy()));
+    let version = store_graph(repos, data, label).await?;
+    println!("✅ Imported {} as graph version #{}.", summary, version);
+    Ok(())
+}
+
+// Replaces the stored graph and records it as a new version, in one
+// transaction; returns its id.
+async fn store_graph(repos: &Repositories, data: GraphData, label: String) -> Result<i64, CommandError> {
+    Ok(repos.graph.replace_graph_as_version(data, label, SYSTEM_ACTOR).await?.id)
+}
+
+// Writes the CSV files of `public/data/` into `dir`, creating it if needed.
+pub fn write_csv_dir(data: &GraphData, dir: &Path) -> Result<(), CommandError> {
+    let io = |path: &Path, e: std::io::Error| CommandError::Io(format!("failed to write {}: {}", path.display(), e));
+    std::fs::create_dir_all(dir).map_err(|e| io(dir, e))?;
+    for (file, csv) in graph_to_csv(data)? {
+        let path = dir.join(file);
+        std::fs::write(&path, csv).map_err(|e| io(&path, e))?;
+    }
+    Ok(())
+}
+
+// Generates a synthetic dataset into `args.out`, or into the database when
+// `repos` is given (`--into-db`).
+pub async fn generate(args: &GenerateArgs, repos: Option<&Repositories>) -> Result<(), CommandError> {
+    let config = args.config();
+    println!("🎲 Generating {} books and {} technologies (seed {})...", config.books, config.technologies, config.seed);
+    let data = generate_graph(&config)?;
+    check(&data)?;
+    let summary = describe(&data);
+    match (repos, &args.out) {
+        (Some(repos), _) => {
+            let label = args.label.clone().unwrap_or_else(|| format!("Synthetic graph (seed {})", config.seed));
+            let version = store_graph(repos, data, label).await?;
+            println!("✅ Stored {} as graph version #{}.", summary, version);
+        }
+        (None, Some(dir)) => {
+            write_csv_dir(&data, dir)?;
+            println!("✅ Wrote {} to {}.", summary, dir.display());
+        }
+        (None, None) => unreachable!("clap requires --out or --into-db"),
+    }
+    Ok(())
+}
+
+pub async fn export(repos: &Repositories, path: &Path) -> Result<(), CommandError> {
+    let data = repos.graph.load_graph().await?;
+    let json = serde_json::to_string_pretty(&data).map_err(|e| CommandError::Failed(e.to_string()))?;
+    std::fs::write(path, json).map_err(|e| CommandError::Io(format!("failed to write {}: {}", path.display(), e)))?;
+    println!("📤 Exported {} to {}.", describe(&data), path.display());
+    Ok(())
+}
+
+// Validates a dataset file, or the stored graph when no path is given.
+pub async fn validate(cli: &Cli, path: Option<&PathBuf>) -> Result<(), CommandError> {
+    let (source, data) = match path {
+        Some(path) => (path.display().to_string(), read_dataset(path)?),
+        None => {
+            let pool = connect(cli.database_url.as_deref()).await?;
+            ("The stored graph".to_string(), pool.repositories().graph.load_graph().await?)
+        }
+    };
+    check(&data)?;
+    println!("✅ {} is consistent ({}).", source, describe(&data));
+    Ok(())
+}
+
+pub async fn stats(pool: &DbPool) -> Result<(), CommandError> {
+    let repos = pool.repositories();
+    let graph = repos.graph.load_graph().await?;
+    println!("📊 Database stats ({:?} database):", pool.backend());
+    println!("   items:          {}", repos.items.count_items().await?);
+    println!("   books:          {}", graph.books.len());
+    println!("   technologies:   {}", graph.technologies.len());
+    println!("   links:          {}", graph.links.len());
+    println!("   graph versions: {}", repos.graph.list_versions().await?.len());
+    println!("   users:          {}", repos.users.list_users().await?.len());
+    Ok(())
+}
+
+pub fn completions(shell: clap_complete::Shell) {
+    use clap::CommandFactory;
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh scratch directory under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("backend-cli-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_new_migration_numbers_after_the_latest_in_either_dialect() {
+        let dir = scratch_dir("new-migration");
+        std::fs::create_dir_all(dir.join("migrations")).unwrap();
+        std::fs::create_dir_all(dir.join("migrations_postgres")).unwrap();
+        std::fs::write(dir.join("migrations/0002_b.up.sql"), "").unwrap();
+        std::fs::write(dir.join("migrations_postgres/0003_c.up.sql"), "").unwrap();
+
+        let created = new_migration(&dir, "Add book-year").unwrap();
+        let names: Vec<String> = created.iter().map(|p| p.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["0004_add_book_year.up.sql", "0004_add_book_year.down.sql", "0004_add_book_year.up.sql", "0004_add_book_year.down.sql"]);
+        assert!(created[2].starts_with(dir.join("migrations_postgres")));
+        assert!(matches!(new_migration(&dir, "drop table;"), Err(CommandError::Failed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_seed_applies_fixture_files() {
+        let dir = scratch_dir("seed");
+        let file = dir.join("chores.yaml");
+        std::fs::write(&file, "items: [Water the plants, Water the plants]\n").unwrap();
+
+        let repos = Repositories::in_memory();
+        seed(&repos, "demo", Some(&file)).await.unwrap();
+        seed(&repos, "demo", Some(&file)).await.unwrap();
+        assert_eq!(repos.items.count_items().await.unwrap(), 1);
+
+        std::fs::write(&file, "items: [Water the plants]\ngraph: remote\n").unwrap();
+        assert!(matches!(seed(&repos, "demo", Some(&file)).await, Err(CommandError::InvalidData(_))));
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_round_trips_the_graph() {
+        let dir = scratch_dir("round-trip");
+        let file = dir.join("graph.json");
+        let source = Repositories::in_memory();
+        frontend::database::seed_graph_if_empty(&source).await.unwrap();
+        export(&source, &file).await.unwrap();
+
+        let target = Repositories::in_memory();
+        import(&target, &file, None).await.unwrap();
+        assert_eq!(target.graph.load_graph().await.unwrap(), source.graph.load_graph().await.unwrap());
+        assert_eq!(target.graph.list_versions().await.unwrap()[0].label, "Imported graph.json");
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_inconsistent_and_unreadable_datasets() {
+        let dir = scratch_dir("invalid");
+        let mut data = frontend::dataset::bundled_graph().unwrap();
+        data.links.push(shared::BookTechLink::new(-1, data.technologies[0].id));
+        let file = dir.join("broken.json");
+        std::fs::write(&file, serde_json::to_string(&data).unwrap()).unwrap();
+
+        let repos = Repositories::in_memory();
+        let err = import(&repos, &file, None).await.unwrap_err();
+        assert!(matches!(err, CommandError::InvalidData(ref problems) if problems.len() == 1), "got {:?}", err);
+        assert!(repos.graph.load_graph().await.unwrap().books.is_empty(), "nothing is written");
+
+        assert!(matches!(read_dataset(&dir.join("missing.json")), Err(CommandError::Io(_))));
+        // A directory must contain the books, technologies and links CSV files.
+        assert!(matches!(read_dataset(&dir), Err(CommandError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn test_generate_writes_importable_csv_or_stores_a_version() {
+        use clap::Parser;
+        let dir = scratch_dir("generate").join("synthetic");
+        let parse = |args: &[&str]| match Cli::parse_from(["backend", "generate", "--books", "200", "--technologies", "40"].iter().chain(args)).command {
+            Some(crate::cli::Command::Generate(args)) => args,
+            other => panic!("expected generate, got {:?}", other),
+        };
+
+        let dir_arg = dir.to_string_lossy().into_owned();
+        generate(&parse(&["--out", &dir_arg]), None).await.unwrap();
+        let written = read_dataset(&dir).unwrap();
+        assert_eq!((written.books.len(), written.technologies.len()), (200, 40));
+
+        let repos = Repositories::in_memory();
+        generate(&parse(&["--into-db"]), Some(&repos)).await.unwrap();
+        assert_eq!(repos.graph.load_graph().await.unwrap(), written, "same seed, same graph");
+        assert_eq!(repos.graph.list_versions().await.unwrap()[0].label, "Synthetic graph (seed 42)");
+    }
+}
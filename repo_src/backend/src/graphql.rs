@@ -0,0 +1,673 @@
+// GraphQL API for external dashboards, served at GRAPHQL_PATH next to the
+// server functions. It exposes items and the tech graph with nested relations
+// (a book's technologies, a technology's category and books, ...), degree and
+// PageRank centrality, and `addItem` / `deleteItem` mutations with the same
+// validation, role checks, audit actor and change events as the AddItem and
+// DeleteItem server functions. Callers authenticate the same way too: a
+// session cookie or an `Authorization: Bearer` API token.
+//
+// Nesting makes it easy to ask for a lot, so every list takes `first` (default
+// DEFAULT_PAGE_SIZE, at most MAX_PAGE_SIZE) and `offset`, and a query is
+// refused before it runs when it is nested deeper than MAX_DEPTH or its
+// complexity (each list counting as `first` times the cost of its items)
+// exceeds MAX_COMPLEXITY.
+//
+// In dev (`--env dev`) a GraphiQL playground is served on GET GRAPHQL_PATH.
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptySubscription, ErrorExtensions, Object, Schema, SimpleObject};
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::Json;
+use frontend::auth::Caller;
+use frontend::errors::AppError;
+use frontend::live::ChangeFeed;
+use frontend::repository::Repositories;
+use shared::{Book, BookTechLink, ChangeEvent, GraphData, Item, Role, Technology, User};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::OnceCell;
+
+use crate::state::AppState;
+
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+pub const MAX_DEPTH: usize = 10;
+pub const MAX_COMPLEXITY: usize = 50_000;
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+pub const MAX_PAGE_SIZE: usize = 1_000;
+
+// Queries are small; anything bigger is a mistake or an attack.
+const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+// PageRank damping factor and iteration count (plenty to converge on a
+// bipartite graph of this shape).
+const PAGE_RANK_DAMPING: f64 = 0.85;
+const PAGE_RANK_ITERATIONS: usize = 40;
+
+pub type GraphSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+pub fn schema() -> GraphSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+// POST GRAPHQL_PATH with a JSON `{"query": ..., "variables": ...}` body.
+// GraphQL errors (including refused queries) come back as `errors` in a 200
+// response, each with an `extensions.code` such as "VALIDATION" or "FORBIDDEN".
+pub async fn execute(State(state): State<AppState>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    let caller = match frontend::auth::request_caller(&state.repos, &parts).await {
+        Ok(caller) => caller,
+        Err(e) => {
+            leptos::logging::error!("GraphQL caller lookup failed: {}", e);
+            return (e.status_code(), e.to_string()).into_response();
+        }
+    };
+    let Ok(body) = axum::body::to_bytes(body, MAX_REQUEST_BYTES).await else {
+        return (StatusCode::PAYLOAD_TOO_LARGE, format!("GraphQL requests are limited to {} bytes", MAX_REQUEST_BYTES)).into_response();
+    };
+    let request: async_graphql::Request = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("invalid GraphQL request: {}", e)).into_response(),
+    };
+    let context = RequestContext::new(state.repos.clone(), state.changes.clone(), caller);
+    Json(state.graphql.execute(request.data(context)).await).into_response()
+}
+
+pub async fn playground() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).title("TechVerse GraphQL").finish())
+}
+
+// Per-request data: storage, the caller, and the graph, loaded at most once
+// per request however many fields need it.
+pub struct RequestContext {
+    repos: Repositories,
+    changes: ChangeFeed,
+    caller: Option<Caller>,
+    graph: OnceCell<Arc<GraphIndex>>,
+}
+
+impl RequestContext {
+    pub fn new(repos: Repositories, changes: ChangeFeed, caller: Option<Caller>) -> Self {
+        Self { repos, changes, caller, graph: OnceCell::new() }
+    }
+
+    async fn graph(&self) -> async_graphql::Result<Arc<GraphIndex>> {
+        self.graph
+            .get_or_try_init(|| async {
+                let data = self.repos.graph.load_graph().await.map_err(graphql_error)?;
+                Ok(Arc::new(GraphIndex::new(data)))
+            })
+            .await
+            .cloned()
+    }
+
+    // Same rules as `require_write_role` in the server functions.
+    fn writer(&self, role: Role) -> async_graphql::Result<&User> {
+        let caller = self.caller.as_ref().ok_or_else(|| graphql_error(AppError::Unauthorized("please log in".to_string())))?;
+        caller.require_write_role(role).map_err(graphql_error)?;
+        Ok(&caller.user)
+    }
+}
+
+fn request<'a>(ctx: &Context<'a>) -> &'a RequestContext {
+    ctx.data_unchecked::<RequestContext>()
+}
+
+// The message is the `AppError` display string the server functions send;
+// `extensions.code` (and `extensions.field` for validation errors) make it
+// machine-readable.
+fn graphql_error(err: AppError) -> async_graphql::Error {
+    leptos::logging::error!("GraphQL request failed: {}", err);
+    let field = match &err {
+        AppError::Validation { field, .. } => Some(field.clone()),
+        _ => None,
+    };
+    async_graphql::Error::new(err.to_string()).extend_with(|_, extensions| {
+        extensions.set("code", err.code());
+        if let Some(field) = &field {
+            extensions.set("field", field.as_str());
+        }
+    })
+}
+
+fn page_size(first: Option<usize>) -> usize {
+    first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+fn page<T>(items: impl Iterator<Item = T>, first: Option<usize>, offset: Option<usize>) -> Vec<T> {
+    items.skip(offset.unwrap_or(0)).take(page_size(first)).collect()
+}
+
+// The graph with adjacency lists, in id order. Links to unknown rows are ignored.
+struct GraphIndex {
+    data: GraphData,
+    books: HashMap<i32, usize>,
+    technologies: HashMap<i32, usize>,
+    techs_of: HashMap<i32, Vec<i32>>,
+    books_of: HashMap<i32, Vec<i32>>,
+    // (book id, technology id) to its link.
+    links: HashMap<(i32, i32), usize>,
+    // Category name to its technologies.
+    categories: BTreeMap<String, Vec<i32>>,
+    // (books, technologies), computed on first use.
+    page_rank: OnceLock<(HashMap<i32, f64>, HashMap<i32, f64>)>,
+}
+
+impl GraphIndex {
+    fn new(data: GraphData) -> Self {
+        let books: HashMap<i32, usize> = data.books.iter().enumerate().map(|(i, b)| (b.id, i)).collect();
+        let technologies: HashMap<i32, usize> = data.technologies.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+        let mut techs_of: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut books_of: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut links: HashMap<(i32, i32), usize> = HashMap::new();
+        for (i, link) in data.links.iter().enumerate() {
+            if books.contains_key(&link.book_id) && technologies.contains_key(&link.tech_id) {
+                techs_of.entry(link.book_id).or_default().push(link.tech_id);
+                books_of.entry(link.tech_id).or_default().push(link.book_id);
+                links.insert((link.book_id, link.tech_id), i);
+            }
+        }
+        for list in techs_of.values_mut().chain(books_of.values_mut()) {
+            list.sort_unstable();
+            list.dedup();
+        }
+        let mut categories: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+        for tech in &data.technologies {
+            categories.entry(tech.category.clone()).or_default().push(tech.id);
+        }
+        Self { data, books, technologies, techs_of, books_of, links, categories, page_rank: OnceLock::new() }
+    }
+
+    fn book(&self, id: i32) -> Option<&Book> {
+        self.books.get(&id).map(|&i| &self.data.books[i])
+    }
+
+    fn technology(&self, id: i32) -> Option<&Technology> {
+        self.technologies.get(&id).map(|&i| &self.data.technologies[i])
+    }
+
+    fn link(&self, book_id: i32, tech_id: i32) -> Option<&BookTechLink> {
+        self.links.get(&(book_id, tech_id)).map(|&i| &self.data.links[i])
+    }
+
+    fn techs_of(&self, book_id: i32) -> &[i32] {
+        self.techs_of.get(&book_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn books_of(&self, tech_id: i32) -> &[i32] {
+        self.books_of.get(&tech_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    // Distinct books linked to any technology of the category, in id order.
+    fn books_in_category(&self, category: &str) -> BTreeSet<i32> {
+        let techs = self.categories.get(category).map(Vec::as_slice).unwrap_or_default();
+        techs.iter().flat_map(|&t| self.books_of(t).iter().copied()).collect()
+    }
+
+    // PageRank over the undirected book–technology graph, summing to 1.
+    // Nodes without links spread their rank evenly, as usual.
+    fn page_rank(&self) -> &(HashMap<i32, f64>, HashMap<i32, f64>) {
+        self.page_rank.get_or_init(|| {
+            let book_count = self.data.books.len();
+            let n = book_count + self.data.technologies.len();
+            if n == 0 {
+                return Default::default();
+            }
+            let edges: Vec<(usize, usize)> = self
+                .techs_of
+                .iter()
+                .flat_map(|(book_id, techs)| techs.iter().map(move |tech_id| (*book_id, *tech_id)))
+                .map(|(b, t)| (self.books[&b], book_count + self.technologies[&t]))
+                .collect();
+            let mut degree = vec![0usize; n];
+            for &(b, t) in &edges {
+                degree[b] += 1;
+                degree[t] += 1;
+            }
+
+            let mut rank = vec![1.0 / n as f64; n];
+            for _ in 0..PAGE_RANK_ITERATIONS {
+                let dangling: f64 = rank.iter().zip(&degree).filter(|(_, &d)| d == 0).map(|(r, _)| r).sum();
+                let base = (1.0 - PAGE_RANK_DAMPING) / n as f64 + PAGE_RANK_DAMPING * dangling / n as f64;
+                let mut next = vec![base; n];
+                for &(b, t) in &edges {
+                    next[t] += PAGE_RANK_DAMPING * rank[b] / degree[b] as f64;
+                    next[b] += PAGE_RANK_DAMPING * rank[t] / degree[t] as f64;
+                }
+                rank = next;
+            }
+
+            let books = self.data.books.iter().zip(&rank).map(|(b, r)| (b.id, *r)).collect();
+            let techs = self.data.technologies.iter().zip(&rank[book_count..]).map(|(t, r)| (t.id, *r)).collect();
+            (books, techs)
+        })
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(name = "Item")]
+struct ItemNode {
+    id: i64,
+    text: String,
+    /// ISO 8601, UTC.
+    created_at: String,
+}
+
+impl From<Item> for ItemNode {
+    fn from(item: Item) -> Self {
+        Self { id: item.id, text: item.text, created_at: item.created_at.format("%Y-%m-%dT%H:%M:%S").to_string() }
+    }
+}
+
+struct BookNode {
+    graph: Arc<GraphIndex>,
+    id: i32,
+}
+
+#[Object(name = "Book")]
+impl BookNode {
+    async fn id(&self) -> i32 {
+        self.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.book().title
+    }
+
+    async fn author(&self) -> &str {
+        &self.book().author
+    }
+
+    async fn series(&self) -> &str {
+        &self.book().series
+    }
+
+    /// Year of first publication, if known.
+    async fn year(&self) -> Option<i32> {
+        self.book().year
+    }
+
+    /// Technologies the book features, by id.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn technologies(&self, first: Option<usize>, offset: Option<usize>) -> Vec<TechnologyNode> {
+        page(self.graph.techs_of(self.id).iter().map(|&id| TechnologyNode { graph: self.graph.clone(), id }), first, offset)
+    }
+
+    /// Number of technologies the book features.
+    async fn degree(&self) -> usize {
+        self.graph.techs_of(self.id).len()
+    }
+
+    /// Degree divided by the number of technologies.
+    async fn degree_centrality(&self) -> f64 {
+        let total = self.graph.data.technologies.len();
+        if total == 0 { 0.0 } else { self.graph.techs_of(self.id).len() as f64 / total as f64 }
+    }
+
+    /// PageRank over the whole book–technology graph (all nodes sum to 1).
+    async fn page_rank(&self) -> f64 {
+        self.graph.page_rank().0.get(&self.id).copied().unwrap_or_default()
+    }
+}
+
+impl BookNode {
+    fn book(&self) -> &Book {
+        self.graph.book(self.id).expect("book nodes are only made for known ids")
+    }
+}
+
+struct TechnologyNode {
+    graph: Arc<GraphIndex>,
+    id: i32,
+}
+
+#[Object(name = "Technology")]
+impl TechnologyNode {
+    async fn id(&self) -> i32 {
+        self.id
+    }
+
+    async fn name(&self) -> &str {
+        &self.technology().name
+    }
+
+    async fn category(&self) -> CategoryNode {
+        CategoryNode { graph: self.graph.clone(), name: self.technology().category.clone() }
+    }
+
+    async fn subcategory(&self) -> &str {
+        &self.technology().subcategory
+    }
+
+    async fn description(&self) -> &str {
+        &self.technology().description
+    }
+
+    /// Books featuring the technology, by id.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn books(&self, first: Option<usize>, offset: Option<usize>) -> Vec<BookNode> {
+        page(self.graph.books_of(self.id).iter().map(|&id| BookNode { graph: self.graph.clone(), id }), first, offset)
+    }
+
+    /// Number of books featuring the technology.
+    async fn degree(&self) -> usize {
+        self.graph.books_of(self.id).len()
+    }
+
+    /// Degree divided by the number of books.
+    async fn degree_centrality(&self) -> f64 {
+        let total = self.graph.data.books.len();
+        if total == 0 { 0.0 } else { self.graph.books_of(self.id).len() as f64 / total as f64 }
+    }
+
+    /// PageRank over the whole book–technology graph (all nodes sum to 1).
+    async fn page_rank(&self) -> f64 {
+        self.graph.page_rank().1.get(&self.id).copied().unwrap_or_default()
+    }
+}
+
+impl TechnologyNode {
+    fn technology(&self) -> &Technology {
+        self.graph.technology(self.id).expect("technology nodes are only made for known ids")
+    }
+}
+
+struct CategoryNode {
+    graph: Arc<GraphIndex>,
+    name: String,
+}
+
+#[Object(name = "Category")]
+impl CategoryNode {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn technologies(&self, first: Option<usize>, offset: Option<usize>) -> Vec<TechnologyNode> {
+        let techs = self.graph.categories.get(&self.name).map(Vec::as_slice).unwrap_or_default();
+        page(techs.iter().map(|&id| TechnologyNode { graph: self.graph.clone(), id }), first, offset)
+    }
+
+    /// Distinct books featuring any technology in the category, by id.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn books(&self, first: Option<usize>, offset: Option<usize>) -> Vec<BookNode> {
+        page(self.graph.books_in_category(&self.name).into_iter().map(|id| BookNode { graph: self.graph.clone(), id }), first, offset)
+    }
+
+    async fn technology_count(&self) -> usize {
+        self.graph.categories.get(&self.name).map_or(0, Vec::len)
+    }
+
+    async fn book_count(&self) -> usize {
+        self.graph.books_in_category(&self.name).len()
+    }
+}
+
+struct LinkNode {
+    graph: Arc<GraphIndex>,
+    book_id: i32,
+    tech_id: i32,
+}
+
+#[Object(name = "Link")]
+impl LinkNode {
+    async fn book(&self) -> BookNode {
+        BookNode { graph: self.graph.clone(), id: self.book_id }
+    }
+
+    async fn technology(&self) -> TechnologyNode {
+        TechnologyNode { graph: self.graph.clone(), id: self.tech_id }
+    }
+
+    /// How the technology figures in the book: central_premise, background_detail or mentioned.
+    async fn kind(&self) -> String {
+        self.graph.link(self.book_id, self.tech_id).map(|l| l.kind).unwrap_or_default().as_str().to_string()
+    }
+
+    /// Prominence in the book, from 1 to 10.
+    async fn weight(&self) -> i32 {
+        self.graph.link(self.book_id, self.tech_id).map_or(1, |l| l.weight)
+    }
+
+    /// A quote or reference backing the link.
+    async fn citation(&self) -> Option<String> {
+        self.graph.link(self.book_id, self.tech_id).and_then(|l| l.citation.clone())
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Newest first.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn items(&self, ctx: &Context<'_>, first: Option<usize>, offset: Option<usize>) -> async_graphql::Result<Vec<ItemNode>> {
+        let items = request(ctx).repos.items.list_items().await.map_err(graphql_error)?;
+        Ok(page(items.into_iter().map(ItemNode::from), first, offset))
+    }
+
+    async fn item(&self, ctx: &Context<'_>, id: i64) -> async_graphql::Result<Option<ItemNode>> {
+        let items = request(ctx).repos.items.list_items().await.map_err(graphql_error)?;
+        Ok(items.into_iter().find(|item| item.id == id).map(ItemNode::from))
+    }
+
+    /// Books by id, optionally only those whose title or author contains `search` (ignoring case).
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn books(
+        &self,
+        ctx: &Context<'_>,
+        search: Option<String>,
+        first: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<BookNode>> {
+        let graph = request(ctx).graph().await?;
+        let search = search.map(|s| s.to_lowercase());
+        let matches = |book: &&Book| {
+            search.as_deref().is_none_or(|s| book.title.to_lowercase().contains(s) || book.author.to_lowercase().contains(s))
+        };
+        let ids: Vec<i32> = graph.data.books.iter().filter(matches).map(|b| b.id).collect();
+        Ok(page(ids.into_iter().map(|id| BookNode { graph: graph.clone(), id }), first, offset))
+    }
+
+    async fn book(&self, ctx: &Context<'_>, id: i32) -> async_graphql::Result<Option<BookNode>> {
+        let graph = request(ctx).graph().await?;
+        Ok(graph.book(id).is_some().then(|| BookNode { graph: graph.clone(), id }))
+    }
+
+    /// Technologies by id, optionally only those of one category.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn technologies(
+        &self,
+        ctx: &Context<'_>,
+        category: Option<String>,
+        first: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Vec<TechnologyNode>> {
+        let graph = request(ctx).graph().await?;
+        let ids: Vec<i32> = graph
+            .data
+            .technologies
+            .iter()
+            .filter(|t| category.as_ref().is_none_or(|c| &t.category == c))
+            .map(|t| t.id)
+            .collect();
+        Ok(page(ids.into_iter().map(|id| TechnologyNode { graph: graph.clone(), id }), first, offset))
+    }
+
+    async fn technology(&self, ctx: &Context<'_>, id: i32) -> async_graphql::Result<Option<TechnologyNode>> {
+        let graph = request(ctx).graph().await?;
+        Ok(graph.technology(id).is_some().then(|| TechnologyNode { graph: graph.clone(), id }))
+    }
+
+    /// Categories by name.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn categories(&self, ctx: &Context<'_>, first: Option<usize>, offset: Option<usize>) -> async_graphql::Result<Vec<CategoryNode>> {
+        let graph = request(ctx).graph().await?;
+        let names: Vec<String> = graph.categories.keys().cloned().collect();
+        Ok(page(names.into_iter().map(|name| CategoryNode { graph: graph.clone(), name }), first, offset))
+    }
+
+    async fn category(&self, ctx: &Context<'_>, name: String) -> async_graphql::Result<Option<CategoryNode>> {
+        let graph = request(ctx).graph().await?;
+        Ok(graph.categories.contains_key(&name).then(|| CategoryNode { graph: graph.clone(), name }))
+    }
+
+    /// Book–technology links, by book id then technology id.
+    #[graphql(complexity = "page_size(first).saturating_mul(child_complexity)")]
+    async fn links(&self, ctx: &Context<'_>, first: Option<usize>, offset: Option<usize>) -> async_graphql::Result<Vec<LinkNode>> {
+        let graph = request(ctx).graph().await?;
+        let pairs: BTreeSet<(i32, i32)> =
+            graph.techs_of.iter().flat_map(|(&book_id, techs)| techs.iter().map(move |&tech_id| (book_id, tech_id))).collect();
+        Ok(page(pairs.into_iter().map(|(book_id, tech_id)| LinkNode { graph: graph.clone(), book_id, tech_id }), first, offset))
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Same as the AddItem server function: needs the editor role (and a write-scoped API token).
+    async fn add_item(&self, ctx: &Context<'_>, text: String) -> async_graphql::Result<ItemNode> {
+        let request = request(ctx);
+        let user = request.writer(Role::Editor)?;
+        frontend::server_fns::validate_item_text(&text).map_err(graphql_error)?;
+        let item = request.repos.items.add_item(text, &user.username).await.map_err(graphql_error)?;
+        request.changes.publish(ChangeEvent::ItemAdded { item: item.clone() });
+        Ok(item.into())
+    }
+
+    /// Same as the DeleteItem server function. Always true; unknown ids are a NOT_FOUND error.
+    async fn delete_item(&self, ctx: &Context<'_>, id: i64) -> async_graphql::Result<bool> {
+        let request = request(ctx);
+        let user = request.writer(Role::Editor)?;
+        request.repos.items.delete_item(id, &user.username).await.map_err(graphql_error)?;
+        request.changes.publish(ChangeEvent::ItemDeleted { id });
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Value;
+    use shared::TokenScope;
+
+    async fn seeded() -> Repositories {
+        let repos = Repositories::in_memory();
+        frontend::database::seed_graph_if_empty(&repos).await.unwrap();
+        repos
+    }
+
+    fn caller(role: Role, token_scope: Option<TokenScope>) -> Caller {
+        let user = User { id: 1, username: "ada".into(), role, created_at: Default::default() };
+        Caller { user, token_scope }
+    }
+
+    async fn run(repos: &Repositories, caller: Option<Caller>, query: &str) -> async_graphql::Response {
+        let context = RequestContext::new(repos.clone(), ChangeFeed::default(), caller);
+        schema().execute(async_graphql::Request::new(query).data(context)).await
+    }
+
+    fn json(response: async_graphql::Response) -> serde_json::Value {
+        assert!(response.errors.is_empty(), "unexpected errors: {:?}", response.errors);
+        response.data.into_json().unwrap()
+    }
+
+    fn error_code(response: &async_graphql::Response) -> Option<String> {
+        let extensions = response.errors.first()?.extensions.as_ref()?;
+        match extensions.get("code")? {
+            Value::String(code) => Some(code.clone()),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nested_graph_query_with_centrality() {
+        let repos = seeded().await;
+        let data = repos.graph.load_graph().await.unwrap();
+        let tech = &data.technologies[0];
+        let query = format!(
+            "{{ technology(id: {}) {{ name degree degreeCentrality pageRank category {{ name technologyCount }} books(first: 2) {{ title technologies {{ id }} }} }} }}",
+            tech.id
+        );
+        let result = json(run(&repos, None, &query).await);
+        let node = &result["technology"];
+
+        let linked: BTreeSet<i32> = data.links.iter().filter(|l| l.tech_id == tech.id).map(|l| l.book_id).collect();
+        assert_eq!(node["name"], tech.name.as_str());
+        assert_eq!(node["degree"], linked.len());
+        assert_eq!(node["degreeCentrality"].as_f64().unwrap(), linked.len() as f64 / data.books.len() as f64);
+        assert!(node["pageRank"].as_f64().unwrap() > 0.0);
+        assert_eq!(node["category"]["name"], tech.category.as_str());
+        assert_eq!(node["books"].as_array().unwrap().len(), linked.len().min(2));
+        // Each listed book links back to the technology.
+        for book in node["books"].as_array().unwrap() {
+            assert!(book["technologies"].as_array().unwrap().iter().any(|t| t["id"] == tech.id));
+        }
+
+        // PageRank is a distribution over every book and technology.
+        let all = json(run(&repos, None, "{ books(first: 1000) { pageRank } technologies(first: 1000) { pageRank } }").await);
+        let total: f64 = ["books", "technologies"]
+            .iter()
+            .flat_map(|key| all[*key].as_array().unwrap().iter().map(|n| n["pageRank"].as_f64().unwrap()))
+            .sum();
+        assert!((total - 1.0).abs() < 1e-6, "PageRank sums to {}", total);
+
+        // Links carry their kind and weight.
+        let first = data.links.iter().min_by_key(|l| (l.book_id, l.tech_id)).unwrap();
+        let links = json(run(&repos, None, "{ links(first: 1) { kind weight citation } }").await);
+        assert_eq!(links["links"][0]["kind"], first.kind.as_str());
+        assert_eq!(links["links"][0]["weight"], first.weight);
+    }
+
+    #[tokio::test]
+    async fn test_deep_and_expensive_queries_are_refused() {
+        let repos = seeded().await;
+        let deep = "{ books(first: 1) { technologies(first: 1) { books(first: 1) { technologies(first: 1) { books(first: 1) { technologies(first: 1) { books(first: 1) { technologies(first: 1) { books(first: 1) { technologies(first: 1) { id } } } } } } } } } } }";
+        let response = run(&repos, None, deep).await;
+        assert!(response.errors[0].message.contains("nested too deep"), "{:?}", response.errors);
+
+        let wide = "{ books(first: 1000) { technologies(first: 1000) { id } } }";
+        let response = run(&repos, None, wide).await;
+        assert!(response.errors[0].message.contains("too complex"), "{:?}", response.errors);
+
+        // Within the limits once the lists are paged down.
+        json(run(&repos, None, "{ books(first: 10) { technologies(first: 10) { id } } }").await);
+    }
+
+    #[tokio::test]
+    async fn test_item_mutations_mirror_the_server_functions() {
+        let repos = Repositories::in_memory();
+        let add = r#"mutation { addItem(text: "Dashboards") { id text } }"#;
+
+        let response = run(&repos, None, add).await;
+        assert_eq!(error_code(&response).as_deref(), Some("UNAUTHORIZED"));
+        let response = run(&repos, Some(caller(Role::Viewer, None)), add).await;
+        assert_eq!(error_code(&response).as_deref(), Some("FORBIDDEN"));
+        let response = run(&repos, Some(caller(Role::Editor, Some(TokenScope::Read))), add).await;
+        assert_eq!(error_code(&response).as_deref(), Some("FORBIDDEN"));
+        let blank = r#"mutation { addItem(text: " ") { id } }"#;
+        let response = run(&repos, Some(caller(Role::Editor, None)), blank).await;
+        assert_eq!(error_code(&response).as_deref(), Some("VALIDATION"));
+        // Only callers allowed to write hear what's wrong with their input.
+        let response = run(&repos, None, blank).await;
+        assert_eq!(error_code(&response).as_deref(), Some("UNAUTHORIZED"));
+
+        let editor = Some(caller(Role::Editor, Some(TokenScope::Write)));
+        let added = json(run(&repos, editor.clone(), add).await);
+        assert_eq!(added["addItem"]["text"], "Dashboards");
+        let id = added["addItem"]["id"].as_i64().unwrap();
+        assert_eq!(json(run(&repos, None, "{ items { text } }").await)["items"][0]["text"], "Dashboards");
+
+        let delete = format!("mutation {{ deleteItem(id: {}) }}", id);
+        assert_eq!(json(run(&repos, editor.clone(), &delete).await)["deleteItem"], true);
+        assert_eq!(error_code(&run(&repos, editor, &delete).await).as_deref(), Some("NOT_FOUND"));
+        assert_eq!(repos.items.count_items().await.unwrap(), 0);
+    }
+}
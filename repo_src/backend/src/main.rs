@@ -1,186 +1,236 @@
 // This main.rs is only compiled and run for the server-side binary.
 // It relies on the "ssr" feature being active for the `frontend` crate.
 
+mod cli;
+mod commands;
+mod graphql;
+mod rest;
+mod state;
+
+use cli::{AppEnv, Cli, Command, CommandError, MigrateAction};
+use state::AppState;
+
+use axum::extract::{Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::middleware::Next;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use clap::Parser;
+use frontend::errors::AppError;
+use frontend::live::ChangeFeed;
+use frontend::repository::Repositories;
+use shared::ChangeEvent;
+use std::process::ExitCode;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+// Authenticates `Authorization: Bearer <api token>` requests for scripts. A
+// valid token is attached to the request as a `BearerPrincipal` extension,
+// which server functions see through the request parts (the role and scope
+// checks happen there). A bad or revoked token is rejected outright instead of
+// silently falling back to anonymous access. Requests without the header
+// (browsers with a session cookie) pass through untouched.
+async fn bearer_auth(State(repos): State<Repositories>, mut req: Request, next: Next) -> Response {
+    let Some(header) = req.headers().get(AUTHORIZATION) else {
+        return next.run(req).await;
+    };
+    let Some(token) = header.to_str().ok().and_then(frontend::auth::bearer_token).map(str::to_string) else {
+        return reject(AppError::Unauthorized("expected `Authorization: Bearer <token>`".to_string()));
+    };
+    match frontend::auth::authenticate_api_token(&repos, &token).await {
+        Ok(Some(principal)) => {
+            req.extensions_mut().insert(principal);
+            next.run(req).await
+        }
+        Ok(None) => reject(AppError::Unauthorized("invalid or revoked API token".to_string())),
+        Err(e) => {
+            leptos::logging::error!("API token lookup failed: {}", e);
+            reject(e)
+        }
+    }
+}
+
+fn reject(err: AppError) -> Response {
+    (err.status_code(), err.to_string()).into_response()
+}
+
+// Server-sent events stream of every data change, one JSON `ChangeEvent` per
+// message (see `frontend::live`). A subscriber that falls too far behind is
+// sent `Resync` instead of the events it missed.
+async fn change_events(State(feed): State<ChangeFeed>) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let stream = BroadcastStream::new(feed.subscribe())
+        .map(|received| Event::default().json_data(received.unwrap_or(ChangeEvent::Resync)));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    // Load .env (from the workspace root, repo_src/.env) before parsing, so its
+    // DATABASE_URL and LEPTOS_ENV act as defaults for the matching flags.
+    let dotenv = dotenvy::dotenv();
+    let cli = Cli::parse();
+
+    // A completion script is the only thing that may go to stdout.
+    if let Some(Command::Completions { shell }) = cli.command {
+        commands::completions(shell);
+        return ExitCode::SUCCESS;
+    }
+    match dotenv {
+        Ok(path) => println!("📄 Loaded .env file from: {:?}", path),
+        Err(_) => println!("⚠️  No .env file found. Using environment variables directly."),
+    }
+    // Leptos reads LEPTOS_ENV itself when loading its configuration; keep it
+    // in line with --env.
+    std::env::set_var("LEPTOS_ENV", cli.env.as_env_value());
+
+    // Leptos SSR and the database commands run inside a LocalSet.
+    let local = tokio::task::LocalSet::new();
+    match local.run_until(run(cli)).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            e.exit_code()
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), CommandError> {
+    let command = cli.command.as_ref().unwrap_or(&Command::Serve);
+    match command {
+        // Only needs the database when validating the stored graph.
+        Command::Validate { path } => return commands::validate(&cli, path.as_ref()).await,
+        Command::Migrate { action: Some(MigrateAction::New { name, dir }) } => return commands::print_new_migration(dir, name),
+        Command::Generate(args) if !args.into_db => return commands::generate(args, None).await,
+        _ => {}
+    }
+
+    let pool = commands::connect(cli.database_url.as_deref()).await?;
+    match command {
+        Command::Serve => serve(&cli, pool).await,
+        Command::Migrate { action: None } => commands::migrate(&pool).await,
+        Command::Migrate { action: Some(MigrateAction::Status) } => commands::migrate_status(&pool).await,
+        Command::Migrate { action: Some(MigrateAction::Rollback { steps }) } => commands::rollback(&pool, *steps).await,
+        Command::Seed { fixture, file } => {
+            commands::auto_migrate(&pool).await?;
+            commands::seed(&pool.repositories(), fixture, file.as_deref()).await
+        }
+        Command::Import { path, label } => {
+            commands::auto_migrate(&pool).await?;
+            commands::import(&pool.repositories(), path, label.clone()).await
+        }
+        Command::Generate(args) => {
+            commands::auto_migrate(&pool).await?;
+            commands::generate(args, Some(&pool.repositories())).await
+        }
+        Command::Export { path } => commands::export(&pool.repositories(), path).await,
+        Command::Stats => commands::stats(&pool).await,
+        Command::Validate { .. } | Command::Migrate { action: Some(MigrateAction::New { .. }) } | Command::Completions { .. } => {
+            unreachable!("handled before connecting")
+        }
+    }
+}
+
+// The single connection pool for the whole process is handed to axum as router
+// state, and to server functions (wrapped in `Repositories`) through Leptos
+// context below. SQLite or PostgreSQL is chosen from the DATABASE_URL scheme;
+// a SQLite file's parent directory is created if missing.
+async fn serve(cli: &Cli, pool: frontend::database::DbPool) -> Result<(), CommandError> {
     use axum::Router;
     use leptos::*;
     use leptos_axum::{generate_route_list, LeptosRoutes};
     use frontend::app::App; // App from the frontend crate
     use tower_http::services::ServeDir;
-    use tokio::task::LocalSet;
-
-    // Check for command line arguments
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 {
-        match args[1].as_str() {
-            "--seed" => {
-                println!("🌱 Manual database seeding requested...");
-                
-                // Load .env file before database operations
-                match dotenvy::dotenv() {
-                    Ok(path) => println!("📄 Loaded .env file from: {:?}", path),
-                    Err(_) => println!("⚠️  No .env file found. Using environment variables directly."),
-                }
-                
-                // Run seeding in a LocalSet
-                let local = LocalSet::new();
-                local.run_until(async move {
-                    // First, run migrations to ensure database schema exists
-                    #[cfg(feature = "DATABASE_AUTO_MIGRATE")]
-                    {
-                        println!("🔧 Running database migrations first...");
-                        match frontend::database::run_migrations().await {
-                            Ok(_) => println!("✅ Database migrations completed successfully!"),
-                            Err(e) => {
-                                println!("❌ Database migrations failed: {}", e);
-                                std::process::exit(1);
-                            }
-                        }
-                    }
-                    
-                    // Then run seeding
-                    match frontend::database::seed_database().await {
-                        Ok(_) => println!("✅ Manual database seeding completed successfully!"),
-                        Err(e) => {
-                            println!("❌ Manual database seeding failed: {}", e);
-                            std::process::exit(1);
-                        }
-                    }
-                }).await;
-                return;
-            }
-            "--force-seed" => {
-                println!("🌱 Force database seeding requested (will add items regardless of existing data)...");
-                
-                // Load .env file before database operations
-                match dotenvy::dotenv() {
-                    Ok(path) => println!("📄 Loaded .env file from: {:?}", path),
-                    Err(_) => println!("⚠️  No .env file found. Using environment variables directly."),
-                }
-                
-                // Run force seeding in a LocalSet
-                let local = LocalSet::new();
-                local.run_until(async move {
-                    // First, run migrations to ensure database schema exists
-                    #[cfg(feature = "DATABASE_AUTO_MIGRATE")]
-                    {
-                        println!("🔧 Running database migrations first...");
-                        match frontend::database::run_migrations().await {
-                            Ok(_) => println!("✅ Database migrations completed successfully!"),
-                            Err(e) => {
-                                println!("❌ Database migrations failed: {}", e);
-                                std::process::exit(1);
-                            }
-                        }
-                    }
-                    
-                    // Then run force seeding
-                    match frontend::database::force_seed_database().await {
-                        Ok(_) => println!("✅ Force database seeding completed successfully!"),
-                        Err(e) => {
-                            println!("❌ Force database seeding failed: {}", e);
-                            std::process::exit(1);
-                        }
-                    }
-                }).await;
-                return;
-            }
-            "--help" | "-h" => {
-                println!("Leptos Full-Stack Web Application");
-                println!("\nUsage:");
-                println!("  ./backend              Start the web server");
-                println!("  ./backend --seed       Seed the database with initial data (only if empty)");
-                println!("  ./backend --force-seed Force seed the database (adds data regardless)");
-                println!("  ./backend --help       Show this help message");
-                return;
-            }
-            _ => {
-                println!("❌ Unknown argument: {}", args[1]);
-                println!("Use --help for available options.");
-                std::process::exit(1);
+
+    // Refuse to serve a schema migrated by a newer build (e.g. after a downgrade).
+    commands::ensure_schema_not_ahead(&pool).await?;
+
+    // Run migrations if the DATABASE_AUTO_MIGRATE feature is enabled for the backend crate.
+    // Without it, run `backend migrate` before starting the server.
+    #[cfg(feature = "DATABASE_AUTO_MIGRATE")] // This block handles migrations and seeding
+    {
+        logging::log!("DATABASE_AUTO_MIGRATE feature is enabled for backend. Attempting to run migrations...");
+        frontend::database::run_migrations(&pool)
+            .await
+            .map_err(|e| CommandError::Database(format!("failed to run database migrations: {}", e)))?;
+        logging::log!("Database migrations completed successfully.");
+
+        // The graph dataset is needed by the Tech Graph page in every environment.
+        if let Err(e) = frontend::database::seed_graph_if_empty(&pool.repositories()).await {
+            logging::error!("Failed to import the bundled graph dataset: {:?}", e);
+        }
+
+        // Conditionally seed the database in development environments
+        if cli.env == AppEnv::Dev {
+            logging::log!("🌱 Development environment detected (LEPTOS_ENV=DEV). Attempting to seed database...");
+            if let Err(e) = frontend::database::seed_database(&pool.repositories()).await {
+                logging::error!("Failed to seed database: {:?}", e);
+                // Seeding demo items is not fatal.
+            } else {
+                logging::log!("🌱 Automatic database seeding check completed.");
             }
+        } else {
+            logging::log!("Production-like environment (LEPTOS_ENV is not DEV). Skipping database seeding.");
         }
     }
 
-    // Load .env file from the workspace root (repo_src/.env)
-    // This needs to happen before any database connection attempts if DB_URL is in .env
-    match dotenvy::dotenv() {
-        Ok(path) => logging::log!("Loaded .env file from: {:?}", path),
-        Err(_) => logging::log!("No .env file found or error loading it. Using environment variables directly or defaults."),
+    // First admin account on a fresh install (needs the users table, so after migrations).
+    match frontend::database::bootstrap_admin_from_env(&pool.repositories()).await {
+        Ok(true) => logging::log!("👤 Created the initial admin account from ADMIN_USERNAME."),
+        Ok(false) => {}
+        Err(e) => logging::error!("Failed to create the initial admin account: {:?}", e),
     }
-    
-    // Setup logging (optional, but good for seeing leptos logs)
-    // You can use a more sophisticated logger like `tracing` or `env_logger`.
-    // simple_logger::init_with_level(log::Level::Debug).expect("couldn't initialize logger");
-
-    // Wrap the server setup in a LocalSet for Leptos SSR support
-    let local = LocalSet::new();
-    
-    local.run_until(async move {
-        // Run migrations if the DATABASE_AUTO_MIGRATE feature is enabled for the backend crate.
-        // This feature, in turn, enables frontend/DATABASE_AUTO_MIGRATE.
-        #[cfg(feature = "DATABASE_AUTO_MIGRATE")] // This block handles migrations and seeding
-        {
-            logging::log!("DATABASE_AUTO_MIGRATE feature is enabled for backend. Attempting to run migrations...");
-            // Ensure the target directory exists for SQLite file creation if using a file-based DB.
-            if let Ok(db_url) = std::env::var("DATABASE_URL") {
-                if db_url.starts_with("sqlite:") {
-                    let path_str = db_url.trim_start_matches("sqlite:");
-                    if let Some(parent_dir) = std::path::Path::new(path_str.split('?').next().unwrap_or("")).parent() {
-                        if !parent_dir.exists() {
-                            logging::log!("Attempting to create database directory: {:?}", parent_dir);
-                            if let Err(e) = std::fs::create_dir_all(parent_dir) {
-                                logging::error!("Failed to create database directory {:?}: {:?}", parent_dir, e);
-                                // std::process::exit(1); // Exit if directory creation fails, as migrations will likely fail.
-                            }
-                        }
-                    }
-                }
-            }
 
-            // The database module and run_migrations function are part of the `frontend` crate,
-            // compiled under its "ssr" and "DATABASE_AUTO_MIGRATE" features.
-            match frontend::database::run_migrations().await {
-                Ok(_) => logging::log!("Database migrations completed successfully."),
-                Err(e) => {
-                    logging::error!("FATAL: Failed to run database migrations: {:?}", e);
-                    // Exit if migrations fail, as the app is likely unusable.
-                    std::process::exit(1);
-                }
-            }
+    // Leptos configuration is read from Cargo.toml workspace metadata in the workspace root.
+    let conf = get_configuration(None)
+        .await
+        .map_err(|e| CommandError::Failed(format!("failed to load the Leptos configuration: {}", e)))?;
+    let mut leptos_options = conf.leptos_options;
+    if let Some(bind) = cli.bind {
+        leptos_options.site_addr = bind;
+    }
+    let addr = leptos_options.site_addr;
 
-            // Conditionally seed the database in development environments
-            let leptos_env = std::env::var("LEPTOS_ENV").unwrap_or_else(|_| "PROD".to_string());
-            if leptos_env == "DEV" {
-                logging::log!("🌱 Development environment detected (LEPTOS_ENV=DEV). Attempting to seed database...");
-                if let Err(e) = frontend::database::seed_database().await {
-                    logging::error!("Failed to seed database: {:?}", e);
-                    // Decide if this is a fatal error. For seeding, perhaps not.
-                } else {
-                    logging::log!("🌱 Automatic database seeding check completed.");
-                }
-            } else {
-                logging::log!("Production-like environment (LEPTOS_ENV is not DEV). Skipping database seeding.");
-            }
-        }
+    // generate_route_list uses the App from the frontend crate.
+    // Server functions defined in `frontend` are automatically registered.
+    let routes = generate_route_list(App);
+
+    let repos = pool.repositories();
+    let changes = ChangeFeed::default();
+    let state = AppState {
+        leptos_options: leptos_options.clone(),
+        pool: pool.clone(),
+        repos: repos.clone(),
+        changes: changes.clone(),
+        graphql: graphql::schema(),
+    };
+
+    // The GraphiQL playground is a development aid only.
+    let mut graphql_route = axum::routing::post(graphql::execute);
+    if cli.env == AppEnv::Dev {
+        graphql_route = graphql_route.get(graphql::playground);
+    }
+
+    let app = Router::new()
+        .route(frontend::live::CHANGE_EVENTS_PATH, axum::routing::get(change_events))
+        .route(graphql::GRAPHQL_PATH, graphql_route)
+        .nest(rest::API_PREFIX, rest::router())
+        .leptos_routes_with_context(
+            &state,
+            routes,
+            move || {
+                provide_context(repos.clone());
+                provide_context(changes.clone());
+            },
+            App,
+        )
+        .fallback_service(ServeDir::new(leptos_options.site_root.clone()))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), bearer_auth))
+        .with_state(state);
 
-        // Leptos configuration is read from Cargo.toml workspace metadata in the workspace root.
-        let conf = get_configuration(None).await.unwrap();
-        let leptos_options = conf.leptos_options;
-        let addr = leptos_options.site_addr;
-        
-        // generate_route_list uses the App from the frontend crate.
-        // Server functions defined in `frontend` are automatically registered.
-        let routes = generate_route_list(App);
-
-        let app = Router::new()
-            .leptos_routes(&leptos_options, routes, App)
-            .fallback_service(ServeDir::new(leptos_options.site_root.clone()))
-            .with_state(leptos_options);
-
-        logging::log!("listening on http://{}", &addr);
-        let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-        axum::serve(listener, app.into_make_service()).await.unwrap();
-    }).await;
-} 
\ No newline at end of file
+    logging::log!("listening on http://{} ({:?} database)", &addr, pool.backend());
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| CommandError::Failed(format!("failed to listen on {}: {}", addr, e)))?;
+    axum::serve(listener, app.into_make_service())
+        .await
+        .map_err(|e| CommandError::Failed(format!("server error: {}", e)))
+}
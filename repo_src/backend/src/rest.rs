@@ -0,0 +1,803 @@
+// Versioned REST API under API_PREFIX for clients that are not Leptos apps.
+// Server functions stay the UI's transport; this is a plain-JSON view of the
+// same repositories with standard verbs and status codes:
+//
+//     GET    /items                  POST /items           GET /items/{id}    DELETE /items/{id}
+//     GET    /books                  GET  /books/{id}
+//     GET    /technologies           GET  /technologies/{id}
+//     GET    /links                  POST /links           DELETE /links/{book_id}/{tech_id}
+//     GET    /graph                  GET  /openapi.json
+//
+// Lists return `{"data": [...], "total", "limit", "offset"}` and take `limit`
+// (default DEFAULT_LIMIT, at most MAX_LIMIT), `offset` and per-resource
+// filters; unknown query parameters are rejected rather than ignored. Errors
+// are `{"error": {"code", "message", "field"?}}` with the status code the
+// server functions use for the same `AppError`. Reads are public; writes need
+// the editor role, from a session cookie or a write-scoped API token.
+//
+// The OpenAPI 3 document served at /openapi.json is written by hand in
+// `openapi()`. The tests call every documented operation against `router()`,
+// check the responses against the documented schemas and check that no other
+// verb is routed on a documented path. Axum can't list its routes, so a route
+// on a new path is only caught by the operation count in that test: update
+// both when adding one.
+
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{FromRef, Path, Query, State};
+use axum::http::header::LOCATION;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use frontend::errors::AppError;
+use frontend::live::ChangeFeed;
+use frontend::repository::Repositories;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use shared::{Book, BookTechLink, ChangeEvent, GraphData, Item, LinkKind, Role, Technology, User};
+use std::fmt::Display;
+
+pub const API_PREFIX: &str = "/api/v1";
+
+pub const DEFAULT_LIMIT: usize = 100;
+pub const MAX_LIMIT: usize = 1_000;
+
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    Repositories: FromRef<S>,
+    ChangeFeed: FromRef<S>,
+{
+    Router::new()
+        .route("/items", get(list_items).post(create_item))
+        .route("/items/:id", get(get_item).delete(delete_item))
+        .route("/books", get(list_books))
+        .route("/books/:id", get(get_book))
+        .route("/technologies", get(list_technologies))
+        .route("/technologies/:id", get(get_technology))
+        .route("/links", get(list_links).post(create_link))
+        .route("/links/:book_id/:tech_id", axum::routing::delete(delete_link))
+        .route("/graph", get(get_graph))
+        .route("/openapi.json", get(openapi_json))
+}
+
+// An `AppError` as a JSON error response.
+pub struct ApiError(AppError);
+
+impl From<AppError> for ApiError {
+    fn from(err: AppError) -> Self {
+        ApiError(err)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let err = self.0;
+        leptos::logging::error!("REST request failed: {}", err);
+        let mut body = json!({ "code": err.code(), "message": err.to_string() });
+        if let AppError::Validation { field, .. } = &err {
+            body["field"] = json!(field);
+        }
+        (err.status_code(), Json(json!({ "error": body }))).into_response()
+    }
+}
+
+// Malformed query strings, paths and bodies are validation errors on that part
+// of the request, with axum's explanation as the message.
+fn invalid(part: &str, rejection: impl Display) -> ApiError {
+    AppError::validation(part, rejection.to_string()).into()
+}
+
+// The caller must be allowed to write as `role` (see `frontend::auth::Caller`).
+async fn writer(repos: &Repositories, parts: &Parts, role: Role) -> Result<User, ApiError> {
+    let caller = frontend::auth::request_caller(repos, parts)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("please log in".to_string()))?;
+    caller.require_write_role(role)?;
+    Ok(caller.user)
+}
+
+#[derive(Serialize)]
+struct Page<T> {
+    data: Vec<T>,
+    total: usize,
+    limit: usize,
+    offset: usize,
+}
+
+fn paginate<T>(rows: Vec<T>, limit: Option<usize>, offset: Option<usize>) -> Result<Page<T>, ApiError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::validation("limit", format!("must be between 1 and {}", MAX_LIMIT)).into());
+    }
+    let offset = offset.unwrap_or(0);
+    let total = rows.len();
+    let data = rows.into_iter().skip(offset).take(limit).collect();
+    Ok(Page { data, total, limit, offset })
+}
+
+fn contains_ignoring_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ItemParams {
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct NewItem {
+    text: String,
+}
+
+async fn list_items(
+    State(repos): State<Repositories>,
+    params: Result<Query<ItemParams>, QueryRejection>,
+) -> Result<Json<Page<Item>>, ApiError> {
+    let Query(params) = params.map_err(|e| invalid("query", e))?;
+    let items = repos.items.list_items().await?;
+    Ok(Json(paginate(items, params.limit, params.offset)?))
+}
+
+async fn get_item(State(repos): State<Repositories>, id: Result<Path<i64>, PathRejection>) -> Result<Json<Item>, ApiError> {
+    let Path(id) = id.map_err(|e| invalid("path", e))?;
+    let items = repos.items.list_items().await?;
+    let item = items.into_iter().find(|item| item.id == id);
+    Ok(Json(item.ok_or_else(|| AppError::NotFound(format!("Item with id {}", id)))?))
+}
+
+// Mirrors the AddItem server function.
+async fn create_item(
+    State(repos): State<Repositories>,
+    State(changes): State<ChangeFeed>,
+    parts: Parts,
+    body: Result<Json<NewItem>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Callers who may not write learn nothing about what they sent.
+    let user = writer(&repos, &parts, Role::Editor).await?;
+    let Json(NewItem { text }) = body.map_err(|e| invalid("body", e))?;
+    frontend::server_fns::validate_item_text(&text)?;
+    let item = repos.items.add_item(text, &user.username).await?;
+    changes.publish(ChangeEvent::ItemAdded { item: item.clone() });
+    let location = format!("{}/items/{}", API_PREFIX, item.id);
+    Ok((StatusCode::CREATED, [(LOCATION, location)], Json(item)))
+}
+
+// Mirrors the DeleteItem server function.
+async fn delete_item(
+    State(repos): State<Repositories>,
+    State(changes): State<ChangeFeed>,
+    parts: Parts,
+    id: Result<Path<i64>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let user = writer(&repos, &parts, Role::Editor).await?;
+    let Path(id) = id.map_err(|e| invalid("path", e))?;
+    repos.items.delete_item(id, &user.username).await?;
+    changes.publish(ChangeEvent::ItemDeleted { id });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BookParams {
+    limit: Option<usize>,
+    offset: Option<usize>,
+    // Title or author contains this, ignoring case.
+    search: Option<String>,
+    // Books featuring this technology.
+    tech_id: Option<i32>,
+}
+
+async fn list_books(
+    State(repos): State<Repositories>,
+    params: Result<Query<BookParams>, QueryRejection>,
+) -> Result<Json<Page<Book>>, ApiError> {
+    let Query(params) = params.map_err(|e| invalid("query", e))?;
+    let data = repos.graph.load_graph().await?;
+    let books = data
+        .books
+        .into_iter()
+        .filter(|b| params.search.as_deref().is_none_or(|s| contains_ignoring_case(&b.title, s) || contains_ignoring_case(&b.author, s)))
+        .filter(|b| params.tech_id.is_none_or(|t| data.links.iter().any(|l| l.book_id == b.id && l.tech_id == t)))
+        .collect();
+    Ok(Json(paginate(books, params.limit, params.offset)?))
+}
+
+async fn get_book(State(repos): State<Repositories>, id: Result<Path<i32>, PathRejection>) -> Result<Json<Book>, ApiError> {
+    let Path(id) = id.map_err(|e| invalid("path", e))?;
+    let data = repos.graph.load_graph().await?;
+    let book = data.books.into_iter().find(|b| b.id == id);
+    Ok(Json(book.ok_or_else(|| AppError::NotFound(format!("Book with id {}", id)))?))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TechnologyParams {
+    limit: Option<usize>,
+    offset: Option<usize>,
+    // Name contains this, ignoring case.
+    search: Option<String>,
+    // Exact category name.
+    category: Option<String>,
+    // Technologies featured in this book.
+    book_id: Option<i32>,
+}
+
+async fn list_technologies(
+    State(repos): State<Repositories>,
+    params: Result<Query<TechnologyParams>, QueryRejection>,
+) -> Result<Json<Page<Technology>>, ApiError> {
+    let Query(params) = params.map_err(|e| invalid("query", e))?;
+    let data = repos.graph.load_graph().await?;
+    let technologies = data
+        .technologies
+        .into_iter()
+        .filter(|t| params.search.as_deref().is_none_or(|s| contains_ignoring_case(&t.name, s)))
+        .filter(|t| params.category.as_ref().is_none_or(|c| &t.category == c))
+        .filter(|t| params.book_id.is_none_or(|b| data.links.iter().any(|l| l.book_id == b && l.tech_id == t.id)))
+        .collect();
+    Ok(Json(paginate(technologies, params.limit, params.offset)?))
+}
+
+async fn get_technology(State(repos): State<Repositories>, id: Result<Path<i32>, PathRejection>) -> Result<Json<Technology>, ApiError> {
+    let Path(id) = id.map_err(|e| invalid("path", e))?;
+    let data = repos.graph.load_graph().await?;
+    let technology = data.technologies.into_iter().find(|t| t.id == id);
+    Ok(Json(technology.ok_or_else(|| AppError::NotFound(format!("Technology with id {}", id)))?))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LinkParams {
+    limit: Option<usize>,
+    offset: Option<usize>,
+    book_id: Option<i32>,
+    tech_id: Option<i32>,
+}
+
+async fn list_links(
+    State(repos): State<Repositories>,
+    params: Result<Query<LinkParams>, QueryRejection>,
+) -> Result<Json<Page<BookTechLink>>, ApiError> {
+    let Query(params) = params.map_err(|e| invalid("query", e))?;
+    let data = repos.graph.load_graph().await?;
+    let links = data
+        .links
+        .into_iter()
+        .filter(|l| params.book_id.is_none_or(|b| l.book_id == b) && params.tech_id.is_none_or(|t| l.tech_id == t))
+        .collect();
+    Ok(Json(paginate(links, params.limit, params.offset)?))
+}
+
+async fn create_link(
+    State(repos): State<Repositories>,
+    State(changes): State<ChangeFeed>,
+    parts: Parts,
+    body: Result<Json<BookTechLink>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = writer(&repos, &parts, Role::Editor).await?;
+    let Json(link) = body.map_err(|e| invalid("body", e))?;
+    if let Some(problem) = link.problem() {
+        return Err(AppError::validation("body", problem).into());
+    }
+    repos.graph.add_link(link.clone(), &user.username).await?;
+    changes.publish(ChangeEvent::GraphChanged);
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+async fn delete_link(
+    State(repos): State<Repositories>,
+    State(changes): State<ChangeFeed>,
+    parts: Parts,
+    ids: Result<Path<(i32, i32)>, PathRejection>,
+) -> Result<StatusCode, ApiError> {
+    let user = writer(&repos, &parts, Role::Editor).await?;
+    let Path((book_id, tech_id)) = ids.map_err(|e| invalid("path", e))?;
+    repos.graph.remove_link(BookTechLink::new(book_id, tech_id), &user.username).await?;
+    changes.publish(ChangeEvent::GraphChanged);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_graph(State(repos): State<Repositories>) -> Result<Json<GraphData>, ApiError> {
+    Ok(Json(repos.graph.load_graph().await?))
+}
+
+async fn openapi_json() -> Json<Value> {
+    Json(openapi())
+}
+
+// --- OpenAPI document ---
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn json_content(schema: Value) -> Value {
+    json!({ "application/json": { "schema": schema } })
+}
+
+fn response(description: &str, schema: &str) -> Value {
+    json!({ "description": description, "content": json_content(schema_ref(schema)) })
+}
+
+fn error(description: &str) -> Value {
+    response(description, "Error")
+}
+
+fn query_param(name: &str, schema: Value, description: &str) -> Value {
+    json!({ "name": name, "in": "query", "required": false, "schema": schema, "description": description })
+}
+
+fn path_param(name: &str, format: &str) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "schema": { "type": "integer", "format": format } })
+}
+
+fn paging_params() -> Vec<Value> {
+    vec![
+        json!({ "$ref": "#/components/parameters/limit" }),
+        json!({ "$ref": "#/components/parameters/offset" }),
+    ]
+}
+
+fn list_operation(id: &str, tag: &str, summary: &str, page: &str, filters: Vec<Value>) -> Value {
+    let mut parameters = paging_params();
+    parameters.extend(filters);
+    json!({
+        "operationId": id,
+        "tags": [tag],
+        "summary": summary,
+        "parameters": parameters,
+        "responses": { "200": response("One page of results", page), "422": error("Invalid query parameters") },
+    })
+}
+
+fn get_operation(id: &str, tag: &str, summary: &str, schema: &str, params: Vec<Value>) -> Value {
+    json!({
+        "operationId": id,
+        "tags": [tag],
+        "summary": summary,
+        "parameters": params,
+        "responses": { "200": response("Found", schema), "404": error("No such resource"), "422": error("Invalid path") },
+    })
+}
+
+// Requires the editor role; `responses` are merged with the auth errors.
+fn write_operation(id: &str, tag: &str, summary: &str, params: Vec<Value>, body: Option<&str>, responses: Value) -> Value {
+    let mut operation = json!({
+        "operationId": id,
+        "tags": [tag],
+        "summary": summary,
+        "parameters": params,
+        "security": [{ "bearerToken": [] }, { "sessionCookie": [] }],
+        "responses": {
+            "401": error("Not signed in"),
+            "403": error("Needs the editor role and a write-scoped token"),
+            "422": error("Invalid input"),
+        },
+    });
+    if let Some(body) = body {
+        operation["requestBody"] = json!({ "required": true, "content": json_content(schema_ref(body)) });
+    }
+    for (status, value) in responses.as_object().into_iter().flatten() {
+        operation["responses"][status] = value.clone();
+    }
+    operation
+}
+
+fn object_schema(properties: Value) -> Value {
+    let required: Vec<&String> = properties.as_object().map(|p| p.keys().collect()).unwrap_or_default();
+    json!({ "type": "object", "required": required, "properties": properties })
+}
+
+fn page_schema(item: &str) -> Value {
+    object_schema(json!({
+        "data": { "type": "array", "items": schema_ref(item) },
+        "total": { "type": "integer", "description": "Rows matching the filters, across all pages" },
+        "limit": { "type": "integer" },
+        "offset": { "type": "integer" },
+    }))
+}
+
+pub fn openapi() -> Value {
+    let int32 = json!({ "type": "integer", "format": "int32" });
+    let string = json!({ "type": "string" });
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "TechVerse API",
+            "version": "1.0.0",
+            "description": "Items and the book–technology graph. Reads are public; writes need the editor role.",
+        },
+        "servers": [{ "url": API_PREFIX }],
+        "paths": {
+            "/items": {
+                "get": list_operation("listItems", "items", "List items, newest first", "ItemPage", vec![]),
+                "post": write_operation("createItem", "items", "Add an item", vec![], Some("NewItem"), json!({
+                    "201": response("Created; `Location` points at the new item", "Item"),
+                })),
+            },
+            "/items/{id}": {
+                "get": get_operation("getItem", "items", "One item", "Item", vec![path_param("id", "int64")]),
+                "delete": write_operation("deleteItem", "items", "Delete an item", vec![path_param("id", "int64")], None, json!({
+                    "204": { "description": "Deleted" },
+                    "404": error("No such item"),
+                })),
+            },
+            "/books": {
+                "get": list_operation("listBooks", "graph", "List books by id", "BookPage", vec![
+                    query_param("search", string.clone(), "Title or author contains this, ignoring case"),
+                    query_param("tech_id", int32.clone(), "Only books featuring this technology"),
+                ]),
+            },
+            "/books/{id}": {
+                "get": get_operation("getBook", "graph", "One book", "Book", vec![path_param("id", "int32")]),
+            },
+            "/technologies": {
+                "get": list_operation("listTechnologies", "graph", "List technologies by id", "TechnologyPage", vec![
+                    query_param("search", string.clone(), "Name contains this, ignoring case"),
+                    query_param("category", string.clone(), "Exact category name"),
+                    query_param("book_id", int32.clone(), "Only technologies featured in this book"),
+                ]),
+            },
+            "/technologies/{id}": {
+                "get": get_operation("getTechnology", "graph", "One technology", "Technology", vec![path_param("id", "int32")]),
+            },
+            "/links": {
+                "get": list_operation("listLinks", "graph", "List book–technology links", "LinkPage", vec![
+                    query_param("book_id", int32.clone(), "Only links from this book"),
+                    query_param("tech_id", int32.clone(), "Only links to this technology"),
+                ]),
+                "post": write_operation("createLink", "graph", "Link a book to a technology", vec![], Some("Link"), json!({
+                    "201": response("Created", "Link"),
+                    "404": error("Unknown book or technology"),
+                    "409": error("Already linked"),
+                })),
+            },
+            "/links/{book_id}/{tech_id}": {
+                "delete": write_operation(
+                    "deleteLink",
+                    "graph",
+                    "Unlink a book from a technology",
+                    vec![path_param("book_id", "int32"), path_param("tech_id", "int32")],
+                    None,
+                    json!({ "204": { "description": "Deleted" }, "404": error("No such link") }),
+                ),
+            },
+            "/graph": {
+                "get": {
+                    "operationId": "getGraph",
+                    "tags": ["graph"],
+                    "summary": "The whole dataset: books, technologies and links",
+                    "responses": { "200": response("The graph", "Graph") },
+                },
+            },
+            "/openapi.json": {
+                "get": {
+                    "operationId": "getOpenApi",
+                    "tags": ["meta"],
+                    "summary": "This document",
+                    "responses": { "200": { "description": "OpenAPI 3 document", "content": json_content(json!({ "type": "object" })) } },
+                },
+            },
+        },
+        "components": {
+            "parameters": {
+                "limit": {
+                    "name": "limit",
+                    "in": "query",
+                    "required": false,
+                    "schema": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT, "default": DEFAULT_LIMIT },
+                },
+                "offset": {
+                    "name": "offset",
+                    "in": "query",
+                    "required": false,
+                    "schema": { "type": "integer", "minimum": 0, "default": 0 },
+                },
+            },
+            "securitySchemes": {
+                "bearerToken": { "type": "http", "scheme": "bearer", "description": "A personal API token (`tv_...`)" },
+                "sessionCookie": { "type": "apiKey", "in": "cookie", "name": frontend::auth::SESSION_COOKIE },
+            },
+            "schemas": {
+                "Item": object_schema(json!({
+                    "id": { "type": "integer", "format": "int64" },
+                    "text": string,
+                    "created_at": { "type": "string", "description": "UTC, ISO 8601 without an offset" },
+                })),
+                "NewItem": object_schema(json!({ "text": { "type": "string", "maxLength": frontend::server_fns::ITEM_TEXT_MAX_LEN } })),
+                "Book": object_schema(json!({
+                    "id": int32,
+                    "title": string,
+                    "author": string,
+                    "series": { "type": "string", "description": "Empty when the book is not part of a series" },
+                    "year": { "type": "integer", "format": "int32", "nullable": true, "description": "Year of first publication, if known" },
+                })),
+                "Technology": object_schema(json!({
+                    "id": int32,
+                    "name": string,
+                    "category": string,
+                    "subcategory": string,
+                    "description": string,
+                    "taxonomy_id": { "type": "integer", "format": "int32", "nullable": true, "description": "The TaxonomyNode it is filed under, if any" },
+                })),
+                // Kind, weight and citation may be left out of a new link.
+                "Link": {
+                    "type": "object",
+                    "required": ["book_id", "tech_id"],
+                    "properties": {
+                        "book_id": int32,
+                        "tech_id": int32,
+                        "kind": {
+                            "type": "string",
+                            "enum": LinkKind::ALL.map(|k| k.as_str()),
+                            "default": LinkKind::default().as_str(),
+                            "description": "How the technology figures in the book",
+                        },
+                        "weight": {
+                            "type": "integer",
+                            "format": "int32",
+                            "minimum": 1,
+                            "maximum": BookTechLink::MAX_WEIGHT,
+                            "default": 1,
+                            "description": "Prominence in the book",
+                        },
+                        "citation": { "type": "string", "nullable": true, "description": "A quote or reference for the link" },
+                    },
+                },
+                "TaxonomyNode": object_schema(json!({
+                    "id": int32,
+                    "parent_id": { "type": "integer", "format": "int32", "nullable": true, "description": "Null for a category" },
+                    "name": string,
+                })),
+                "Graph": object_schema(json!({
+                    "books": { "type": "array", "items": schema_ref("Book") },
+                    "technologies": { "type": "array", "items": schema_ref("Technology") },
+                    "links": { "type": "array", "items": schema_ref("Link") },
+                    "taxonomy": { "type": "array", "items": schema_ref("TaxonomyNode") },
+                })),
+                "ItemPage": page_schema("Item"),
+                "BookPage": page_schema("Book"),
+                "TechnologyPage": page_schema("Technology"),
+                "LinkPage": page_schema("Link"),
+                "Error": object_schema(json!({
+                    "error": {
+                        "type": "object",
+                        "required": ["code", "message"],
+                        "properties": {
+                            "code": { "type": "string", "enum": ["NOT_FOUND", "VALIDATION", "CONFLICT", "INTERNAL", "UNAUTHORIZED", "FORBIDDEN"] },
+                            "message": string,
+                            "field": { "type": "string", "description": "The invalid field, for VALIDATION errors" },
+                        },
+                    },
+                })),
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Method, Request};
+    use frontend::auth::BearerPrincipal;
+    use shared::{ApiToken, TokenScope};
+    use tower::Service;
+
+    #[derive(Clone)]
+    struct TestState {
+        repos: Repositories,
+        changes: ChangeFeed,
+    }
+
+    impl FromRef<TestState> for Repositories {
+        fn from_ref(state: &TestState) -> Self {
+            state.repos.clone()
+        }
+    }
+
+    impl FromRef<TestState> for ChangeFeed {
+        fn from_ref(state: &TestState) -> Self {
+            state.changes.clone()
+        }
+    }
+
+    async fn app() -> (Router, Repositories) {
+        let repos = Repositories::in_memory();
+        frontend::database::seed_graph_if_empty(&repos).await.unwrap();
+        let router = router().with_state(TestState { repos: repos.clone(), changes: ChangeFeed::default() });
+        (router, repos)
+    }
+
+    // What the bearer middleware attaches for a valid API token.
+    fn editor_token(scope: TokenScope) -> BearerPrincipal {
+        let user = User { id: 1, username: "ada".into(), role: Role::Editor, created_at: Default::default() };
+        let token = ApiToken {
+            id: 1,
+            user_id: 1,
+            name: "tests".into(),
+            scope,
+            created_at: Default::default(),
+            last_used_at: None,
+            revoked_at: None,
+        };
+        BearerPrincipal { user, token }
+    }
+
+    async fn send(router: &mut Router, method: Method, uri: &str, body: Option<Value>, principal: Option<BearerPrincipal>) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if body.is_some() {
+            request = request.header("content-type", "application/json");
+        }
+        let mut request = request.body(body.map_or_else(Body::empty, |b| Body::from(b.to_string()))).unwrap();
+        if let Some(principal) = principal {
+            request.extensions_mut().insert(principal);
+        }
+        let response = router.call(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+    }
+
+    fn resolve<'a>(doc: &'a Value, schema: &'a Value) -> &'a Value {
+        match schema["$ref"].as_str() {
+            Some(reference) => &doc["components"]["schemas"][reference.rsplit('/').next().unwrap()],
+            None => schema,
+        }
+    }
+
+    // Just enough JSON Schema for the document above: types, required and
+    // declared properties (no extra ones), array items, enums and nullable.
+    fn check(doc: &Value, schema: &Value, value: &Value, at: &str) {
+        let schema = resolve(doc, schema);
+        if value.is_null() && schema["nullable"] == true {
+            return;
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            assert!(allowed.contains(value), "{}: {} is not one of {:?}", at, value, allowed);
+        }
+        match schema["type"].as_str() {
+            Some("object") => {
+                let object = value.as_object().unwrap_or_else(|| panic!("{}: expected an object, got {}", at, value));
+                for key in schema["required"].as_array().into_iter().flatten() {
+                    assert!(object.contains_key(key.as_str().unwrap()), "{}: missing {}", at, key);
+                }
+                if let Some(properties) = schema["properties"].as_object() {
+                    for (key, field) in object {
+                        let property = properties.get(key).unwrap_or_else(|| panic!("{}: undocumented field {}", at, key));
+                        check(doc, property, field, &format!("{}.{}", at, key));
+                    }
+                }
+            }
+            Some("array") => {
+                for (i, element) in value.as_array().unwrap_or_else(|| panic!("{}: expected an array", at)).iter().enumerate() {
+                    check(doc, &schema["items"], element, &format!("{}[{}]", at, i));
+                }
+            }
+            Some("integer") => assert!(value.is_i64(), "{}: expected an integer, got {}", at, value),
+            Some("string") => assert!(value.is_string(), "{}: expected a string, got {}", at, value),
+            _ => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document_matches_the_routes() {
+        let (mut router, repos) = app().await;
+        let doc = openapi();
+        let data = repos.graph.load_graph().await.unwrap();
+        let link = data.links[0].clone();
+        let unlinked = data.books.iter().find(|b| !data.links.iter().any(|l| l.book_id == b.id)).unwrap().id;
+        let item = repos.items.add_item("Documented".into(), "test").await.unwrap();
+
+        // Calls every documented operation with arguments that succeed, reads
+        // before writes on each path.
+        let mut calls = 0;
+        for (path, operations) in doc["paths"].as_object().unwrap() {
+            let mut operations: Vec<(&String, &Value)> = operations.as_object().unwrap().iter().collect();
+            operations.sort_by_key(|(method, _)| ["get", "post", "delete"].iter().position(|m| m == method));
+            for (method, operation) in operations {
+                let method: Method = method.to_uppercase().parse().unwrap();
+                let (uri, body) = match (&method, path.as_str()) {
+                    (&Method::POST, "/items") => (path.clone(), Some(json!({ "text": "Created" }))),
+                    (&Method::POST, "/links") => (path.clone(), Some(json!({ "book_id": unlinked, "tech_id": link.tech_id }))),
+                    (_, "/links/{book_id}/{tech_id}") => (format!("/links/{}/{}", link.book_id, link.tech_id), None),
+                    (_, "/books/{id}") => (format!("/books/{}", link.book_id), None),
+                    (_, "/technologies/{id}") => (format!("/technologies/{}", link.tech_id), None),
+                    (_, "/items/{id}") => (format!("/items/{}", item.id), None),
+                    _ => (path.clone(), None),
+                };
+                assert!(!uri.contains('{'), "no arguments for {} {}", method, path);
+                let principal = (method != Method::GET).then(|| editor_token(TokenScope::Write));
+                let (status, response) = send(&mut router, method.clone(), &uri, body, principal).await;
+
+                let documented = &operation["responses"][status.as_str()];
+                assert!(documented.is_object(), "{} {} returned undocumented {}: {}", method, path, status, response);
+                assert!(status.is_success(), "{} {} failed with {}: {}", method, path, status, response);
+                if let Some(schema) = documented["content"]["application/json"]["schema"].as_object() {
+                    check(&doc, &Value::Object(schema.clone()), &response, &format!("{} {}", method, path));
+                }
+                calls += 1;
+            }
+        }
+        // The number of method routes in `router()`.
+        assert_eq!(calls, 13);
+
+        // Undocumented verbs on documented paths are not routed.
+        for (path, operations) in doc["paths"].as_object().unwrap() {
+            let uri = path.replace("{id}", "1").replace("{book_id}", "1").replace("{tech_id}", "1");
+            for method in [Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+                if !operations.as_object().unwrap().contains_key(&method.as_str().to_lowercase()) {
+                    let (status, _) = send(&mut router, method.clone(), &uri, None, None).await;
+                    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED, "{} {} is routed but undocumented", method, path);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pagination_filters_and_error_bodies() {
+        let (mut router, repos) = app().await;
+        let data = repos.graph.load_graph().await.unwrap();
+        let doc = openapi();
+        let error_schema = schema_ref("Error");
+
+        let (status, page) = send(&mut router, Method::GET, "/books?limit=2&offset=1", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["total"], data.books.len());
+        assert_eq!(page["data"][0]["id"], data.books[1].id);
+        assert_eq!(page["data"].as_array().unwrap().len(), 2);
+
+        let tech = &data.technologies[0];
+        let uri = format!("/links?tech_id={}&limit={}", tech.id, MAX_LIMIT);
+        let (_, page) = send(&mut router, Method::GET, &uri, None, None).await;
+        assert_eq!(page["total"], data.links.iter().filter(|l| l.tech_id == tech.id).count());
+        let uri = format!("/technologies?category={}", tech.category.replace(' ', "%20").replace('&', "%26"));
+        let (_, page) = send(&mut router, Method::GET, &uri, None, None).await;
+        assert_eq!(page["total"], data.technologies.iter().filter(|t| t.category == tech.category).count());
+
+        // Error bodies, each with the documented shape.
+        let cases = [
+            (Method::GET, "/books?limit=0", StatusCode::UNPROCESSABLE_ENTITY, "VALIDATION"),
+            (Method::GET, "/books?colour=red", StatusCode::UNPROCESSABLE_ENTITY, "VALIDATION"),
+            (Method::GET, "/books/999999", StatusCode::NOT_FOUND, "NOT_FOUND"),
+            (Method::GET, "/items/abc", StatusCode::UNPROCESSABLE_ENTITY, "VALIDATION"),
+            (Method::DELETE, "/items/1", StatusCode::UNAUTHORIZED, "UNAUTHORIZED"),
+        ];
+        for (method, uri, expected, code) in cases {
+            let (status, body) = send(&mut router, method, uri, None, None).await;
+            assert_eq!((status, body["error"]["code"].as_str()), (expected, Some(code)), "{}: {}", uri, body);
+            check(&doc, &error_schema, &body, uri);
+        }
+        let (_, body) = send(&mut router, Method::GET, "/books?limit=0", None, None).await;
+        assert_eq!(body["error"]["field"], "limit");
+
+        // Writes follow the server functions' rules, and are authorized before
+        // their body or path is looked at.
+        let (status, _) = send(&mut router, Method::POST, "/items", Some(json!({ "text": " " })), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        for uri in ["/items/abc", "/links/abc/1"] {
+            let (status, _) = send(&mut router, Method::DELETE, uri, None, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+            let (status, body) = send(&mut router, Method::DELETE, uri, None, Some(editor_token(TokenScope::Write))).await;
+            assert_eq!((status, body["error"]["field"].as_str()), (StatusCode::UNPROCESSABLE_ENTITY, Some("path")), "{}", uri);
+        }
+        let read_only = Some(editor_token(TokenScope::Read));
+        let (status, _) = send(&mut router, Method::POST, "/items", Some(json!({ "text": " " })), read_only.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(&mut router, Method::POST, "/links", Some(json!({ "weight": 11 })), read_only).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let writer = || Some(editor_token(TokenScope::Write));
+        let (status, body) = send(&mut router, Method::POST, "/items", Some(json!({ "text": " " })), writer()).await;
+        assert_eq!((status, body["error"]["field"].as_str()), (StatusCode::UNPROCESSABLE_ENTITY, Some("text")));
+        let link = json!({ "book_id": data.links[0].book_id, "tech_id": data.links[0].tech_id });
+        let (status, _) = send(&mut router, Method::POST, "/links", Some(link), writer()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let heavy = json!({ "book_id": data.links[0].book_id, "tech_id": data.links[0].tech_id, "kind": "mentioned", "weight": 11 });
+        let (status, body) = send(&mut router, Method::POST, "/links", Some(heavy), writer()).await;
+        assert_eq!((status, body["error"]["field"].as_str()), (StatusCode::UNPROCESSABLE_ENTITY, Some("body")));
+    }
+}
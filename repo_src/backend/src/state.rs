@@ -0,0 +1,44 @@
+use axum::extract::FromRef;
+use frontend::database::DbPool;
+use frontend::live::ChangeFeed;
+use frontend::repository::Repositories;
+use leptos::LeptosOptions;
+
+use crate::graphql::GraphSchema;
+
+// Router state shared by every axum handler. `leptos_axum` needs to pull the
+// `LeptosOptions` out of it, and plain handlers (and middleware) can extract the
+// pool or the repositories directly. Server functions receive the same
+// repositories and change feed through Leptos context instead.
+#[derive(Clone)]
+pub struct AppState {
+    pub leptos_options: LeptosOptions,
+    pub pool: DbPool,
+    pub repos: Repositories,
+    pub changes: ChangeFeed,
+    pub graphql: GraphSchema,
+}
+
+impl FromRef<AppState> for LeptosOptions {
+    fn from_ref(state: &AppState) -> Self {
+        state.leptos_options.clone()
+    }
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Repositories {
+    fn from_ref(state: &AppState) -> Self {
+        state.repos.clone()
+    }
+}
+
+impl FromRef<AppState> for ChangeFeed {
+    fn from_ref(state: &AppState) -> Self {
+        state.changes.clone()
+    }
+}
@@ -0,0 +1,7 @@
+// `sqlx::migrate!` embeds the migration directories at compile time but cannot
+// tell cargo to watch them, so a newly added migration would otherwise be
+// missed until something else in the crate changed.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+    println!("cargo:rerun-if-changed=migrations_postgres");
+}
@@ -0,0 +1,23 @@
+-- Create tables for the sci-fi technology graph (books, technologies and the links between them)
+CREATE TABLE IF NOT EXISTS books (
+    id INTEGER PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    author TEXT NOT NULL,
+    series TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE IF NOT EXISTS technologies (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    category TEXT NOT NULL,
+    subcategory TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT ''
+);
+
+CREATE TABLE IF NOT EXISTS book_tech_links (
+    book_id INTEGER NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+    tech_id INTEGER NOT NULL REFERENCES technologies(id) ON DELETE CASCADE,
+    PRIMARY KEY (book_id, tech_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_book_tech_links_tech_id ON book_tech_links(tech_id);
@@ -0,0 +1,8 @@
+-- Immutable snapshots of the graph tables. `data` holds the whole dataset
+-- (books, technologies, links) serialized as JSON; rows are only ever inserted.
+CREATE TABLE IF NOT EXISTS graph_versions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    label TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    data TEXT NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Year of first publication, for the timeline on the graph page. Nullable:
+-- books added before it existed (or without a known year) leave it empty.
+ALTER TABLE books ADD COLUMN year INTEGER;
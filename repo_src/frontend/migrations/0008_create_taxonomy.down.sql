@@ -0,0 +1,2 @@
+ALTER TABLE technologies DROP COLUMN taxonomy_id;
+DROP TABLE IF EXISTS taxonomy_nodes;
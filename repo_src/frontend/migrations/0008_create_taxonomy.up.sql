@@ -0,0 +1,13 @@
+-- The technology taxonomy: categories at the root, subcategories below them,
+-- then any finer groupings. A technology points at the node it is filed
+-- under; without one it sits under its category and subcategory. The column
+-- has no foreign key so that it can be dropped again; `validate_graph` checks it.
+CREATE TABLE IF NOT EXISTS taxonomy_nodes (
+    id INTEGER PRIMARY KEY NOT NULL,
+    parent_id INTEGER REFERENCES taxonomy_nodes(id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_taxonomy_nodes_parent_id ON taxonomy_nodes(parent_id);
+
+ALTER TABLE technologies ADD COLUMN taxonomy_id INTEGER;
@@ -0,0 +1,3 @@
+ALTER TABLE book_tech_links DROP COLUMN citation;
+ALTER TABLE book_tech_links DROP COLUMN weight;
+ALTER TABLE book_tech_links DROP COLUMN kind;
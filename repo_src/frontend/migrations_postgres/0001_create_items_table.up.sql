@@ -0,0 +1,6 @@
+-- Create items table (PostgreSQL counterpart of migrations/0001_create_items_table.sql)
+CREATE TABLE IF NOT EXISTS items (
+    id BIGSERIAL PRIMARY KEY,
+    text TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
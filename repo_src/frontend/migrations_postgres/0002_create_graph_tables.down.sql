@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS book_tech_links;
+DROP TABLE IF EXISTS technologies;
+DROP TABLE IF EXISTS books;
@@ -0,0 +1,7 @@
+-- Immutable snapshots of the graph tables (PostgreSQL counterpart of migrations/0003_create_graph_versions.sql)
+CREATE TABLE IF NOT EXISTS graph_versions (
+    id BIGSERIAL PRIMARY KEY,
+    label TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    data TEXT NOT NULL
+);
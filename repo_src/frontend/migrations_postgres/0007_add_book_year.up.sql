@@ -0,0 +1,2 @@
+-- Year of first publication (PostgreSQL counterpart of migrations/0007_add_book_year.sql)
+ALTER TABLE books ADD COLUMN year INTEGER;
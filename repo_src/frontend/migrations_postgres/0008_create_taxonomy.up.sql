@@ -0,0 +1,10 @@
+-- The technology taxonomy (PostgreSQL counterpart of migrations/0008_create_taxonomy.sql)
+CREATE TABLE IF NOT EXISTS taxonomy_nodes (
+    id INTEGER PRIMARY KEY,
+    parent_id INTEGER REFERENCES taxonomy_nodes(id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_taxonomy_nodes_parent_id ON taxonomy_nodes(parent_id);
+
+ALTER TABLE technologies ADD COLUMN taxonomy_id INTEGER;
@@ -0,0 +1,8 @@
+-- How a technology figures in a book: the kind of relationship (stored as the
+-- snake_case names of `shared::LinkKind`), its prominence from 1 to 10 and an
+-- optional quote or page reference. Existing links become background details
+-- of weight 1, so weighted counts over them stay what they were.
+ALTER TABLE book_tech_links ADD COLUMN kind TEXT NOT NULL DEFAULT 'background_detail'
+    CHECK (kind IN ('central_premise', 'background_detail', 'mentioned'));
+ALTER TABLE book_tech_links ADD COLUMN weight INTEGER NOT NULL DEFAULT 1 CHECK (weight BETWEEN 1 AND 10);
+ALTER TABLE book_tech_links ADD COLUMN citation TEXT;
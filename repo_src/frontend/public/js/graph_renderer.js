@@ -1,6 +1,10 @@
 var network = null;
 
-function renderTechGraph(containerId, nodesJson, edgesJson) {
+// `onNodeClick`, if given, is called with the id of each clicked node.
+// `view` ({focus, viewport}) is restored once the layout settles, and
+// `onViewChange`, if given, is called with {x, y, scale} after the user
+// stops zooming or panning.
+function renderTechGraph(containerId, nodesJson, edgesJson, onNodeClick, view, onViewChange) {
     var container = document.getElementById(containerId);
     if (!container) {
         console.error("Graph container not found:", containerId);
@@ -40,6 +44,54 @@ function renderTechGraph(containerId, nodesJson, edgesJson) {
                 borderWidth: 3,
                 shadow: true,
             },
+            CategoryHighlighted: {
+                shape: "diamond",
+                size: 34,
+                color: {
+                    background: "#ff6b6b",
+                    border: "#e55454"
+                },
+                font: {
+                    size: 17,
+                    color: "#ffffff",
+                    strokeWidth: 2,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 3,
+                shadow: true,
+            },
+            Subcategory: {
+                shape: "diamond",
+                size: 22,
+                color: {
+                    background: "#90caf9",
+                    border: "#4a90e2"
+                },
+                font: {
+                    size: 13,
+                    color: "#ffffff",
+                    strokeWidth: 2,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 2,
+                shadow: true,
+            },
+            SubcategoryHighlighted: {
+                shape: "diamond",
+                size: 26,
+                color: {
+                    background: "#ff6b6b",
+                    border: "#e55454"
+                },
+                font: {
+                    size: 14,
+                    color: "#ffffff",
+                    strokeWidth: 2,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 3,
+                shadow: true,
+            },
             Technology: {
                 shape: "dot",
                 size: 20,
@@ -88,6 +140,86 @@ function renderTechGraph(containerId, nodesJson, edgesJson) {
                 borderWidth: 2,
                 shadow: true,
             },
+            Author: {
+                shape: "triangle",
+                size: 18,
+                color: {
+                    background: "#26a69a",
+                    border: "#00796b"
+                },
+                font: {
+                    size: 12,
+                    color: "#ffffff",
+                    strokeWidth: 1,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 2,
+                shadow: true,
+            },
+            AuthorHighlighted: {
+                shape: "triangle",
+                size: 22,
+                color: {
+                    background: "#ff6b6b",
+                    border: "#e55454"
+                },
+                font: {
+                    size: 13,
+                    color: "#ffffff",
+                    strokeWidth: 2,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 3,
+                shadow: true,
+            },
+            Series: {
+                shape: "hexagon",
+                size: 18,
+                color: {
+                    background: "#8d6e63",
+                    border: "#5d4037"
+                },
+                font: {
+                    size: 12,
+                    color: "#ffffff",
+                    strokeWidth: 1,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 2,
+                shadow: true,
+            },
+            SeriesHighlighted: {
+                shape: "hexagon",
+                size: 22,
+                color: {
+                    background: "#ff6b6b",
+                    border: "#e55454"
+                },
+                font: {
+                    size: 13,
+                    color: "#ffffff",
+                    strokeWidth: 2,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 3,
+                shadow: true,
+            },
+            MoreBooks: {
+                shape: "box",
+                size: 15,
+                color: {
+                    background: "#9e9e9e",
+                    border: "#757575"
+                },
+                font: {
+                    size: 10,
+                    color: "#ffffff",
+                    strokeWidth: 1,
+                    strokeColor: "#000000"
+                },
+                borderWidth: 1,
+                shapeProperties: { borderDashes: [4, 4] },
+            },
             BookHighlighted: {
                 shape: "box",
                 size: 18,
@@ -115,9 +247,19 @@ function renderTechGraph(containerId, nodesJson, edgesJson) {
             },
             borderWidth: 2,
             shadow: true,
+            // Cluster nodes carry a `value` (their book count).
+            scaling: {
+                min: 16,
+                max: 60,
+            },
         },
         edges: {
             width: 2,
+            // Bundled edges carry a `value` (the links they stand for).
+            scaling: {
+                min: 1,
+                max: 12,
+            },
             color: {
                 color: "#848484",
                 highlight: "#c8c8c8",
@@ -159,4 +301,52 @@ function renderTechGraph(containerId, nodesJson, edgesJson) {
     };
 
     network = new vis.Network(container, data, options);
+
+    if (onNodeClick) {
+        network.on("click", function (params) {
+            if (params.nodes.length > 0) {
+                onNodeClick(String(params.nodes[0]));
+            }
+        });
+    }
+
+    // Later renders replace `network`; callbacks below only act on this one.
+    var shown = network;
+
+    if (view) {
+        network.once("stabilizationIterationsDone", function () {
+            if (shown !== network) {
+                return;
+            }
+            var focus = view.focus && nodes.get(view.focus) ? view.focus : null;
+            if (focus) {
+                network.selectNodes([focus]);
+            }
+            if (view.viewport) {
+                network.moveTo({
+                    position: { x: view.viewport.x, y: view.viewport.y },
+                    scale: view.viewport.scale,
+                });
+            } else if (focus) {
+                network.focus(focus, { scale: 1.2 });
+            }
+        });
+    }
+
+    if (onViewChange) {
+        // Zooming fires per wheel step; report once things stay still.
+        var timer = null;
+        var report = function () {
+            clearTimeout(timer);
+            timer = setTimeout(function () {
+                if (shown !== network) {
+                    return;
+                }
+                var position = network.getViewPosition();
+                onViewChange({ x: position.x, y: position.y, scale: network.getScale() });
+            }, 300);
+        };
+        network.on("zoom", report);
+        network.on("dragEnd", report);
+    }
 } 
\ No newline at end of file
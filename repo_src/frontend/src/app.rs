@@ -2,14 +2,22 @@ use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
 
+use crate::auth::AuthContext;
 use crate::components::nav::NavBar;
+use crate::pages::activity_page::ActivityPage;
+use crate::pages::authors_page::AuthorsPage;
 use crate::pages::home::HomePage;
+use crate::pages::login_page::{LoginPage, LogoutPage};
+use crate::pages::query_page::QueryPage;
 use crate::pages::tech_graph_page::TechGraphPage;
+use crate::pages::tokens_page::TokensPage;
 
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
-    
+    // Current user, login and logout, for the NavBar and the login/logout pages.
+    AuthContext::provide();
+
     view! {
         <Title text="Sci-Fi Tech-Verse"/>
         <Meta name="description" content="A visualization of technologies in science fiction."/>
@@ -21,6 +29,12 @@ pub fn App() -> impl IntoView {
                 <Routes>
                     <Route path="" view=HomePage />
                     <Route path="/tech-graph" view=TechGraphPage />
+                    <Route path="/query" view=QueryPage />
+                    <Route path="/authors" view=AuthorsPage />
+                    <Route path="/activity" view=ActivityPage />
+                    <Route path="/login" view=LoginPage />
+                    <Route path="/logout" view=LogoutPage />
+                    <Route path="/tokens" view=TokensPage />
                 </Routes>
             </main>
         </Router>
@@ -0,0 +1,301 @@
+// Local accounts: username/password validation (shared with the login form),
+// and on the server the password hashing, session tokens and session cookie.
+// Session tokens are random and only their sha256 hash is stored, so a leaked
+// `sessions` table cannot be replayed.
+
+use leptos::*;
+use shared::{Role, User};
+
+use crate::errors::AppError;
+use crate::server_fns::{current_user_info, Login, Logout};
+
+pub const USERNAME_MIN_LEN: usize = 3;
+pub const USERNAME_MAX_LEN: usize = 32;
+pub const PASSWORD_MIN_LEN: usize = 8;
+pub const TOKEN_NAME_MAX_LEN: usize = 64;
+
+pub fn validate_username(username: &str) -> Result<(), AppError> {
+    let len = username.chars().count();
+    if !(USERNAME_MIN_LEN..=USERNAME_MAX_LEN).contains(&len) {
+        return Err(AppError::validation(
+            "username",
+            format!("Username must be {} to {} characters.", USERNAME_MIN_LEN, USERNAME_MAX_LEN),
+        ));
+    }
+    if !username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(AppError::validation("username", "Username may only contain letters, digits, '_' and '-'."));
+    }
+    Ok(())
+}
+
+pub fn validate_password(password: &str) -> Result<(), AppError> {
+    if password.chars().count() < PASSWORD_MIN_LEN {
+        return Err(AppError::validation(
+            "password",
+            format!("Password must be at least {} characters.", PASSWORD_MIN_LEN),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_token_name(name: &str) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::validation("name", "Token name cannot be empty."));
+    }
+    if name.chars().count() > TOKEN_NAME_MAX_LEN {
+        return Err(AppError::validation("name", format!("Token name too long (max {} chars).", TOKEN_NAME_MAX_LEN)));
+    }
+    Ok(())
+}
+
+// Who is signed in, shared through context by `App`. The user resource is
+// refetched whenever a login or logout completes.
+#[derive(Clone, Copy)]
+pub struct AuthContext {
+    pub login: Action<Login, Result<User, ServerFnError<AppError>>>,
+    pub logout: Action<Logout, Result<(), ServerFnError<AppError>>>,
+    pub user: Resource<(usize, usize), Option<User>>,
+}
+
+impl AuthContext {
+    pub fn provide() -> Self {
+        let login = create_server_action::<Login>();
+        let logout = create_server_action::<Logout>();
+        let user = create_resource(
+            move || (login.version().get(), logout.version().get()),
+            |_| async move { current_user_info().await.ok().flatten() },
+        );
+        let auth = AuthContext { login, logout, user };
+        provide_context(auth);
+        auth
+    }
+
+    // Whether the signed-in user has at least `role`. Only used to adjust the
+    // UI; the server functions do their own checks.
+    pub fn has_role(&self, role: Role) -> bool {
+        self.user.get().flatten().is_some_and(|user| user.role >= role)
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use server::*;
+
+#[cfg(feature = "ssr")]
+mod server {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+    use argon2::Argon2;
+    use chrono::Duration;
+    use rand::RngCore;
+    use sha2::{Digest, Sha256};
+
+    use shared::{ApiToken, Role, TokenScope, User};
+
+    use crate::errors::AppError;
+    use crate::repository::Repositories;
+
+    pub const SESSION_COOKIE: &str = "techverse_session";
+
+    pub fn session_ttl() -> Duration {
+        Duration::days(7)
+    }
+
+    // PHC string (algorithm, parameters and salt included), as stored in `users.password_hash`.
+    pub fn hash_password(password: &str) -> Result<String, AppError> {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AppError::Storage(format!("failed to hash password: {}", e)))
+    }
+
+    // A malformed stored hash counts as a mismatch rather than an error.
+    pub fn verify_password(password: &str, stored_hash: &str) -> bool {
+        PasswordHash::new(stored_hash)
+            .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false)
+    }
+
+    // Verified instead of a stored hash when the username is unknown, so an
+    // unknown user takes as long to refuse as a wrong password.
+    pub fn dummy_password_hash() -> &'static str {
+        static HASH: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+        HASH.get_or_init(|| hash_password("not anyone's password").expect("hashing a fixed password"))
+    }
+
+    // 256 random bits, hex encoded. Handed to the client once and never stored.
+    pub fn new_session_token() -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    pub fn hash_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    pub fn session_cookie(token: &str) -> String {
+        cookie(token, session_ttl().num_seconds(), dev_server())
+    }
+
+    // Expires the cookie immediately in the browser.
+    pub fn clear_session_cookie() -> String {
+        cookie("", 0, dev_server())
+    }
+
+    // The backend sets LEPTOS_ENV from --env before serving.
+    fn dev_server() -> bool {
+        std::env::var("LEPTOS_ENV").is_ok_and(|env| env.eq_ignore_ascii_case("dev"))
+    }
+
+    // Secure everywhere except on a DEV server, which is usually plain http.
+    pub(crate) fn cookie(value: &str, max_age: i64, dev: bool) -> String {
+        let secure = if dev { "" } else { " Secure;" };
+        format!("{}={}; HttpOnly;{} SameSite=Lax; Path=/; Max-Age={}", SESSION_COOKIE, value, secure, max_age)
+    }
+
+    // API tokens carry a prefix so they are recognisable in scripts and secret scanners.
+    pub const API_TOKEN_PREFIX: &str = "tv_";
+
+    pub fn new_api_token() -> String {
+        format!("{}{}", API_TOKEN_PREFIX, new_session_token())
+    }
+
+    // Set as a request extension by the backend's bearer-auth middleware once an
+    // `Authorization: Bearer` token checks out; server functions read it from
+    // the request parts.
+    #[derive(Clone, Debug)]
+    pub struct BearerPrincipal {
+        pub user: User,
+        pub token: ApiToken,
+    }
+
+    // The token from an `Authorization` header value, if it uses the Bearer scheme.
+    pub fn bearer_token(header: &str) -> Option<&str> {
+        let (scheme, token) = header.trim().split_once(' ')?;
+        let token = token.trim();
+        (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+    }
+
+    // Looks the token up by hash (recording its use). None for unknown or revoked tokens.
+    pub async fn authenticate_api_token(repos: &Repositories, token: &str) -> Result<Option<BearerPrincipal>, AppError> {
+        let found = repos.tokens.authenticate_token(&hash_token(token), chrono::Utc::now().naive_utc()).await?;
+        Ok(found.map(|(user, token)| BearerPrincipal { user, token }))
+    }
+
+    // The session token from a `Cookie` request header, if present.
+    pub fn session_token_from_cookies(header: &str) -> Option<&str> {
+        header
+            .split(';')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == SESSION_COOKIE)
+            .map(|(_, value)| value)
+            .filter(|value| !value.is_empty())
+    }
+
+    // The session token from the request's cookies, if any.
+    pub fn request_session_token(parts: &http::request::Parts) -> Option<&str> {
+        parts.headers.get_all(http::header::COOKIE).iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(session_token_from_cookies)
+    }
+
+    // Who is making a request. Browser sessions act with the user's full role;
+    // API tokens are further limited by their scope.
+    #[derive(Clone, Debug)]
+    pub struct Caller {
+        pub user: User,
+        // None for a browser session.
+        pub token_scope: Option<TokenScope>,
+    }
+
+    impl Caller {
+        // Roles are ordered (viewer < editor < admin), so a higher role
+        // satisfies a lower one.
+        pub fn require_role(&self, role: Role) -> Result<(), AppError> {
+            if self.user.role < role {
+                return Err(AppError::Forbidden(format!("requires the {} role", role.as_str())));
+            }
+            Ok(())
+        }
+
+        // As `require_role`, but read-scoped API tokens are refused.
+        pub fn require_write_role(&self, role: Role) -> Result<(), AppError> {
+            self.require_role(role)?;
+            if self.token_scope == Some(TokenScope::Read) {
+                return Err(AppError::Forbidden("this API token is read-only".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    // The caller behind a request: the API token principal attached by the
+    // backend's bearer middleware, or else the user of the session cookie.
+    pub async fn request_caller(repos: &Repositories, parts: &http::request::Parts) -> Result<Option<Caller>, AppError> {
+        if let Some(principal) = parts.extensions.get::<BearerPrincipal>() {
+            return Ok(Some(Caller { user: principal.user.clone(), token_scope: Some(principal.token.scope) }));
+        }
+        let user = match request_session_token(parts) {
+            Some(token) => repos.users.session_user(&hash_token(token), chrono::Utc::now().naive_utc()).await?,
+            None => None,
+        };
+        Ok(user.map(|user| Caller { user, token_scope: None }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_username_and_password_validation() {
+        assert!(validate_username("ada_l-2").is_ok());
+        assert!(validate_username("ab").unwrap_err().field_message("username").is_some());
+        assert!(validate_username("has space").is_err());
+        assert!(validate_username(&"x".repeat(USERNAME_MAX_LEN + 1)).is_err());
+        assert!(validate_password("longenough").is_ok());
+        assert!(validate_password("short").unwrap_err().field_message("password").is_some());
+        assert!(validate_token_name("nightly import").is_ok());
+        assert!(validate_token_name(" ").unwrap_err().field_message("name").is_some());
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_password_hash_round_trip() {
+        let hash = hash_password("correct horse").unwrap();
+        assert!(hash.starts_with("$argon2"));
+        assert_ne!(hash, hash_password("correct horse").unwrap(), "each hash gets its own salt");
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+        assert!(!verify_password("", dummy_password_hash()));
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_session_tokens_and_cookies() {
+        let token = new_session_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_session_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), token);
+
+        let header = format!("theme=dark; {}={}; other=1", SESSION_COOKIE, token);
+        assert_eq!(session_token_from_cookies(&header), Some(token.as_str()));
+        assert_eq!(session_token_from_cookies("theme=dark"), None);
+        assert_eq!(session_token_from_cookies(&clear_session_cookie()), None);
+        assert!(session_cookie(&token).contains("HttpOnly"));
+        assert_eq!(cookie(&token, 60, false), format!("{}={}; HttpOnly; Secure; SameSite=Lax; Path=/; Max-Age=60", SESSION_COOKIE, token));
+        assert!(!cookie(&token, 60, true).contains("Secure"));
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_bearer_header_parsing() {
+        let token = new_api_token();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(bearer_token(&format!("Bearer {}", token)), Some(token.as_str()));
+        assert_eq!(bearer_token("bearer  abc "), Some("abc"));
+        assert_eq!(bearer_token("Basic dXNlcjpwdw=="), None);
+        assert_eq!(bearer_token("Bearer "), None);
+    }
+}
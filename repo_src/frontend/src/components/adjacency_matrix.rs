@@ -0,0 +1,162 @@
+use leptos::*;
+use shared::{BookTechLink, LinkKind};
+use std::collections::{BTreeSet, HashMap};
+use crate::graph_charts::{move_item, AdjacencyMatrix, AxisItem, AxisOrder, MAX_MATRIX_BOOKS, MAX_MATRIX_TECHNOLOGIES};
+
+// Geometry of the matrix, in SVG user units.
+const CELL: f64 = 14.0;
+const ROW_LABEL_WIDTH: f64 = 220.0;
+const COLUMN_LABEL_HEIGHT: f64 = 150.0;
+
+// Where a header drag started: the axis (true for books) and the position.
+type DragStart = Option<(bool, usize)>;
+
+// Books × technologies, each link a cell shaded by its weight. Either axis
+// sorts by name, group or links; clicking a header sorts the other axis by
+// its links, and dragging a header onto another moves it there.
+#[component]
+pub fn AdjacencyMatrixChart(
+    matrix: AdjacencyMatrix,
+    // Books the filter matches, whose rows are highlighted.
+    #[prop(into)]
+    matching: Signal<BTreeSet<i32>>,
+) -> impl IntoView {
+    let matrix = store_value(matrix);
+    let book_order = create_rw_signal(matrix.with_value(|m| m.sorted(true, AxisOrder::Name)));
+    let tech_order = create_rw_signal(matrix.with_value(|m| m.sorted(false, AxisOrder::Name)));
+    let (dragging, set_dragging) = create_signal::<DragStart>(None);
+
+    let sort = move |books: bool, order: AxisOrder| {
+        let ids = matrix.with_value(|m| m.sorted(books, order));
+        if books { book_order.set(ids) } else { tech_order.set(ids) }
+    };
+    // A press and release on the same header sorts the other axis by it;
+    // releasing on another header of the same axis moves the first one there.
+    let release = move |books: bool, to: usize, id: i32| {
+        match dragging.get_untracked() {
+            Some((axis, from)) if axis == books && from != to => {
+                let order = if books { book_order } else { tech_order };
+                order.update(|ids| move_item(ids, from, to));
+            }
+            Some((axis, _)) if axis == books => sort(!books, AxisOrder::Along(id)),
+            _ => {}
+        }
+        set_dragging.set(None);
+    };
+
+    let order_picker = move |books: bool, id: &'static str, label: &'static str| view! {
+        <div class="control-group">
+            <label for=id>{label}</label>
+            <select
+                id=id
+                on:change=move |ev| {
+                    if let Some(order) = AxisOrder::parse(&event_target_value(&ev)) {
+                        sort(books, order);
+                    }
+                }
+            >
+                <option value="name">"Name"</option>
+                <option value="group">{if books { "Year" } else { "Category" }}</option>
+                <option value="links">"Most links"</option>
+            </select>
+        </div>
+    };
+
+    let (total_books, total_technologies) = matrix.with_value(|m| (m.books.len(), m.technologies.len()));
+    let chart = move || {
+        let rows: Vec<i32> = book_order.get().into_iter().take(MAX_MATRIX_BOOKS).collect();
+        let columns: Vec<i32> = tech_order.get().into_iter().take(MAX_MATRIX_TECHNOLOGIES).collect();
+        let width = ROW_LABEL_WIDTH + columns.len() as f64 * CELL;
+        let height = COLUMN_LABEL_HEIGHT + rows.len() as f64 * CELL;
+        matrix.with_value(|m| {
+            let labels = |items: &[AxisItem]| -> HashMap<i32, (String, String)> {
+                items.iter().map(|i| (i.id, (i.label.clone(), i.group.clone()))).collect()
+            };
+            let (book_labels, tech_labels) = (labels(&m.books), labels(&m.technologies));
+            let headers = columns.iter().enumerate().map(|(col, &tech_id)| {
+                let (name, category) = tech_labels[&tech_id].clone();
+                let x = ROW_LABEL_WIDTH + (col as f64 + 0.5) * CELL;
+                let y = COLUMN_LABEL_HEIGHT - 6.0;
+                view! {
+                    <text
+                        class="matrix-header"
+                        x=x
+                        y=y
+                        transform=format!("rotate(-60 {:.1} {:.1})", x, y)
+                        on:pointerdown=move |_| set_dragging.set(Some((false, col)))
+                        on:pointerup=move |_| release(false, col, tech_id)
+                    >
+                        <title>{format!("{} ({}): click to sort the books by it, drag to move it", name, category)}</title>
+                        {name.clone()}
+                    </text>
+                }
+            }).collect_view();
+            let body = rows.iter().enumerate().map(|(row, &book_id)| {
+                let (title, author) = book_labels[&book_id].clone();
+                let y = COLUMN_LABEL_HEIGHT + row as f64 * CELL;
+                let cells = columns.iter().enumerate().filter_map(|(col, tech_id)| {
+                    let (kind, weight) = *m.cells.get(&(book_id, *tech_id))?;
+                    let class = match kind {
+                        LinkKind::CentralPremise => "matrix-cell central",
+                        LinkKind::BackgroundDetail => "matrix-cell background",
+                        LinkKind::Mentioned => "matrix-cell mentioned",
+                    };
+                    let opacity = 0.25 + 0.75 * weight as f64 / BookTechLink::MAX_WEIGHT as f64;
+                    Some(view! {
+                        <rect class=class x=ROW_LABEL_WIDTH + col as f64 * CELL + 1.0 y=y + 1.0 width=CELL - 2.0 height=CELL - 2.0 fill-opacity=opacity>
+                            <title>{format!("{} · {}: {}, weight {}", title, tech_labels[tech_id].0, kind.label(), weight)}</title>
+                        </rect>
+                    })
+                }).collect_view();
+                view! {
+                    <g class="matrix-row" class:matching=move || matching.with(|m| m.contains(&book_id))>
+                        <rect class="row-band" x=0 y=y width=width height=CELL/>
+                        <text
+                            class="matrix-header"
+                            x=ROW_LABEL_WIDTH - 6.0
+                            y=y + CELL - 3.0
+                            text-anchor="end"
+                            on:pointerdown=move |_| set_dragging.set(Some((true, row)))
+                            on:pointerup=move |_| release(true, row, book_id)
+                        >
+                            <title>{format!("{} by {}: click to sort the technologies by it, drag to move it", title, author)}</title>
+                            {title.clone()}
+                        </text>
+                        {cells}
+                    </g>
+                }
+            }).collect_view();
+            view! {
+                <svg
+                    class="adjacency-matrix"
+                    viewBox=format!("0 0 {} {}", width, height)
+                    width=width
+                    height=height
+                    role="img"
+                    aria-label="Which books feature which technologies"
+                    on:pointerleave=move |_| set_dragging.set(None)
+                >
+                    {headers}
+                    {body}
+                </svg>
+            }
+        })
+    };
+
+    view! {
+        <div class="card graph-chart">
+            <div class="graph-controls">
+                {order_picker(true, "matrix-rows", "Sort books by:")}
+                {order_picker(false, "matrix-columns", "Sort technologies by:")}
+            </div>
+            <div class="matrix-scroll">{chart}</div>
+            {(total_books > MAX_MATRIX_BOOKS).then(|| view! {
+                <p class="hint">{format!("Showing the first {} of {} books in this order.", MAX_MATRIX_BOOKS, total_books)}</p>
+            })}
+            {(total_technologies > MAX_MATRIX_TECHNOLOGIES).then(|| view! {
+                <p class="hint">{format!("Showing the first {} of {} technologies in this order.", MAX_MATRIX_TECHNOLOGIES, total_technologies)}</p>
+            })}
+            <p class="hint">"Darker cells are heavier links: purple for a central premise, blue for a background detail, grey for a mention."</p>
+        </div>
+    }
+}
@@ -0,0 +1,84 @@
+use leptos::*;
+use std::collections::BTreeSet;
+use crate::components::tech_graph_view::GROUP_PALETTE;
+use crate::graph_charts::{arc_path, chord_layout, polar, ribbon_path, CoOccurrence, CHORD_GAP};
+use crate::graph_filter::FilterChip;
+
+// Geometry of the diagram, in SVG user units, centred on the origin.
+const RADIUS: f64 = 200.0;
+const RING: f64 = 16.0;
+const LABEL_OFFSET: f64 = 12.0;
+const EXTENT: f64 = 380.0;
+
+// Which categories share books: each category an arc as wide as the books it
+// shares, and a ribbon between two categories as wide as the books featuring
+// both. Hovering a category fades the ribbons not touching it; clicking it
+// adds it to the filter.
+#[component]
+pub fn ChordDiagram(
+    cooccurrence: CoOccurrence,
+    // Categories in the filter, whose arcs are outlined.
+    selected: BTreeSet<String>,
+    on_pick: Callback<FilterChip>,
+) -> impl IntoView {
+    let layout = chord_layout(&cooccurrence, CHORD_GAP);
+    let CoOccurrence { categories, books } = cooccurrence;
+    if layout.ribbons.is_empty() {
+        return view! { <p class="hint">"No book features technologies from two categories yet."</p> }.into_view();
+    }
+    let color = |i: usize| GROUP_PALETTE[i % GROUP_PALETTE.len()];
+    let (hovered, set_hovered) = create_signal::<Option<usize>>(None);
+
+    let ribbons = layout.ribbons.iter().map(|ribbon| {
+        let (from, to) = (ribbon.from, ribbon.to);
+        let faded = move || hovered.get().is_some_and(|h| h != from && h != to);
+        view! {
+            <path class="chord-ribbon" class:faded=faded d=ribbon_path(RADIUS, ribbon.source, ribbon.target) fill=color(from)>
+                <title>{format!("{} and {}: {} books", categories[from], categories[to], ribbon.books)}</title>
+            </path>
+        }
+    }).collect_view();
+
+    let groups = layout.groups.iter().enumerate().filter(|(_, (start, end))| end > start).map(|(i, &(start, end))| {
+        let category = categories[i].clone();
+        let middle = (start + end) / 2.0;
+        let (x, y) = polar(RADIUS + RING + LABEL_OFFSET, middle);
+        // Labels on the left half read outwards from the circle too.
+        let anchor = if x < 0.0 { "end" } else { "start" };
+        let rotation = middle.to_degrees() - 90.0 + if x < 0.0 { 180.0 } else { 0.0 };
+        let chip = FilterChip::Category(category.clone());
+        view! {
+            <g
+                class="chord-group"
+                class:selected=selected.contains(&category)
+                on:mouseenter=move |_| set_hovered.set(Some(i))
+                on:mouseleave=move |_| set_hovered.set(None)
+                on:click=move |_| on_pick.call(chip.clone())
+            >
+                <title>{format!("{}: {} books. Click to add it to the filter.", category, books[i][i])}</title>
+                <path d=arc_path(RADIUS, RADIUS + RING, start, end) fill=color(i)/>
+                <text x=x y=y text-anchor=anchor dominant-baseline="middle" transform=format!("rotate({:.1} {:.1} {:.1})", rotation, x, y)>
+                    {category.clone()}
+                </text>
+            </g>
+        }
+    }).collect_view();
+
+    view! {
+        <div class="card graph-chart">
+            <svg
+                class="chord-diagram"
+                viewBox=format!("{} {} {} {}", -EXTENT, -EXTENT, 2.0 * EXTENT, 2.0 * EXTENT)
+                width=2.0 * EXTENT
+                height=2.0 * EXTENT
+                role="img"
+                aria-label="Books shared between technology categories"
+            >
+                {ribbons}
+                {groups}
+            </svg>
+            <p class="hint">"A ribbon's width is the number of books featuring technologies of both categories it joins."</p>
+        </div>
+    }
+    .into_view()
+}
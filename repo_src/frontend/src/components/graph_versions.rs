@@ -0,0 +1,114 @@
+use leptos::*;
+use shared::GraphVersion;
+use crate::errors::AppError;
+use crate::server_fns::{list_graph_versions, CreateGraphSnapshot, RevertGraph, SNAPSHOT_LABEL_MAX_LEN};
+
+// Which versions the graph page is comparing: (from, to), where `to: None` means the current graph.
+pub type DiffRequest = Option<(i64, Option<i64>)>;
+
+#[component]
+pub fn GraphVersionsPanel(
+    snapshot_action: Action<CreateGraphSnapshot, Result<GraphVersion, ServerFnError<AppError>>>,
+    revert_action: Action<RevertGraph, Result<GraphVersion, ServerFnError<AppError>>>,
+    diff_request: ReadSignal<DiffRequest>,
+    set_diff_request: WriteSignal<DiffRequest>,
+    // Bumped when someone else saves a version (see `crate::live`).
+    versions_changed: ReadSignal<usize>,
+) -> impl IntoView {
+    let versions = create_resource(
+        move || (snapshot_action.version().get(), revert_action.version().get(), versions_changed.get()),
+        |_| async move { list_graph_versions().await.unwrap_or_default() },
+    );
+
+    let (label, set_label) = create_signal(String::new());
+    let (from, set_from) = create_signal::<Option<i64>>(None);
+    let (to, set_to) = create_signal::<Option<i64>>(None);
+
+    let on_snapshot = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        snapshot_action.dispatch(CreateGraphSnapshot { label: label.get() });
+        set_label.set(String::new());
+    };
+
+    // After a revert the graph has changed underneath the diff, so drop it.
+    create_effect(move |_| {
+        if revert_action.version().get() > 0 {
+            set_diff_request.set(None);
+        }
+    });
+
+    let error = move || {
+        snapshot_action.value().get().and_then(|r| r.err())
+            .or_else(|| revert_action.value().get().and_then(|r| r.err()))
+            .map(|e| match e {
+                ServerFnError::WrappedServerError(e) => e.field_message("label").map(str::to_string).unwrap_or_else(|| e.to_string()),
+                other => other.to_string(),
+            })
+    };
+
+    let version_options = move |selected: ReadSignal<Option<i64>>| {
+        versions.get().unwrap_or_default().into_iter().map(move |v| {
+            let id = v.id;
+            view! {
+                <option value=id selected=move || selected.get() == Some(id)>
+                    {format!("#{} {} ({})", v.id, v.label, v.created_at.format("%Y-%m-%d %H:%M"))}
+                </option>
+            }
+        }).collect_view()
+    };
+
+    view! {
+        <div class="graph-versions card">
+            <form class="control-group" on:submit=on_snapshot>
+                <label for="snapshot-label">"Snapshot label:"</label>
+                <input
+                    type="text"
+                    id="snapshot-label"
+                    prop:value=label
+                    on:input=move |ev| set_label.set(event_target_value(&ev))
+                    maxlength=SNAPSHOT_LABEL_MAX_LEN
+                />
+                <button type="submit" disabled=move || snapshot_action.pending().get() || label.get().trim().is_empty()>
+                    "Save snapshot"
+                </button>
+            </form>
+            <div class="control-group">
+                <label for="diff-from">"Compare version:"</label>
+                <select id="diff-from" on:change=move |ev| set_from.set(event_target_value(&ev).parse().ok())>
+                    <option value="none">"-- Select a version --"</option>
+                    {move || version_options(from)}
+                </select>
+                <label for="diff-to">"with:"</label>
+                <select id="diff-to" on:change=move |ev| set_to.set(event_target_value(&ev).parse().ok())>
+                    <option value="current">"Current graph"</option>
+                    {move || version_options(to)}
+                </select>
+            </div>
+            <div class="control-group">
+                <button
+                    disabled=move || from.get().is_none()
+                    on:click=move |_| set_diff_request.set(from.get().map(|f| (f, to.get())))
+                >
+                    "Show diff"
+                </button>
+                <button disabled=move || diff_request.get().is_none() on:click=move |_| set_diff_request.set(None)>
+                    "Hide diff"
+                </button>
+                <button
+                    disabled=move || from.get().is_none() || revert_action.pending().get()
+                    on:click=move |_| {
+                        if let Some(version_id) = from.get() {
+                            revert_action.dispatch(RevertGraph { version_id });
+                        }
+                    }
+                >
+                    "Revert to selected version"
+                </button>
+            </div>
+            {move || revert_action.value().get().and_then(|r| r.ok()).map(|backup| view! {
+                <p class="hint">{format!("Reverted. The previous graph was saved as version #{}.", backup.id)}</p>
+            })}
+            {move || error().map(|msg| view! { <p class="field-error">{msg}</p> })}
+        </div>
+    }
+}
@@ -1,24 +1,50 @@
 use leptos::*;
-use crate::server_fns::AddItem; // Use the server function struct
+use crate::errors::AppError;
+use crate::components::item_list::AddOutcome;
+use crate::server_fns::{validate_item_text, AddItem, ITEM_TEXT_MAX_LEN}; // Use the server function struct
 
 #[component]
-pub fn ItemForm(add_item_action: Action<AddItem, Result<(), ServerFnError>>) -> impl IntoView {
+pub fn ItemForm(add_items_action: MultiAction<AddItem, AddOutcome>) -> impl IntoView {
     let (text, set_text) = create_signal(String::new());
+    // Validation failure caught before dispatching; cleared as soon as the user edits the field.
+    let (client_error, set_client_error) = create_signal::<Option<AppError>>(None);
 
     let on_submit = move |ev: ev::SubmitEvent| {
         ev.prevent_default();
         let current_text = text.get().trim().to_string();
-        if current_text.is_empty() {
-            // Optionally, show a client-side validation message
-            // log::warn!("Item text cannot be empty");
+        if let Err(e) = validate_item_text(&current_text) {
+            set_client_error.set(Some(e));
             return;
         }
         // Dispatch the action with parameters matching the fields of the AddItem struct
         // (which correspond to the parameters of the add_item_server_fn)
-        add_item_action.dispatch(AddItem { text: current_text });
+        // ItemList shows the new item straight away (pending) while this runs,
+        // so the form is ready for the next one immediately.
+        add_items_action.dispatch(AddItem { text: current_text });
         set_text.set(String::new()); // Clear input after dispatch
     };
 
+    // The verdict on the most recently finished add; a later success clears an earlier failure.
+    let last_outcome = move || add_items_action.submissions().get().iter().rev().find_map(|s| s.value.get());
+    let server_error = move || last_outcome().and_then(|(_, result)| result.err());
+
+    // Message shown directly under the text input: client-side first, then the server's verdict.
+    let text_error = move || {
+        client_error.get()
+            .and_then(|e| e.field_message("text").map(str::to_string))
+            .or_else(|| match server_error() {
+                Some(ServerFnError::WrappedServerError(e)) => e.field_message("text").map(str::to_string),
+                _ => None,
+            })
+    };
+
+    // Anything that is not tied to a field (storage failures, network errors, ...).
+    // The optimistic row has already been rolled back, so say which item it was.
+    let form_error = move || match last_outcome() {
+        Some((_, Err(ServerFnError::WrappedServerError(AppError::Validation { .. })))) | Some((_, Ok(_))) | None => None,
+        Some((text, Err(e))) => Some(format!("Could not add \"{}\": {}", text, e)),
+    };
+
     view! {
         <form class="item-form" on:submit=on_submit>
             <div>
@@ -27,25 +53,25 @@ pub fn ItemForm(add_item_action: Action<AddItem, Result<(), ServerFnError>>) ->
                     type="text"
                     id="item-text"
                     name="text"
+                    class:input-invalid=move || text_error().is_some()
                     prop:value=text // Use prop:value for controlled component
-                    on:input=move |ev| set_text.set(event_target_value(&ev))
+                    on:input=move |ev| {
+                        set_client_error.set(None);
+                        set_text.set(event_target_value(&ev));
+                    }
                     required
-                    maxlength="100" // Corresponds to server-side validation
+                    maxlength=ITEM_TEXT_MAX_LEN // Corresponds to server-side validation
                 />
+                {move || text_error().map(|msg| view! { <p class="field-error">{msg}</p> })}
             </div>
             <button
                 type="submit"
                 class="button-primary"
-                disabled=move || add_item_action.pending().get() || text.get().trim().is_empty()
+                disabled=move || text.get().trim().is_empty()
             >
-                {move || if add_item_action.pending().get() { "Adding..." } else { "Add Item" }}
+                "Add Item"
             </button>
-            {move || {
-                add_item_action.value().get().map(|result| match result {
-                    Err(e) => view! { <p class="error-detail" style="color: red;">{format!("Error: {}", e)}</p>}.into_view(),
-                    Ok(_) => view! { <></> }.into_view(), // No message on success, list will update
-                })
-            }}
+            {move || form_error().map(|msg| view! { <p class="error-detail" style="color: red;">{msg}</p> })}
         </form>
     }
-} 
\ No newline at end of file
+}
@@ -1,55 +1,299 @@
 use leptos::*;
 use shared::Item;
-use crate::server_fns::DeleteItem; // Use the server function struct
+use std::collections::HashSet;
+use chrono::NaiveDateTime;
+use crate::errors::AppError;
+use crate::server_fns::{add_item, delete_item, AddItem, DeleteItem};
+
+// Leptos clears a submission's input once it resolves, so the item actions
+// return it alongside the result: the text of a failed add is needed for its
+// error message, and the id of a finished delete keeps the row hidden until
+// the refetched list no longer contains it.
+pub type AddOutcome = (String, Result<Item, ServerFnError<AppError>>);
+pub type DeleteOutcome = (i64, Result<(), ServerFnError<AppError>>);
+
+pub fn create_add_items_action() -> MultiAction<AddItem, AddOutcome> {
+    create_multi_action(|input: &AddItem| {
+        let text = input.text.clone();
+        async move { (text.clone(), add_item(text).await) }
+    })
+}
+
+pub fn create_delete_items_action() -> MultiAction<DeleteItem, DeleteOutcome> {
+    create_multi_action(|input: &DeleteItem| {
+        let id = input.id;
+        async move { (id, delete_item(id).await) }
+    })
+}
+
+// One row of the list: stored on the server, or still on its way there.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListedItem {
+    // Item id, or a negative placeholder while the add is pending.
+    pub key: i64,
+    pub text: String,
+    // None until the server has stored the item.
+    pub created_at: Option<NaiveDateTime>,
+    pub pending: bool,
+}
+
+impl From<&Item> for ListedItem {
+    fn from(item: &Item) -> Self {
+        ListedItem { key: item.id, text: item.text.clone(), created_at: Some(item.created_at), pending: false }
+    }
+}
+
+// What the list shows, newest first like `get_items`:
+// - `adding`: texts whose add is in flight, by submission index, shown with
+//   pending styling and keyed by that index so the keys don't shift as other
+//   adds finish;
+// - `added`: items the server confirmed, each with the number of fetches that
+//   had finished when it was confirmed. Only the fetch after that can predate
+//   it: once a later one has loaded, a confirmed item missing from it was
+//   deleted by someone else and is dropped;
+// - the fetched `server` items, minus `hidden` ids (deletes that are in flight
+//   or done). A failed delete is not in `hidden`, so its row comes back.
+pub fn merge_optimistic(
+    server: &[Item],
+    fetches: u64,
+    adding: &[(usize, String)],
+    added: &[(Item, u64)],
+    hidden: &HashSet<i64>,
+) -> Vec<ListedItem> {
+    let on_server: HashSet<i64> = server.iter().map(|item| item.id).collect();
+    let pending = adding.iter().map(|(submission, text)| ListedItem {
+        key: -(*submission as i64) - 1,
+        text: text.clone(),
+        created_at: None,
+        pending: true,
+    });
+    let confirmed = added.iter().rev()
+        .filter(|(item, confirmed_at)| fetches <= *confirmed_at && !on_server.contains(&item.id))
+        .map(|(item, _)| ListedItem::from(item));
+    let stored = server.iter().map(ListedItem::from);
+    pending.rev()
+        .chain(confirmed)
+        .chain(stored)
+        .filter(|row| !hidden.contains(&row.key))
+        .collect()
+}
+
+// Which submissions of an action have had their outcome recorded: every one
+// below `below`, plus those in `above` that resolved out of order.
+#[derive(Default)]
+struct Settled {
+    below: usize,
+    above: HashSet<usize>,
+}
+
+// The outcomes of submissions that resolved since the last call. Tracks only
+// the unresolved ones, so each submission is read until it resolves and then
+// never again.
+fn settle<I, O>(action: MultiAction<I, O>, settled: StoredValue<Settled>) -> Vec<O>
+where
+    I: 'static,
+    O: Clone + 'static,
+{
+    action.submissions().with(|submissions| {
+        settled.try_update_value(|settled| {
+            let mut outcomes = Vec::new();
+            for (i, submission) in submissions.iter().enumerate().skip(settled.below) {
+                if settled.above.contains(&i) {
+                    continue;
+                }
+                if let Some(outcome) = submission.value.get() {
+                    settled.above.insert(i);
+                    outcomes.push(outcome);
+                }
+            }
+            while settled.above.remove(&settled.below) {
+                settled.below += 1;
+            }
+            outcomes
+        })
+        .unwrap_or_default()
+    })
+}
+
+// Drops entries recorded before the latest fetch, which is newer than them.
+fn prune<T: 'static>(list: RwSignal<Vec<(T, u64)>>, fetched: u64) {
+    if list.with_untracked(|list| list.iter().any(|(_, at)| *at < fetched)) {
+        list.update(|list| list.retain(|(_, at)| *at >= fetched));
+    }
+}
 
 #[component]
 pub fn ItemList(
-    items: Vec<Item>,
-    delete_item_action: Action<DeleteItem, Result<(), ServerFnError>>,
+    // Items as last fetched from the server.
+    #[prop(into)] items: Signal<Vec<Item>>,
+    // How many fetches of `items` have finished.
+    #[prop(into)] fetches: Signal<u64>,
+    add_items_action: MultiAction<AddItem, AddOutcome>,
+    delete_items_action: MultiAction<DeleteItem, DeleteOutcome>,
 ) -> impl IntoView {
-    if items.is_empty() {
-        return view! { <p>"No items to display."</p> }.into_view();
-    }
+    // Confirmed adds and finished deletes, each with the fetch count it was
+    // recorded at. Both only matter until a later fetch has loaded.
+    let added = create_rw_signal(Vec::<(Item, u64)>::new());
+    let deleted = create_rw_signal(Vec::<(i64, u64)>::new());
+    let settled_adds = store_value(Settled::default());
+    let settled_deletes = store_value(Settled::default());
+    create_effect(move |_| {
+        for outcome in settle(add_items_action, settled_adds) {
+            if let (_, Ok(item)) = outcome {
+                added.update(|added| added.push((item, fetches.get_untracked())));
+            }
+        }
+    });
+    create_effect(move |_| {
+        for outcome in settle(delete_items_action, settled_deletes) {
+            // NotFound means someone else got there first.
+            if let (id, Ok(()) | Err(ServerFnError::WrappedServerError(AppError::NotFound(_)))) = outcome {
+                deleted.update(|deleted| deleted.push((id, fetches.get_untracked())));
+            }
+        }
+    });
+    create_effect(move |_| {
+        let fetched = fetches.get();
+        prune(added, fetched);
+        prune(deleted, fetched);
+    });
+
+    let rows = move || {
+        let fetched = fetches.get();
+        // Submissions are only ever appended, so their index identifies them.
+        // Those below the settled mark have resolved and are skipped.
+        let adding: Vec<(usize, String)> = add_items_action.submissions().with(|adds| {
+            adds.iter().enumerate().skip(settled_adds.with_value(|s| s.below))
+                .filter_map(|(i, s)| s.input.get().map(|input| (i, input.text)))
+                .collect()
+        });
+        // In-flight deletes, plus finished ones.
+        let mut hidden: HashSet<i64> = delete_items_action.submissions().with(|deletes| {
+            deletes.iter().skip(settled_deletes.with_value(|s| s.below))
+                .filter_map(|s| s.input.get().map(|input| input.id))
+                .collect()
+        });
+        deleted.with(|deleted| hidden.extend(deleted.iter().map(|(id, _)| *id)));
+
+        added.with(|added| merge_optimistic(&items.get(), fetched, &adding, added, &hidden))
+    };
+
+    // The most recent failed delete, rolled back into the list above.
+    let delete_error = move || {
+        delete_items_action.submissions().get().iter().rev().find_map(|s| s.value.get()).and_then(|(_, result)| result.err()).map(|e| match e {
+            ServerFnError::WrappedServerError(AppError::NotFound(_)) => {
+                "That item had already been deleted; the list has been refreshed.".to_string()
+            }
+            other => format!("Error deleting item: {}", other),
+        })
+    };
 
     view! {
-        <ul class="item-list">
-            <For
-                each=move || items.clone()
-                key=|item| item.id
-                children=move |item| {
-                    let item_for_delete = item.clone(); // Clone for the closure
-                    let on_delete_click = move |_| {
-                        // Dispatch the action with parameters matching the fields of DeleteItem
-                        delete_item_action.dispatch(DeleteItem { id: item_for_delete.id });
-                    };
-
-                    // Check if this specific item's deletion is pending
-                    let is_deleting_this_item = Signal::derive(move || {
-                        delete_item_action.pending().get() &&
-                        delete_item_action.input().get().map_or(false, |params| params.id == item.id)
-                    });
-
-                    view! {
-                        <li class="item">
-                            <span class="item-text">{item.text.clone()}</span>
-                            <span class="item-date">{item.created_at.format("%Y-%m-%d %H:%M:%S").to_string()}</span>
-                            <button
-                                class="item-delete"
-                                on:click=on_delete_click
-                                disabled=is_deleting_this_item
-                            >
-                                {move || if is_deleting_this_item.get() { "Deleting..." } else { "Delete" }}
-                            </button>
-                        </li>
+        <Show when=move || !rows().is_empty() fallback=|| view! { <p>"No items to display."</p> }>
+            <ul class="item-list">
+                <For
+                    each=rows
+                    key=|row| (row.key, row.pending)
+                    children=move |row| {
+                        let id = row.key;
+                        let on_delete_click = move |_| {
+                            delete_items_action.dispatch(DeleteItem { id });
+                        };
+
+                        view! {
+                            <li class="item" class:item-pending=row.pending>
+                                <span class="item-text">{row.text.clone()}</span>
+                                <span class="item-date">
+                                    {row.created_at.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_else(|| "Saving...".to_string())}
+                                </span>
+                                <button class="item-delete" on:click=on_delete_click disabled=row.pending>
+                                    "Delete"
+                                </button>
+                            </li>
+                        }
                     }
+                />
+            </ul>
+        </Show>
+        {move || delete_error().map(|message| view! { <p class="error-detail" style="color: red;">{message}</p> })}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i64, text: &str) -> Item {
+        Item { id, text: text.to_string(), created_at: NaiveDateTime::default() }
+    }
+
+    #[test]
+    fn test_merge_optimistic_orders_and_hides_rows() {
+        let server = vec![item(2, "b"), item(1, "a")];
+        let adding = [(1, "c".to_string()), (3, "d".to_string())];
+        let rows = merge_optimistic(&server, 0, &adding, &[(item(3, "confirmed"), 0)], &HashSet::from([1]));
+
+        let texts: Vec<&str> = rows.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, vec!["d", "c", "confirmed", "b"], "newest first, deleted row hidden");
+        assert!(rows[0].pending && rows[0].created_at.is_none());
+        assert!(!rows[2].pending);
+        // Pending rows are keyed by their submission, not by their place among the pending ones.
+        assert_eq!((rows[0].key, rows[1].key), (-4, -2));
+    }
+
+    #[test]
+    fn test_merge_optimistic_reconciles_with_refetched_list() {
+        // Once the refetch includes the confirmed item it is shown once, from the server copy.
+        let server = vec![item(3, "confirmed"), item(1, "a")];
+        let rows = merge_optimistic(&server, 1, &[], &[(item(3, "confirmed"), 0)], &HashSet::new());
+        assert_eq!(rows.iter().map(|r| r.key).collect::<Vec<_>>(), vec![3, 1]);
+    }
+
+    #[test]
+    fn test_merge_optimistic_drops_confirmed_items_a_newer_fetch_lacks() {
+        let server = vec![item(1, "a")];
+        let added = [(item(3, "confirmed"), 4)];
+        // The fetch in flight when the add was confirmed may predate it...
+        let keys = |fetches| merge_optimistic(&server, fetches, &[], &added, &HashSet::new()).iter().map(|r| r.key).collect::<Vec<_>>();
+        assert_eq!(keys(4), vec![3, 1]);
+        // ...but one finishing after it doesn't, so the item was deleted elsewhere.
+        assert_eq!(keys(5), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_settle_returns_each_outcome_once() {
+        let rt = create_runtime();
+        tokio::task::LocalSet::new().run_until(async {
+            let action = create_multi_action(|delay: &u64| {
+                let delay = *delay;
+                async move {
+                    tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                    delay
                 }
-            />
-        </ul>
-        {move || { // Global error for delete action, if any
-            delete_item_action.value().get().map(|result| match result {
-                Err(e) => view! { <p class="error-detail" style="color: red;">{format!("Error deleting item: {}", e)}</p> }.into_view(),
-                Ok(_) => view! { <></> }.into_view(),
-            })
-        }}
-    }.into_view()
-} 
\ No newline at end of file
+            });
+            let settled = store_value(Settled::default());
+            action.dispatch(200);
+            action.dispatch(10);
+
+            // The second submission resolves first and is recorded on its own.
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            assert_eq!(settle(action, settled), vec![10]);
+            assert_eq!(settled.with_value(|s| (s.below, s.above.len())), (0, 1));
+            // Once the first does too, both are behind the mark and never read again.
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            assert_eq!(settle(action, settled), vec![200]);
+            assert_eq!(settled.with_value(|s| (s.below, s.above.len())), (2, 0));
+            assert_eq!(settle(action, settled), Vec::<u64>::new());
+        }).await;
+        rt.dispose();
+    }
+
+    #[test]
+    fn test_prune_drops_entries_older_than_the_latest_fetch() {
+        let rt = create_runtime();
+        let list = create_rw_signal(vec![(1, 3), (2, 4)]);
+        prune(list, 4);
+        assert_eq!(list.get_untracked(), vec![(2, 4)]);
+        rt.dispose();
+    }
+}
@@ -1,4 +1,10 @@
+pub mod adjacency_matrix;
+pub mod chord_diagram;
+pub mod graph_versions;
 pub mod item_form;
 pub mod item_list;
 pub mod nav;
-pub mod tech_graph_view; 
\ No newline at end of file
+pub mod sunburst_chart;
+pub mod taxonomy_navigator;
+pub mod tech_graph_view;
+pub mod timeline_panel; 
\ No newline at end of file
@@ -1,8 +1,11 @@
 use leptos::*;
 use leptos_router::A;
+use crate::auth::AuthContext;
 
 #[component]
 pub fn NavBar() -> impl IntoView {
+    let auth = expect_context::<AuthContext>();
+
     view! {
         <header class="header">
             <div class="container nav-container">
@@ -13,7 +16,22 @@ pub fn NavBar() -> impl IntoView {
                 <nav class="nav-links">
                     <A href="/" exact=true> "Item Manager (Home)" </A>
                     <A href="/tech-graph">"Tech Graph"</A>
+                    <A href="/query">"Query"</A>
+                    <A href="/authors">"Authors"</A>
+                    <A href="/activity">"Activity"</A>
                 </nav>
+                <div class="nav-user">
+                    <Transition fallback=|| ()>
+                        {move || auth.user.get().map(|user| match user {
+                            Some(user) => view! {
+                                <span class="nav-username">{user.username}" ("{user.role.as_str()}")"</span>
+                                <A href="/tokens">"API tokens"</A>
+                                <A href="/logout">"Log out"</A>
+                            }.into_view(),
+                            None => view! { <A href="/login">"Log in"</A> }.into_view(),
+                        })}
+                    </Transition>
+                </div>
             </div>
         </header>
     }
@@ -0,0 +1,91 @@
+use leptos::*;
+use shared::{GraphFilter, TaxonomyBranch};
+use crate::components::taxonomy_navigator::branch_chips;
+use crate::components::tech_graph_view::GROUP_PALETTE;
+use crate::graph_charts::{arc_path, polar, sunburst, SunburstArc};
+use crate::graph_filter::FilterChip;
+use crate::taxonomy::PATH_SEPARATOR;
+
+// Geometry of the chart, in SVG user units, centred on the origin.
+const HOLE: f64 = 50.0;
+const EXTENT: f64 = 320.0;
+// Segments narrower than this along their middle go unlabelled.
+const MIN_LABEL_LENGTH: f64 = 36.0;
+// Each ring out is paler by this much, down to the floor.
+const RING_FADE: f64 = 0.18;
+const MIN_OPACITY: f64 = 0.25;
+
+// The branch at `path`, if the tree has one.
+fn find<'a>(tree: &'a [TaxonomyBranch], path: &[String]) -> Option<&'a TaxonomyBranch> {
+    let mut branch = tree.iter().find(|b| b.path[..] == path[..1])?;
+    for depth in 2..=path.len() {
+        branch = branch.children.iter().find(|b| b.path[..] == path[..depth])?;
+    }
+    Some(branch)
+}
+
+// Whether the filter names the segment itself.
+fn selected(filter: &GraphFilter, arc: &SunburstArc) -> bool {
+    match (arc.technology, arc.path.as_slice()) {
+        (Some(id), _) => filter.technologies.contains(&id),
+        (None, [category]) => filter.categories.contains(category),
+        (None, [_, subcategory]) => filter.subcategories.contains(subcategory),
+        _ => false,
+    }
+}
+
+// The taxonomy as rings: categories in the middle, then subcategories and
+// any finer groupings, and technologies outermost, each segment as wide as
+// its share of the books. Clicking a segment adds it to the filter.
+#[component]
+pub fn SunburstChart(tree: Vec<TaxonomyBranch>, filter: GraphFilter, on_pick: Callback<Vec<FilterChip>>) -> impl IntoView {
+    let arcs = sunburst(&tree);
+    let Some(depth) = arcs.iter().map(|a| a.depth).max() else {
+        return view! { <p class="hint">"No technology is featured in a book yet."</p> }.into_view();
+    };
+    let ring = (EXTENT - HOLE) / depth as f64;
+    let categories: Vec<&str> = tree.iter().map(|b| b.name()).collect();
+
+    let segments = arcs.iter().map(|arc| {
+        let inner = HOLE + (arc.depth - 1) as f64 * ring;
+        let hue = categories.iter().position(|c| *c == arc.path[0]).unwrap_or(0);
+        let chips = match arc.technology {
+            Some(id) => vec![FilterChip::Technology(id)],
+            None => find(&tree, &arc.path).map(branch_chips).unwrap_or_default(),
+        };
+        let name = arc.path.last().cloned().unwrap_or_default();
+        let middle = (arc.start + arc.end) / 2.0;
+        let (x, y) = polar(inner + ring / 2.0, middle);
+        let labelled = (arc.end - arc.start) * (inner + ring / 2.0) >= MIN_LABEL_LENGTH;
+        view! {
+            <g class="sunburst-segment" class:selected=selected(&filter, arc) on:click=move |_| on_pick.call(chips.clone())>
+                <title>{format!("{}: {} books. Click to add it to the filter.", arc.path.join(PATH_SEPARATOR), arc.books)}</title>
+                <path
+                    d=arc_path(inner, inner + ring - 1.0, arc.start, arc.end)
+                    fill=GROUP_PALETTE[hue % GROUP_PALETTE.len()]
+                    fill-opacity=(1.0 - RING_FADE * (arc.depth - 1) as f64).max(MIN_OPACITY)
+                />
+                {labelled.then(|| view! {
+                    <text x=x y=y text-anchor="middle" dominant-baseline="middle">{name}</text>
+                })}
+            </g>
+        }
+    }).collect_view();
+
+    view! {
+        <div class="card graph-chart">
+            <svg
+                class="sunburst-chart"
+                viewBox=format!("{} {} {} {}", -EXTENT, -EXTENT, 2.0 * EXTENT, 2.0 * EXTENT)
+                width=2.0 * EXTENT
+                height=2.0 * EXTENT
+                role="img"
+                aria-label="The technology taxonomy sized by books"
+            >
+                {segments}
+            </svg>
+            <p class="hint">"A segment's width is the number of books featuring its technologies; a book counts once for each technology it features."</p>
+        </div>
+    }
+    .into_view()
+}
@@ -0,0 +1,97 @@
+use leptos::*;
+use shared::TaxonomyBranch;
+use crate::graph_filter::FilterChip;
+use crate::taxonomy::branch_technologies;
+
+// What picking a branch adds to the filter: its category or subcategory, or
+// below those (which the filter has no facet for) each of its technologies.
+pub(crate) fn branch_chips(branch: &TaxonomyBranch) -> Vec<FilterChip> {
+    match branch.path.as_slice() {
+        [category] => vec![FilterChip::Category(category.clone())],
+        [_, subcategory] => vec![FilterChip::Subcategory(subcategory.clone())],
+        _ => branch_technologies(branch).into_iter().map(FilterChip::Technology).collect(),
+    }
+}
+
+// One branch as a collapsible list of its children and then its own technologies.
+fn branch_view(branch: TaxonomyBranch, on_pick: Callback<Vec<FilterChip>>) -> View {
+    let chips = branch_chips(&branch);
+    let empty = chips.is_empty() || branch.technology_count == 0;
+    let counts = format!("{} technologies · {} books", branch.technology_count, branch.book_count);
+    let name = branch.name().to_string();
+    let TaxonomyBranch { children, technologies, .. } = branch;
+
+    view! {
+        <details class="taxonomy-branch">
+            <summary>
+                <span class="taxonomy-name">{name}</span>
+                <span class="taxonomy-counts">{counts}</span>
+                <button
+                    type="button"
+                    title="Add to the filter"
+                    disabled=empty
+                    // Keeps the click from opening or closing the branch.
+                    on:click=move |ev| {
+                        ev.prevent_default();
+                        on_pick.call(chips.clone());
+                    }
+                >"+"</button>
+            </summary>
+            {children.into_iter().map(|child| branch_view(child, on_pick)).collect_view()}
+            <ul>
+                {technologies.into_iter().map(|tech| view! {
+                    <li>
+                        <span class="taxonomy-name">{tech.name}</span>
+                        <span class="taxonomy-counts">{format!("{} books", tech.books)}</span>
+                        <button
+                            type="button"
+                            title="Add to the filter"
+                            on:click=move |_| on_pick.call(vec![FilterChip::Technology(tech.id)])
+                        >"+"</button>
+                    </li>
+                }).collect_view()}
+            </ul>
+        </details>
+    }
+    .into_view()
+}
+
+// The taxonomy as a tree of collapsible branches, each with the technologies
+// and books it holds; + adds a branch or technology to the graph's filter.
+#[component]
+pub fn TaxonomyNavigator(tree: Vec<TaxonomyBranch>, on_pick: Callback<Vec<FilterChip>>) -> impl IntoView {
+    view! {
+        <details class="card taxonomy-navigator">
+            <summary>"Browse the Taxonomy"</summary>
+            {if tree.is_empty() {
+                view! { <p class="hint">"No technology has a category yet."</p> }.into_view()
+            } else {
+                tree.into_iter().map(|branch| branch_view(branch, on_pick)).collect_view()
+            }}
+            <p class="hint">"Counts include everything below a branch; a book counts once however many of its technologies are there. Adding a grouping finer than a subcategory adds each of its technologies."</p>
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::TaxonomyLeaf;
+
+    #[test]
+    fn test_branch_chips_by_depth() {
+        let branch = |path: &[&str], technologies: Vec<TaxonomyLeaf>, children| TaxonomyBranch {
+            path: path.iter().map(|s| s.to_string()).collect(),
+            children,
+            technologies,
+            technology_count: 0,
+            book_count: 0,
+        };
+        let leaf = |id| TaxonomyLeaf { id, name: format!("Tech {}", id), books: 0 };
+        let ftl = branch(&["PHYSICS", "Propulsion", "FTL"], vec![leaf(1)], vec![branch(&["PHYSICS", "Propulsion", "FTL", "Jump"], vec![leaf(2)], vec![])]);
+
+        assert_eq!(branch_chips(&branch(&["PHYSICS"], vec![], vec![])), [FilterChip::Category("PHYSICS".into())]);
+        assert_eq!(branch_chips(&branch(&["PHYSICS", "Propulsion"], vec![], vec![])), [FilterChip::Subcategory("Propulsion".into())]);
+        assert_eq!(branch_chips(&ftl), [FilterChip::Technology(1), FilterChip::Technology(2)]);
+    }
+}
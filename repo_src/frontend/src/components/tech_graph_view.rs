@@ -8,10 +8,10 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_name = renderTechGraph)]
-    fn render_tech_graph(container_id: &str, nodes: JsValue, edges: JsValue);
+    fn render_tech_graph(container_id: &str, nodes: JsValue, edges: JsValue, on_node_click: JsValue, view: JsValue, on_view_change: JsValue);
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct Node {
     pub id: String,
     pub label: String,
@@ -19,31 +19,100 @@ pub struct Node {
     pub group: String,
     pub title: String, // Tooltip
     pub shape: String,
+    // Overrides the group colour, e.g. to mark nodes added/removed in a version diff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // Scales the node with its size, e.g. the number of books in a cluster.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<usize>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct Edge {
     pub from: String,
     pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    // Scales the edge width, e.g. the number of links a bundled edge stands for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    // A dash pattern (dash and gap lengths); empty draws a solid line.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dashes: Vec<u32>,
 }
 
+// The camera: the canvas point at the centre of the view and the zoom level.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub scale: f64,
+}
+
+// What the renderer restores after each layout: the node to select and the
+// camera. Without a viewport it centres on the focused node instead.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+struct InitialView {
+    focus: Option<String>,
+    viewport: Option<Viewport>,
+}
+
+// Colours used when rendering a version diff.
+pub const DIFF_ADDED_COLOR: &str = "#2ecc71";
+pub const DIFF_REMOVED_COLOR: &str = "#e74c3c";
+pub const DIFF_CHANGED_COLOR: &str = "#f1c40f";
+
+// Dash patterns: bundled edges and background details are dashed, mentions dotted.
+pub const DASHED: [u32; 2] = [8, 6];
+pub const DOTTED: [u32; 2] = [2, 5];
+
+// Colours handed out in turn when books are coloured by author or series.
+pub const GROUP_PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd",
+    "#8c564b", "#e377c2", "#7f7f7f", "#bcbd22", "#17becf",
+];
+
 #[component]
 pub fn TechGraphView(
     nodes: Signal<Vec<Node>>,
     edges: Signal<Vec<Edge>>,
+    // Called with the id of a clicked node.
+    #[prop(optional, into)]
+    on_node_click: Option<Callback<String>>,
+    // Selected after each render. Changing it does not re-render the graph.
+    #[prop(optional, into)]
+    focus: Option<Signal<Option<String>>>,
+    // Restored after each render. Changing it does not re-render the graph.
+    #[prop(optional, into)]
+    viewport: Option<Signal<Option<Viewport>>>,
+    // Called once the user stops zooming or panning.
+    #[prop(optional, into)]
+    on_viewport_change: Option<Callback<Viewport>>,
 ) -> impl IntoView {
+    #[cfg(not(feature = "hydrate"))]
+    let _ = (on_node_click, on_viewport_change);
+
     let graph_container_ref = create_node_ref::<html::Div>();
 
     create_effect(move |_| {
         let current_nodes = nodes.get();
+        // Only read by the hydrate-only block below, but always tracked so the effect re-runs.
+        #[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
         let current_edges = edges.get();
-        
+
         if current_nodes.is_empty() {
             return;
         }
 
         if let Some(div) = graph_container_ref.get() {
             div.set_id("tech-graph-container");
+            #[cfg_attr(not(feature = "hydrate"), allow(unused_variables))]
+            let initial_view = InitialView {
+                focus: focus.and_then(|f| f.get_untracked()),
+                viewport: viewport.and_then(|v| v.get_untracked()),
+            };
 
             #[cfg(feature = "hydrate")]
             {
@@ -51,12 +120,28 @@ pub fn TechGraphView(
                 match (
                     serde_wasm_bindgen::to_value(&current_nodes),
                     serde_wasm_bindgen::to_value(&current_edges),
+                    serde_wasm_bindgen::to_value(&initial_view),
                 ) {
-                    (Ok(nodes_js), Ok(edges_js)) => {
-                        render_tech_graph("tech-graph-container", nodes_js, edges_js);
+                    (Ok(nodes_js), Ok(edges_js), Ok(view_js)) => {
+                        // Handed over to JS, which drops them with the network.
+                        let on_click = match on_node_click {
+                            Some(callback) => Closure::<dyn Fn(String)>::new(move |id: String| callback.call(id)).into_js_value(),
+                            None => JsValue::NULL,
+                        };
+                        let on_view_change = match on_viewport_change {
+                            Some(callback) => Closure::<dyn Fn(JsValue)>::new(move |value: JsValue| {
+                                match serde_wasm_bindgen::from_value::<Viewport>(value) {
+                                    Ok(viewport) => callback.call(viewport),
+                                    Err(e) => logging::error!("Failed to read viewport: {:?}", e),
+                                }
+                            }).into_js_value(),
+                            None => JsValue::NULL,
+                        };
+                        render_tech_graph("tech-graph-container", nodes_js, edges_js, on_click, view_js, on_view_change);
                     }
-                    (Err(e), _) => logging::error!("Failed to serialize nodes: {:?}", e),
-                    (_, Err(e)) => logging::error!("Failed to serialize edges: {:?}", e),
+                    (Err(e), _, _) => logging::error!("Failed to serialize nodes: {:?}", e),
+                    (_, Err(e), _) => logging::error!("Failed to serialize edges: {:?}", e),
+                    (_, _, Err(e)) => logging::error!("Failed to serialize view: {:?}", e),
                 }
             }
         }
@@ -0,0 +1,183 @@
+use leptos::*;
+use leptos::leptos_dom::helpers::IntervalHandle;
+use shared::Timeline;
+use crate::graph_timeline::next_year;
+
+// Time between playback steps, one publication year each.
+const PLAYBACK_STEP: std::time::Duration = std::time::Duration::from_millis(800);
+
+// Geometry of the first-appearance chart, in SVG user units.
+const CHART_WIDTH: f64 = 720.0;
+const LABEL_WIDTH: f64 = 240.0;
+const ROW_HEIGHT: f64 = 16.0;
+const AXIS_HEIGHT: f64 = 24.0;
+const MARGIN: f64 = 12.0;
+
+// Horizontal position of `year` on a chart spanning `first..=last`.
+fn year_x(year: i32, first: i32, last: i32) -> f64 {
+    let plot = CHART_WIDTH - LABEL_WIDTH - 2.0 * MARGIN;
+    let share = if last > first { (year - first) as f64 / (last - first) as f64 } else { 0.5 };
+    LABEL_WIDTH + MARGIN + share * plot
+}
+
+// Round years to label the axis with, at most eight of them.
+fn ticks(first: i32, last: i32) -> Vec<i32> {
+    let span = last - first;
+    let step = [1, 2, 5, 10, 20, 25, 50, 100].into_iter().find(|step| span / step < 8).unwrap_or(100);
+    let start = first + (step - first.rem_euclid(step)) % step;
+    (start..=last).step_by(step as usize).collect()
+}
+
+// The timeline under the tech graph's controls: a year slider, playback
+// through the publication years, and when each technology first appeared.
+#[component]
+pub fn TimelinePanel(
+    timeline: Timeline,
+    // The year shown; None shows every book, dated or not.
+    year: Signal<Option<i32>>,
+    on_change: Callback<Option<i32>>,
+) -> impl IntoView {
+    let Timeline { years, first_appearances, undated_books } = timeline;
+    let (Some(&first), Some(&last)) = (years.first(), years.last()) else {
+        return view! { <p class="hint">"No book has a publication year yet, so there is no timeline."</p> }.into_view();
+    };
+    let years = store_value(years);
+
+    let (playing, set_playing) = create_signal(false);
+    let interval = store_value::<Option<IntervalHandle>>(None);
+    let stop = move || {
+        if let Some(handle) = interval.get_value() {
+            handle.clear();
+        }
+        interval.set_value(None);
+        set_playing.set(false);
+    };
+    on_cleanup(move || {
+        if let Some(handle) = interval.get_value() {
+            handle.clear();
+        }
+    });
+
+    // Plays on from the year shown, or from the start when every book (or the
+    // last year) is shown already.
+    let toggle_playback = move |_| {
+        if playing.get_untracked() {
+            stop();
+            return;
+        }
+        let current = year.get_untracked();
+        if years.with_value(|years| current.and_then(|y| next_year(years, Some(y)))).is_none() {
+            on_change.call(Some(first));
+        }
+        let step = move || match years.with_value(|years| next_year(years, year.get_untracked())) {
+            Some(next) => on_change.call(Some(next)),
+            None => stop(),
+        };
+        match set_interval_with_handle(step, PLAYBACK_STEP) {
+            Ok(handle) => {
+                interval.set_value(Some(handle));
+                set_playing.set(true);
+            }
+            Err(e) => logging::error!("Failed to start timeline playback: {:?}", e),
+        }
+    };
+
+    let pick_year = move |picked: Option<i32>| {
+        stop();
+        on_change.call(picked);
+    };
+
+    let height = AXIS_HEIGHT + first_appearances.len() as f64 * ROW_HEIGHT + MARGIN;
+    let technologies = first_appearances.len();
+    let marker_x = move || year.get().map(|y| year_x(y.clamp(first, last), first, last));
+
+    view! {
+        <div class="card graph-timeline">
+            <div class="timeline-controls">
+                <button type="button" on:click=toggle_playback>
+                    {move || if playing.get() { "Pause" } else { "Play" }}
+                </button>
+                <label for="timeline-year">{first}</label>
+                <input
+                    id="timeline-year"
+                    type="range"
+                    min=first
+                    max=last
+                    step="1"
+                    prop:value=move || year.get().unwrap_or(last).to_string()
+                    on:change=move |ev| {
+                        if let Ok(picked) = event_target_value(&ev).parse::<i32>() {
+                            pick_year(Some(picked));
+                        }
+                    }
+                />
+                <span>{last}</span>
+                <b class="timeline-year">
+                    {move || year.get().map_or_else(|| "All years".to_string(), |y| format!("Up to {}", y))}
+                </b>
+                <button type="button" disabled=move || year.get().is_none() on:click=move |_| pick_year(None)>
+                    "Show All Years"
+                </button>
+            </div>
+            {(undated_books > 0).then(|| view! {
+                <p class="hint">{format!("{} books have no publication year and are hidden while a year is picked.", undated_books)}</p>
+            })}
+            <details class="first-appearances">
+                <summary>{format!("First appearances ({} technologies)", technologies)}</summary>
+                <svg
+                    class="first-appearance-chart"
+                    viewBox=format!("0 0 {} {}", CHART_WIDTH, height)
+                    width=CHART_WIDTH
+                    height=height
+                    role="img"
+                    aria-label="The year each technology first appeared in a book"
+                >
+                    {ticks(first, last).into_iter().map(|tick| {
+                        let x = year_x(tick, first, last);
+                        view! {
+                            <line class="tick" x1=x x2=x y1=AXIS_HEIGHT - 4.0 y2=height - MARGIN/>
+                            <text class="tick-label" x=x y=AXIS_HEIGHT - 8.0 text-anchor="middle">{tick}</text>
+                        }
+                    }).collect_view()}
+                    {move || marker_x().map(|x| view! {
+                        <line class="year-marker" x1=x x2=x y1=AXIS_HEIGHT - 4.0 y2=height - MARGIN/>
+                    })}
+                    {first_appearances.into_iter().enumerate().map(|(row, appearance)| {
+                        let y = AXIS_HEIGHT + (row as f64 + 0.5) * ROW_HEIGHT;
+                        let x = year_x(appearance.year, first, last);
+                        let appeared = appearance.year;
+                        let later = move || year.get().is_some_and(|y| appeared > y);
+                        view! {
+                            <g class="appearance" class:later=later on:click=move |_| pick_year(Some(appeared))>
+                                <title>{format!("{} ({}): {}, in {}", appearance.name, appearance.category, appeared, appearance.book_title)}</title>
+                                <text x=LABEL_WIDTH y=y + 4.0 text-anchor="end">{appearance.name}</text>
+                                <line class="row-guide" x1=LABEL_WIDTH + MARGIN / 2.0 x2=x y1=y y2=y/>
+                                <circle cx=x cy=y r="5"/>
+                            </g>
+                        }
+                    }).collect_view()}
+                </svg>
+                <p class="hint">"Click a technology to show the graph as of its first appearance."</p>
+            </details>
+        </div>
+    }
+    .into_view()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_scale_and_ticks() {
+        assert_eq!(year_x(1884, 1884, 2021), LABEL_WIDTH + MARGIN);
+        assert_eq!(year_x(2021, 1884, 2021), CHART_WIDTH - MARGIN);
+        assert!(year_x(1950, 1884, 2021) < year_x(1951, 1884, 2021));
+        // A single year sits in the middle rather than dividing by zero.
+        assert!(year_x(1990, 1990, 1990).is_finite());
+
+        assert_eq!(ticks(1884, 2021), [1900, 1920, 1940, 1960, 1980, 2000, 2020]);
+        assert_eq!(ticks(1965, 1990), [1965, 1970, 1975, 1980, 1985, 1990]);
+        assert_eq!(ticks(1990, 1990), [1990]);
+    }
+}
@@ -1,44 +1,111 @@
 // This entire module is only compiled when the "ssr" feature is enabled.
 #![cfg(feature = "ssr")]
 
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool, Row};
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+#[cfg(feature = "postgres")]
+use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::env;
-use std::sync::OnceLock;
-use shared::Item;
-use chrono::{Utc, NaiveDateTime};
-
-// Global static pool, initialized once.
-static POOL: OnceLock<SqlitePool> = OnceLock::new();
-
-async fn init_pool() -> Result<SqlitePool, sqlx::Error> {
-    let database_url = if cfg!(test) {
-        // For tests, prioritize a specific test env var, then TEST_DATABASE_URL, then fallback to in-memory
-        env::var("TEST_DATABASE_URL_FOR_SERVER_FN_TESTS")
-            .or_else(|_| env::var("TEST_DATABASE_URL"))
-            .unwrap_or_else(|_| {
-                leptos::logging::warn!("[DB LOG Init - Test Mode] Neither TEST_DATABASE_URL_FOR_SERVER_FN_TESTS nor TEST_DATABASE_URL set, defaulting POOL init to sqlite::memory:");
-                "sqlite::memory:".to_string()
-            })
-    } else {
-        env::var("DATABASE_URL")
-            .map_err(|e| sqlx::Error::Configuration(format!("DATABASE_URL not set: {}",e).into()))?
-    };
-    
+use crate::dataset::bundled_graph;
+use crate::errors::AppError;
+use crate::fixtures::{named_fixture, DEFAULT_FIXTURE_SET};
+use crate::repository::{Fixture, FixtureReport, Repositories};
+
+// The pool is created once by the backend binary and handed to everything else:
+// axum state for plain handlers and Leptos context (`provide_context`) for
+// server functions. Nothing in this module holds a global connection.
+//
+// Which database is used is decided by the DATABASE_URL scheme: `sqlite:` is
+// always available, `postgres://` / `postgresql://` need the "postgres" feature.
+#[derive(Clone, Debug)]
+pub enum DbPool {
+    Sqlite(SqlitePool),
+    #[cfg(feature = "postgres")]
+    Postgres(PgPool),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DbBackend {
+    Sqlite,
+    Postgres,
+}
+
+impl DbBackend {
+    pub fn from_url(database_url: &str) -> Result<Self, sqlx::Error> {
+        if database_url.starts_with("sqlite:") {
+            Ok(DbBackend::Sqlite)
+        } else if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+            Ok(DbBackend::Postgres)
+        } else {
+            Err(sqlx::Error::Configuration(
+                format!("Unsupported DATABASE_URL scheme (expected sqlite: or postgres://): {}", database_url).into(),
+            ))
+        }
+    }
+}
+
+impl DbPool {
+    pub fn backend(&self) -> DbBackend {
+        match self {
+            DbPool::Sqlite(_) => DbBackend::Sqlite,
+            #[cfg(feature = "postgres")]
+            DbPool::Postgres(_) => DbBackend::Postgres,
+        }
+    }
+
+    // Repositories matching the pool's backend, ready for `provide_context`.
+    pub fn repositories(&self) -> Repositories {
+        match self {
+            DbPool::Sqlite(pool) => Repositories::sqlite(pool.clone()),
+            #[cfg(feature = "postgres")]
+            DbPool::Postgres(pool) => Repositories::postgres(pool.clone()),
+        }
+    }
+}
+
+pub async fn init_pool(database_url: &str) -> Result<DbPool, sqlx::Error> {
     leptos::logging::log!("[DB LOG Init] Initializing pool with URL: {}", database_url);
-    SqlitePoolOptions::new()
-        .max_connections(if cfg!(test) { 1 } else { 5 }) // Fewer connections for tests
-        .connect(&database_url)
-        .await
+    match DbBackend::from_url(database_url)? {
+        DbBackend::Sqlite => {
+            ensure_sqlite_parent_dir(database_url);
+            let pool = SqlitePoolOptions::new()
+                .max_connections(5)
+                .connect(database_url)
+                .await?;
+            Ok(DbPool::Sqlite(pool))
+        }
+        #[cfg(feature = "postgres")]
+        DbBackend::Postgres => {
+            let pool = PgPoolOptions::new()
+                .max_connections(5)
+                .connect(database_url)
+                .await?;
+            Ok(DbPool::Postgres(pool))
+        }
+        #[cfg(not(feature = "postgres"))]
+        DbBackend::Postgres => Err(sqlx::Error::Configuration(
+            "DATABASE_URL points at PostgreSQL but this binary was built without the \"postgres\" feature".into(),
+        )),
+    }
 }
 
-pub async fn get_db_pool() -> Result<&'static SqlitePool, sqlx::Error> {
-    if POOL.get().is_none() {
-        // Ensure dotenvy is called before first pool access if .env is used for DATABASE_URL
-        // dotenvy::dotenv().ok(); // This should ideally be called once at app startup in backend/main.rs
-        let pool = init_pool().await?;
-        POOL.set(pool).map_err(|_| sqlx::Error::PoolClosed)?;
+// Ensure the target directory exists for SQLite file creation if using a file-based DB.
+fn ensure_sqlite_parent_dir(database_url: &str) {
+    let path_str = database_url.trim_start_matches("sqlite:").trim_start_matches("//");
+    if let Some(parent_dir) = std::path::Path::new(path_str.split('?').next().unwrap_or("")).parent() {
+        if !parent_dir.as_os_str().is_empty() && !parent_dir.exists() {
+            leptos::logging::log!("[DB LOG Init] Attempting to create database directory: {:?}", parent_dir);
+            if let Err(e) = std::fs::create_dir_all(parent_dir) {
+                leptos::logging::error!("[DB LOG Init] Failed to create database directory {:?}: {:?}", parent_dir, e);
+            }
+        }
     }
-    Ok(POOL.get().unwrap())
+}
+
+// Convenience wrapper for the binary: reads DATABASE_URL (after .env has been loaded).
+pub async fn init_pool_from_env() -> Result<DbPool, sqlx::Error> {
+    let database_url = env::var("DATABASE_URL")
+        .map_err(|e| sqlx::Error::Configuration(format!("DATABASE_URL not set: {}", e).into()))?;
+    init_pool(&database_url).await
 }
 
 // Separate function for test database pool if needed (ensure TEST_DATABASE_URL is set for tests)
@@ -53,147 +120,234 @@ pub async fn get_db_pool_test() -> Result<SqlitePool, sqlx::Error> {
         .await
 }
 
-// Called from backend/main.rs on server startup if DATABASE_AUTO_MIGRATE feature is enabled
-#[cfg(feature = "DATABASE_AUTO_MIGRATE")]
-pub async fn run_migrations() -> Result<(), sqlx::Error> {
-    leptos::logging::log!("[DB LOG] Attempting to run migrations from frontend::database::run_migrations...");
-    let pool = get_db_pool().await?;
-    leptos::logging::log!("[DB LOG] Acquired DB pool for migrations. Migration source path: ./migrations (relative to frontend crate).");
-
-    // Path is relative to CARGO_MANIFEST_DIR of the crate where this is compiled,
-    // which is `frontend` crate. So, `frontend/migrations`.
-    sqlx::migrate!("./migrations")
-        .run(pool)
-        .await?;
+// Isolated, fully migrated database for tests. Each call returns a fresh
+// in-memory database (single connection, so the data lives as long as the pool).
+#[cfg(test)]
+pub(crate) async fn setup_test_db_with_migrations() -> Result<SqlitePool, sqlx::Error> {
+    let pool = get_db_pool_test().await?;
+    // The path is relative to CARGO_MANIFEST_DIR of `frontend` crate.
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+// Fresh, migrated PostgreSQL database for tests, or `None` when TEST_POSTGRES_URL
+// is not set (see with_temp_postgres.sh at the repo root). Each call creates a new database
+// on that server so tests stay isolated from each other.
+#[cfg(all(test, feature = "postgres"))]
+pub(crate) async fn setup_test_pg_with_migrations() -> Option<PgPool> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let admin_url = env::var("TEST_POSTGRES_URL").ok()?;
+    let db_name = format!("test_{}_{}", std::process::id(), COUNTER.fetch_add(1, Ordering::SeqCst));
+    let admin = PgPoolOptions::new().max_connections(1).connect(&admin_url).await
+        .expect("Failed to connect to TEST_POSTGRES_URL");
+    sqlx::query(&format!("CREATE DATABASE {}", db_name)).execute(&admin).await
+        .expect("Failed to create test database");
+    admin.close().await;
+
+    let (base, _) = admin_url.rsplit_once('/').expect("TEST_POSTGRES_URL must include a database name");
+    let pool = PgPoolOptions::new().max_connections(2).connect(&format!("{}/{}", base, db_name)).await
+        .expect("Failed to connect to test database");
+    sqlx::migrate!("./migrations_postgres").run(&pool).await
+        .expect("Failed to run Postgres migrations on test DB");
+    Some(pool)
+}
+
+// Called by the backend's `migrate` command, and on server startup if the
+// DATABASE_AUTO_MIGRATE feature is enabled. SQLite and PostgreSQL have parallel
+// migration directories with the same numbering.
+pub async fn run_migrations(pool: &DbPool) -> Result<(), sqlx::Error> {
+    // Paths are relative to CARGO_MANIFEST_DIR of the crate where this is compiled,
+    // which is `frontend` crate. So, `frontend/migrations` and `frontend/migrations_postgres`.
+    let migrator = migrator(pool);
+    match pool {
+        DbPool::Sqlite(pool) => {
+            leptos::logging::log!("[DB LOG] Running SQLite migrations from ./migrations (relative to frontend crate).");
+            migrator.run(pool).await?;
+        }
+        #[cfg(feature = "postgres")]
+        DbPool::Postgres(pool) => {
+            leptos::logging::log!("[DB LOG] Running PostgreSQL migrations from ./migrations_postgres (relative to frontend crate).");
+            migrator.run(pool).await?;
+        }
+    }
     leptos::logging::log!("[DB LOG] Database migrations applied successfully from run_migrations.");
     Ok(())
 }
 
-// --- CRUD Operations ---
-// These now return Result<_, String> for errors.
+// Where an embedded or recorded migration stands in the database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    // Recorded as applied but not embedded in this binary: the schema was
+    // migrated by a newer build.
+    Unknown,
+}
 
-pub async fn get_all_items_db() -> Result<Vec<Item>, String> {
-    let pool = get_db_pool().await.map_err(|e| format!("DB Pool error: {}", e))?;
-    
-    let rows = sqlx::query("SELECT id, text, created_at FROM items ORDER BY created_at DESC")
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Failed to fetch items: {}", e))?;
-
-    let items = rows.into_iter().map(|row| {
-        let id: i64 = row.get("id");
-        let text: String = row.get("text");
-        // SQLx can parse recognized TEXT formats (ISO8601 subset) into NaiveDateTime directly
-        // For SQLite default (TEXT as YYYY-MM-DD HH:MM:SS), this should work.
-        let created_at: NaiveDateTime = row.get("created_at");
-        Item { id, text, created_at }
-    }).collect();
-
-    Ok(items)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
 }
 
-pub async fn add_item_db(text: String) -> Result<(), String> {
-    let pool = get_db_pool().await.map_err(|e| format!("DB Pool error: {}", e))?;
-    
-    // Using NaiveDateTime directly with SQLx for SQLite will store it as TEXT in 'YYYY-MM-DD HH:MM:SS' format.
-    let now_utc_naive = Utc::now().naive_utc();
-    
-    sqlx::query("INSERT INTO items (text, created_at) VALUES (?, ?)")
-        .bind(text)
-        .bind(now_utc_naive) // SQLx handles NaiveDateTime to TEXT
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to add item: {}", e))?;
-    
-    Ok(())
+// Migrations are up/down pairs (`NNNN_name.up.sql` / `.down.sql`) so they can
+// be rolled back; `migrator` lists both halves.
+fn migrator(pool: &DbPool) -> sqlx::migrate::Migrator {
+    match pool {
+        DbPool::Sqlite(_) => sqlx::migrate!("./migrations"),
+        #[cfg(feature = "postgres")]
+        DbPool::Postgres(_) => sqlx::migrate!("./migrations_postgres"),
+    }
 }
 
-pub async fn delete_item_db(id: i64) -> Result<(), String> {
-    let pool = get_db_pool().await.map_err(|e| format!("DB Pool error: {}", e))?;
-    
-    let result = sqlx::query("DELETE FROM items WHERE id = ?")
-        .bind(id)
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Failed to delete item: {}", e))?;
+// Versions recorded in sqlx's bookkeeping table, which is created if it does
+// not exist yet (so a brand new database has nothing applied).
+async fn applied_versions(pool: &DbPool) -> Result<Vec<i64>, sqlx::Error> {
+    use sqlx::migrate::Migrate;
 
-    if result.rows_affected() == 0 {
-        Err(format!("Item with id {} not found for deletion", id))
-    } else {
-        Ok(())
+    let applied = match pool {
+        DbPool::Sqlite(pool) => {
+            let mut conn = pool.acquire().await?;
+            conn.ensure_migrations_table().await?;
+            conn.list_applied_migrations().await?
+        }
+        #[cfg(feature = "postgres")]
+        DbPool::Postgres(pool) => {
+            let mut conn = pool.acquire().await?;
+            conn.ensure_migrations_table().await?;
+            conn.list_applied_migrations().await?
+        }
+    };
+    Ok(applied.into_iter().map(|m| m.version).collect())
+}
+
+// Every migration of this binary plus any unknown applied ones, by version.
+pub async fn migration_status(pool: &DbPool) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+    let applied = applied_versions(pool).await?;
+    let migrator = migrator(pool);
+    let mut statuses: Vec<MigrationStatus> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            state: if applied.contains(&m.version) { MigrationState::Applied } else { MigrationState::Pending },
+        })
+        .collect();
+    for version in applied {
+        if !statuses.iter().any(|m| m.version == version) {
+            statuses.push(MigrationStatus {
+                version,
+                description: "(not in this binary)".to_string(),
+                state: MigrationState::Unknown,
+            });
+        }
     }
+    statuses.sort_by_key(|m| m.version);
+    Ok(statuses)
 }
 
-pub async fn seed_database() -> Result<(), String> {
-    leptos::logging::log!("[DB LOG] Checking if database seeding is required...");
-    let pool = get_db_pool().await.map_err(|e| format!("DB Pool error for seeding: {}", e))?;
+// Reverts the `steps` most recently applied migrations, newest first, and
+// returns them. Fails without changing anything if the schema is ahead of the
+// binary, since the newer migrations' down scripts are not available here.
+pub async fn rollback_migrations(pool: &DbPool, steps: usize) -> Result<Vec<MigrationStatus>, sqlx::Error> {
+    let applied: Vec<MigrationStatus> = migration_status(pool)
+        .await?
+        .into_iter()
+        .filter(|m| m.state != MigrationState::Pending)
+        .collect();
+    let keep = applied.len().saturating_sub(steps);
+    // Everything above `target` is reverted.
+    let target = keep.checked_sub(1).map_or(0, |i| applied[i].version);
 
-    // Check if items table is empty
-    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
-        .fetch_one(pool)
-        .await
-        .map_err(|e| format!("Failed to count items for seeding: {}", e))?;
+    let migrator = migrator(pool);
+    match pool {
+        DbPool::Sqlite(pool) => migrator.undo(pool, target).await?,
+        #[cfg(feature = "postgres")]
+        DbPool::Postgres(pool) => migrator.undo(pool, target).await?,
+    }
+    leptos::logging::log!("[DB LOG] Rolled back migrations above version {}.", target);
+    Ok(applied.into_iter().skip(keep).rev().collect())
+}
+
+// --- Seeding ---
+// Seeding goes through the repository traits, so it works for any storage backend.
+
+// Audit log actor for changes made by the server itself (seeding, imports).
+pub const SYSTEM_ACTOR: &str = "system";
+
+// Applies a fixture and, if it changed the graph, records the result as a
+// graph version like any other import.
+pub async fn seed_fixture(repos: &Repositories, name: &str, fixture: &Fixture) -> Result<FixtureReport, AppError> {
+    leptos::logging::log!("[DB LOG] Applying fixture '{}' ({} items, {} books)...", name, fixture.items.len(), fixture.graph.books.len());
+    let report = repos.fixtures.apply_fixture(name, fixture, SYSTEM_ACTOR).await?;
+    if report.graph_changed() {
+        repos.graph.create_version(format!("Seeded fixture {}", name), SYSTEM_ACTOR).await?;
+    }
+    leptos::logging::log!("[DB LOG] Fixture '{}' applied: {:?}", name, report);
+    Ok(report)
+}
+
+// Automatic seeding for development: the default fixture set, but only into
+// an empty items table so deleted demo items stay deleted across restarts.
+pub async fn seed_database(repos: &Repositories) -> Result<(), AppError> {
+    leptos::logging::log!("[DB LOG] Checking if database seeding is required...");
 
+    let count = repos.items.count_items().await?;
     if count == 0 {
-        leptos::logging::log!("[DB LOG] Items table is empty. Seeding initial data...");
-        let initial_items = vec![
-            "Buy groceries",
-            "Read a book",
-            "Learn Leptos",
-        ];
-        for item_text in initial_items {
-            // Use existing add_item_db which handles created_at
-            // Note: add_item_db itself gets a new pool. For seeding, it might be more efficient
-            // to have an add_item_core(text, pool) and use the pool obtained above.
-            // However, for a few items, this is fine.
-            if let Err(e) = add_item_db(item_text.to_string()).await {
-                 // Log error but continue seeding other items if possible
-                leptos::logging::error!("[DB LOG] Error seeding item '{}': {}", item_text, e);
-            }
-        }
-        leptos::logging::log!("[DB LOG] ✅ Database seeding completed successfully!");
+        leptos::logging::log!("[DB LOG] Items table is empty. Seeding the '{}' fixture set...", DEFAULT_FIXTURE_SET);
+        seed_fixture(repos, DEFAULT_FIXTURE_SET, &named_fixture(DEFAULT_FIXTURE_SET)?).await?;
     } else {
         leptos::logging::log!("[DB LOG] Database already has data ({} items). Skipping seeding.", count);
     }
-    Ok(())
+
+    seed_graph_if_empty(repos).await
 }
 
-// Force seed the database regardless of existing data (useful for testing or manual seeding)
-pub async fn force_seed_database() -> Result<(), String> {
-    leptos::logging::log!("[DB LOG] Force seeding database (will add items regardless of existing data)...");
-    let initial_items = vec![
-        "Buy groceries",
-        "Read a book", 
-        "Learn Leptos",
-    ];
-    for item_text in initial_items {
-        if let Err(e) = add_item_db(item_text.to_string()).await {
-            leptos::logging::error!("[DB LOG] Error force seeding item '{}': {}", item_text, e);
-        }
+// The tech graph is the app's reference data rather than demo content, so the
+// backend calls this on every start, not only in DEV. The import is recorded as
+// the first graph version so later edits can be diffed against it.
+pub async fn seed_graph_if_empty(repos: &Repositories) -> Result<(), AppError> {
+    if repos.graph.load_graph().await?.books.is_empty() {
+        leptos::logging::log!("[DB LOG] Graph tables are empty. Importing bundled dataset...");
+        repos.graph.replace_graph(bundled_graph()?, SYSTEM_ACTOR).await?;
+        repos.graph.create_version("Imported bundled dataset".to_string(), SYSTEM_ACTOR).await?;
     }
-    leptos::logging::log!("[DB LOG] ✅ Force database seeding completed!");
     Ok(())
 }
 
+// Creates the first admin account from ADMIN_USERNAME / ADMIN_PASSWORD when
+// the users table is empty, so a fresh install can be logged into. Returns
+// whether an account was created; later accounts are made with CreateUser.
+pub async fn bootstrap_admin(repos: &Repositories, username: &str, password: &str) -> Result<bool, AppError> {
+    if !repos.users.list_users().await?.is_empty() {
+        return Ok(false);
+    }
+    crate::auth::validate_username(username)?;
+    crate::auth::validate_password(password)?;
+    let hash = crate::auth::hash_password(password)?;
+    repos.users.create_user(username, &hash, shared::Role::Admin, SYSTEM_ACTOR).await?;
+    leptos::logging::log!("[DB LOG] Created admin account '{}'", username);
+    Ok(true)
+}
+
+pub async fn bootstrap_admin_from_env(repos: &Repositories) -> Result<bool, AppError> {
+    match (env::var("ADMIN_USERNAME"), env::var("ADMIN_PASSWORD")) {
+        (Ok(username), Ok(password)) => bootstrap_admin(repos, username.trim(), &password).await,
+        _ => Ok(false),
+    }
+}
+
 #[cfg(all(test, feature = "ssr"))] // Ensure ssr features are active for tests needing DB
 mod tests {
-    use super::*; // To access get_db_pool_test, add_item_db etc.
+    use super::*; // To access setup_test_db_with_migrations, seed_database etc.
+    use crate::repository::{ItemRepository, SqliteItemRepository};
+    use chrono::Utc;
     use sqlx::Row;
 
-    // Helper to setup an in-memory test DB and run migrations
-    async fn setup_test_db_with_migrations() -> Result<SqlitePool, sqlx::Error> {
-        // get_db_pool_test() returns a new in-memory pool or one based on TEST_DATABASE_URL
-        let pool = get_db_pool_test().await?;
-        
-        // Run migrations on this specific pool
-        // The path is relative to CARGO_MANIFEST_DIR of `frontend` crate.
-        sqlx::migrate!("./migrations")
-            .run(&pool)
-            .await
-            .expect("Failed to run migrations on test DB");
-        Ok(pool)
-    }
-
     #[tokio::test]
     async fn test_migrations_on_test_db() {
         let pool_result = setup_test_db_with_migrations().await;
@@ -207,6 +361,62 @@ mod tests {
         assert!(row.is_some(), "items table not found after migrations");
     }
 
+    #[tokio::test]
+    async fn test_migration_status_tracks_applied_migrations() {
+        let pool = DbPool::Sqlite(get_db_pool_test().await.unwrap());
+        let pending = migration_status(&pool).await.unwrap();
+        assert!(!pending.is_empty());
+        assert!(pending.iter().all(|m| m.state == MigrationState::Pending), "fresh database has nothing applied");
+        assert_eq!(pending[0].version, 1);
+
+        run_migrations(&pool).await.unwrap();
+        assert!(migration_status(&pool).await.unwrap().iter().all(|m| m.state == MigrationState::Applied));
+    }
+
+    #[tokio::test]
+    async fn test_rollback_reverts_newest_migrations_first() {
+        let pool = DbPool::Sqlite(get_db_pool_test().await.unwrap());
+        run_migrations(&pool).await.unwrap();
+        let total = migration_status(&pool).await.unwrap().len();
+
+        let reverted = rollback_migrations(&pool, 2).await.unwrap();
+        assert_eq!(reverted.iter().map(|m| m.version).collect::<Vec<_>>(), vec![total as i64, total as i64 - 1]);
+        let status = migration_status(&pool).await.unwrap();
+        assert_eq!(status.iter().filter(|m| m.state == MigrationState::Pending).count(), 2);
+
+        // The down scripts leave a schema the up scripts can be re-applied to.
+        assert_eq!(rollback_migrations(&pool, 100).await.unwrap().len(), total - 2);
+        run_migrations(&pool).await.unwrap();
+        assert!(migration_status(&pool).await.unwrap().iter().all(|m| m.state == MigrationState::Applied));
+    }
+
+    #[tokio::test]
+    async fn test_migrations_from_a_newer_binary_are_reported_and_block_rollback() {
+        let sqlite = get_db_pool_test().await.unwrap();
+        let pool = DbPool::Sqlite(sqlite.clone());
+        run_migrations(&pool).await.unwrap();
+        sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (9999, 'from the future', 1, x'00', 0)")
+            .execute(&sqlite)
+            .await
+            .unwrap();
+
+        let status = migration_status(&pool).await.unwrap();
+        assert_eq!(status.last().map(|m| (m.version, m.state)), Some((9999, MigrationState::Unknown)));
+        assert!(rollback_migrations(&pool, 1).await.is_err());
+        assert!(run_migrations(&pool).await.is_err());
+    }
+
+    #[cfg(feature = "postgres")]
+    #[tokio::test]
+    async fn test_postgres_down_migrations_round_trip() {
+        let Some(pg) = setup_test_pg_with_migrations().await else { return };
+        let pool = DbPool::Postgres(pg);
+        let total = migration_status(&pool).await.unwrap().len();
+        assert_eq!(rollback_migrations(&pool, total).await.unwrap().len(), total);
+        run_migrations(&pool).await.unwrap();
+        assert!(migration_status(&pool).await.unwrap().iter().all(|m| m.state == MigrationState::Applied));
+    }
+
     #[tokio::test]
     async fn test_add_item_db_via_direct_sql_with_pool() {
         let pool = setup_test_db_with_migrations().await.unwrap();
@@ -228,30 +438,8 @@ mod tests {
         assert_eq!(item_row.get::<String, _>("text"), "Test item for direct pool");
     }
 
-    // Helper DB functions for testing that accept a pool
-    // These are effectively what the main db functions should be like if refactored for testability
-    async fn get_all_items_db_with_pool(pool: &SqlitePool) -> Result<Vec<Item>, String> {
-        let rows = sqlx::query("SELECT id, text, created_at FROM items ORDER BY created_at DESC")
-            .fetch_all(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch items: {}", e))?;
-        Ok(rows.into_iter().map(|row| Item {
-            id: row.get("id"), text: row.get("text"), created_at: row.get("created_at")
-        }).collect())
-    }
-    async fn delete_item_db_with_pool(id: i64, pool: &SqlitePool) -> Result<(), String> {
-        let result = sqlx::query("DELETE FROM items WHERE id = ?")
-            .bind(id)
-            .execute(pool)
-            .await
-            .map_err(|e| format!("Failed to delete item: {}", e))?;
-        if result.rows_affected() == 0 {
-            Err(format!("Item with id {} not found", id))
-        } else { Ok(()) }
-    }
-
     #[tokio::test]
-    async fn test_get_all_items_db_with_pool_helper() {
+    async fn test_list_items_sees_direct_inserts() {
         let pool = setup_test_db_with_migrations().await.unwrap();
         
         sqlx::query("INSERT INTO items (text, created_at) VALUES (?, ?)")
@@ -261,14 +449,14 @@ mod tests {
             .bind("Item 2".to_string()).bind(Utc::now().naive_utc())
             .execute(&pool).await.unwrap();
 
-        let items = get_all_items_db_with_pool(&pool).await.unwrap();
+        let items = SqliteItemRepository::new(pool.clone()).list_items().await.unwrap();
         assert_eq!(items.len(), 2);
         assert!(items.iter().any(|item| item.text == "Item 1"));
         assert!(items.iter().any(|item| item.text == "Item 2"));
     }
 
     #[tokio::test]
-    async fn test_delete_item_db_with_pool_helper() {
+    async fn test_delete_item_removes_row() {
         let pool = setup_test_db_with_migrations().await.unwrap();
         
         let text = "Item to delete".to_string();
@@ -279,11 +467,104 @@ mod tests {
         let item_id: i64 = sqlx::query_scalar("SELECT id FROM items WHERE text = ?")
             .bind(text).fetch_one(&pool).await.unwrap();
 
-        let delete_result = delete_item_db_with_pool(item_id, &pool).await;
+        let delete_result = SqliteItemRepository::new(pool.clone()).delete_item(item_id, "test").await;
         assert!(delete_result.is_ok());
 
         let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items WHERE id = ?")
             .bind(item_id).fetch_one(&pool).await.unwrap();
         assert_eq!(count, 0);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_delete_missing_item_is_not_found() {
+        let pool = setup_test_db_with_migrations().await.unwrap();
+
+        let delete_result = SqliteItemRepository::new(pool).delete_item(4242, "test").await;
+        assert!(matches!(delete_result, Err(AppError::NotFound(_))), "got {:?}", delete_result);
+    }
+
+    #[tokio::test]
+    async fn test_unique_violation_maps_to_conflict() {
+        let pool = setup_test_db_with_migrations().await.unwrap();
+        sqlx::query("CREATE TABLE tags (name TEXT NOT NULL UNIQUE)")
+            .execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO tags (name) VALUES ('dup')")
+            .execute(&pool).await.unwrap();
+
+        let err: AppError = sqlx::query("INSERT INTO tags (name) VALUES ('dup')")
+            .execute(&pool).await.unwrap_err().into();
+        assert!(matches!(err, AppError::Conflict(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_seed_database_only_seeds_empty_db() {
+        let repos = Repositories::sqlite(setup_test_db_with_migrations().await.unwrap());
+
+        seed_database(&repos).await.unwrap();
+        seed_database(&repos).await.unwrap();
+        assert_eq!(repos.items.count_items().await.unwrap(), 3);
+        assert_eq!(repos.graph.load_graph().await.unwrap(), bundled_graph().unwrap());
+        assert_eq!(repos.graph.list_versions().await.unwrap().len(), 1, "import should be versioned once");
+    }
+
+    #[tokio::test]
+    async fn test_seed_fixture_is_idempotent_and_versions_graph_changes() {
+        let repos = Repositories::sqlite(setup_test_db_with_migrations().await.unwrap());
+        seed_database(&repos).await.unwrap();
+
+        // Already applied by seed_database: nothing changes, no new version.
+        let demo = named_fixture("demo").unwrap();
+        assert_eq!(seed_fixture(&repos, "demo", &demo).await.unwrap(), FixtureReport::default());
+        assert_eq!(repos.graph.list_versions().await.unwrap().len(), 1);
+
+        // The test set's graph is a slice of the bundled one, so only its items are new.
+        let report = seed_fixture(&repos, "test", &named_fixture("test").unwrap()).await.unwrap();
+        assert_eq!(report, FixtureReport { items: 2, ..Default::default() });
+        assert_eq!(repos.graph.list_versions().await.unwrap().len(), 1);
+
+        let mut renamed = named_fixture("test").unwrap();
+        renamed.graph.books[0].title = "Excession (2nd ed.)".to_string();
+        assert_eq!(seed_fixture(&repos, "renamed", &renamed).await.unwrap().books, 1);
+        assert_eq!(repos.graph.list_versions().await.unwrap()[0].label, "Seeded fixture renamed");
+    }
+
+    #[test]
+    fn test_backend_is_chosen_from_url_scheme() {
+        assert_eq!(DbBackend::from_url("sqlite:data/app.db").unwrap(), DbBackend::Sqlite);
+        assert_eq!(DbBackend::from_url("sqlite::memory:").unwrap(), DbBackend::Sqlite);
+        assert_eq!(DbBackend::from_url("postgres://u:p@localhost/app").unwrap(), DbBackend::Postgres);
+        assert_eq!(DbBackend::from_url("postgresql://localhost/app").unwrap(), DbBackend::Postgres);
+        assert!(DbBackend::from_url("mysql://localhost/app").is_err());
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    #[tokio::test]
+    async fn test_postgres_url_without_feature_is_a_configuration_error() {
+        let err = init_pool("postgres://localhost/app").await.unwrap_err();
+        assert!(matches!(err, sqlx::Error::Configuration(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_seed_database_works_without_sqlite() {
+        let repos = Repositories::in_memory();
+
+        seed_database(&repos).await.unwrap();
+        assert_eq!(repos.items.count_items().await.unwrap(), 3);
+        assert!(!repos.graph.load_graph().await.unwrap().books.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_admin_only_when_no_users_exist() {
+        let repos = Repositories::in_memory();
+
+        assert!(bootstrap_admin(&repos, "ad", "long-enough").await.is_err(), "invalid names are rejected");
+        assert!(bootstrap_admin(&repos, "admin", "long-enough").await.unwrap());
+        assert!(!bootstrap_admin(&repos, "other", "long-enough").await.unwrap());
+
+        let users = repos.users.list_users().await.unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].role, shared::Role::Admin);
+        let (_, hash) = repos.users.find_credentials("admin").await.unwrap().unwrap();
+        assert!(crate::auth::verify_password("long-enough", &hash));
+    }
+}
@@ -0,0 +1,159 @@
+// Parsing of the tech graph CSV files (`public/data/*.csv`) into `shared::GraphData`.
+// Used by the graph page (files fetched over HTTP) and by the server, which
+// embeds the same files to seed the graph tables.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use shared::{Book, BookTechLink, GraphData, TaxonomyNode, Technology};
+
+use crate::errors::AppError;
+use crate::taxonomy::taxonomy_problems;
+
+pub const BOOKS_CSV: &str = include_str!("../public/data/books.csv");
+pub const TECHNOLOGIES_CSV: &str = include_str!("../public/data/technologies.csv");
+pub const BOOK_TECH_LINKS_CSV: &str = include_str!("../public/data/book_tech_links.csv");
+pub const TAXONOMY_CSV: &str = include_str!("../public/data/taxonomy.csv");
+
+pub fn parse_csv<T: DeserializeOwned>(name: &str, text: &str) -> Result<Vec<T>, AppError> {
+    let mut rdr = csv::Reader::from_reader(text.as_bytes());
+    rdr.deserialize()
+        .collect::<Result<Vec<T>, _>>()
+        .map_err(|e| AppError::validation(name, format!("invalid CSV: {}", e)))
+}
+
+// `taxonomy` is optional: datasets from before it have no `taxonomy.csv`.
+pub fn parse_graph_csv(books: &str, technologies: &str, links: &str, taxonomy: Option<&str>) -> Result<GraphData, AppError> {
+    Ok(GraphData {
+        books: parse_csv::<Book>("books", books)?,
+        technologies: parse_csv::<Technology>("technologies", technologies)?,
+        links: parse_csv::<BookTechLink>("book_tech_links", links)?,
+        taxonomy: taxonomy.map(|text| parse_csv::<TaxonomyNode>("taxonomy", text)).transpose()?.unwrap_or_default(),
+    })
+}
+
+// The dataset shipped in `public/data/`, compiled into the binary.
+pub fn bundled_graph() -> Result<GraphData, AppError> {
+    parse_graph_csv(BOOKS_CSV, TECHNOLOGIES_CSV, BOOK_TECH_LINKS_CSV, Some(TAXONOMY_CSV))
+}
+
+// Serialises one table with the same header row as the files in `public/data/`.
+pub fn to_csv<T: Serialize>(name: &str, rows: &[T]) -> Result<String, AppError> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        wtr.serialize(row)
+            .map_err(|e| AppError::validation(name, format!("failed to write CSV: {}", e)))?;
+    }
+    let bytes = wtr.into_inner().map_err(|e| AppError::validation(name, format!("failed to write CSV: {}", e)))?;
+    String::from_utf8(bytes).map_err(|e| AppError::validation(name, format!("failed to write CSV: {}", e)))
+}
+
+// The four CSV files for `data`, keyed by their file name in `public/data/`.
+pub fn graph_to_csv(data: &GraphData) -> Result<[(&'static str, String); 4], AppError> {
+    Ok([
+        ("books.csv", to_csv("books", &data.books)?),
+        ("technologies.csv", to_csv("technologies", &data.technologies)?),
+        ("book_tech_links.csv", to_csv("book_tech_links", &data.links)?),
+        ("taxonomy.csv", to_csv("taxonomy", &data.taxonomy)?),
+    ])
+}
+
+// Consistency problems that would break the graph page or the graph tables'
+// constraints, one human-readable line each. Empty means the data is usable.
+pub fn validate_graph(data: &GraphData) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let mut book_ids = HashSet::new();
+    for book in &data.books {
+        if !book_ids.insert(book.id) {
+            problems.push(format!("duplicate book id {}", book.id));
+        }
+        if book.title.trim().is_empty() {
+            problems.push(format!("book {} has an empty title", book.id));
+        }
+    }
+    let mut tech_ids = HashSet::new();
+    for tech in &data.technologies {
+        if !tech_ids.insert(tech.id) {
+            problems.push(format!("duplicate technology id {}", tech.id));
+        }
+        if tech.name.trim().is_empty() {
+            problems.push(format!("technology {} has an empty name", tech.id));
+        }
+    }
+    let mut links = HashSet::new();
+    for link in &data.links {
+        if !book_ids.contains(&link.book_id) {
+            problems.push(format!("link {}-{} points at unknown book {}", link.book_id, link.tech_id, link.book_id));
+        }
+        if !tech_ids.contains(&link.tech_id) {
+            problems.push(format!("link {}-{} points at unknown technology {}", link.book_id, link.tech_id, link.tech_id));
+        }
+        if !links.insert((link.book_id, link.tech_id)) {
+            problems.push(format!("duplicate link {}-{}", link.book_id, link.tech_id));
+        }
+        if let Some(problem) = link.problem() {
+            problems.push(format!("link {}-{}: {}", link.book_id, link.tech_id, problem));
+        }
+    }
+    problems.extend(taxonomy_problems(data));
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_graph_is_consistent() {
+        let data = bundled_graph().expect("bundled CSVs should parse");
+        assert!(!data.books.is_empty());
+        assert!(!data.technologies.is_empty());
+
+        let book_ids: HashSet<i32> = data.books.iter().map(|b| b.id).collect();
+        let tech_ids: HashSet<i32> = data.technologies.iter().map(|t| t.id).collect();
+        for link in &data.links {
+            assert!(book_ids.contains(&link.book_id), "dangling book id {}", link.book_id);
+            assert!(tech_ids.contains(&link.tech_id), "dangling tech id {}", link.tech_id);
+        }
+    }
+
+    #[test]
+    fn test_validate_graph_reports_each_problem() {
+        assert!(validate_graph(&bundled_graph().unwrap()).is_empty());
+
+        let mut data = bundled_graph().unwrap();
+        let first = data.links[0].clone();
+        data.books.push(data.books[0].clone());
+        data.links.push(first.clone());
+        data.links.push(BookTechLink::new(-1, first.tech_id));
+        data.links[1].weight = 0;
+        data.links[2].citation = Some(" ".into());
+        let problems = validate_graph(&data);
+        assert_eq!(problems.len(), 5, "got {:?}", problems);
+        assert!(problems.iter().any(|p| p.contains("unknown book -1")));
+        assert!(problems.iter().any(|p| p.contains("weight must be between 1 and 10, got 0")));
+    }
+
+    #[test]
+    fn test_graph_to_csv_round_trips_the_bundled_files() {
+        let data = bundled_graph().unwrap();
+        let [(books_file, books), (techs_file, techs), (links_file, links), (taxonomy_file, taxonomy)] = graph_to_csv(&data).unwrap();
+        assert_eq!(
+            (books_file, techs_file, links_file, taxonomy_file),
+            ("books.csv", "technologies.csv", "book_tech_links.csv", "taxonomy.csv")
+        );
+        assert_eq!(books.lines().next(), BOOKS_CSV.lines().next(), "same header row");
+        assert_eq!(techs.lines().next(), TECHNOLOGIES_CSV.lines().next(), "same header row");
+        assert_eq!(parse_graph_csv(&books, &techs, &links, Some(&taxonomy)).unwrap(), data);
+
+        // Without a taxonomy file the hierarchy is just categories and subcategories.
+        assert!(parse_graph_csv(&books, &techs, &links, None).unwrap().taxonomy.is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv_reports_file_as_field() {
+        let err = parse_csv::<Book>("books", "id,title\nnot-a-number,X\n").unwrap_err();
+        assert!(err.field_message("books").is_some(), "got {:?}", err);
+    }
+}
@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+#[cfg(feature = "ssr")]
+use http::status::StatusCode;
+
+// Typed error shared by the database layer and the server functions.
+// It travels to the client inside `ServerFnError::WrappedServerError`, which
+// serializes it with `Display` and restores it with `FromStr`. The `Display`
+// formats below are therefore both the user-facing message and the wire format,
+// so keep `FromStr` in sync when changing them.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum AppError {
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("validation failed on `{field}`: {message}")]
+    Validation { field: String, message: String },
+    #[error("conflict: {0}")]
+    Conflict(String),
+    #[error("storage error: {0}")]
+    Storage(String),
+    // Not signed in (or the session/token is invalid or expired).
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    // Signed in, but the role does not allow this.
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+}
+
+impl AppError {
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::Validation { field: field.into(), message: message.into() }
+    }
+
+    // Message for a specific form field, if this is a validation error on it.
+    pub fn field_message(&self, name: &str) -> Option<&str> {
+        match self {
+            AppError::Validation { field, message } if field == name => Some(message.as_str()),
+            _ => None,
+        }
+    }
+
+    // Stable machine-readable name of the variant, used by the GraphQL and
+    // REST APIs next to the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Validation { .. } => "VALIDATION",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Storage(_) => "INTERNAL",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl FromStr for AppError {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(msg) = s.strip_prefix("not found: ") {
+            Ok(AppError::NotFound(msg.to_string()))
+        } else if let Some(rest) = s.strip_prefix("validation failed on `") {
+            let (field, message) = rest.split_once("`: ").ok_or(())?;
+            Ok(AppError::validation(field, message))
+        } else if let Some(msg) = s.strip_prefix("conflict: ") {
+            Ok(AppError::Conflict(msg.to_string()))
+        } else if let Some(msg) = s.strip_prefix("storage error: ") {
+            Ok(AppError::Storage(msg.to_string()))
+        } else if let Some(msg) = s.strip_prefix("unauthorized: ") {
+            Ok(AppError::Unauthorized(msg.to_string()))
+        } else if let Some(msg) = s.strip_prefix("forbidden: ") {
+            Ok(AppError::Forbidden(msg.to_string()))
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => AppError::NotFound("requested row does not exist".to_string()),
+            sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                AppError::Conflict(db_err.message().to_string())
+            }
+            other => AppError::Storage(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_round_trips_through_from_str() {
+        let errors = vec![
+            AppError::NotFound("Item with id 7".to_string()),
+            AppError::validation("text", "Item text cannot be empty."),
+            AppError::Conflict("UNIQUE constraint failed: items.text".to_string()),
+            AppError::Storage("pool timed out".to_string()),
+            AppError::Unauthorized("please log in".to_string()),
+            AppError::Forbidden("requires the editor role".to_string()),
+        ];
+        for err in errors {
+            let parsed: AppError = err.to_string().parse().expect("error should parse back");
+            assert_eq!(parsed, err);
+        }
+    }
+
+    #[test]
+    fn test_field_message_only_matches_named_field() {
+        let err = AppError::validation("text", "too long");
+        assert_eq!(err.field_message("text"), Some("too long"));
+        assert_eq!(err.field_message("id"), None);
+        assert_eq!(AppError::Storage("x".into()).field_message("text"), None);
+        assert_eq!(err.code(), "VALIDATION");
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(AppError::NotFound(String::new()).status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(AppError::validation("f", "m").status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(AppError::Conflict(String::new()).status_code(), StatusCode::CONFLICT);
+        assert_eq!(AppError::Storage(String::new()).status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(AppError::Unauthorized(String::new()).status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(AppError::Forbidden(String::new()).status_code(), StatusCode::FORBIDDEN);
+    }
+}
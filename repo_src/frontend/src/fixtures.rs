@@ -0,0 +1,139 @@
+// Seed data sets for `backend seed`: YAML or JSON files listing items and
+// graph rows. The named sets in `fixtures/` are compiled into the binary;
+// other files can be passed by path. How a fixture is written (one
+// transaction, natural keys) is up to `FixtureRepository`.
+#![cfg(feature = "ssr")]
+
+use serde::Deserialize;
+use shared::GraphData;
+use std::path::Path;
+
+use crate::dataset::{bundled_graph, validate_graph};
+use crate::errors::AppError;
+use crate::repository::Fixture;
+use crate::server_fns::validate_item_text;
+
+pub const FIXTURE_SETS: [&str; 3] = ["demo", "test", "large"];
+pub const DEFAULT_FIXTURE_SET: &str = "demo";
+
+const DEMO_YAML: &str = include_str!("../fixtures/demo.yaml");
+const TEST_JSON: &str = include_str!("../fixtures/test.json");
+const LARGE_YAML: &str = include_str!("../fixtures/large.yaml");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixtureFormat {
+    Yaml,
+    Json,
+}
+
+impl FixtureFormat {
+    // `.json` files are JSON; anything else is read as YAML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => FixtureFormat::Json,
+            _ => FixtureFormat::Yaml,
+        }
+    }
+}
+
+// The file layout. Every key is optional.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FixtureFile {
+    #[serde(default)]
+    items: Vec<String>,
+    generated_items: Option<GeneratedItems>,
+    graph: Option<GraphSource>,
+}
+
+// `count` items named "<prefix> 1" to "<prefix> <count>".
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GeneratedItems {
+    prefix: String,
+    count: usize,
+}
+
+// `bundled` (the dataset in public/data/) or inline books/technologies/links.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GraphSource {
+    Named(String),
+    Inline(GraphData),
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::validation("fixture", message)
+}
+
+// Parses and checks a fixture: item texts must pass the AddItem rules and the
+// graph must be self-contained (`validate_graph`).
+pub fn parse_fixture(text: &str, format: FixtureFormat) -> Result<Fixture, AppError> {
+    let file: FixtureFile = match format {
+        FixtureFormat::Yaml => serde_yaml::from_str(text).map_err(|e| invalid(format!("invalid YAML: {}", e)))?,
+        FixtureFormat::Json => serde_json::from_str(text).map_err(|e| invalid(format!("invalid JSON: {}", e)))?,
+    };
+
+    let mut items = file.items;
+    if let Some(generated) = file.generated_items {
+        items.extend((1..=generated.count).map(|n| format!("{} {}", generated.prefix, n)));
+    }
+    for text in &items {
+        validate_item_text(text)
+            .map_err(|e| invalid(format!("item {:?}: {}", text, e.field_message("text").unwrap_or("invalid"))))?;
+    }
+
+    let graph = match file.graph {
+        None => GraphData::default(),
+        Some(GraphSource::Named(name)) if name == "bundled" => bundled_graph()?,
+        Some(GraphSource::Named(name)) => {
+            return Err(invalid(format!("unknown graph {:?} (expected `bundled` or inline books/technologies/links)", name)))
+        }
+        Some(GraphSource::Inline(graph)) => graph,
+    };
+    let problems = validate_graph(&graph);
+    if !problems.is_empty() {
+        return Err(invalid(problems.join("; ")));
+    }
+    Ok(Fixture { items, graph })
+}
+
+pub fn named_fixture(name: &str) -> Result<Fixture, AppError> {
+    match name {
+        "demo" => parse_fixture(DEMO_YAML, FixtureFormat::Yaml),
+        "test" => parse_fixture(TEST_JSON, FixtureFormat::Json),
+        "large" => parse_fixture(LARGE_YAML, FixtureFormat::Yaml),
+        other => Err(invalid(format!("unknown fixture set {:?} (expected one of: {})", other, FIXTURE_SETS.join(", ")))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_fixture_sets_parse() {
+        for name in FIXTURE_SETS {
+            assert!(named_fixture(name).is_ok(), "fixture set {} should parse", name);
+        }
+        let demo = named_fixture("demo").unwrap();
+        assert_eq!(demo.items, ["Buy groceries", "Read a book", "Learn Leptos"]);
+        assert_eq!(demo.graph, bundled_graph().unwrap());
+        assert_eq!(named_fixture("test").unwrap().graph.links.len(), 2);
+        assert_eq!(named_fixture("large").unwrap().items.last().map(String::as_str), Some("Backlog task 1000"));
+        assert!(named_fixture("huge").unwrap_err().field_message("fixture").is_some());
+    }
+
+    #[test]
+    fn test_parse_fixture_rejects_bad_content() {
+        let yaml = |text: &str| parse_fixture(text, FixtureFormat::Yaml);
+        assert_eq!(yaml("items: [one]").unwrap(), Fixture { items: vec!["one".into()], graph: GraphData::default() });
+        assert!(yaml("itemz: [one]").is_err(), "unknown keys are typos");
+        assert!(yaml("items: ['  ']").is_err());
+        assert!(yaml("graph: remote").is_err());
+        let dangling = "graph:\n  books: []\n  technologies: []\n  links: [{book_id: 1, tech_id: 2}]";
+        assert!(yaml(dangling).unwrap_err().field_message("fixture").unwrap().contains("unknown book 1"));
+        assert_eq!(FixtureFormat::from_path(Path::new("seed.JSON")), FixtureFormat::Json);
+        assert_eq!(FixtureFormat::from_path(Path::new("seed.yml")), FixtureFormat::Yaml);
+    }
+}
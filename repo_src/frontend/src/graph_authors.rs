@@ -0,0 +1,118 @@
+// Authors and series as entities of the tech graph. Each distinct author and
+// series gets a node linked to its books, and `fingerprints` summarises which
+// technologies a group's books use most (GetTechnologyFingerprints). Compiles
+// for the client too.
+
+use shared::{BookGrouping, GraphData, Technology, TechnologyFingerprint, TechnologyUse};
+use std::collections::{BTreeMap, HashMap};
+
+// Technologies listed per fingerprint by default.
+pub const DEFAULT_FINGERPRINT_SIZE: usize = 10;
+
+pub fn author_node_id(author: &str) -> String {
+    format!("a_{}", author)
+}
+
+pub fn series_node_id(series: &str) -> String {
+    format!("s_{}", series)
+}
+
+pub fn group_node_id(grouping: BookGrouping, name: &str) -> String {
+    match grouping {
+        BookGrouping::Author => author_node_id(name),
+        BookGrouping::Series => series_node_id(name),
+    }
+}
+
+// Each distinct (non-empty) author or series with its book ids, by name.
+pub fn groups(data: &GraphData, grouping: BookGrouping) -> BTreeMap<String, Vec<i32>> {
+    let mut groups: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for book in &data.books {
+        let key = grouping.key(book);
+        if !key.is_empty() {
+            groups.entry(key.to_string()).or_default().push(book.id);
+        }
+    }
+    groups
+}
+
+// One fingerprint per author or series, the most prolific first, each listing
+// at most `size` technologies by summed link weight, then by books. Ties go to
+// the technology's name.
+pub fn fingerprints(data: &GraphData, grouping: BookGrouping, size: usize) -> Vec<TechnologyFingerprint> {
+    let techs: HashMap<i32, &Technology> = data.technologies.iter().map(|t| (t.id, t)).collect();
+    let mut techs_of: HashMap<i32, BTreeMap<i32, i32>> = HashMap::new();
+    for link in data.links.iter().filter(|l| techs.contains_key(&l.tech_id)) {
+        techs_of.entry(link.book_id).or_default().insert(link.tech_id, link.weight);
+    }
+
+    let mut fingerprints: Vec<TechnologyFingerprint> = groups(data, grouping)
+        .into_iter()
+        .map(|(name, books)| {
+            let mut counts: HashMap<i32, (usize, i32)> = HashMap::new();
+            for (tech_id, weight) in books.iter().filter_map(|b| techs_of.get(b)).flatten() {
+                let (books, total) = counts.entry(*tech_id).or_default();
+                *books += 1;
+                *total += weight;
+            }
+            let distinct_technologies = counts.len();
+            let mut technologies: Vec<TechnologyUse> = counts
+                .into_iter()
+                .map(|(id, (books, weight))| {
+                    let tech = techs[&id];
+                    TechnologyUse { id, name: tech.name.clone(), category: tech.category.clone(), books, weight }
+                })
+                .collect();
+            technologies.sort_by(|a, b| {
+                b.weight.cmp(&a.weight).then(b.books.cmp(&a.books)).then_with(|| a.name.cmp(&b.name)).then(a.id.cmp(&b.id))
+            });
+            technologies.truncate(size);
+            TechnologyFingerprint { name, books: books.len(), technologies, distinct_technologies }
+        })
+        .collect();
+    // Stable, so equally prolific groups stay in name order.
+    fingerprints.sort_by_key(|f| std::cmp::Reverse(f.books));
+    fingerprints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use shared::{Book, BookTechLink};
+
+    #[test]
+    fn test_fingerprints_rank_technologies_by_use() {
+        let book = |id, author: &str, series: &str| Book { id, title: format!("Book {}", id), author: author.into(), series: series.into(), year: None };
+        let tech = |id, name: &str| Technology { id, name: name.into(), category: "C".into(), subcategory: "S".into(), description: String::new(), taxonomy_id: None };
+        let link = |book_id, tech_id, weight| BookTechLink { weight, ..BookTechLink::new(book_id, tech_id) };
+        let data = GraphData {
+            books: vec![book(1, "Banks", "Culture"), book(2, "Banks", "Culture"), book(3, "Banks", ""), book(4, "Reynolds", "")],
+            technologies: vec![tech(10, "Drive"), tech(11, "Minds"), tech(12, "Habitats")],
+            // Habitats, central to one book, outranks Drive, mentioned in one.
+            links: vec![link(1, 11, 2), link(2, 11, 2), link(3, 11, 2), link(1, 10, 1), link(3, 12, 9), link(4, 10, 1), link(4, 99, 1)],
+            taxonomy: Vec::new(),
+        };
+
+        let authors = fingerprints(&data, BookGrouping::Author, 2);
+        assert_eq!(authors.iter().map(|f| (f.name.as_str(), f.books)).collect::<Vec<_>>(), [("Banks", 3), ("Reynolds", 1)]);
+        let banks = &authors[0];
+        assert_eq!(banks.technologies.iter().map(|t| (t.name.as_str(), t.books, t.weight)).collect::<Vec<_>>(), [("Habitats", 1, 9), ("Minds", 3, 6)]);
+        assert_eq!(banks.distinct_technologies, 3);
+        assert_eq!(authors[1].distinct_technologies, 1, "links to unknown technologies are ignored");
+
+        // Standalone books are not a series.
+        let series = fingerprints(&data, BookGrouping::Series, DEFAULT_FINGERPRINT_SIZE);
+        assert_eq!(series.len(), 1);
+        assert_eq!((series[0].name.as_str(), series[0].books), ("Culture", 2));
+    }
+
+    #[test]
+    fn test_every_book_belongs_to_its_author() {
+        let data = bundled_graph().unwrap();
+        let authors = groups(&data, BookGrouping::Author);
+        assert_eq!(authors.values().map(Vec::len).sum::<usize>(), data.books.len());
+        assert!(groups(&data, BookGrouping::Series).values().map(Vec::len).sum::<usize>() < data.books.len());
+        assert_eq!(group_node_id(BookGrouping::Series, "Culture"), "s_Culture");
+    }
+}
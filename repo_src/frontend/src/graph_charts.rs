@@ -0,0 +1,394 @@
+// Layouts for the tech graph page's other charts, drawn as plain SVG: the
+// books × technologies adjacency matrix, the chord diagram of which categories
+// share books, and the sunburst of the taxonomy. Angles are in radians,
+// clockwise from twelve o'clock. Compiles for the client too.
+
+use shared::{GraphData, LinkKind, TaxonomyBranch};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::f64::consts::TAU;
+
+// Rows and columns drawn in the matrix, after sorting; the rest are counted
+// below it.
+pub const MAX_MATRIX_BOOKS: usize = 150;
+pub const MAX_MATRIX_TECHNOLOGIES: usize = 100;
+
+// Radians left empty after each chord group.
+pub const CHORD_GAP: f64 = 0.04;
+
+// A book or technology along one axis of the matrix.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisItem {
+    pub id: i32,
+    pub label: String,
+    // The technology's category, or the book's author.
+    pub group: String,
+    // Books sort on this too: their year, if any.
+    pub year: Option<i32>,
+    pub links: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdjacencyMatrix {
+    pub books: Vec<AxisItem>,
+    pub technologies: Vec<AxisItem>,
+    // (book id, technology id) to the link's kind and weight.
+    pub cells: HashMap<(i32, i32), (LinkKind, i32)>,
+}
+
+// Books and technologies in id order, with every link between known rows.
+pub fn adjacency_matrix(data: &GraphData) -> AdjacencyMatrix {
+    let book_ids: HashSet<i32> = data.books.iter().map(|b| b.id).collect();
+    let tech_ids: HashSet<i32> = data.technologies.iter().map(|t| t.id).collect();
+    let cells: HashMap<(i32, i32), (LinkKind, i32)> = data
+        .links
+        .iter()
+        .filter(|l| book_ids.contains(&l.book_id) && tech_ids.contains(&l.tech_id))
+        .map(|l| ((l.book_id, l.tech_id), (l.kind, l.weight)))
+        .collect();
+    // Books and technologies may share ids, so each has its own counts.
+    let mut book_links: HashMap<i32, usize> = HashMap::new();
+    let mut tech_links: HashMap<i32, usize> = HashMap::new();
+    for &(book_id, tech_id) in cells.keys() {
+        *book_links.entry(book_id).or_default() += 1;
+        *tech_links.entry(tech_id).or_default() += 1;
+    }
+
+    let mut books: Vec<AxisItem> = data
+        .books
+        .iter()
+        .map(|b| AxisItem { id: b.id, label: b.title.clone(), group: b.author.clone(), year: b.year, links: book_links.get(&b.id).copied().unwrap_or(0) })
+        .collect();
+    let mut technologies: Vec<AxisItem> = data
+        .technologies
+        .iter()
+        .map(|t| AxisItem { id: t.id, label: t.name.clone(), group: t.category.clone(), year: None, links: tech_links.get(&t.id).copied().unwrap_or(0) })
+        .collect();
+    books.sort_by_key(|b| b.id);
+    technologies.sort_by_key(|t| t.id);
+    AdjacencyMatrix { books, technologies, cells }
+}
+
+// How one axis of the matrix is sorted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AxisOrder {
+    #[default]
+    Name,
+    // By category for technologies; by year, then author, for books.
+    Group,
+    // Most linked first.
+    Links,
+    // Heaviest link to this book or technology on the other axis first.
+    Along(i32),
+}
+
+impl AxisOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AxisOrder::Name => "name",
+            AxisOrder::Group => "group",
+            AxisOrder::Links => "links",
+            AxisOrder::Along(_) => "along",
+        }
+    }
+
+    // The orders a picker offers; `Along` comes from clicking a header.
+    pub fn parse(s: &str) -> Option<AxisOrder> {
+        [AxisOrder::Name, AxisOrder::Group, AxisOrder::Links].into_iter().find(|o| o.as_str() == s)
+    }
+}
+
+impl AdjacencyMatrix {
+    fn weight(&self, book_id: i32, tech_id: i32) -> i32 {
+        self.cells.get(&(book_id, tech_id)).map_or(0, |&(_, weight)| weight)
+    }
+
+    // Ids of one axis in `order`; ties keep the name order.
+    pub fn sorted(&self, books: bool, order: AxisOrder) -> Vec<i32> {
+        let mut items: Vec<&AxisItem> = if books { self.books.iter().collect() } else { self.technologies.iter().collect() };
+        items.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+        match order {
+            AxisOrder::Name => {}
+            AxisOrder::Group if books => items.sort_by(|a, b| a.year.is_none().cmp(&b.year.is_none()).then(a.year.cmp(&b.year)).then_with(|| a.group.cmp(&b.group))),
+            AxisOrder::Group => items.sort_by(|a, b| a.group.cmp(&b.group)),
+            AxisOrder::Links => items.sort_by_key(|item| std::cmp::Reverse(item.links)),
+            AxisOrder::Along(other) => items.sort_by_key(|item| {
+                std::cmp::Reverse(if books { self.weight(item.id, other) } else { self.weight(other, item.id) })
+            }),
+        }
+        items.into_iter().map(|item| item.id).collect()
+    }
+}
+
+// Moves the id at `from` to `to`, shifting those between; for dragging a header.
+pub fn move_item(ids: &mut Vec<i32>, from: usize, to: usize) {
+    if from < ids.len() && to < ids.len() {
+        let id = ids.remove(from);
+        ids.insert(to, id);
+    }
+}
+
+// Books featuring technologies of each pair of categories. `books[i][j]` for
+// i ≠ j counts books with both, and `books[i][i]` those with category i at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoOccurrence {
+    pub categories: Vec<String>,
+    pub books: Vec<Vec<usize>>,
+}
+
+pub fn category_cooccurrence(data: &GraphData) -> CoOccurrence {
+    let categories: Vec<String> = data.technologies.iter().map(|t| t.category.clone()).collect::<BTreeSet<_>>().into_iter().collect();
+    let index: HashMap<&str, usize> = categories.iter().enumerate().map(|(i, c)| (c.as_str(), i)).collect();
+    let category_of: HashMap<i32, usize> = data.technologies.iter().map(|t| (t.id, index[t.category.as_str()])).collect();
+    let mut of_book: BTreeMap<i32, BTreeSet<usize>> = BTreeMap::new();
+    for link in &data.links {
+        if let Some(&category) = category_of.get(&link.tech_id) {
+            of_book.entry(link.book_id).or_default().insert(category);
+        }
+    }
+
+    let mut books = vec![vec![0; categories.len()]; categories.len()];
+    for featured in of_book.values() {
+        for &i in featured {
+            for &j in featured {
+                books[i][j] += 1;
+            }
+        }
+    }
+    CoOccurrence { categories, books }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChordRibbon {
+    pub from: usize,
+    pub to: usize,
+    pub books: usize,
+    // The ribbon's ends along the two groups' arcs.
+    pub source: (f64, f64),
+    pub target: (f64, f64),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChordLayout {
+    // Each category's arc, as wide as the books it shares with the others.
+    pub groups: Vec<(f64, f64)>,
+    pub ribbons: Vec<ChordRibbon>,
+}
+
+// Lays the categories around the circle in order; a category sharing no
+// books gets no arc (an empty range) and no ribbons.
+pub fn chord_layout(cooccurrence: &CoOccurrence, gap: f64) -> ChordLayout {
+    let n = cooccurrence.categories.len();
+    let shared = |i: usize, j: usize| if i == j { 0 } else { cooccurrence.books[i][j] };
+    let totals: Vec<usize> = (0..n).map(|i| (0..n).map(|j| shared(i, j)).sum()).collect();
+    let total: usize = totals.iter().sum();
+    if total == 0 {
+        return ChordLayout { groups: vec![(0.0, 0.0); n], ribbons: Vec::new() };
+    }
+    let arcs = totals.iter().filter(|t| **t > 0).count();
+    let gap = gap.min(TAU / arcs as f64 / 2.0);
+    let per_book = (TAU - gap * arcs as f64) / total as f64;
+
+    // Where each (i, j) share starts along i's arc.
+    let mut groups = Vec::with_capacity(n);
+    let mut starts = vec![vec![0.0; n]; n];
+    let mut angle = 0.0;
+    for (i, row) in starts.iter_mut().enumerate() {
+        let start = angle;
+        for (j, share_start) in row.iter_mut().enumerate() {
+            *share_start = angle;
+            angle += shared(i, j) as f64 * per_book;
+        }
+        groups.push((start, angle));
+        if totals[i] > 0 {
+            angle += gap;
+        }
+    }
+
+    let mut ribbons = Vec::new();
+    for (i, row) in starts.iter().enumerate() {
+        for (j, &source) in row.iter().enumerate().skip(i + 1) {
+            let books = shared(i, j);
+            if books > 0 {
+                let width = books as f64 * per_book;
+                ribbons.push(ChordRibbon {
+                    from: i,
+                    to: j,
+                    books,
+                    source: (source, source + width),
+                    target: (starts[j][i], starts[j][i] + width),
+                });
+            }
+        }
+    }
+    ChordLayout { groups, ribbons }
+}
+
+// One segment of the sunburst: a taxonomy branch or, on the outside, a technology.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SunburstArc {
+    pub path: Vec<String>,
+    pub technology: Option<i32>,
+    // 1 for categories, one more per level below.
+    pub depth: usize,
+    pub start: f64,
+    pub end: f64,
+    // Books using its technologies, counted once per technology.
+    pub books: usize,
+}
+
+// Sum of the book counts of every technology in the branch.
+fn branch_size(branch: &TaxonomyBranch) -> usize {
+    branch.technologies.iter().map(|t| t.books).sum::<usize>() + branch.children.iter().map(branch_size).sum::<usize>()
+}
+
+// The taxonomy tree around the circle, each segment as wide as its share of
+// the books; segments of no books are left out. A book using two technologies
+// counts for both, so every segment is the sum of those outside it.
+pub fn sunburst(tree: &[TaxonomyBranch]) -> Vec<SunburstArc> {
+    fn place(branch: &TaxonomyBranch, start: f64, per_book: f64, arcs: &mut Vec<SunburstArc>) -> f64 {
+        let books = branch_size(branch);
+        let depth = branch.path.len();
+        arcs.push(SunburstArc { path: branch.path.clone(), technology: None, depth, start, end: start + books as f64 * per_book, books });
+        let mut angle = start;
+        for child in branch.children.iter().filter(|c| branch_size(c) > 0) {
+            angle = place(child, angle, per_book, arcs);
+        }
+        for tech in branch.technologies.iter().filter(|t| t.books > 0) {
+            let mut path = branch.path.clone();
+            path.push(tech.name.clone());
+            let end = angle + tech.books as f64 * per_book;
+            arcs.push(SunburstArc { path, technology: Some(tech.id), depth: depth + 1, start: angle, end, books: tech.books });
+            angle = end;
+        }
+        angle
+    }
+
+    let total: usize = tree.iter().map(branch_size).sum();
+    let mut arcs = Vec::new();
+    if total == 0 {
+        return arcs;
+    }
+    let per_book = TAU / total as f64;
+    let mut angle = 0.0;
+    for branch in tree.iter().filter(|b| branch_size(b) > 0) {
+        angle = place(branch, angle, per_book, &mut arcs);
+    }
+    arcs
+}
+
+// The point at `angle` on a circle of `radius` around the origin.
+pub fn polar(radius: f64, angle: f64) -> (f64, f64) {
+    (radius * angle.sin(), -radius * angle.cos())
+}
+
+// SVG path of the ring segment between two radii and two angles.
+pub fn arc_path(inner: f64, outer: f64, start: f64, end: f64) -> String {
+    let large = if end - start > TAU / 2.0 { 1 } else { 0 };
+    // A full circle would start and end on the same point and draw nothing.
+    let end = end.min(start + TAU - 1e-6);
+    let (x0, y0) = polar(outer, start);
+    let (x1, y1) = polar(outer, end);
+    let (x2, y2) = polar(inner, end);
+    let (x3, y3) = polar(inner, start);
+    format!(
+        "M{:.2},{:.2}A{:.2},{:.2} 0 {} 1 {:.2},{:.2}L{:.2},{:.2}A{:.2},{:.2} 0 {} 0 {:.2},{:.2}Z",
+        x0, y0, outer, outer, large, x1, y1, x2, y2, inner, inner, large, x3, y3
+    )
+}
+
+// SVG path of a chord ribbon: along the circle over `source`, through the
+// centre to `target`, along it and back.
+pub fn ribbon_path(radius: f64, source: (f64, f64), target: (f64, f64)) -> String {
+    let large = |(start, end): (f64, f64)| if end - start > TAU / 2.0 { 1 } else { 0 };
+    let (s0, s1) = (polar(radius, source.0), polar(radius, source.1));
+    let (t0, t1) = (polar(radius, target.0), polar(radius, target.1));
+    format!(
+        "M{:.2},{:.2}A{r:.2},{r:.2} 0 {} 1 {:.2},{:.2}Q0,0 {:.2},{:.2}A{r:.2},{r:.2} 0 {} 1 {:.2},{:.2}Q0,0 {:.2},{:.2}Z",
+        s0.0, s0.1, large(source), s1.0, s1.1, t0.0, t0.1, large(target), t1.0, t1.1, s0.0, s0.1, r = radius
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::taxonomy::taxonomy_tree;
+    use crate::test_data::{test_book, test_graph, test_tech};
+    use shared::{Book, Technology};
+
+    fn tiny() -> GraphData {
+        let books = [(1, "Cyan", Some(1990)), (2, "Amber", None), (3, "Blue", Some(1970))]
+            .map(|(id, title, year)| Book { title: title.into(), year, ..test_book(id) });
+        let technologies = [(10, "Drive", "PHYSICS"), (11, "Minds", "COMPUTING"), (12, "Habitats", "SPACE")]
+            .map(|(id, name, category)| Technology { name: name.into(), category: category.into(), ..test_tech(id) });
+        let mut data = test_graph(books, technologies, &[(1, 10), (1, 11), (2, 11), (3, 10), (3, 11), (3, 12)]);
+        for (link, weight) in data.links.iter_mut().zip([9, 2, 5, 1, 7, 3]) {
+            link.weight = weight;
+        }
+        data
+    }
+
+    #[test]
+    fn test_matrix_axes_sort_and_reorder() {
+        let matrix = adjacency_matrix(&tiny());
+        assert_eq!(matrix.cells.len(), 6);
+        assert_eq!(matrix.sorted(true, AxisOrder::Name), [2, 3, 1]);
+        // Undated books go last.
+        assert_eq!(matrix.sorted(true, AxisOrder::Group), [3, 1, 2]);
+        assert_eq!(matrix.sorted(true, AxisOrder::Links), [3, 1, 2]);
+        assert_eq!(matrix.sorted(true, AxisOrder::Along(11)), [3, 2, 1]);
+        assert_eq!(matrix.sorted(false, AxisOrder::Group), [11, 10, 12]);
+        assert_eq!(matrix.sorted(false, AxisOrder::Along(1)), [10, 11, 12]);
+        assert_eq!(AxisOrder::parse("links"), Some(AxisOrder::Links));
+
+        let mut ids = vec![1, 2, 3, 4];
+        move_item(&mut ids, 0, 2);
+        assert_eq!(ids, [2, 3, 1, 4]);
+        move_item(&mut ids, 3, 0);
+        assert_eq!(ids, [4, 2, 3, 1]);
+        move_item(&mut ids, 9, 0);
+        assert_eq!(ids, [4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_chords_share_books_between_categories() {
+        let cooccurrence = category_cooccurrence(&tiny());
+        assert_eq!(cooccurrence.categories, ["COMPUTING", "PHYSICS", "SPACE"]);
+        assert_eq!(cooccurrence.books, [[3, 2, 1], [2, 2, 1], [1, 1, 1]]);
+
+        let layout = chord_layout(&cooccurrence, CHORD_GAP);
+        assert_eq!(layout.ribbons.iter().map(|r| (r.from, r.to, r.books)).collect::<Vec<_>>(), [(0, 1, 2), (0, 2, 1), (1, 2, 1)]);
+        let span = |(start, end): (f64, f64)| end - start;
+        // COMPUTING shares three books, SPACE two, and the gaps take the rest.
+        assert!((span(layout.groups[0]) / span(layout.groups[2]) - 1.5).abs() < 1e-9);
+        assert!((layout.groups.iter().copied().map(span).sum::<f64>() + 3.0 * CHORD_GAP - TAU).abs() < 1e-9);
+        for ribbon in &layout.ribbons {
+            assert!((span(ribbon.source) - span(ribbon.target)).abs() < 1e-9);
+        }
+
+        let bundled = category_cooccurrence(&bundled_graph().unwrap());
+        assert!(chord_layout(&bundled, CHORD_GAP).ribbons.len() > 1);
+    }
+
+    #[test]
+    fn test_sunburst_nests_segments() {
+        let data = bundled_graph().unwrap();
+        let arcs = sunburst(&taxonomy_tree(&data));
+        let categories: Vec<&SunburstArc> = arcs.iter().filter(|a| a.depth == 1).collect();
+        assert!((categories.last().unwrap().end - TAU).abs() < 1e-9);
+        assert_eq!(categories.iter().map(|a| a.books).sum::<usize>(), data.links.len());
+
+        // Every segment lies within the one inside it.
+        for arc in arcs.iter().filter(|a| a.depth > 1) {
+            let parent = arcs.iter().find(|p| p.depth == arc.depth - 1 && arc.path.starts_with(&p.path)).unwrap();
+            assert!(arc.start >= parent.start - 1e-9 && arc.end <= parent.end + 1e-9, "{:?} outside {:?}", arc.path, parent.path);
+        }
+        // Technologies no book features take no room.
+        let featured: BTreeSet<i32> = data.links.iter().map(|l| l.tech_id).collect();
+        assert_eq!(arcs.iter().filter(|a| a.technology.is_some()).count(), featured.len());
+
+        assert_eq!(polar(10.0, 0.0), (0.0, -10.0));
+        assert!(arc_path(10.0, 20.0, 0.0, TAU).contains(" 0 1 1 "), "a full ring takes the large arc");
+        assert_eq!(ribbon_path(10.0, (0.0, TAU / 4.0), (TAU / 2.0, TAU / 2.0)), "M0.00,-10.00A10.00,10.00 0 0 1 10.00,-0.00Q0,0 0.00,10.00A10.00,10.00 0 0 1 0.00,10.00Q0,0 0.00,-10.00Z");
+    }
+}
@@ -0,0 +1,280 @@
+// Level-of-detail aggregation of the tech graph. Instead of every book and
+// technology, the page gets one node per category with counts; expanding a
+// category shows its technologies and expanding a technology shows its books.
+// Links that end inside a collapsed cluster are bundled into one weighted edge
+// per pair of visible nodes, so the view stays small whatever the graph size.
+// A book's edges weigh the summed weights of the links they stand for, and an
+// expanded technology shows its most prominent books first.
+// A filter only highlights: nodes it picks out are flagged, nothing is hidden.
+// Used by the GetGraphClusters server function; compiles for the client too.
+
+use crate::graph_filter::{matching_books, technology_selected};
+use shared::{ClusterEdge, ClusterEdgeKind, ClusterExpansion, ClusterNode, ClusterNodeKind, ClusterView, GraphData, GraphFilter, Technology};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+// Books shown for one expanded technology; the rest are counted in a "more" node.
+pub const MAX_BOOKS_PER_TECHNOLOGY: usize = 100;
+
+// Bundled edges kept, heaviest first. Each one is only a hint of overlap, and
+// thousands of them make the layout unreadable long before they make it slow.
+pub const MAX_SHARED_EDGES: usize = 1_000;
+
+pub fn category_node_id(category: &str) -> String {
+    format!("c_{}", category)
+}
+
+pub fn technology_node_id(id: i32) -> String {
+    format!("t_{}", id)
+}
+
+pub fn cluster_graph(data: &GraphData, expansion: &ClusterExpansion, filter: &GraphFilter) -> ClusterView {
+    let matching = matching_books(data, filter);
+    let open_categories: HashSet<&str> = expansion.categories.iter().map(String::as_str).collect();
+    let open_technologies: HashSet<i32> = expansion.technologies.iter().copied().collect();
+    let techs: HashMap<i32, &Technology> = data.technologies.iter().map(|t| (t.id, t)).collect();
+    let book_ids: HashSet<i32> = data.books.iter().map(|b| b.id).collect();
+
+    // Adjacency in id order; links to unknown rows are ignored.
+    let mut books_of: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    let mut techs_of: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    let mut weights: HashMap<(i32, i32), usize> = HashMap::new();
+    for link in &data.links {
+        if techs.contains_key(&link.tech_id) && book_ids.contains(&link.book_id) {
+            books_of.entry(link.tech_id).or_default().push(link.book_id);
+            techs_of.entry(link.book_id).or_default().push(link.tech_id);
+            weights.insert((link.book_id, link.tech_id), link.weight.max(1) as usize);
+        }
+    }
+    for list in books_of.values_mut().chain(techs_of.values_mut()) {
+        list.sort_unstable();
+        list.dedup();
+    }
+    let weight = |book_id: i32, tech_id: i32| weights.get(&(book_id, tech_id)).copied().unwrap_or(1);
+
+    // The visible node standing in for a technology.
+    let shows_technology = |tech: &Technology| open_categories.contains(tech.category.as_str()) || open_technologies.contains(&tech.id);
+    let representative = |tech_id: i32| {
+        let tech = techs[&tech_id];
+        if shows_technology(tech) { technology_node_id(tech.id) } else { category_node_id(&tech.category) }
+    };
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+
+    // Categories, always shown, with their technology and distinct book counts.
+    let mut categories: BTreeMap<&str, (usize, HashSet<i32>, bool)> = BTreeMap::new();
+    for tech in &data.technologies {
+        let (count, books, selected) = categories.entry(tech.category.as_str()).or_default();
+        *count += 1;
+        books.extend(books_of.get(&tech.id).into_iter().flatten());
+        *selected |= technology_selected(filter, tech);
+    }
+    for (category, (technologies, books, selected)) in &categories {
+        nodes.push(ClusterNode {
+            id: category_node_id(category),
+            kind: ClusterNodeKind::Category,
+            label: category.to_string(),
+            category: category.to_string(),
+            detail: String::new(),
+            technologies: *technologies,
+            books: books.len(),
+            expanded: open_categories.contains(category),
+            highlighted: *selected,
+        });
+    }
+
+    // Technologies of open categories, plus any opened on their own.
+    let mut shown_techs: Vec<&Technology> = data.technologies.iter().filter(|t| shows_technology(t)).collect();
+    shown_techs.sort_by_key(|t| t.id);
+    for tech in &shown_techs {
+        let books = books_of.get(&tech.id).map_or(0, Vec::len);
+        nodes.push(ClusterNode {
+            id: technology_node_id(tech.id),
+            kind: ClusterNodeKind::Technology,
+            label: tech.name.clone(),
+            category: tech.category.clone(),
+            detail: format!("{} — {}", tech.subcategory, tech.description),
+            technologies: 1,
+            books,
+            expanded: open_technologies.contains(&tech.id),
+            highlighted: technology_selected(filter, tech),
+        });
+        edges.push(ClusterEdge {
+            from: technology_node_id(tech.id),
+            to: category_node_id(&tech.category),
+            kind: ClusterEdgeKind::Member,
+            weight: 1,
+        });
+    }
+
+    // Books of open technologies, heaviest links first, up to the limit for each.
+    let mut visible_books: BTreeSet<i32> = BTreeSet::new();
+    for tech in shown_techs.iter().filter(|t| open_technologies.contains(&t.id)) {
+        let mut books = books_of.get(&tech.id).cloned().unwrap_or_default();
+        // Stable, so equal weights stay in id order.
+        books.sort_by_key(|&book_id| std::cmp::Reverse(weight(book_id, tech.id)));
+        visible_books.extend(books.iter().take(MAX_BOOKS_PER_TECHNOLOGY).copied());
+        if books.len() > MAX_BOOKS_PER_TECHNOLOGY {
+            let hidden = books.len() - MAX_BOOKS_PER_TECHNOLOGY;
+            let more_id = format!("m_{}", tech.id);
+            nodes.push(ClusterNode {
+                id: more_id.clone(),
+                kind: ClusterNodeKind::MoreBooks,
+                label: format!("+{} more", hidden),
+                category: String::new(),
+                detail: String::new(),
+                technologies: 0,
+                books: hidden,
+                expanded: false,
+                highlighted: false,
+            });
+            edges.push(ClusterEdge { from: technology_node_id(tech.id), to: more_id, kind: ClusterEdgeKind::Member, weight: hidden });
+        }
+    }
+    for book in data.books.iter().filter(|b| visible_books.contains(&b.id)) {
+        let linked = techs_of.get(&book.id).map(Vec::as_slice).unwrap_or_default();
+        nodes.push(ClusterNode {
+            id: format!("b_{}", book.id),
+            kind: ClusterNodeKind::Book,
+            label: book.title.clone(),
+            category: String::new(),
+            detail: if book.series.is_empty() { format!("by {}", book.author) } else { format!("by {} · {}", book.author, book.series) },
+            technologies: linked.len(),
+            books: 1,
+            expanded: false,
+            highlighted: matching.contains(&book.id),
+        });
+        let mut targets: BTreeMap<String, usize> = BTreeMap::new();
+        for &tech_id in linked {
+            *targets.entry(representative(tech_id)).or_default() += weight(book.id, tech_id);
+        }
+        edges.extend(targets.into_iter().map(|(to, weight)| ClusterEdge {
+            from: format!("b_{}", book.id),
+            to,
+            kind: ClusterEdgeKind::Link,
+            weight,
+        }));
+    }
+
+    // Every other book ties together the visible nodes it is linked through.
+    let mut shared: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for (book_id, linked) in &techs_of {
+        if visible_books.contains(book_id) {
+            continue;
+        }
+        let ends: Vec<String> = linked.iter().map(|&t| representative(t)).collect::<BTreeSet<_>>().into_iter().collect();
+        for (i, from) in ends.iter().enumerate() {
+            for to in &ends[i + 1..] {
+                *shared.entry((from.clone(), to.clone())).or_default() += 1;
+            }
+        }
+    }
+    let mut shared: Vec<ClusterEdge> = shared
+        .into_iter()
+        .map(|((from, to), weight)| ClusterEdge { from, to, kind: ClusterEdgeKind::Shared, weight })
+        .collect();
+    // Stable, so equal weights stay in (from, to) order.
+    shared.sort_by_key(|edge| std::cmp::Reverse(edge.weight));
+    shared.truncate(MAX_SHARED_EDGES);
+    edges.extend(shared);
+
+    ClusterView {
+        nodes,
+        edges,
+        total_books: data.books.len(),
+        total_technologies: data.technologies.len(),
+        total_links: data.links.len(),
+        matching_books: matching.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use shared::{Book, BookTechLink};
+
+    fn kinds(view: &ClusterView, kind: ClusterNodeKind) -> Vec<&ClusterNode> {
+        view.nodes.iter().filter(|n| n.kind == kind).collect()
+    }
+
+    #[test]
+    fn test_collapsed_view_has_one_node_per_category() {
+        let data = bundled_graph().unwrap();
+        let view = cluster_graph(&data, &ClusterExpansion::default(), &GraphFilter::default());
+
+        let categories: BTreeSet<&str> = data.technologies.iter().map(|t| t.category.as_str()).collect();
+        assert_eq!(view.nodes.len(), categories.len());
+        assert_eq!(view.nodes.iter().map(|n| n.technologies).sum::<usize>(), data.technologies.len());
+        assert!(view.nodes.iter().all(|n| n.kind == ClusterNodeKind::Category && !n.expanded && !n.highlighted));
+        assert!(view.edges.iter().all(|e| e.kind == ClusterEdgeKind::Shared && e.weight > 0));
+        assert_eq!((view.total_books, view.total_technologies), (data.books.len(), data.technologies.len()));
+    }
+
+    #[test]
+    fn test_expanding_shows_technologies_then_books() {
+        let data = bundled_graph().unwrap();
+        let tech = &data.technologies[0];
+        let expansion = ClusterExpansion { categories: vec![tech.category.clone()], technologies: vec![tech.id] };
+        let view = cluster_graph(&data, &expansion, &GraphFilter::default());
+
+        let in_category = data.technologies.iter().filter(|t| t.category == tech.category).count();
+        assert_eq!(kinds(&view, ClusterNodeKind::Technology).len(), in_category);
+        let linked: BTreeSet<i32> = data.links.iter().filter(|l| l.tech_id == tech.id).map(|l| l.book_id).collect();
+        assert_eq!(kinds(&view, ClusterNodeKind::Book).len(), linked.len());
+
+        // Every visible book links to the expanded technology, and every edge
+        // ends at a visible node.
+        let tech_id = technology_node_id(tech.id);
+        for book in linked {
+            let from = format!("b_{}", book);
+            assert!(view.edges.iter().any(|e| e.from == from && e.to == tech_id && e.kind == ClusterEdgeKind::Link));
+        }
+        let ids: HashSet<&str> = view.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(view.edges.iter().all(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str())));
+    }
+
+    #[test]
+    fn test_books_past_the_limit_collapse_into_a_more_node() {
+        let count = MAX_BOOKS_PER_TECHNOLOGY + 5;
+        let data = GraphData {
+            books: (1..=count as i32).map(|id| Book { id, title: format!("Book {}", id), author: "A".into(), series: String::new(), year: None }).collect(),
+            technologies: vec![Technology { id: 1000, name: "Drive".into(), category: "C".into(), subcategory: "S".into(), description: String::new(), taxonomy_id: None }],
+            // The last book features the drive most, so it is shown ahead of earlier ids.
+            links: (1..=count as i32)
+                .map(|book_id| BookTechLink { weight: if book_id == count as i32 { 8 } else { 1 }, ..BookTechLink::new(book_id, 1000) })
+                .collect(),
+            taxonomy: Vec::new(),
+        };
+        let view = cluster_graph(&data, &ClusterExpansion { categories: vec![], technologies: vec![1000] }, &GraphFilter::default());
+
+        let books = kinds(&view, ClusterNodeKind::Book);
+        assert_eq!(books.len(), MAX_BOOKS_PER_TECHNOLOGY);
+        let last = format!("b_{}", count);
+        assert!(books.iter().any(|n| n.id == last));
+        let edge = view.edges.iter().find(|e| e.from == last).unwrap();
+        assert_eq!((edge.kind, edge.weight), (ClusterEdgeKind::Link, 8));
+        let more = kinds(&view, ClusterNodeKind::MoreBooks);
+        assert_eq!((more.len(), more[0].books), (1, 5));
+        assert_eq!(kinds(&view, ClusterNodeKind::Category)[0].books, count);
+    }
+
+    #[test]
+    fn test_filter_highlights_without_hiding() {
+        let data = bundled_graph().unwrap();
+        let tech = &data.technologies[0];
+        let filter = GraphFilter { technologies: [tech.id].into(), ..Default::default() };
+        let expansion = ClusterExpansion { categories: vec![], technologies: vec![tech.id] };
+        let plain = cluster_graph(&data, &expansion, &GraphFilter::default());
+        let view = cluster_graph(&data, &expansion, &filter);
+
+        assert_eq!(plain.nodes.len(), view.nodes.len());
+        let linked: BTreeSet<i32> = data.links.iter().filter(|l| l.tech_id == tech.id).map(|l| l.book_id).collect();
+        assert_eq!(view.matching_books, linked.len());
+        let highlighted: Vec<&ClusterNode> = view.nodes.iter().filter(|n| n.highlighted).collect();
+        assert!(highlighted.iter().any(|n| n.id == category_node_id(&tech.category)));
+        assert!(highlighted.iter().any(|n| n.id == technology_node_id(tech.id)));
+        assert_eq!(highlighted.iter().filter(|n| n.kind == ClusterNodeKind::Book).count(), linked.len());
+        assert_eq!(plain.matching_books, 0);
+    }
+}
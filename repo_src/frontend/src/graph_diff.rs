@@ -0,0 +1,93 @@
+// Comparing two versions of the tech graph. Books and technologies are matched
+// by id and links by their book and technology, so an edited title or a
+// reweighted link shows up as a change rather than a remove + add.
+// Used by the DiffGraphVersions server function; compiles for the client too.
+
+use shared::{Changed, GraphData, GraphDiff};
+use std::collections::BTreeMap;
+
+pub fn diff_graphs(before: &GraphData, after: &GraphData) -> GraphDiff {
+    let (added_books, removed_books, changed_books) =
+        diff_by_id(&before.books, &after.books, |b| b.id);
+    let (added_technologies, removed_technologies, changed_technologies) =
+        diff_by_id(&before.technologies, &after.technologies, |t| t.id);
+    let (added_links, removed_links, changed_links) =
+        diff_by_id(&before.links, &after.links, |l| (l.book_id, l.tech_id));
+
+    GraphDiff {
+        added_books,
+        removed_books,
+        changed_books,
+        added_technologies,
+        removed_technologies,
+        changed_technologies,
+        added_links,
+        removed_links,
+        changed_links,
+    }
+}
+
+// (added, removed, changed), each in id order.
+fn diff_by_id<T: Clone + PartialEq, K: Ord>(
+    before: &[T],
+    after: &[T],
+    id: impl Fn(&T) -> K,
+) -> (Vec<T>, Vec<T>, Vec<Changed<T>>) {
+    let old: BTreeMap<K, &T> = before.iter().map(|x| (id(x), x)).collect();
+    let new: BTreeMap<K, &T> = after.iter().map(|x| (id(x), x)).collect();
+
+    let added = new.iter().filter(|(k, _)| !old.contains_key(k)).map(|(_, v)| (*v).clone()).collect();
+    let removed = old.iter().filter(|(k, _)| !new.contains_key(k)).map(|(_, v)| (*v).clone()).collect();
+    let changed = new
+        .iter()
+        .filter_map(|(k, after)| {
+            let before = old.get(k)?;
+            (before != after).then(|| Changed { before: (*before).clone(), after: (*after).clone() })
+        })
+        .collect();
+    (added, removed, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use shared::BookTechLink;
+
+    #[test]
+    fn test_identical_graphs_have_empty_diff() {
+        let data = bundled_graph().unwrap();
+        assert!(diff_graphs(&data, &data).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_and_changed() {
+        let before = bundled_graph().unwrap();
+        let mut after = before.clone();
+
+        let removed_book = after.books.remove(0);
+        after.links.retain(|l| l.book_id != removed_book.id);
+        after.technologies[0].description = "Rewritten".to_string();
+        let new_link = BookTechLink::new(after.books[0].id, after.technologies[0].id);
+        if !after.links.iter().any(|l| (l.book_id, l.tech_id) == (new_link.book_id, new_link.tech_id)) {
+            after.links.push(new_link.clone());
+        }
+        after.links[0].weight += 1;
+
+        let diff = diff_graphs(&before, &after);
+        assert_eq!(diff.removed_books, vec![removed_book.clone()]);
+        assert!(diff.added_books.is_empty());
+        assert_eq!(diff.changed_technologies.len(), 1);
+        assert_eq!(diff.changed_technologies[0].after.description, "Rewritten");
+        assert!(diff.removed_links.iter().all(|l| l.book_id == removed_book.id));
+        assert!(!diff.removed_links.is_empty());
+        assert_eq!(diff.changed_links.len(), 1);
+        assert_eq!(diff.changed_links[0].after.weight, diff.changed_links[0].before.weight + 1);
+
+        // Diffing the other way round swaps added and removed.
+        let reverse = diff_graphs(&after, &before);
+        assert_eq!(reverse.added_books, vec![removed_book]);
+        assert_eq!(reverse.added_links, diff.removed_links);
+        assert_eq!(reverse.removed_links, diff.added_links);
+    }
+}
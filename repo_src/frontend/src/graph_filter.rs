@@ -0,0 +1,236 @@
+// Evaluating a `GraphFilter` against the tech graph. A technology is selected
+// when the filter names it, its category or its subcategory. A book matches
+// when, in `MatchMode::Any`, it meets at least one selection, and in
+// `MatchMode::All` it uses every selected technology and something from every
+// selected category and subcategory. A book has one author and one series, so
+// those selections are always alternatives: under `All` the book's author must
+// be one of the selected authors (if any are selected), likewise for series.
+//
+// The same rules drive highlighting on the tech graph page, the clustered view
+// (GetGraphClusters) and subgraph extraction (GetFilteredGraph); compiles for
+// the client too.
+
+use shared::{Book, BookTechLink, FilterOptions, GraphData, GraphFilter, MatchMode, Technology};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+pub fn technology_selected(filter: &GraphFilter, tech: &Technology) -> bool {
+    filter.technologies.contains(&tech.id)
+        || filter.categories.contains(&tech.category)
+        || filter.subcategories.contains(&tech.subcategory)
+}
+
+// Whether the filter says anything about technologies (as opposed to only
+// authors and series).
+pub fn has_technology_selections(filter: &GraphFilter) -> bool {
+    !(filter.technologies.is_empty() && filter.categories.is_empty() && filter.subcategories.is_empty())
+}
+
+fn book_matches(filter: &GraphFilter, book: &Book, techs: &[&Technology]) -> bool {
+    let technology_selections = filter.technologies.iter().map(|id| techs.iter().any(|t| t.id == *id))
+        .chain(filter.categories.iter().map(|c| techs.iter().any(|t| &t.category == c)))
+        .chain(filter.subcategories.iter().map(|s| techs.iter().any(|t| &t.subcategory == s)));
+    let by_author = filter.authors.contains(&book.author);
+    let in_series = filter.series.contains(&book.series);
+    match filter.mode {
+        MatchMode::Any => by_author || in_series || technology_selections.into_iter().any(|met| met),
+        MatchMode::All => {
+            (filter.authors.is_empty() || by_author)
+                && (filter.series.is_empty() || in_series)
+                && technology_selections.into_iter().all(|met| met)
+        }
+    }
+}
+
+// Ids of the books matching the filter. Nothing matches an empty filter.
+pub fn matching_books(data: &GraphData, filter: &GraphFilter) -> BTreeSet<i32> {
+    if filter.is_empty() {
+        return BTreeSet::new();
+    }
+    let techs: HashMap<i32, &Technology> = data.technologies.iter().map(|t| (t.id, t)).collect();
+    let mut techs_of: HashMap<i32, Vec<&Technology>> = HashMap::new();
+    for link in &data.links {
+        if let Some(tech) = techs.get(&link.tech_id) {
+            techs_of.entry(link.book_id).or_default().push(tech);
+        }
+    }
+    data.books
+        .iter()
+        .filter(|book| book_matches(filter, book, techs_of.get(&book.id).map(Vec::as_slice).unwrap_or_default()))
+        .map(|book| book.id)
+        .collect()
+}
+
+// The part of the graph the filter is about: the matching books, the selected
+// technologies (or, when only authors and series are selected, every
+// technology of the matching books) and the links between them. An empty
+// filter keeps the whole graph.
+pub fn extract_subgraph(data: &GraphData, filter: &GraphFilter) -> GraphData {
+    if filter.is_empty() {
+        return data.clone();
+    }
+    let books = matching_books(data, filter);
+    let tech_ids: HashSet<i32> = if has_technology_selections(filter) {
+        data.technologies.iter().filter(|t| technology_selected(filter, t)).map(|t| t.id).collect()
+    } else {
+        data.links.iter().filter(|l| books.contains(&l.book_id)).map(|l| l.tech_id).collect()
+    };
+    let links: Vec<BookTechLink> = data
+        .links
+        .iter()
+        .filter(|l| books.contains(&l.book_id) && tech_ids.contains(&l.tech_id))
+        .cloned()
+        .collect();
+    GraphData {
+        books: data.books.iter().filter(|b| books.contains(&b.id)).cloned().collect(),
+        technologies: data.technologies.iter().filter(|t| tech_ids.contains(&t.id)).cloned().collect(),
+        links,
+        taxonomy: data.taxonomy.clone(),
+    }
+}
+
+pub fn filter_options(data: &GraphData) -> FilterOptions {
+    let mut technologies = data.technologies.clone();
+    technologies.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    let distinct = |values: Vec<&String>| -> Vec<String> {
+        values.into_iter().filter(|v| !v.is_empty()).cloned().collect::<BTreeSet<_>>().into_iter().collect()
+    };
+    FilterOptions {
+        categories: distinct(data.technologies.iter().map(|t| &t.category).collect()),
+        subcategories: distinct(data.technologies.iter().map(|t| &t.subcategory).collect()),
+        authors: distinct(data.books.iter().map(|b| &b.author).collect()),
+        series: distinct(data.books.iter().map(|b| &b.series).collect()),
+        technologies,
+    }
+}
+
+// One selection in a filter, as shown on a removable chip.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FilterChip {
+    Technology(i32),
+    Category(String),
+    Subcategory(String),
+    Author(String),
+    Series(String),
+}
+
+impl FilterChip {
+    pub fn facet(&self) -> &'static str {
+        match self {
+            FilterChip::Technology(_) => "Technology",
+            FilterChip::Category(_) => "Category",
+            FilterChip::Subcategory(_) => "Subcategory",
+            FilterChip::Author(_) => "Author",
+            FilterChip::Series(_) => "Series",
+        }
+    }
+
+    // Returns false if the filter already had it.
+    pub fn insert_into(self, filter: &mut GraphFilter) -> bool {
+        match self {
+            FilterChip::Technology(id) => filter.technologies.insert(id),
+            FilterChip::Category(name) => filter.categories.insert(name),
+            FilterChip::Subcategory(name) => filter.subcategories.insert(name),
+            FilterChip::Author(name) => filter.authors.insert(name),
+            FilterChip::Series(name) => filter.series.insert(name),
+        }
+    }
+
+    // Returns false if the filter did not have it.
+    pub fn remove_from(&self, filter: &mut GraphFilter) -> bool {
+        match self {
+            FilterChip::Technology(id) => filter.technologies.remove(id),
+            FilterChip::Category(name) => filter.categories.remove(name),
+            FilterChip::Subcategory(name) => filter.subcategories.remove(name),
+            FilterChip::Author(name) => filter.authors.remove(name),
+            FilterChip::Series(name) => filter.series.remove(name),
+        }
+    }
+}
+
+// Every selection in the filter, facet by facet.
+pub fn chips(filter: &GraphFilter) -> Vec<FilterChip> {
+    filter.technologies.iter().map(|id| FilterChip::Technology(*id))
+        .chain(filter.categories.iter().cloned().map(FilterChip::Category))
+        .chain(filter.subcategories.iter().cloned().map(FilterChip::Subcategory))
+        .chain(filter.authors.iter().cloned().map(FilterChip::Author))
+        .chain(filter.series.iter().cloned().map(FilterChip::Series))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::test_data::{test_book, test_graph, test_tech};
+
+    fn tiny() -> GraphData {
+        let books = [(1, "Banks", "Culture"), (2, "Banks", ""), (3, "Reynolds", "Revelation Space")]
+            .map(|(id, author, series)| Book { author: author.into(), series: series.into(), ..test_book(id) });
+        let technologies = [(10, "PHYSICS", "Propulsion"), (11, "COMPUTING", "AI"), (12, "PHYSICS", "Weapons")]
+            .map(|(id, category, subcategory)| Technology { category: category.into(), subcategory: subcategory.into(), ..test_tech(id) });
+        test_graph(books, technologies, &[(1, 10), (1, 11), (2, 11), (3, 10), (3, 12)])
+    }
+
+    fn filter(chips: Vec<FilterChip>, mode: MatchMode) -> GraphFilter {
+        let mut filter = GraphFilter { mode, ..Default::default() };
+        for chip in chips {
+            chip.insert_into(&mut filter);
+        }
+        filter
+    }
+
+    #[test]
+    fn test_any_and_all_semantics() {
+        let data = tiny();
+        let techs = vec![FilterChip::Technology(10), FilterChip::Technology(11)];
+        assert_eq!(matching_books(&data, &filter(techs.clone(), MatchMode::Any)), BTreeSet::from([1, 2, 3]));
+        assert_eq!(matching_books(&data, &filter(techs, MatchMode::All)), BTreeSet::from([1]));
+
+        // Categories and subcategories are met by any technology inside them.
+        let mixed = vec![FilterChip::Category("PHYSICS".into()), FilterChip::Subcategory("Weapons".into())];
+        assert_eq!(matching_books(&data, &filter(mixed, MatchMode::All)), BTreeSet::from([3]));
+
+        // Authors and series are alternatives even under All.
+        let authors = vec![FilterChip::Author("Banks".into()), FilterChip::Author("Reynolds".into()), FilterChip::Technology(10)];
+        assert_eq!(matching_books(&data, &filter(authors.clone(), MatchMode::All)), BTreeSet::from([1, 3]));
+        assert_eq!(matching_books(&data, &filter(authors, MatchMode::Any)), BTreeSet::from([1, 2, 3]));
+
+        assert!(matching_books(&data, &GraphFilter { mode: MatchMode::All, ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn test_extract_subgraph() {
+        let data = tiny();
+        let sub = extract_subgraph(&data, &filter(vec![FilterChip::Technology(10)], MatchMode::Any));
+        assert_eq!(sub.books.iter().map(|b| b.id).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(sub.technologies.iter().map(|t| t.id).collect::<Vec<_>>(), [10]);
+        assert_eq!(sub.links.len(), 2);
+
+        // Only an author: the books keep all their technologies.
+        let sub = extract_subgraph(&data, &filter(vec![FilterChip::Series("Revelation Space".into())], MatchMode::All));
+        assert_eq!(sub.books.iter().map(|b| b.id).collect::<Vec<_>>(), [3]);
+        assert_eq!(sub.technologies.iter().map(|t| t.id).collect::<Vec<_>>(), [10, 12]);
+
+        assert_eq!(extract_subgraph(&data, &GraphFilter::default()), data);
+    }
+
+    #[test]
+    fn test_chips_and_options() {
+        let data = bundled_graph().unwrap();
+        let options = filter_options(&data);
+        assert!(options.technologies.windows(2).all(|w| w[0].name <= w[1].name));
+        assert!(!options.series.contains(&String::new()));
+        assert!(options.authors.contains(&"Iain M. Banks".to_string()));
+
+        let mut selected = GraphFilter::default();
+        assert!(FilterChip::Author("Iain M. Banks".into()).insert_into(&mut selected));
+        assert!(!FilterChip::Author("Iain M. Banks".into()).insert_into(&mut selected));
+        assert!(FilterChip::Technology(options.technologies[0].id).insert_into(&mut selected));
+        let all = chips(&selected);
+        assert_eq!(all.iter().map(FilterChip::facet).collect::<Vec<_>>(), ["Technology", "Author"]);
+        for chip in &all {
+            assert!(chip.remove_from(&mut selected));
+        }
+        assert!(selected.is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+// Checking and running a parsed query against the graph: a backtracking
+// matcher over an adjacency index of books, technologies and categories.
+
+use shared::{Book, GraphData, QueryEdge, QueryResult, QueryValue, Technology};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::parser::{CompareOp, Direction, EdgeKind, EdgePattern, Expr, Label, Literal, NodePattern, Pattern, Query};
+use crate::errors::AppError;
+
+// Units of work (candidate nodes and edges tried, conditions evaluated, nodes
+// and edges recorded for a match) before a query is given up on. Keeps a
+// careless cross product (`MATCH (a), (b), (c)`) from tying up the server.
+pub const MAX_STEPS: usize = 2_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum NodeRef {
+    Book(usize),
+    Tech(usize),
+    Category(usize),
+}
+
+enum Value<'a> {
+    Str(&'a str),
+    Int(i64),
+}
+
+struct GraphIndex<'a> {
+    books: Vec<&'a Book>,
+    techs: Vec<&'a Technology>,
+    categories: Vec<&'a str>,
+    book_by_id: HashMap<i32, usize>,
+    tech_by_id: HashMap<i32, usize>,
+    // Adjacency by position in the vectors above.
+    uses: Vec<Vec<usize>>,
+    used_by: Vec<Vec<usize>>,
+    category_of: Vec<usize>,
+    techs_in: Vec<Vec<usize>>,
+}
+
+impl<'a> GraphIndex<'a> {
+    fn new(data: &'a GraphData) -> Self {
+        let books: Vec<&Book> = data.books.iter().collect();
+        let techs: Vec<&Technology> = data.technologies.iter().collect();
+        let categories: Vec<&str> = techs.iter().map(|t| t.category.as_str()).collect::<BTreeSet<_>>().into_iter().collect();
+        let category_index: HashMap<&str, usize> = categories.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+        let book_by_id: HashMap<i32, usize> = books.iter().enumerate().map(|(i, b)| (b.id, i)).collect();
+        let tech_by_id: HashMap<i32, usize> = techs.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+
+        let mut uses = vec![Vec::new(); books.len()];
+        let mut used_by = vec![Vec::new(); techs.len()];
+        for link in &data.links {
+            if let (Some(&b), Some(&t)) = (book_by_id.get(&link.book_id), tech_by_id.get(&link.tech_id)) {
+                uses[b].push(t);
+                used_by[t].push(b);
+            }
+        }
+        let category_of: Vec<usize> = techs.iter().map(|t| category_index[t.category.as_str()]).collect();
+        let mut techs_in = vec![Vec::new(); categories.len()];
+        for (t, &c) in category_of.iter().enumerate() {
+            techs_in[c].push(t);
+        }
+        Self { books, techs, categories, book_by_id, tech_by_id, uses, used_by, category_of, techs_in }
+    }
+
+    fn label(node: NodeRef) -> Label {
+        match node {
+            NodeRef::Book(_) => Label::Book,
+            NodeRef::Tech(_) => Label::Technology,
+            NodeRef::Category(_) => Label::Category,
+        }
+    }
+
+    fn property(&self, node: NodeRef, prop: &str) -> Option<Value<'a>> {
+        Some(match (node, prop) {
+            (NodeRef::Book(b), "id") => Value::Int(self.books[b].id.into()),
+            (NodeRef::Book(b), "title") => Value::Str(&self.books[b].title),
+            (NodeRef::Book(b), "author") => Value::Str(&self.books[b].author),
+            (NodeRef::Book(b), "series") => Value::Str(&self.books[b].series),
+            // No year compares as false, like a property that does not exist.
+            (NodeRef::Book(b), "year") => Value::Int(self.books[b].year?.into()),
+            (NodeRef::Tech(t), "id") => Value::Int(self.techs[t].id.into()),
+            (NodeRef::Tech(t), "name") => Value::Str(&self.techs[t].name),
+            (NodeRef::Tech(t), "category") => Value::Str(&self.techs[t].category),
+            (NodeRef::Tech(t), "subcategory") => Value::Str(&self.techs[t].subcategory),
+            (NodeRef::Tech(t), "description") => Value::Str(&self.techs[t].description),
+            (NodeRef::Category(c), "name") => Value::Str(self.categories[c]),
+            _ => return None,
+        })
+    }
+
+    fn all(&self, label: Option<Label>) -> Vec<NodeRef> {
+        let books = (0..self.books.len()).map(NodeRef::Book);
+        let techs = (0..self.techs.len()).map(NodeRef::Tech);
+        let categories = (0..self.categories.len()).map(NodeRef::Category);
+        match label {
+            Some(Label::Book) => books.collect(),
+            Some(Label::Technology) => techs.collect(),
+            Some(Label::Category) => categories.collect(),
+            None => books.chain(techs).chain(categories).collect(),
+        }
+    }
+
+    // (neighbour, edge as (from, to)) for every edge the pattern allows.
+    fn neighbours(&self, node: NodeRef, edge: &EdgePattern) -> Vec<(NodeRef, (NodeRef, NodeRef))> {
+        let uses = edge.kind != Some(EdgeKind::In);
+        let is_in = edge.kind != Some(EdgeKind::Uses);
+        let outgoing = edge.direction != Direction::Left;
+        let incoming = edge.direction != Direction::Right;
+        let mut found = Vec::new();
+        match node {
+            NodeRef::Book(b) if uses && outgoing => {
+                found.extend(self.uses[b].iter().map(|&t| (NodeRef::Tech(t), (node, NodeRef::Tech(t)))));
+            }
+            NodeRef::Book(_) => {}
+            NodeRef::Tech(t) => {
+                if uses && incoming {
+                    found.extend(self.used_by[t].iter().map(|&b| (NodeRef::Book(b), (NodeRef::Book(b), node))));
+                }
+                if is_in && outgoing {
+                    let category = NodeRef::Category(self.category_of[t]);
+                    found.push((category, (node, category)));
+                }
+            }
+            NodeRef::Category(c) if is_in && incoming => {
+                found.extend(self.techs_in[c].iter().map(|&t| (NodeRef::Tech(t), (NodeRef::Tech(t), node))));
+            }
+            NodeRef::Category(_) => {}
+        }
+        found
+    }
+
+    fn value(&self, node: NodeRef) -> QueryValue {
+        match node {
+            NodeRef::Book(b) => QueryValue::Book(self.books[b].clone()),
+            NodeRef::Tech(t) => QueryValue::Technology(self.techs[t].clone()),
+            NodeRef::Category(c) => QueryValue::Category { name: self.categories[c].to_string() },
+        }
+    }
+}
+
+fn compare(actual: Option<Value>, op: CompareOp, expected: &Literal) -> bool {
+    match (actual, expected) {
+        (Some(Value::Int(a)), Literal::Int(b)) => match op {
+            CompareOp::Eq => a == *b,
+            CompareOp::Ne => a != *b,
+            CompareOp::Lt => a < *b,
+            CompareOp::Le => a <= *b,
+            CompareOp::Gt => a > *b,
+            CompareOp::Ge => a >= *b,
+            CompareOp::Contains | CompareOp::StartsWith | CompareOp::EndsWith => false,
+        },
+        (Some(Value::Str(a)), Literal::Str(b)) => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b.as_str(),
+            CompareOp::Le => a <= b.as_str(),
+            CompareOp::Gt => a > b.as_str(),
+            CompareOp::Ge => a >= b.as_str(),
+            CompareOp::Contains => a.contains(b.as_str()),
+            CompareOp::StartsWith => a.starts_with(b.as_str()),
+            CompareOp::EndsWith => a.ends_with(b.as_str()),
+        },
+        // A missing property or a type mismatch never matches, as in Cypher.
+        _ => false,
+    }
+}
+
+// Variables bound so far, every node and edge matched along the way, and
+// how to undo back to an earlier point.
+#[derive(Default)]
+struct State {
+    bindings: Vec<(String, NodeRef)>,
+    nodes: Vec<NodeRef>,
+    edges: Vec<(NodeRef, NodeRef)>,
+}
+
+impl State {
+    fn get(&self, var: &str) -> Option<NodeRef> {
+        self.bindings.iter().find(|(name, _)| name == var).map(|(_, node)| *node)
+    }
+
+    fn mark(&self) -> (usize, usize, usize) {
+        (self.bindings.len(), self.nodes.len(), self.edges.len())
+    }
+
+    fn reset(&mut self, (bindings, nodes, edges): (usize, usize, usize)) {
+        self.bindings.truncate(bindings);
+        self.nodes.truncate(nodes);
+        self.edges.truncate(edges);
+    }
+
+    fn bind(&mut self, pattern: &NodePattern, node: NodeRef) {
+        if let Some(var) = &pattern.var {
+            if self.get(var).is_none() {
+                self.bindings.push((var.clone(), node));
+            }
+        }
+        self.nodes.push(node);
+    }
+}
+
+// Called for each complete match; returns false to stop matching.
+type Visit<'f, 'a> = dyn FnMut(&mut Matcher<'a>, &mut State) -> Result<bool, AppError> + 'f;
+
+struct Matcher<'a> {
+    index: &'a GraphIndex<'a>,
+    steps: usize,
+}
+
+impl<'a> Matcher<'a> {
+    fn tick(&mut self) -> Result<(), AppError> {
+        self.charge(1)
+    }
+
+    fn charge(&mut self, steps: usize) -> Result<(), AppError> {
+        self.steps += steps;
+        if self.steps > MAX_STEPS {
+            return Err(AppError::validation("query", "the query is too expensive; narrow it with labels or properties"));
+        }
+        Ok(())
+    }
+
+    fn node_matches(&self, pattern: &NodePattern, node: NodeRef, state: &State) -> bool {
+        pattern.label.is_none_or(|label| label == GraphIndex::label(node))
+            && pattern.var.as_ref().and_then(|var| state.get(var)).is_none_or(|bound| bound == node)
+            && pattern.props.iter().all(|(prop, value)| compare(self.index.property(node, prop), CompareOp::Eq, value))
+    }
+
+    fn candidates(&self, pattern: &NodePattern, state: &State) -> Vec<NodeRef> {
+        if let Some(bound) = pattern.var.as_ref().and_then(|var| state.get(var)) {
+            return vec![bound];
+        }
+        // Look nodes up by id rather than scanning when the pattern names one.
+        let id = pattern.props.iter().find_map(|(prop, value)| match value {
+            Literal::Int(id) if prop == "id" => Some(*id),
+            _ => None,
+        });
+        match (pattern.label, id.and_then(|id| i32::try_from(id).ok())) {
+            (Some(Label::Book), Some(id)) => self.index.book_by_id.get(&id).map(|&b| NodeRef::Book(b)).into_iter().collect(),
+            (Some(Label::Technology), Some(id)) => self.index.tech_by_id.get(&id).map(|&t| NodeRef::Tech(t)).into_iter().collect(),
+            (label, _) => self.index.all(label),
+        }
+    }
+
+    fn match_all(&mut self, patterns: &[Pattern], state: &mut State, visit: &mut Visit<'_, 'a>) -> Result<bool, AppError> {
+        let Some((pattern, rest)) = patterns.split_first() else {
+            return visit(self, state);
+        };
+        for node in self.candidates(&pattern.start, state) {
+            self.tick()?;
+            if !self.node_matches(&pattern.start, node, state) {
+                continue;
+            }
+            let mark = state.mark();
+            state.bind(&pattern.start, node);
+            let go_on = self.match_steps(pattern, 0, node, rest, state, visit)?;
+            state.reset(mark);
+            if !go_on {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn match_steps(
+        &mut self,
+        pattern: &Pattern,
+        step: usize,
+        current: NodeRef,
+        rest: &[Pattern],
+        state: &mut State,
+        visit: &mut Visit<'_, 'a>,
+    ) -> Result<bool, AppError> {
+        let Some((edge, next)) = pattern.steps.get(step) else {
+            return self.match_all(rest, state, visit);
+        };
+        for (neighbour, traversed) in self.index.neighbours(current, edge) {
+            self.tick()?;
+            if !self.node_matches(next, neighbour, state) {
+                continue;
+            }
+            let mark = state.mark();
+            state.bind(next, neighbour);
+            state.edges.push(traversed);
+            let go_on = self.match_steps(pattern, step + 1, neighbour, rest, state, visit)?;
+            state.reset(mark);
+            if !go_on {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn eval(&mut self, expr: &Expr, state: &mut State) -> Result<bool, AppError> {
+        self.tick()?;
+        Ok(match expr {
+            Expr::Or(a, b) => self.eval(a, state)? || self.eval(b, state)?,
+            Expr::And(a, b) => self.eval(a, state)? && self.eval(b, state)?,
+            Expr::Not(inner) => !self.eval(inner, state)?,
+            Expr::Compare { var, prop, op, value } => {
+                let node = state.get(var).expect("checked: variables in conditions are bound");
+                compare(self.index.property(node, prop), *op, value)
+            }
+            Expr::Exists { patterns, filter } => {
+                let mut found = false;
+                self.match_all(patterns, state, &mut |matcher, state| {
+                    found = match filter {
+                        Some(filter) => matcher.eval(filter, state)?,
+                        None => true,
+                    };
+                    Ok(!found)
+                })?;
+                found
+            }
+        })
+    }
+}
+
+// Variables in scope with their label, if known.
+type Scope = Vec<(String, Option<Label>)>;
+
+fn declare(patterns: &[Pattern], scope: &mut Scope) -> Result<(), AppError> {
+    let nodes = patterns.iter().flat_map(|p| std::iter::once(&p.start).chain(p.steps.iter().map(|(_, n)| n)));
+    for node in nodes {
+        let mut label = node.label;
+        if let Some(var) = &node.var {
+            match scope.iter_mut().find(|(name, _)| name == var) {
+                Some((_, known)) => match (*known, node.label) {
+                    (Some(a), Some(b)) if a != b => {
+                        return Err(AppError::validation("query", format!("{} cannot be both a {} and a {}", var, a.name(), b.name())));
+                    }
+                    (Some(a), _) => label = Some(a),
+                    (None, b) => *known = b,
+                },
+                None => scope.push((var.clone(), node.label)),
+            }
+        }
+        for (prop, _) in &node.props {
+            check_property(node.var.as_deref().unwrap_or("node"), label, prop)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_property(var: &str, label: Option<Label>, prop: &str) -> Result<(), AppError> {
+    let known = match label {
+        Some(label) => label.properties().contains(&prop),
+        None => [Label::Book, Label::Technology, Label::Category].iter().any(|l| l.properties().contains(&prop)),
+    };
+    if known {
+        return Ok(());
+    }
+    let hint = match label {
+        Some(label) => format!("a {} has {}", label.name(), label.properties().join(", ")),
+        None => "no node type has it".to_string(),
+    };
+    Err(AppError::validation("query", format!("unknown property {}.{}: {}", var, prop, hint)))
+}
+
+fn check_expr(expr: &Expr, scope: &Scope) -> Result<(), AppError> {
+    match expr {
+        Expr::Or(a, b) | Expr::And(a, b) => {
+            check_expr(a, scope)?;
+            check_expr(b, scope)
+        }
+        Expr::Not(inner) => check_expr(inner, scope),
+        Expr::Compare { var, prop, .. } => match scope.iter().find(|(name, _)| name == var) {
+            Some((_, label)) => check_property(var, *label, prop),
+            None => Err(AppError::validation("query", format!("unknown variable {}", var))),
+        },
+        Expr::Exists { patterns, filter } => {
+            // Variables first named inside EXISTS are local to it.
+            let mut inner = scope.clone();
+            declare(patterns, &mut inner)?;
+            filter.as_deref().map_or(Ok(()), |filter| check_expr(filter, &inner))
+        }
+    }
+}
+
+// The returned columns, after checking every variable and property is known.
+fn check_query(query: &Query) -> Result<Vec<String>, AppError> {
+    let mut scope = Scope::new();
+    declare(&query.patterns, &mut scope)?;
+    if let Some(filter) = &query.filter {
+        check_expr(filter, &scope)?;
+    }
+    if query.returns.is_empty() {
+        if scope.is_empty() {
+            return Err(AppError::validation("query", "name at least one node, e.g. (b:Book), to return it"));
+        }
+        return Ok(scope.into_iter().map(|(name, _)| name).collect());
+    }
+    for var in &query.returns {
+        if !scope.iter().any(|(name, _)| name == var) {
+            return Err(AppError::validation("query", format!("unknown variable {} in RETURN", var)));
+        }
+    }
+    Ok(query.returns.clone())
+}
+
+pub fn execute(data: &GraphData, query: &Query, max_rows: usize) -> Result<QueryResult, AppError> {
+    let columns = check_query(query)?;
+    let index = GraphIndex::new(data);
+    let mut matcher = Matcher { index: &index, steps: 0 };
+    let limit = query.limit.unwrap_or(max_rows).min(max_rows);
+
+    let mut rows: Vec<Vec<NodeRef>> = Vec::new();
+    let mut seen_rows = HashSet::new();
+    let mut nodes: Vec<NodeRef> = Vec::new();
+    let mut seen_nodes = HashSet::new();
+    let mut edges = BTreeSet::new();
+    let mut truncated = false;
+
+    matcher.match_all(&query.patterns, &mut State::default(), &mut |matcher, state| {
+        if let Some(filter) = &query.filter {
+            if !matcher.eval(filter, state)? {
+                return Ok(true);
+            }
+        }
+        // Recording a match costs as much as the nodes and edges it binds.
+        matcher.charge(columns.len() + state.nodes.len() + state.edges.len())?;
+        let row: Vec<NodeRef> = columns.iter().map(|var| state.get(var).expect("checked: returned variables are bound")).collect();
+        if !seen_rows.contains(&row) {
+            if rows.len() == limit {
+                truncated = true;
+                return Ok(false);
+            }
+            seen_rows.insert(row.clone());
+            rows.push(row);
+        }
+        nodes.extend(state.nodes.iter().filter(|node| seen_nodes.insert(**node)));
+        edges.extend(state.edges.iter().copied());
+        Ok(true)
+    })?;
+
+    let edge_id = |node: NodeRef| index.value(node).node_id();
+    Ok(QueryResult {
+        columns,
+        rows: rows.into_iter().map(|row| row.into_iter().map(|node| index.value(node)).collect()).collect(),
+        truncated,
+        nodes: nodes.into_iter().map(|node| index.value(node)).collect(),
+        edges: edges.into_iter().map(|(from, to)| QueryEdge { from: edge_id(from), to: edge_id(to) }).collect(),
+    })
+}
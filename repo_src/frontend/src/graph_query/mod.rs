@@ -0,0 +1,155 @@
+// A small Cypher-like pattern language for structural questions about the
+// tech graph, e.g. books that feature both FTL travel and sentient AI but no
+// nanotechnology:
+//
+//     MATCH (b:Book)-[:USES]->(:Technology {name: "Faster-Than-Light (FTL) Drive"}),
+//           (b)-[:USES]->(ai:Technology)
+//     WHERE ai.name CONTAINS "Sentient AI"
+//       AND NOT (b)-[:USES]->(:Technology {subcategory: "Nano-engineering"})
+//     RETURN b
+//
+// The graph has three node labels, `Book` (id, title, author, series, year),
+// `Technology` (id, name, category, subcategory, description) and `Category`
+// (name), and two relationship types: `(:Book)-[:USES]->(:Technology)` and
+// `(:Technology)-[:IN]->(:Category)`.
+//
+// - MATCH takes comma-separated patterns of `(var:Label {prop: value})` nodes
+//   joined by `-[:TYPE]->`, `<-[:TYPE]-` or `-[:TYPE]-`; the variable, label,
+//   properties and type are all optional. A variable used twice is the same node.
+// - WHERE combines `var.prop` comparisons (=, <>, <, <=, >, >=, CONTAINS,
+//   STARTS WITH, ENDS WITH against a string or number literal) and patterns
+//   (`(b)-[:USES]->(:Technology)`, or `EXISTS { pattern WHERE condition }`)
+//   with AND, OR, NOT and parentheses, nested at most 64 levels deep. A query
+//   names at most 32 nodes, EXISTS blocks included.
+// - RETURN lists the variables to return (all named ones by default); rows
+//   are always distinct. LIMIT caps the rows, up to `MAX_ROWS`.
+//
+// Keywords, labels and types are case-insensitive; `//` starts a comment.
+// Used by the RunGraphQuery server function; compiles for the client too.
+
+mod eval;
+mod parser;
+
+pub use parser::parse_query;
+
+use shared::{GraphData, QueryResult};
+
+use crate::errors::AppError;
+
+pub const MAX_QUERY_LEN: usize = 10_000;
+pub const MAX_ROWS: usize = 1_000;
+
+pub fn run_query(data: &GraphData, source: &str) -> Result<QueryResult, AppError> {
+    if source.len() > MAX_QUERY_LEN {
+        return Err(AppError::validation("query", format!("must be at most {} characters", MAX_QUERY_LEN)));
+    }
+    eval::execute(data, &parse_query(source)?, MAX_ROWS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::test_data::{test_book, test_graph, test_tech};
+    use shared::{Book, QueryValue, Technology};
+
+    fn tiny() -> GraphData {
+        let books = [(1, "Both"), (2, "Both and nano"), (3, "Only FTL")].map(|(id, title)| Book { title: title.into(), ..test_book(id) });
+        let technologies = [(10, "FTL", "PHYSICS"), (11, "AI", "COMPUTING"), (12, "Nanotech", "MATERIALS")]
+            .map(|(id, name, category)| Technology { name: name.into(), category: category.into(), ..test_tech(id) });
+        test_graph(books, technologies, &[(1, 10), (1, 11), (2, 10), (2, 11), (2, 12), (3, 10)])
+    }
+
+    fn titles(result: &QueryResult) -> Vec<&str> {
+        result.rows.iter().map(|row| row[0].label()).collect()
+    }
+
+    #[test]
+    fn test_both_but_not_query() {
+        let result = run_query(
+            &tiny(),
+            "MATCH (b:Book)-[:USES]->(:Technology {name: 'FTL'}), (b)-[:USES]->(:Technology {name: 'AI'})
+             WHERE NOT (b)-[:USES]->(:Technology {name: 'Nanotech'})
+             RETURN b",
+        )
+        .unwrap();
+        assert_eq!(result.columns, ["b"]);
+        assert_eq!(titles(&result), ["Both"]);
+        assert!(!result.truncated);
+
+        // The highlighted subgraph: the book, both technologies and the two edges.
+        let ids: Vec<String> = result.nodes.iter().map(QueryValue::node_id).collect();
+        assert_eq!(ids, ["b_1", "t_10", "t_11"]);
+        assert_eq!(result.edges.len(), 2);
+    }
+
+    #[test]
+    fn test_categories_directions_and_exists() {
+        let data = tiny();
+        let result = run_query(&data, "MATCH (c:Category)<-[:IN]-(t)<-[:USES]-(b {id: 3}) RETURN c, t").unwrap();
+        assert_eq!(result.rows, vec![vec![QueryValue::Category { name: "PHYSICS".into() }, QueryValue::Technology(data.technologies[0].clone())]]);
+
+        let result = run_query(
+            &data,
+            "MATCH (b:Book) WHERE EXISTS { (b)-->(t:Technology) WHERE t.name STARTS WITH 'Nano' } OR b.id = 3",
+        )
+        .unwrap();
+        assert_eq!(titles(&result), ["Both and nano", "Only FTL"]);
+
+        // Books without a year never satisfy a comparison on it.
+        let mut dated = data.clone();
+        dated.books[1].year = Some(1995);
+        assert_eq!(titles(&run_query(&dated, "MATCH (b:Book) WHERE b.year < 2000 OR b.year >= 2000").unwrap()), ["Both and nano"]);
+
+        // Undirected, untyped edges reach both books and categories.
+        let result = run_query(&data, "MATCH (t:Technology {id: 12})--(n) RETURN n").unwrap();
+        assert_eq!(result.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_rows_are_distinct_and_limited() {
+        let data = bundled_graph().unwrap();
+        let result = run_query(&data, "MATCH (b:Book)-[:USES]->(:Technology) RETURN b").unwrap();
+        let linked: std::collections::HashSet<i32> = data.links.iter().map(|l| l.book_id).collect();
+        assert_eq!(result.rows.len(), linked.len(), "one row per linked book, however many links");
+
+        let result = run_query(&data, "MATCH (b:Book)-[:USES]->(t) RETURN b, t LIMIT 3").unwrap();
+        assert_eq!(result.rows.len(), 3);
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn test_semantic_errors() {
+        let data = tiny();
+        let message = |query: &str| run_query(&data, query).unwrap_err().field_message("query").map(str::to_string);
+        assert_eq!(message("MATCH (b:Book) WHERE b.name = 'x'").as_deref(), Some("unknown property b.name: a Book has id, title, author, series, year"));
+        assert_eq!(message("MATCH (b:Book) RETURN t").as_deref(), Some("unknown variable t in RETURN"));
+        assert_eq!(message("MATCH (b:Book), (b:Technology)").as_deref(), Some("b cannot be both a Book and a Technology"));
+        assert!(message("MATCH (b:Book) WHERE EXISTS { (b)-->(t) } AND t.id = 1").unwrap().contains("unknown variable t"));
+        assert!(message("MATCH (:Book)").unwrap().contains("name at least one node"));
+        assert!(message(&"x".repeat(MAX_QUERY_LEN + 1)).unwrap().contains("at most"));
+    }
+
+    #[test]
+    fn test_expensive_queries_are_refused() {
+        let mut data = tiny();
+        data.books = (1..=2_000).map(|id| Book { title: id.to_string(), ..test_book(id) }).collect();
+        let err = run_query(&data, "MATCH (a:Book), (b:Book) WHERE a.id = -1 RETURN a").unwrap_err();
+        assert!(err.field_message("query").unwrap().contains("too expensive"), "got {:?}", err);
+    }
+
+    #[test]
+    fn test_wide_comma_queries_fail_fast() {
+        let data = bundled_graph().unwrap();
+        let message = |patterns: usize| {
+            let query = format!("MATCH (b:Book){}", ", ()".repeat(patterns - 1));
+            run_query(&data, &query).unwrap_err().field_message("query").unwrap().to_string()
+        };
+        assert!(message(500).contains("too many node patterns"));
+        // At the limit each match is charged for everything it binds, so the
+        // budget runs out after a bounded amount of work.
+        let started = std::time::Instant::now();
+        assert!(message(parser::MAX_NODE_PATTERNS).contains("too expensive"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(10), "took {:?}", started.elapsed());
+    }
+}
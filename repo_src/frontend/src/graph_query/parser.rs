@@ -0,0 +1,590 @@
+// Tokenizer and recursive-descent parser for the query language described in
+// `graph_query/mod.rs`. Errors carry the line and column they were found at.
+
+use crate::errors::AppError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    pub patterns: Vec<Pattern>,
+    pub filter: Option<Expr>,
+    // Empty means every named node variable, in the order they first appear.
+    pub returns: Vec<String>,
+    pub limit: Option<usize>,
+}
+
+// `(a)-[:USES]->(b)<-[:USES]-(c)`: a start node and any number of steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub start: NodePattern,
+    pub steps: Vec<(EdgePattern, NodePattern)>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodePattern {
+    pub var: Option<String>,
+    pub label: Option<Label>,
+    // `{name: "x", id: 3}`: every property must be equal.
+    pub props: Vec<(String, Literal)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    Book,
+    Technology,
+    Category,
+}
+
+impl Label {
+    fn parse(name: &str) -> Option<Label> {
+        match name.to_ascii_lowercase().as_str() {
+            "book" => Some(Label::Book),
+            "technology" | "tech" => Some(Label::Technology),
+            "category" => Some(Label::Category),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Label::Book => "Book",
+            Label::Technology => "Technology",
+            Label::Category => "Category",
+        }
+    }
+
+    pub fn properties(self) -> &'static [&'static str] {
+        match self {
+            Label::Book => &["id", "title", "author", "series", "year"],
+            Label::Technology => &["id", "name", "category", "subcategory", "description"],
+            Label::Category => &["name"],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    // Book to technology.
+    Uses,
+    // Technology to category.
+    In,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    // `-->`: from the left node to the right one.
+    Right,
+    // `<--`
+    Left,
+    // `--`
+    Either,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdgePattern {
+    // None matches either kind.
+    pub kind: Option<EdgeKind>,
+    pub direction: Direction,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Int(i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    // `var.prop <op> literal`
+    Compare { var: String, prop: String, op: CompareOp, value: Literal },
+    // A pattern, or `EXISTS { patterns [WHERE expr] }`: true if it matches at
+    // least once with the outer variables kept as bound.
+    Exists { patterns: Vec<Pattern>, filter: Option<Box<Expr>> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    // Punctuation and operators: ( ) [ ] { } : , . - > < = <> != <= >= *
+    Sym(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Int(n) => write!(f, "{}", n),
+            Token::Sym(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+fn error_at(source: &str, offset: usize, message: impl std::fmt::Display) -> AppError {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    AppError::validation("query", format!("line {}, column {}: {}", line, column, message))
+}
+
+const SYMBOLS: [&str; 18] = ["<>", "!=", "<=", ">=", "(", ")", "[", "]", "{", "}", ":", ",", ".", "-", ">", "<", "=", "*"];
+
+// Each token with its byte offset in the source, for error positions.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, AppError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '/' && source[start..].starts_with("//") {
+            // Comment to the end of the line.
+            while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, escaped)) => value.push(escaped),
+                        None => return Err(error_at(source, start, "unterminated string")),
+                    },
+                    Some((_, ch)) if ch == c => break,
+                    Some((_, ch)) => value.push(ch),
+                    None => return Err(error_at(source, start, "unterminated string")),
+                }
+            }
+            tokens.push((Token::Str(value), start));
+        } else if c.is_ascii_digit() {
+            let mut end = start;
+            while let Some((i, _)) = chars.next_if(|&(_, c)| c.is_ascii_digit()) {
+                end = i + 1;
+            }
+            let value = source[start..end].parse().map_err(|_| error_at(source, start, "number is too large"))?;
+            tokens.push((Token::Int(value), start));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_') {
+                end = i + c.len_utf8();
+            }
+            tokens.push((Token::Ident(source[start..end].to_string()), start));
+        } else if c == '`' {
+            // `quoted identifier`
+            chars.next();
+            let mut name = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '`')) => break,
+                    Some((_, ch)) => name.push(ch),
+                    None => return Err(error_at(source, start, "unterminated `identifier`")),
+                }
+            }
+            tokens.push((Token::Ident(name), start));
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| source[start..].starts_with(**s))
+                .ok_or_else(|| error_at(source, start, format!("unexpected character '{}'", c)))?;
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((Token::Sym(symbol), start));
+        }
+    }
+    Ok(tokens)
+}
+
+// Parentheses, NOTs and EXISTS blocks open inside each other, at most. The
+// parser recurses once per level, so without a limit a short query of
+// brackets could overflow the stack.
+const MAX_NESTING: usize = 64;
+
+// Node patterns in the whole query, EXISTS blocks included. Every match binds
+// each of them, so a long list of `, ()` would make each match costly.
+pub const MAX_NODE_PATTERNS: usize = 32;
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    // Conditions currently open inside each other.
+    depth: usize,
+    // Node patterns parsed so far.
+    nodes: usize,
+}
+
+pub fn parse_query(source: &str) -> Result<Query, AppError> {
+    let mut parser = Parser { source, tokens: tokenize(source)?, pos: 0, depth: 0, nodes: 0 };
+    let query = parser.query()?;
+    match parser.peek() {
+        None => Ok(query),
+        Some(token) => Err(parser.error(format!("unexpected {}", token))),
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + ahead).map(|(t, _)| t)
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> AppError {
+        let offset = self.tokens.get(self.pos).map_or(self.source.len(), |(_, offset)| *offset);
+        error_at(self.source, offset, message)
+    }
+
+    // Parses one more level of nesting with `parse`.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, AppError>) -> Result<T, AppError> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(format!("nested too deeply (at most {} levels)", MAX_NESTING)));
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn expected(&self, what: &str) -> AppError {
+        match self.peek() {
+            Some(token) => self.error(format!("expected {}, found {}", what, token)),
+            None => self.error(format!("expected {}, found the end of the query", what)),
+        }
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.at_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), AppError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.expected(keyword))
+        }
+    }
+
+    fn at_sym(&self, sym: &str) -> bool {
+        matches!(self.peek(), Some(Token::Sym(s)) if *s == sym)
+    }
+
+    fn eat_sym(&mut self, sym: &str) -> bool {
+        let found = self.at_sym(sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<(), AppError> {
+        if self.eat_sym(sym) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("'{}'", sym)))
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<String, AppError> {
+        match self.peek() {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, AppError> {
+        self.expect_keyword("MATCH")?;
+        let patterns = self.patterns()?;
+        let filter = if self.eat_keyword("WHERE") { Some(self.expr()?) } else { None };
+        let mut returns = Vec::new();
+        if self.eat_keyword("RETURN") {
+            // Rows are always distinct; the keyword is accepted for familiarity.
+            self.eat_keyword("DISTINCT");
+            if !self.eat_sym("*") {
+                loop {
+                    returns.push(self.ident("a variable to return")?);
+                    if !self.eat_sym(",") {
+                        break;
+                    }
+                }
+            }
+        }
+        let limit = if self.eat_keyword("LIMIT") {
+            match self.peek() {
+                Some(&Token::Int(n)) => {
+                    self.pos += 1;
+                    Some(n as usize)
+                }
+                _ => return Err(self.expected("a row count")),
+            }
+        } else {
+            None
+        };
+        Ok(Query { patterns, filter, returns, limit })
+    }
+
+    fn patterns(&mut self) -> Result<Vec<Pattern>, AppError> {
+        let mut patterns = vec![self.pattern()?];
+        while self.eat_sym(",") {
+            patterns.push(self.pattern()?);
+        }
+        Ok(patterns)
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, AppError> {
+        let start = self.node()?;
+        let mut steps = Vec::new();
+        while self.at_sym("-") || self.at_sym("<") {
+            let edge = self.edge()?;
+            steps.push((edge, self.node()?));
+        }
+        Ok(Pattern { start, steps })
+    }
+
+    fn node(&mut self) -> Result<NodePattern, AppError> {
+        if self.nodes == MAX_NODE_PATTERNS {
+            return Err(self.error(format!("too many node patterns (at most {})", MAX_NODE_PATTERNS)));
+        }
+        self.nodes += 1;
+        self.expect_sym("(")?;
+        let mut node = NodePattern::default();
+        if let Some(Token::Ident(_)) = self.peek() {
+            node.var = Some(self.ident("a variable")?);
+        }
+        if self.eat_sym(":") {
+            let at = self.pos;
+            let name = self.ident("a label")?;
+            node.label = Label::parse(&name);
+            if node.label.is_none() {
+                self.pos = at;
+                return Err(self.error(format!("unknown label '{}'; use Book, Technology or Category", name)));
+            }
+        }
+        if self.eat_sym("{") {
+            loop {
+                let prop = self.ident("a property name")?;
+                self.expect_sym(":")?;
+                node.props.push((prop, self.literal()?));
+                if !self.eat_sym(",") {
+                    break;
+                }
+            }
+            self.expect_sym("}")?;
+        }
+        self.expect_sym(")")?;
+        Ok(node)
+    }
+
+    // `-[:USES]->`, `<-[:USES]-`, `-[]-`, `-->`, `<--` or `--`.
+    fn edge(&mut self) -> Result<EdgePattern, AppError> {
+        let left = self.eat_sym("<");
+        self.expect_sym("-")?;
+        let mut kind = None;
+        if self.eat_sym("[") {
+            if let Some(Token::Ident(_)) = self.peek() {
+                return Err(self.error("relationship variables are not supported"));
+            }
+            if self.eat_sym(":") {
+                let at = self.pos;
+                let name = self.ident("a relationship type")?;
+                kind = match name.to_ascii_uppercase().as_str() {
+                    "USES" => Some(EdgeKind::Uses),
+                    "IN" => Some(EdgeKind::In),
+                    _ => {
+                        self.pos = at;
+                        return Err(self.error(format!("unknown relationship type '{}'; use USES or IN", name)));
+                    }
+                };
+            }
+            self.expect_sym("]")?;
+        }
+        self.expect_sym("-")?;
+        let right = self.eat_sym(">");
+        let direction = match (left, right) {
+            (false, true) => Direction::Right,
+            (true, false) => Direction::Left,
+            (false, false) => Direction::Either,
+            (true, true) => return Err(self.error("a relationship cannot point both ways")),
+        };
+        Ok(EdgePattern { kind, direction })
+    }
+
+    fn literal(&mut self) -> Result<Literal, AppError> {
+        let negative = self.eat_sym("-");
+        let literal = match self.peek() {
+            Some(Token::Str(s)) if !negative => Literal::Str(s.clone()),
+            Some(&Token::Int(n)) => Literal::Int(if negative { -n } else { n }),
+            _ => return Err(self.expected("a string or number")),
+        };
+        self.pos += 1;
+        Ok(literal)
+    }
+
+    fn expr(&mut self) -> Result<Expr, AppError> {
+        let mut left = self.and_expr()?;
+        while self.eat_keyword("OR") {
+            left = Expr::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, AppError> {
+        let mut left = self.not_expr()?;
+        while self.eat_keyword("AND") {
+            left = Expr::And(Box::new(left), Box::new(self.not_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, AppError> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.nested(Self::not_expr)?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, AppError> {
+        if self.at_keyword("EXISTS") && matches!(self.peek_at(1), Some(Token::Sym("{"))) {
+            self.pos += 2;
+            return self.nested(|parser| {
+                parser.eat_keyword("MATCH");
+                let patterns = parser.patterns()?;
+                let filter = if parser.eat_keyword("WHERE") { Some(Box::new(parser.expr()?)) } else { None };
+                parser.expect_sym("}")?;
+                Ok(Expr::Exists { patterns, filter })
+            });
+        }
+        if self.at_sym("(") {
+            // A pattern predicate such as `(b)-[:USES]->(t)`, or a
+            // parenthesised expression: try the pattern first.
+            let start = self.pos;
+            if let Ok(pattern) = self.pattern() {
+                if !pattern.steps.is_empty() {
+                    return Ok(Expr::Exists { patterns: vec![pattern], filter: None });
+                }
+            }
+            self.pos = start + 1;
+            return self.nested(|parser| {
+                let inner = parser.expr()?;
+                parser.expect_sym(")")?;
+                Ok(inner)
+            });
+        }
+        let var = self.ident("a condition")?;
+        self.expect_sym(".")?;
+        let prop = self.ident("a property name")?;
+        let op = if self.eat_sym("=") {
+            CompareOp::Eq
+        } else if self.eat_sym("<>") || self.eat_sym("!=") {
+            CompareOp::Ne
+        } else if self.eat_sym("<=") {
+            CompareOp::Le
+        } else if self.eat_sym(">=") {
+            CompareOp::Ge
+        } else if self.eat_sym("<") {
+            CompareOp::Lt
+        } else if self.eat_sym(">") {
+            CompareOp::Gt
+        } else if self.eat_keyword("CONTAINS") {
+            CompareOp::Contains
+        } else if self.eat_keyword("STARTS") {
+            self.expect_keyword("WITH")?;
+            CompareOp::StartsWith
+        } else if self.eat_keyword("ENDS") {
+            self.expect_keyword("WITH")?;
+            CompareOp::EndsWith
+        } else {
+            return Err(self.expected("a comparison (=, <>, <, <=, >, >=, CONTAINS, STARTS WITH, ENDS WITH)"));
+        };
+        let value = self.literal()?;
+        Ok(Expr::Compare { var, prop, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_patterns_filters_and_returns() {
+        let query = parse_query(
+            "match (b:Book)-[:USES]->(t:Technology {category: 'PHYSICS & SPACETIME'}), (b)--(:tech)\n\
+             WHERE NOT (b)-[:USES]->(:Technology {id: 105}) AND (t.name CONTAINS \"Drive\" OR t.id >= -1)\n\
+             RETURN DISTINCT b, t LIMIT 5",
+        )
+        .unwrap();
+        assert_eq!(query.patterns.len(), 2);
+        let (edge, node) = &query.patterns[0].steps[0];
+        assert_eq!(*edge, EdgePattern { kind: Some(EdgeKind::Uses), direction: Direction::Right });
+        assert_eq!(node.props, vec![("category".to_string(), Literal::Str("PHYSICS & SPACETIME".to_string()))]);
+        assert_eq!(query.patterns[1].steps[0].0, EdgePattern { kind: None, direction: Direction::Either });
+        assert_eq!(query.returns, ["b", "t"]);
+        assert_eq!(query.limit, Some(5));
+
+        let Some(Expr::And(left, right)) = query.filter else { panic!("expected AND") };
+        assert!(matches!(*left, Expr::Not(ref inner) if matches!(**inner, Expr::Exists { .. })));
+        assert!(matches!(*right, Expr::Or(..)), "parentheses group the OR");
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        let err = parse_query("MATCH (b:Book)\nWHERE b.title ~ 'x'").unwrap_err();
+        assert_eq!(err.field_message("query"), Some("line 2, column 15: unexpected character '~'"));
+
+        let err = parse_query("MATCH (b:Author)").unwrap_err();
+        assert!(err.field_message("query").unwrap().starts_with("line 1, column 10: unknown label 'Author'"), "got {:?}", err);
+
+        let err = parse_query("MATCH (b)-[:WROTE]->(t)").unwrap_err();
+        assert!(err.field_message("query").unwrap().contains("unknown relationship type 'WROTE'"));
+
+        assert!(parse_query("MATCH (b").unwrap_err().field_message("query").unwrap().contains("found the end of the query"));
+        assert!(parse_query("RETURN b").is_err());
+        assert!(parse_query("MATCH (a)<-[:USES]->(b)").is_err());
+    }
+
+    #[test]
+    fn test_deep_nesting_is_refused_not_recursed_into() {
+        let nested = |open: &str, close: &str, levels: usize| {
+            format!("MATCH (b:Book) WHERE {}b.id = 1{}", open.repeat(levels), close.repeat(levels))
+        };
+        let too_deep = |query: String| parse_query(&query).unwrap_err().field_message("query").unwrap().contains("nested too deeply");
+        assert!(too_deep(nested("(", ")", 10_000)));
+        assert!(too_deep(nested("NOT ", "", 10_000)));
+        // Each EXISTS names a node too, so those run out first.
+        let err = parse_query(&nested("EXISTS { (b) WHERE ", "}", 10_000)).unwrap_err();
+        assert!(err.field_message("query").unwrap().contains("too many node patterns"), "got {:?}", err);
+        // Up to the limit is fine.
+        assert!(parse_query(&nested("(", ")", MAX_NESTING)).is_ok());
+        assert!(parse_query(&nested("NOT (", ")", MAX_NESTING / 2)).is_ok());
+    }
+}
@@ -0,0 +1,106 @@
+// The tech graph over time, by the books' year of first publication. The graph
+// "as of" a year holds the books published up to then and the technologies
+// they feature, so playing the years forward grows it the way the genre did;
+// books without a year are left out of it. Used by the timeline on the tech
+// graph page (GetTechnologyTimeline, and GetGraphClusters when a year is set);
+// compiles for the client too.
+
+use shared::{Book, FirstAppearance, GraphData, Technology, Timeline};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// The books published up to the end of `year`, the technologies they feature
+// and the links between them.
+pub fn graph_until(data: &GraphData, year: i32) -> GraphData {
+    let books: HashSet<i32> = data.books.iter().filter(|b| b.year.is_some_and(|y| y <= year)).map(|b| b.id).collect();
+    let links: Vec<_> = data.links.iter().filter(|l| books.contains(&l.book_id)).cloned().collect();
+    let techs: HashSet<i32> = links.iter().map(|l| l.tech_id).collect();
+    GraphData {
+        books: data.books.iter().filter(|b| books.contains(&b.id)).cloned().collect(),
+        technologies: data.technologies.iter().filter(|t| techs.contains(&t.id)).cloned().collect(),
+        links,
+        taxonomy: data.taxonomy.clone(),
+    }
+}
+
+// The year playback moves to after `current`: the next year with a book, the
+// first one when nothing is set, and None past the last.
+pub fn next_year(years: &[i32], current: Option<i32>) -> Option<i32> {
+    match current {
+        None => years.first().copied(),
+        Some(current) => years.iter().copied().find(|y| *y > current),
+    }
+}
+
+pub fn timeline(data: &GraphData) -> Timeline {
+    let years: BTreeSet<i32> = data.books.iter().filter_map(|b| b.year).collect();
+    let books: HashMap<i32, &Book> = data.books.iter().map(|b| (b.id, b)).collect();
+    let techs: HashMap<i32, &Technology> = data.technologies.iter().map(|t| (t.id, t)).collect();
+
+    // Earliest (year, book id) per technology; the lower id breaks ties.
+    let mut first: HashMap<i32, (i32, i32)> = HashMap::new();
+    for link in data.links.iter().filter(|l| techs.contains_key(&l.tech_id)) {
+        let Some(year) = books.get(&link.book_id).and_then(|b| b.year) else { continue };
+        let earliest = first.entry(link.tech_id).or_insert((year, link.book_id));
+        *earliest = (*earliest).min((year, link.book_id));
+    }
+    let mut first_appearances: Vec<FirstAppearance> = first
+        .into_iter()
+        .map(|(id, (year, book_id))| FirstAppearance {
+            id,
+            name: techs[&id].name.clone(),
+            category: techs[&id].category.clone(),
+            year,
+            book_id,
+            book_title: books[&book_id].title.clone(),
+        })
+        .collect();
+    first_appearances.sort_by(|a, b| a.year.cmp(&b.year).then_with(|| a.name.cmp(&b.name)).then(a.id.cmp(&b.id)));
+
+    Timeline {
+        years: years.into_iter().collect(),
+        first_appearances,
+        undated_books: data.books.iter().filter(|b| b.year.is_none()).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::test_data::{test_book, test_graph, test_tech};
+
+    fn tiny() -> GraphData {
+        let books = [(1, Some(1990)), (2, Some(1965)), (3, None), (4, Some(1965))].map(|(id, year)| Book { year, ..test_book(id) });
+        test_graph(books, [10, 11, 12, 13].map(test_tech), &[(1, 10), (1, 11), (2, 11), (4, 11), (3, 12), (4, 13)])
+    }
+
+    #[test]
+    fn test_graph_grows_cumulatively() {
+        let data = tiny();
+        let ids = |g: &GraphData| (g.books.iter().map(|b| b.id).collect::<Vec<_>>(), g.technologies.iter().map(|t| t.id).collect::<Vec<_>>());
+        assert_eq!(ids(&graph_until(&data, 1964)), (vec![], vec![]));
+        assert_eq!(ids(&graph_until(&data, 1965)), (vec![2, 4], vec![11, 13]));
+        // The undated book 3 (and Tech 12, only it uses) never appear.
+        assert_eq!(ids(&graph_until(&data, 3000)), (vec![1, 2, 4], vec![10, 11, 13]));
+        assert_eq!(graph_until(&data, 1990).links.len(), 5);
+
+        let years = [1965, 1990];
+        assert_eq!(next_year(&years, None), Some(1965));
+        assert_eq!(next_year(&years, Some(1970)), Some(1990));
+        assert_eq!(next_year(&years, Some(1990)), None);
+    }
+
+    #[test]
+    fn test_first_appearances() {
+        let small = timeline(&tiny());
+        assert_eq!((small.years.as_slice(), small.undated_books), ([1965, 1990].as_slice(), 1));
+        let firsts: Vec<(i32, i32, i32)> = small.first_appearances.iter().map(|f| (f.id, f.year, f.book_id)).collect();
+        assert_eq!(firsts, [(11, 1965, 2), (13, 1965, 4), (10, 1990, 1)]);
+
+        // Every bundled book has a year.
+        let bundled = timeline(&bundled_graph().unwrap());
+        assert_eq!(bundled.undated_books, 0);
+        assert_eq!((bundled.years.first(), bundled.years.last()), (Some(&1884), Some(&2021)));
+        assert!(bundled.first_appearances.windows(2).all(|w| w[0].year <= w[1].year));
+    }
+}
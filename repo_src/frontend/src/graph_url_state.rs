@@ -0,0 +1,279 @@
+// The tech graph page's state as URL query parameters, so a view can be shared
+// as a link and is restored on load, server-side rendering included. Every key
+// is optional and defaults are left out, so a plain `/tech-graph` is the default
+// view. Lists are joined with `|` (ids with `,`); values that do not parse are
+// ignored rather than failing the page.
+//
+//   tech=101,102 category=BIOTECH|PHYSICS subcategory=.. author=.. series=..
+//   match=all              books must meet every selection (default: any)
+//   view=all|matches       projection (default: clusters)
+//   color=author|series    colour books by author or series
+//   taxonomy=1             draw the subcategories and finer taxonomy nodes
+//   chart=matrix|chord|sunburst
+//                          another chart in place of the network
+//   open=BIOTECH open_tech=101
+//                          expanded clusters
+//   year=1990              only books published up to then (the timeline)
+//   focus=b_12             selected node
+//   x=.. y=.. zoom=..      camera
+
+use crate::components::tech_graph_view::Viewport;
+use leptos_router::ParamsMap;
+use shared::{BookGrouping, GraphFilter, MatchMode};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+// How the graph is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Projection {
+    // Server-side clusters, with the filter highlighted.
+    #[default]
+    Clusters,
+    // Every node, with the filter highlighted.
+    AllNodes,
+    // Only what the filter matches (every node while it is empty).
+    Matches,
+}
+
+// The chart the page shows, one tab each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GraphChart {
+    // The vis.js network, with the projections above.
+    #[default]
+    Network,
+    // Books × technologies.
+    Matrix,
+    // Books shared between categories.
+    Chord,
+    // The taxonomy, sized by books.
+    Sunburst,
+}
+
+impl GraphChart {
+    pub const ALL: [GraphChart; 4] = [GraphChart::Network, GraphChart::Matrix, GraphChart::Chord, GraphChart::Sunburst];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GraphChart::Network => "network",
+            GraphChart::Matrix => "matrix",
+            GraphChart::Chord => "chord",
+            GraphChart::Sunburst => "sunburst",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GraphChart::Network => "Network",
+            GraphChart::Matrix => "Matrix",
+            GraphChart::Chord => "Category Chords",
+            GraphChart::Sunburst => "Sunburst",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphUrlState {
+    pub filter: GraphFilter,
+    pub projection: Projection,
+    pub color_by: Option<BookGrouping>,
+    pub taxonomy_layer: bool,
+    pub chart: GraphChart,
+    pub open_categories: BTreeSet<String>,
+    pub open_technologies: BTreeSet<i32>,
+    pub year: Option<i32>,
+    // Id of the selected node, e.g. "b_12".
+    pub focus: Option<String>,
+    pub viewport: Option<Viewport>,
+}
+
+const LIST_SEPARATOR: char = '|';
+
+fn strings(query: &ParamsMap, key: &str) -> BTreeSet<String> {
+    query.get(key).into_iter()
+        .flat_map(|value| value.split(LIST_SEPARATOR))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn ids(query: &ParamsMap, key: &str) -> BTreeSet<i32> {
+    query.get(key).into_iter()
+        .flat_map(|value| value.split(','))
+        .filter_map(|v| v.trim().parse().ok())
+        .collect()
+}
+
+fn number<T: FromStr>(query: &ParamsMap, key: &str) -> Option<T> {
+    query.get(key)?.trim().parse().ok()
+}
+
+impl GraphUrlState {
+    pub fn from_query(query: &ParamsMap) -> Self {
+        let filter = GraphFilter {
+            technologies: ids(query, "tech"),
+            categories: strings(query, "category"),
+            subcategories: strings(query, "subcategory"),
+            authors: strings(query, "author"),
+            series: strings(query, "series"),
+            mode: if query.get("match").is_some_and(|m| m == "all") { MatchMode::All } else { MatchMode::Any },
+        };
+        let projection = match query.get("view").map(String::as_str) {
+            Some("all") => Projection::AllNodes,
+            Some("matches") => Projection::Matches,
+            _ => Projection::Clusters,
+        };
+        let color_by = match query.get("color").map(String::as_str) {
+            Some("author") => Some(BookGrouping::Author),
+            Some("series") => Some(BookGrouping::Series),
+            _ => None,
+        };
+        let viewport = match (number::<f64>(query, "x"), number::<f64>(query, "y"), number::<f64>(query, "zoom")) {
+            (Some(x), Some(y), Some(scale)) if x.is_finite() && y.is_finite() && scale.is_finite() && scale > 0.0 => {
+                Some(Viewport { x, y, scale })
+            }
+            _ => None,
+        };
+        Self {
+            filter,
+            projection,
+            color_by,
+            taxonomy_layer: query.get("taxonomy").is_some_and(|t| t == "1"),
+            chart: query.get("chart").and_then(|c| GraphChart::ALL.into_iter().find(|chart| chart.as_str() == c)).unwrap_or_default(),
+            open_categories: strings(query, "open"),
+            open_technologies: ids(query, "open_tech"),
+            year: number(query, "year"),
+            focus: query.get("focus").map(|f| f.trim().to_string()).filter(|f| !f.is_empty()),
+            viewport,
+        }
+    }
+
+    pub fn to_query(&self) -> ParamsMap {
+        let mut query = ParamsMap::new();
+        let mut put = |key: &str, value: String| {
+            if !value.is_empty() {
+                query.insert(key.to_string(), value);
+            }
+        };
+        let join_ids = |ids: &BTreeSet<i32>| ids.iter().map(i32::to_string).collect::<Vec<_>>().join(",");
+        let join = |values: &BTreeSet<String>| {
+            // A value holding the separator could not be read back.
+            values.iter().filter(|v| !v.contains(LIST_SEPARATOR)).cloned().collect::<Vec<_>>().join(&LIST_SEPARATOR.to_string())
+        };
+
+        put("tech", join_ids(&self.filter.technologies));
+        put("category", join(&self.filter.categories));
+        put("subcategory", join(&self.filter.subcategories));
+        put("author", join(&self.filter.authors));
+        put("series", join(&self.filter.series));
+        if self.filter.mode == MatchMode::All {
+            put("match", "all".to_string());
+        }
+        match self.projection {
+            Projection::Clusters => {}
+            Projection::AllNodes => put("view", "all".to_string()),
+            Projection::Matches => put("view", "matches".to_string()),
+        }
+        match self.color_by {
+            None => {}
+            Some(BookGrouping::Author) => put("color", "author".to_string()),
+            Some(BookGrouping::Series) => put("color", "series".to_string()),
+        }
+        if self.taxonomy_layer {
+            put("taxonomy", "1".to_string());
+        }
+        if self.chart != GraphChart::Network {
+            put("chart", self.chart.as_str().to_string());
+        }
+        put("open", join(&self.open_categories));
+        put("open_tech", join_ids(&self.open_technologies));
+        put("year", self.year.map(|y| y.to_string()).unwrap_or_default());
+        put("focus", self.focus.clone().unwrap_or_default());
+        if let Some(viewport) = self.viewport {
+            // Rounded: a fraction of a pixel is not worth the longer link.
+            put("x", format!("{:.1}", viewport.x));
+            put("y", format!("{:.1}", viewport.y));
+            put("zoom", format!("{:.3}", viewport.scale));
+        }
+        query
+    }
+
+    // The projection actually drawn: only matches of an empty filter would be
+    // the whole graph, so that falls back to clusters.
+    pub fn effective_projection(&self) -> Projection {
+        match self.projection {
+            Projection::Matches if self.filter.is_empty() => Projection::Clusters,
+            projection => projection,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> ParamsMap {
+        let mut query = ParamsMap::new();
+        for (key, value) in pairs {
+            query.insert(key.to_string(), value.to_string());
+        }
+        query
+    }
+
+    #[test]
+    fn test_round_trips_through_query_params() {
+        let state = GraphUrlState {
+            filter: GraphFilter {
+                technologies: [101, 205].into(),
+                categories: ["BIOTECH & MEDICINE".to_string()].into(),
+                subcategories: ["Nano-engineering".to_string()].into(),
+                authors: ["Iain M. Banks".to_string(), "Alastair Reynolds".to_string()].into(),
+                series: ["Culture".to_string()].into(),
+                mode: MatchMode::All,
+            },
+            projection: Projection::Matches,
+            color_by: Some(BookGrouping::Series),
+            taxonomy_layer: true,
+            chart: GraphChart::Sunburst,
+            open_categories: ["PHYSICS & SPACETIME".to_string()].into(),
+            open_technologies: [101].into(),
+            year: Some(1987),
+            focus: Some("b_12".to_string()),
+            viewport: Some(Viewport { x: -120.5, y: 40.0, scale: 1.25 }),
+        };
+        let params = state.to_query();
+        assert_eq!(params.get("tech").map(String::as_str), Some("101,205"));
+        assert_eq!(params.get("author").map(String::as_str), Some("Alastair Reynolds|Iain M. Banks"));
+        assert_eq!(GraphUrlState::from_query(&params), state);
+    }
+
+    #[test]
+    fn test_defaults_are_left_out_and_bad_values_ignored() {
+        assert!(GraphUrlState::default().to_query().0.is_empty());
+        assert_eq!(GraphUrlState::from_query(&ParamsMap::new()), GraphUrlState::default());
+
+        let state = GraphUrlState::from_query(&query(&[
+            ("tech", "101,abc,,7"),
+            ("category", "|BIOTECH||"),
+            ("match", "sometimes"),
+            ("view", "sideways"),
+            ("color", "plaid"),
+            ("taxonomy", "yes"),
+            ("chart", "pie"),
+            ("focus", "  "),
+            ("x", "10"),
+            ("y", "NaN"),
+            ("zoom", "2"),
+            ("year", "1987.5"),
+        ]));
+        assert_eq!(state.filter.technologies, [7, 101].into());
+        assert_eq!(state.filter.categories, ["BIOTECH".to_string()].into());
+        assert_eq!((state.filter.mode, state.projection, state.color_by), (MatchMode::Any, Projection::Clusters, None));
+        assert_eq!((state.focus, state.viewport, state.year), (None, None, None));
+        assert!(!state.taxonomy_layer);
+        assert_eq!(state.chart, GraphChart::Network);
+
+        let matches = GraphUrlState::from_query(&query(&[("view", "matches")]));
+        assert_eq!(matches.effective_projection(), Projection::Clusters);
+    }
+}
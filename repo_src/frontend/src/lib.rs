@@ -2,6 +2,22 @@ pub mod app;
 pub mod components;
 pub mod pages;
 pub mod error_template;
+pub mod errors;
+pub mod dataset;
+pub mod auth;
+pub mod graph_diff;
+pub mod graph_clusters;
+pub mod graph_authors;
+pub mod graph_filter;
+pub mod graph_timeline;
+pub mod graph_charts;
+pub mod taxonomy;
+pub mod graph_url_state;
+pub mod graph_query;
+pub mod live;
+pub mod repository;
+#[cfg(test)]
+mod test_data;
 
 // These modules contain server-side logic or depend on server-side features.
 // They are conditionally compiled:
@@ -11,6 +27,8 @@ pub mod error_template;
 // are typically defined in the same crate as the client-side app.
 pub mod server_fns; 
 pub mod database;
+pub mod fixtures;
+pub mod synthetic;
 
 // pub mod models; // if models are separate from shared, usually on server side
 
@@ -0,0 +1,154 @@
+// Live updates: server functions publish a `ChangeEvent` after every change,
+// the backend streams them to browsers as server-sent events on
+// CHANGE_EVENTS_PATH, and pages subscribe with `subscribe_to_changes` so their
+// resources refetch when someone else changes the data.
+
+use leptos::*;
+use shared::ChangeEvent;
+
+pub const CHANGE_EVENTS_PATH: &str = "/api/events";
+
+// Counters bumped by incoming events. Resources include the relevant counter
+// in their source signal, so a bump triggers a refetch.
+#[derive(Clone, Copy)]
+pub struct LiveUpdates {
+    pub items: ReadSignal<usize>,
+    pub graph: ReadSignal<usize>,
+    pub versions: ReadSignal<usize>,
+}
+
+// Which counters an event bumps: (items, graph, versions).
+pub fn affected(event: &ChangeEvent) -> (bool, bool, bool) {
+    match event {
+        ChangeEvent::ItemAdded { .. } | ChangeEvent::ItemDeleted { .. } => (true, false, false),
+        ChangeEvent::GraphChanged => (false, true, false),
+        ChangeEvent::GraphVersionCreated { .. } => (false, false, true),
+        ChangeEvent::Resync => (true, true, true),
+    }
+}
+
+// Opens the event stream for the lifetime of the calling component. During
+// server rendering this only creates the (never bumped) counters.
+pub fn subscribe_to_changes() -> LiveUpdates {
+    let (items, set_items) = create_signal(0usize);
+    let (graph, set_graph) = create_signal(0usize);
+    let (versions, set_versions) = create_signal(0usize);
+
+    let apply = move |event: ChangeEvent| {
+        let (i, g, v) = affected(&event);
+        if i {
+            set_items.update(|n| *n += 1);
+        }
+        if g {
+            set_graph.update(|n| *n += 1);
+        }
+        if v {
+            set_versions.update(|n| *n += 1);
+        }
+    };
+
+    #[cfg(feature = "hydrate")]
+    {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        match web_sys::EventSource::new(CHANGE_EVENTS_PATH) {
+            Ok(source) => {
+                let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(move |msg: web_sys::MessageEvent| {
+                    let Some(text) = msg.data().as_string() else { return };
+                    match serde_json::from_str::<ChangeEvent>(&text) {
+                        Ok(event) => apply(event),
+                        Err(e) => logging::warn!("Ignoring malformed change event {:?}: {}", text, e),
+                    }
+                });
+                // EventSource reconnects on its own; anything sent while we
+                // were disconnected is lost, so reload after every reconnect.
+                let mut opened_before = false;
+                let on_open = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+                    if opened_before {
+                        apply(ChangeEvent::Resync);
+                    }
+                    opened_before = true;
+                });
+                source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+                source.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+                on_cleanup(move || {
+                    source.close();
+                    drop((on_message, on_open));
+                });
+            }
+            Err(e) => logging::error!("Could not open the live update stream: {:?}", e),
+        }
+    }
+    #[cfg(not(feature = "hydrate"))]
+    let _ = apply;
+
+    LiveUpdates { items, graph, versions }
+}
+
+#[cfg(feature = "ssr")]
+pub use server::ChangeFeed;
+
+#[cfg(feature = "ssr")]
+mod server {
+    use shared::ChangeEvent;
+    use tokio::sync::broadcast;
+
+    // Events waiting for a slow subscriber before it is told to resync.
+    const CHANGE_FEED_CAPACITY: usize = 256;
+
+    // One per process. The backend provides it to server functions through
+    // Leptos context and serves `subscribe()` on the SSE route.
+    #[derive(Clone)]
+    pub struct ChangeFeed {
+        sender: broadcast::Sender<ChangeEvent>,
+    }
+
+    impl Default for ChangeFeed {
+        fn default() -> Self {
+            Self { sender: broadcast::channel(CHANGE_FEED_CAPACITY).0 }
+        }
+    }
+
+    impl ChangeFeed {
+        // Having nobody subscribed is normal, not an error.
+        pub fn publish(&self, event: ChangeEvent) {
+            let _ = self.sender.send(event);
+        }
+
+        pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+            self.sender.subscribe()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_bump_the_matching_counters() {
+        assert_eq!(affected(&ChangeEvent::ItemDeleted { id: 1 }), (true, false, false));
+        assert_eq!(affected(&ChangeEvent::GraphChanged), (false, true, false));
+        assert_eq!(affected(&ChangeEvent::Resync), (true, true, true));
+    }
+
+    #[cfg(feature = "ssr")]
+    #[tokio::test]
+    async fn test_change_feed_fans_out_to_subscribers() {
+        let feed = ChangeFeed::default();
+        feed.publish(ChangeEvent::GraphChanged); // nobody listening yet
+
+        let mut first = feed.subscribe();
+        let mut second = feed.subscribe();
+        feed.publish(ChangeEvent::ItemDeleted { id: 7 });
+        assert_eq!(first.recv().await.unwrap(), ChangeEvent::ItemDeleted { id: 7 });
+        assert_eq!(second.recv().await.unwrap(), ChangeEvent::ItemDeleted { id: 7 });
+    }
+
+    #[test]
+    fn test_change_event_wire_format() {
+        let json = serde_json::to_string(&ChangeEvent::ItemDeleted { id: 3 }).unwrap();
+        assert_eq!(json, r#"{"type":"item_deleted","id":3}"#);
+        assert_eq!(serde_json::from_str::<ChangeEvent>(r#"{"type":"resync"}"#).unwrap(), ChangeEvent::Resync);
+    }
+}
@@ -0,0 +1,128 @@
+use leptos::*;
+use leptos_meta::*;
+use shared::AuditEntry;
+use crate::server_fns::get_audit_log;
+
+// Values understood by the audit log's entity_type / action columns.
+const ENTITY_TYPES: [&str; 7] = ["item", "link", "graph", "graph_version", "user", "api_token", "fixture"];
+const ACTIONS: [&str; 6] = ["create", "delete", "replace", "revert", "revoke", "apply"];
+
+fn describe(entry: &AuditEntry) -> String {
+    let entity = match entry.entity_type.as_str() {
+        "link" => format!("link {}", entry.entity_id.replace(':', " → tech ")),
+        "graph" => "the graph".to_string(),
+        "graph_version" => format!("graph version #{}", entry.entity_id),
+        "api_token" => format!("API token #{}", entry.entity_id),
+        "fixture" => format!("fixture '{}'", entry.entity_id),
+        other => format!("{} #{}", other, entry.entity_id),
+    };
+    let verb = match entry.action.as_str() {
+        "create" => "created",
+        "delete" => "deleted",
+        "replace" => "replaced",
+        "revert" => "reverted",
+        "revoke" => "revoked",
+        "apply" => "applied",
+        other => other,
+    };
+    format!("{} {} {}", entry.actor, verb, entity)
+}
+
+#[component]
+pub fn ActivityPage() -> impl IntoView {
+    let (entity_type, set_entity_type) = create_signal(String::new());
+    let (action, set_action) = create_signal(String::new());
+    let (actor, set_actor) = create_signal(String::new());
+
+    let entries = create_resource(
+        move || (entity_type.get(), action.get(), actor.get()),
+        |(entity_type, action, actor)| async move {
+            // Resources must be serializable, so keep only the message.
+            get_audit_log(Some(entity_type), None, Some(action), Some(actor), None)
+                .await
+                .map_err(|e| e.to_string())
+        },
+    );
+
+    let options = |values: &'static [&'static str]| {
+        values.iter().map(|v| view! { <option value=*v>{*v}</option> }).collect_view()
+    };
+
+    view! {
+        <Title text="Activity"/>
+
+        <h1>"Activity"</h1>
+
+        <div class="card activity-filters">
+            <label for="activity-entity">"Entity:"</label>
+            <select id="activity-entity" on:change=move |ev| set_entity_type.set(event_target_value(&ev))>
+                <option value="">"All"</option>
+                {options(&ENTITY_TYPES)}
+            </select>
+            <label for="activity-action">"Action:"</label>
+            <select id="activity-action" on:change=move |ev| set_action.set(event_target_value(&ev))>
+                <option value="">"All"</option>
+                {options(&ACTIONS)}
+            </select>
+            <label for="activity-actor">"Actor:"</label>
+            <input
+                type="text"
+                id="activity-actor"
+                placeholder="anyone"
+                prop:value=actor
+                on:change=move |ev| set_actor.set(event_target_value(&ev))
+            />
+        </div>
+
+        <div class="card">
+            <Suspense fallback=move || view! { <p class="loading-indicator">"Loading activity..."</p> }>
+                {move || entries.get().map(|result| match result {
+                    Err(e) => view! { <p class="field-error">{format!("Error loading activity: {}", e)}</p> }.into_view(),
+                    Ok(entries) if entries.is_empty() => view! { <p>"No matching activity."</p> }.into_view(),
+                    Ok(entries) => view! {
+                        <ul class="activity-feed">
+                            {entries.into_iter().map(|entry| view! {
+                                <li class="activity-entry">
+                                    <span class="item-date">{entry.created_at.format("%Y-%m-%d %H:%M:%S").to_string()}</span>
+                                    <span class="activity-summary">{describe(&entry)}</span>
+                                    // Before/after values, collapsed by default.
+                                    <details>
+                                        <summary>"Details"</summary>
+                                        {entry.before.clone().map(|b| view! { <pre>"Before: "{b}</pre> })}
+                                        {entry.after.clone().map(|a| view! { <pre>"After: "{a}</pre> })}
+                                    </details>
+                                </li>
+                            }).collect_view()}
+                        </ul>
+                    }.into_view(),
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn entry(entity_type: &str, entity_id: &str, action: &str) -> AuditEntry {
+        AuditEntry {
+            id: 1,
+            entity_type: entity_type.to_string(),
+            entity_id: entity_id.to_string(),
+            action: action.to_string(),
+            before: None,
+            after: None,
+            actor: "bob".to_string(),
+            created_at: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_describe_entries() {
+        assert_eq!(describe(&entry("link", "3:101", "delete")), "bob deleted link 3 → tech 101");
+        assert_eq!(describe(&entry("item", "7", "create")), "bob created item #7");
+        assert_eq!(describe(&entry("graph", "current", "revert")), "bob reverted the graph");
+    }
+}
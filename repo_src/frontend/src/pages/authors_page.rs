@@ -0,0 +1,111 @@
+use leptos::*;
+use leptos_meta::*;
+use shared::{BookGrouping, GraphFilter, TechnologyFingerprint};
+use crate::graph_url_state::{GraphUrlState, Projection};
+use crate::live::subscribe_to_changes;
+use crate::server_fns::get_technology_fingerprints;
+
+// The tech graph showing just this author's (or series') books.
+fn graph_link(grouping: BookGrouping, name: &str) -> String {
+    let mut filter = GraphFilter::default();
+    match grouping {
+        BookGrouping::Author => filter.authors.insert(name.to_string()),
+        BookGrouping::Series => filter.series.insert(name.to_string()),
+    };
+    let state = GraphUrlState { filter, projection: Projection::Matches, color_by: Some(grouping), ..Default::default() };
+    format!("/tech-graph{}", state.to_query().to_query_string())
+}
+
+// "3 of 4 books", and the bar width as a percentage.
+fn usage(books: usize, of: usize) -> (String, f64) {
+    let share = if of == 0 { 0.0 } else { books as f64 * 100.0 / of as f64 };
+    (format!("{} of {} books", books, of), share)
+}
+
+#[component]
+fn Fingerprint(fingerprint: TechnologyFingerprint, grouping: BookGrouping) -> impl IntoView {
+    let TechnologyFingerprint { name, books, technologies, distinct_technologies } = fingerprint;
+    let href = graph_link(grouping, &name);
+    view! {
+        <div class="card fingerprint">
+            <h3>{name}</h3>
+            <p class="hint">
+                {format!("{} books · {} technologies · ", books, distinct_technologies)}
+                <a href=href>"Show in graph"</a>
+            </p>
+            <table class="fingerprint-bars">
+                <tbody>
+                    {technologies.into_iter().map(|tech| {
+                        let (label, share) = usage(tech.books, books);
+                        view! {
+                            <tr>
+                                <td title=tech.category>{tech.name}</td>
+                                <td class="bar-cell">
+                                    <span class="bar" style=format!("width: {:.0}%", share)></span>
+                                </td>
+                                <td class="bar-label" title="Summed weight of the books' links to it">
+                                    {format!("{} · weight {}", label, tech.weight)}
+                                </td>
+                            </tr>
+                        }
+                    }).collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[component]
+pub fn AuthorsPage() -> impl IntoView {
+    let live = subscribe_to_changes();
+    let (grouping, set_grouping) = create_signal(BookGrouping::Author);
+
+    let fingerprints = create_resource(
+        move || (grouping.get(), live.graph.get()),
+        |(grouping, _)| async move {
+            // Resources must be serializable, so keep only the message.
+            get_technology_fingerprints(grouping).await.map(|list| (grouping, list)).map_err(|e| e.to_string())
+        },
+    );
+
+    view! {
+        <Title text="Authors and series"/>
+
+        <h1>"Authors and series"</h1>
+
+        <div class="card activity-filters">
+            <label for="fingerprint-grouping">"Group books by:"</label>
+            <select
+                id="fingerprint-grouping"
+                on:change=move |ev| set_grouping.set(if event_target_value(&ev) == "series" { BookGrouping::Series } else { BookGrouping::Author })
+            >
+                <option value="author">"Author"</option>
+                <option value="series">"Series"</option>
+            </select>
+            <span class="hint">"The technologies each one's books use most."</span>
+        </div>
+
+        <Suspense fallback=move || view! { <p class="loading-indicator">"Loading fingerprints..."</p> }>
+            {move || fingerprints.get().map(|result| match result {
+                Err(e) => view! { <p class="field-error">{format!("Error loading fingerprints: {}", e)}</p> }.into_view(),
+                Ok((_, list)) if list.is_empty() => view! { <p>"No books yet."</p> }.into_view(),
+                Ok((grouping, list)) => view! {
+                    <div class="fingerprints">
+                        {list.into_iter().map(|fingerprint| view! { <Fingerprint fingerprint grouping /> }).collect_view()}
+                    </div>
+                }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_labels_and_bar_widths() {
+        assert_eq!(usage(3, 4), ("3 of 4 books".to_string(), 75.0));
+        assert_eq!(usage(0, 0).1, 0.0);
+    }
+}
@@ -1,22 +1,23 @@
 use leptos::*;
 use leptos_meta::*;
 use crate::components::item_form::ItemForm;
-use crate::components::item_list::ItemList;
-// Ensure server function structs are directly usable.
-// The `#[server]` macro makes them available.
-use crate::server_fns::{AddItem, DeleteItem};
+use crate::components::item_list::{create_add_items_action, create_delete_items_action, ItemList};
+use crate::live::subscribe_to_changes;
 
 #[component]
 pub fn HomePage() -> impl IntoView {
-    // Action to add an item
-    let add_item_action = create_server_action::<AddItem>();
+    // Adds and deletes can overlap, so each call is its own submission. ItemList
+    // shows them optimistically until the server answers.
+    let add_items_action = create_add_items_action();
+    let delete_items_action = create_delete_items_action();
 
-    // Action to delete an item
-    let delete_item_action = create_server_action::<DeleteItem>();
+    // Changes made by anyone else, pushed by the server.
+    let live = subscribe_to_changes();
 
-    // Resource to fetch items. It refetches whenever an item is added or deleted.
+    // Resource to fetch items. It refetches whenever an item is added or deleted,
+    // here or in another browser.
     let items_resource = create_resource(
-        move || (add_item_action.version().get(), delete_item_action.version().get()),
+        move || (add_items_action.version().get(), delete_items_action.version().get(), live.items.get()),
         |_| async move {
             // GetItems server function call
             match crate::server_fns::get_items().await {
@@ -28,6 +29,13 @@ pub fn HomePage() -> impl IntoView {
             }
         }
     );
+    // Fetches that have landed, which tell ItemList when the list has caught
+    // up with the adds it confirmed. Counted once the items are in place.
+    let (fetches, set_fetches) = create_signal(0u64);
+    create_effect(move |_| {
+        items_resource.track();
+        set_fetches.update(|n| *n += 1);
+    });
     
     view! {
         <Title text="Item Management App"/>
@@ -36,16 +44,24 @@ pub fn HomePage() -> impl IntoView {
         
         <div class="card">
             <h2>"Add New Item"</h2>
-            <ItemForm add_item_action=add_item_action />
+            <ItemForm add_items_action=add_items_action />
         </div>
 
         <div class="card">
             <h2>"Current Items"</h2>
-            <Suspense fallback=move || view! { <p class="loading-indicator">"Loading items..."</p> }>
+            // Transition (not Suspense) keeps the current list on screen while it refetches.
+            <Transition fallback=move || view! { <p class="loading-indicator">"Loading items..."</p> }>
                 <ErrorBoundary fallback = |_| view!{<p>"Error loading items"</p>}>
-                    {move || items_resource.map(|items| view! { <ItemList items=items.clone() delete_item_action=delete_item_action /> })}
+                    {move || items_resource.map(|_| view! {
+                        <ItemList
+                            items=Signal::derive(move || items_resource.get().unwrap_or_default())
+                            fetches=fetches
+                            add_items_action=add_items_action
+                            delete_items_action=delete_items_action
+                        />
+                    })}
                 </ErrorBoundary>
-            </Suspense>
+            </Transition>
         </div>
     }
 } 
\ No newline at end of file
@@ -0,0 +1,106 @@
+use leptos::*;
+use leptos_meta::*;
+use leptos_router::{use_navigate, A};
+use crate::auth::{validate_username, AuthContext, PASSWORD_MIN_LEN, USERNAME_MAX_LEN};
+use crate::errors::AppError;
+use crate::server_fns::{Login, Logout};
+
+#[component]
+pub fn LoginPage() -> impl IntoView {
+    let auth = expect_context::<AuthContext>();
+    let (username, set_username) = create_signal(String::new());
+    let (password, set_password) = create_signal(String::new());
+    let (client_error, set_client_error) = create_signal::<Option<AppError>>(None);
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let name = username.get().trim().to_string();
+        if let Err(e) = validate_username(&name) {
+            set_client_error.set(Some(e));
+            return;
+        }
+        auth.login.dispatch(Login { username: name, password: password.get() });
+        set_password.set(String::new());
+    };
+
+    // Back to the item manager once signed in.
+    create_effect(move |_| {
+        if let Some(Ok(_)) = auth.login.value().get() {
+            use_navigate()("/", Default::default());
+        }
+    });
+
+    let error = move || {
+        client_error.get().map(|e| e.field_message("username").map(str::to_string).unwrap_or_else(|| e.to_string()))
+            .or_else(|| auth.login.value().get().and_then(|r| r.err()).map(|e| match e {
+                ServerFnError::WrappedServerError(AppError::Unauthorized(msg)) => msg,
+                other => other.to_string(),
+            }))
+    };
+
+    view! {
+        <Title text="Log in"/>
+
+        <h1>"Log in"</h1>
+
+        <div class="card">
+            <form class="login-form" on:submit=on_submit>
+                <div>
+                    <label for="login-username">"Username:"</label>
+                    <input
+                        type="text"
+                        id="login-username"
+                        autocomplete="username"
+                        prop:value=username
+                        on:input=move |ev| {
+                            set_client_error.set(None);
+                            set_username.set(event_target_value(&ev));
+                        }
+                        required
+                        maxlength=USERNAME_MAX_LEN
+                    />
+                </div>
+                <div>
+                    <label for="login-password">"Password:"</label>
+                    <input
+                        type="password"
+                        id="login-password"
+                        autocomplete="current-password"
+                        prop:value=password
+                        on:input=move |ev| set_password.set(event_target_value(&ev))
+                        required
+                        minlength=PASSWORD_MIN_LEN
+                    />
+                </div>
+                <button type="submit" class="button-primary" disabled=move || auth.login.pending().get()>
+                    {move || if auth.login.pending().get() { "Logging in..." } else { "Log in" }}
+                </button>
+                {move || error().map(|msg| view! { <p class="field-error">{msg}</p> })}
+            </form>
+        </div>
+    }
+}
+
+#[component]
+pub fn LogoutPage() -> impl IntoView {
+    let auth = expect_context::<AuthContext>();
+
+    // Effects only run in the browser, so the session ends once the page has loaded.
+    create_effect(move |_| {
+        auth.logout.dispatch(Logout {});
+    });
+
+    view! {
+        <Title text="Log out"/>
+
+        <h1>"Log out"</h1>
+
+        <div class="card">
+            {move || match auth.logout.value().get() {
+                None => view! { <p class="loading-indicator">"Logging out..."</p> }.into_view(),
+                Some(Ok(())) => view! { <p>"You have been logged out. " <A href="/login">"Log in again"</A></p> }.into_view(),
+                Some(Err(e)) => view! { <p class="field-error">{format!("Logout failed: {}", e)}</p> }.into_view(),
+            }}
+        </div>
+    }
+}
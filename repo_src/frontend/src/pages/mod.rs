@@ -1,2 +1,7 @@
+pub mod activity_page;
+pub mod authors_page;
 pub mod home;
-pub mod tech_graph_page; 
\ No newline at end of file
+pub mod login_page;
+pub mod query_page;
+pub mod tech_graph_page;
+pub mod tokens_page;
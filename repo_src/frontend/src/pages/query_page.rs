@@ -0,0 +1,170 @@
+use leptos::*;
+use leptos_meta::*;
+use shared::{QueryResult, QueryValue};
+use std::collections::HashSet;
+use crate::components::tech_graph_view::{Edge, Node, TechGraphView};
+use crate::server_fns::RunGraphQuery;
+
+// (title, query) pairs offered as starting points.
+const EXAMPLES: [(&str, &str); 4] = [
+    (
+        "FTL and AI, but no nanotech",
+        "MATCH (b:Book)-[:USES]->(:Technology {name: \"Faster-Than-Light (FTL) Drive\"}),\n      (b)-[:USES]->(ai:Technology)\nWHERE ai.name CONTAINS \"Sentient AI\"\n  AND NOT (b)-[:USES]->(:Technology {subcategory: \"Nano-engineering\"})\nRETURN b",
+    ),
+    (
+        "Technologies in a category",
+        "MATCH (t:Technology)-[:IN]->(:Category {name: \"PHYSICS & SPACETIME\"})\nRETURN t",
+    ),
+    (
+        "Books by an author and what they use",
+        "MATCH (b:Book)-[:USES]->(t:Technology)\nWHERE b.author STARTS WITH \"Iain\"\nRETURN b, t",
+    ),
+    (
+        "Technologies no book uses",
+        "MATCH (t:Technology)\nWHERE NOT (t)<-[:USES]-(:Book)\nRETURN t",
+    ),
+];
+
+fn describe(value: &QueryValue) -> String {
+    match value {
+        QueryValue::Book(book) => format!("{} ({})", book.title, book.author),
+        QueryValue::Technology(tech) => format!("{} ({})", tech.name, tech.category),
+        QueryValue::Category { name } => name.clone(),
+    }
+}
+
+// The matched subgraph, with the returned nodes highlighted.
+fn build_result_graph(result: &QueryResult) -> (Vec<Node>, Vec<Edge>) {
+    let returned: HashSet<String> = result.rows.iter().flatten().map(QueryValue::node_id).collect();
+    let nodes = result.nodes.iter().map(|value| {
+        let highlighted = returned.contains(&value.node_id());
+        let (group, shape, title) = match value {
+            QueryValue::Book(book) => (
+                if highlighted { "BookHighlighted" } else { "Book" },
+                "box",
+                format!("<b>{}</b><br>by {}", book.title, book.author),
+            ),
+            QueryValue::Technology(tech) => (
+                if highlighted { "TechnologyHighlighted" } else { "Technology" },
+                "dot",
+                format!("<b>{}</b><br><i>{}</i><br>{}", tech.name, tech.subcategory, tech.description),
+            ),
+            QueryValue::Category { name } => ("Category", "diamond", format!("<b>Category: {}</b>", name)),
+        };
+        Node {
+            id: value.node_id(),
+            label: value.label().to_string(),
+            group: group.to_string(),
+            title,
+            shape: shape.to_string(),
+            ..Default::default()
+        }
+    }).collect();
+    let edges = result.edges.iter().map(|edge| Edge {
+        from: edge.from.clone(),
+        to: edge.to.clone(),
+        ..Default::default()
+    }).collect();
+    (nodes, edges)
+}
+
+#[component]
+pub fn QueryPage() -> impl IntoView {
+    let run_action = create_server_action::<RunGraphQuery>();
+    let (query, set_query) = create_signal(EXAMPLES[0].1.to_string());
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        run_action.dispatch(RunGraphQuery { query: query.get() });
+    };
+
+    let result = move || run_action.value().get().and_then(|r| r.ok());
+    let error = move || {
+        run_action.value().get().and_then(|r| r.err()).map(|e| match e {
+            ServerFnError::WrappedServerError(e) => e.field_message("query").map(str::to_string).unwrap_or_else(|| e.to_string()),
+            other => other.to_string(),
+        })
+    };
+
+    let graph = create_memo(move |_| result().map(|r| build_result_graph(&r)).unwrap_or_default());
+    let nodes = Signal::derive(move || graph.get().0);
+    let edges = Signal::derive(move || graph.get().1);
+
+    view! {
+        <Title text="Graph query"/>
+
+        <h1>"Graph query"</h1>
+
+        <div class="card query-console">
+            <p class="hint">
+                "Match patterns such as " <code>"(b:Book)-[:USES]->(t:Technology)-[:IN]->(c:Category)"</code>
+                ", filter with " <code>"WHERE"</code> " (comparisons, patterns, AND / OR / NOT) and pick columns with "
+                <code>"RETURN"</code> "."
+            </p>
+            <div class="query-examples">
+                {EXAMPLES.into_iter().map(|(title, text)| view! {
+                    <button type="button" on:click=move |_| set_query.set(text.to_string())>{title}</button>
+                }).collect_view()}
+            </div>
+            <form on:submit=on_submit>
+                <textarea
+                    class="query-input"
+                    rows="7"
+                    spellcheck="false"
+                    prop:value=query
+                    on:input=move |ev| set_query.set(event_target_value(&ev))
+                ></textarea>
+                <button type="submit" class="button-primary" disabled=move || run_action.pending().get() || query.get().trim().is_empty()>
+                    {move || if run_action.pending().get() { "Running..." } else { "Run query" }}
+                </button>
+            </form>
+            {move || error().map(|msg| view! { <p class="field-error">{msg}</p> })}
+        </div>
+
+        {move || result().map(|result| view! {
+            <div class="card">
+                <p class="query-summary">
+                    {format!("{} row(s)", result.rows.len())}
+                    {result.truncated.then_some(" (more matched; add a LIMIT or narrow the query)")}
+                </p>
+                <table class="query-results">
+                    <thead>
+                        <tr>{result.columns.iter().map(|c| view! { <th>{c.clone()}</th> }).collect_view()}</tr>
+                    </thead>
+                    <tbody>
+                        {result.rows.iter().map(|row| view! {
+                            <tr>{row.iter().map(|value| view! { <td>{describe(value)}</td> }).collect_view()}</tr>
+                        }).collect_view()}
+                    </tbody>
+                </table>
+            </div>
+        })}
+
+        <Show when=move || !nodes.get().is_empty()>
+            <TechGraphView nodes=nodes edges=edges />
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::graph_query::run_query;
+
+    #[test]
+    fn test_example_queries_run_and_highlight_returned_nodes() {
+        let data = bundled_graph().unwrap();
+        for (title, text) in EXAMPLES {
+            let result = run_query(&data, text).unwrap_or_else(|e| panic!("example {:?} failed: {}", title, e));
+            let (nodes, edges) = build_result_graph(&result);
+            assert_eq!(nodes.len(), result.nodes.len());
+            assert_eq!(edges.len(), result.edges.len());
+        }
+
+        let result = run_query(&data, EXAMPLES[2].1).unwrap();
+        assert!(!result.rows.is_empty());
+        let (nodes, _) = build_result_graph(&result);
+        assert!(nodes.iter().all(|n| n.group.ends_with("Highlighted")), "both columns are returned");
+    }
+}
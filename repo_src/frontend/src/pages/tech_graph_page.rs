@@ -1,238 +1,1090 @@
 use leptos::*;
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use crate::components::tech_graph_view::{TechGraphView, Node, Edge};
-
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
-struct Book {
-    id: i32,
-    title: String,
-    author: String,
-    series: String,
+use leptos_router::{use_location, use_navigate, use_query_map, NavigateOptions};
+use shared::{
+    Book, BookGrouping, BookTechLink, ClusterEdgeKind, ClusterNodeKind, ClusterView, FilterOptions, GraphData, GraphDiff, GraphFilter, LinkKind,
+    MatchMode, Technology,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::rc::Rc;
+use crate::components::adjacency_matrix::AdjacencyMatrixChart;
+use crate::components::chord_diagram::ChordDiagram;
+use crate::components::graph_versions::{DiffRequest, GraphVersionsPanel};
+use crate::components::sunburst_chart::SunburstChart;
+use crate::components::taxonomy_navigator::TaxonomyNavigator;
+use crate::components::timeline_panel::TimelinePanel;
+use crate::components::tech_graph_view::{
+    TechGraphView, Node, Edge, Viewport, DASHED, DOTTED, DIFF_ADDED_COLOR, DIFF_CHANGED_COLOR, DIFF_REMOVED_COLOR, GROUP_PALETTE,
+};
+use crate::graph_authors::{group_node_id, groups};
+use crate::graph_charts::{adjacency_matrix, category_cooccurrence};
+use crate::graph_filter::{chips, has_technology_selections, matching_books, technology_selected, FilterChip};
+use crate::graph_timeline::graph_until;
+use crate::graph_url_state::{GraphChart, GraphUrlState, Projection};
+use crate::live::subscribe_to_changes;
+use crate::server_fns::{
+    diff_graph_versions, get_filter_options, get_filtered_graph, get_graph, get_graph_clusters, get_graph_version,
+    get_taxonomy, get_technology_timeline, CreateGraphSnapshot, RevertGraph,
+};
+use crate::taxonomy::{node_paths, path_of_node_id, taxonomy_node_id, taxonomy_tree, technology_path, PATH_SEPARATOR};
+
+// Diff colour for a node or edge key, if it is part of the diff.
+struct DiffColors {
+    books: HashMap<i32, &'static str>,
+    technologies: HashMap<i32, &'static str>,
+    links: HashMap<(i32, i32), &'static str>,
+}
+
+impl DiffColors {
+    fn new(diff: &GraphDiff) -> Self {
+        let mut books = HashMap::new();
+        books.extend(diff.added_books.iter().map(|b| (b.id, DIFF_ADDED_COLOR)));
+        books.extend(diff.removed_books.iter().map(|b| (b.id, DIFF_REMOVED_COLOR)));
+        books.extend(diff.changed_books.iter().map(|c| (c.after.id, DIFF_CHANGED_COLOR)));
+        let mut technologies = HashMap::new();
+        technologies.extend(diff.added_technologies.iter().map(|t| (t.id, DIFF_ADDED_COLOR)));
+        technologies.extend(diff.removed_technologies.iter().map(|t| (t.id, DIFF_REMOVED_COLOR)));
+        technologies.extend(diff.changed_technologies.iter().map(|c| (c.after.id, DIFF_CHANGED_COLOR)));
+        let mut links = HashMap::new();
+        links.extend(diff.added_links.iter().map(|l| ((l.book_id, l.tech_id), DIFF_ADDED_COLOR)));
+        links.extend(diff.removed_links.iter().map(|l| ((l.book_id, l.tech_id), DIFF_REMOVED_COLOR)));
+        links.extend(diff.changed_links.iter().map(|c| ((c.after.book_id, c.after.tech_id), DIFF_CHANGED_COLOR)));
+        Self { books, technologies, links }
+    }
+}
+
+// The "to" side of a diff plus everything the diff removed, so removed
+// nodes and edges can still be drawn (in red).
+fn with_removed(mut data: GraphData, diff: &GraphDiff) -> GraphData {
+    data.books.extend(diff.removed_books.iter().cloned());
+    data.technologies.extend(diff.removed_technologies.iter().cloned());
+    data.links.extend(diff.removed_links.iter().cloned());
+    data
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, Hash)]
-struct Tech {
-    id: i32,
-    name: String,
-    category: String,
-    subcategory: String,
-    description: String,
+fn diff_summary(diff: &GraphDiff) -> String {
+    if diff.is_empty() {
+        return "No differences between the selected versions.".to_string();
+    }
+    format!(
+        "Books: +{} −{} ~{} · Technologies: +{} −{} ~{} · Links: +{} −{} ~{}",
+        diff.added_books.len(), diff.removed_books.len(), diff.changed_books.len(),
+        diff.added_technologies.len(), diff.removed_technologies.len(), diff.changed_technologies.len(),
+        diff.added_links.len(), diff.removed_links.len(), diff.changed_links.len(),
+    )
+}
+
+// A link's edge: as wide as its weight, solid for a central premise, dashed
+// for a background detail and dotted for a mention.
+fn link_edge(link: &BookTechLink) -> Edge {
+    let dashes = match link.kind {
+        LinkKind::CentralPremise => Vec::new(),
+        LinkKind::BackgroundDetail => DASHED.to_vec(),
+        LinkKind::Mentioned => DOTTED.to_vec(),
+    };
+    let citation = link.citation.as_ref().map(|c| format!("<br>“{}”", c)).unwrap_or_default();
+    Edge {
+        from: format!("b_{}", link.book_id),
+        to: format!("t_{}", link.tech_id),
+        value: Some(link.weight.max(1) as usize),
+        title: Some(format!("<b>{}</b> · weight {}{}", link.kind.label(), link.weight, citation)),
+        dashes,
+        ..Default::default()
+    }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
-struct BookTechLink {
-    book_id: i32,
-    tech_id: i32,
+// Palette colour for each author or series, in name order.
+fn group_colors(data: &GraphData, grouping: BookGrouping) -> HashMap<String, &'static str> {
+    groups(data, grouping)
+        .into_keys()
+        .zip(GROUP_PALETTE.iter().cycle())
+        .map(|(name, color)| (name, *color))
+        .collect()
 }
 
-async fn fetch_csv_data<T: for<'de> Deserialize<'de>>(url: &str) -> Result<Vec<T>, ()> {
-    #[cfg(feature = "hydrate")]
-    {
-        let text = gloo_net::http::Request::get(url).send().await.map_err(|e| {
-            logging::error!("Failed to fetch {}: {:?}", url, e);
-        })?.text().await.map_err(|e| {
-            logging::error!("Failed to get text from {}: {:?}", url, e);
-        })?;
-        let mut rdr = csv::Reader::from_reader(text.as_bytes());
-        rdr.deserialize().collect::<Result<Vec<T>, _>>().map_err(|e| {
-            logging::error!("Failed to parse CSV from {}: {:?}", url, e);
-        })
-    }
-    #[cfg(not(feature = "hydrate"))]
-    {
-        // Server-side: return empty data
-        logging::log!("Server-side: returning empty data for {}", url);
-        Err(())
+fn build_graph(
+    data: &GraphData,
+    filter: &GraphFilter,
+    diff: Option<&DiffColors>,
+    color_by: Option<BookGrouping>,
+    taxonomy_layer: bool,
+) -> (Vec<Node>, Vec<Edge>) {
+    let GraphData { books, technologies: techs, links, taxonomy } = data;
+    let tech_map: HashMap<i32, Technology> = techs.iter().cloned().map(|t| (t.id, t)).collect();
+    let book_map: HashMap<i32, Book> = books.iter().cloned().map(|b| (b.id, b)).collect();
+    let matching = matching_books(data, filter);
+    // With technologies selected, only their links are drawn; a filter on
+    // authors or series alone keeps every link of the highlighted books.
+    let narrow_links = has_technology_selections(filter);
+
+    // A diff colour wins over the book's author or series colour.
+    let palette = color_by.map(|grouping| (grouping, group_colors(data, grouping)));
+    let group_color = |grouping: BookGrouping, name: &str| {
+        palette.as_ref().filter(|(by, _)| *by == grouping).and_then(|(_, colors)| colors.get(name)).map(|c| c.to_string())
+    };
+    let book_color = |book: &Book| {
+        diff.and_then(|d| d.books.get(&book.id)).map(|c| c.to_string())
+            .or_else(|| color_by.and_then(|grouping| group_color(grouping, grouping.key(book))))
+    };
+    let tech_color = |id: i32| diff.and_then(|d| d.technologies.get(&id)).map(|c| c.to_string());
+    let link_color = |book_id: i32, tech_id: i32| diff.and_then(|d| d.links.get(&(book_id, tech_id))).map(|c| c.to_string());
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+
+    // Create category nodes (large nodes)
+    let selected_categories: HashSet<&str> = techs.iter()
+        .filter(|t| technology_selected(filter, t))
+        .map(|t| t.category.as_str())
+        .collect();
+    let categories: HashSet<String> = techs.iter().map(|t| t.category.clone()).collect();
+    for category in &categories {
+        let is_filtered = selected_categories.contains(category.as_str());
+        nodes.push(Node {
+            id: format!("c_{}", category),
+            label: category.clone(),
+            group: if is_filtered { "CategoryHighlighted".to_string() } else { "Category".to_string() },
+            title: format!("<b>Category: {}</b><br>Click to add or remove it from the filter", category),
+            shape: "diamond".to_string(),
+            ..Default::default()
+        });
+    }
+
+    // Create subcategory and finer taxonomy nodes (between categories and
+    // technologies) when the layer is on. A technology then hangs off the
+    // deepest of them, unless its taxonomy disagrees with its category.
+    let mut branches: BTreeMap<Vec<String>, (usize, bool)> = BTreeMap::new();
+    let mut tech_parents: HashMap<i32, String> = HashMap::new();
+    if taxonomy_layer {
+        let paths = node_paths(taxonomy);
+        for tech in techs {
+            let path = technology_path(tech, &paths);
+            if path.len() < 2 || path[0] != tech.category {
+                continue;
+            }
+            for depth in 2..=path.len() {
+                let (count, highlighted) = branches.entry(path[..depth].to_vec()).or_default();
+                *count += 1;
+                *highlighted |= technology_selected(filter, tech);
+            }
+            tech_parents.insert(tech.id, taxonomy_node_id(&path));
+        }
+    }
+    for (path, (count, highlighted)) in &branches {
+        let id = taxonomy_node_id(path);
+        let title = if path.len() == 2 {
+            format!("<b>Subcategory: {}</b><br>{} technologies<br>Click to add or remove it from the filter", path[1], count)
+        } else {
+            format!("<b>{}</b><br>{} technologies", path.join(PATH_SEPARATOR), count)
+        };
+        edges.push(Edge { from: id.clone(), to: taxonomy_node_id(&path[..path.len() - 1]), ..Default::default() });
+        nodes.push(Node {
+            id,
+            label: path[path.len() - 1].clone(),
+            group: if *highlighted { "SubcategoryHighlighted".to_string() } else { "Subcategory".to_string() },
+            title,
+            shape: "diamond".to_string(),
+            ..Default::default()
+        });
+    }
+
+    // Create technology nodes (medium nodes) and connect to categories
+    for tech in techs {
+        let is_filtered = technology_selected(filter, tech);
+
+        nodes.push(Node {
+            id: format!("t_{}", tech.id),
+            label: tech.name.clone(),
+            group: if is_filtered { "TechnologyHighlighted".to_string() } else { "Technology".to_string() },
+            title: format!("<b>{}</b><br><i>{}</i><br>{}<br>Click to add or remove it from the filter",
+                         tech.name, tech.subcategory, tech.description),
+            shape: "dot".to_string(),
+            color: tech_color(tech.id),
+            ..Default::default()
+        });
+
+        // Connect technology to its category or taxonomy node (an added/removed technology takes its edge with it)
+        edges.push(Edge {
+            from: format!("t_{}", tech.id),
+            to: tech_parents.get(&tech.id).cloned().unwrap_or_else(|| format!("c_{}", tech.category)),
+            color: tech_color(tech.id).filter(|c| c != DIFF_CHANGED_COLOR),
+            ..Default::default()
+        });
     }
+
+    // Create book nodes (small nodes) and connect to technologies
+    let mut connected_books = HashSet::new();
+
+    for link in links {
+        if let (Some(tech), Some(book)) = (tech_map.get(&link.tech_id), book_map.get(&link.book_id)) {
+            // Always include the book, but highlight if it matches the filter
+            if !connected_books.contains(&book.id) {
+                connected_books.insert(book.id);
+                nodes.push(Node {
+                    id: format!("b_{}", book.id),
+                    label: book.title.clone(),
+                    group: if matching.contains(&book.id) { "BookHighlighted".to_string() } else { "Book".to_string() },
+                    title: format!("<b>{}</b><br>by {}{}<br>Series: {}",
+                                 book.title, book.author,
+                                 book.year.map(|y| format!(", {}", y)).unwrap_or_default(),
+                                 if book.series.is_empty() { "Standalone".to_string() } else { book.series.clone() }),
+                    shape: "box".to_string(),
+                    color: book_color(book),
+                    ..Default::default()
+                });
+            }
+
+            // Connect book to technology (only show if no technology filter or tech is selected)
+            if !narrow_links || technology_selected(filter, tech) {
+                edges.push(Edge { color: link_color(book.id, tech.id), ..link_edge(link) });
+            }
+        }
+    }
+
+    // Create author and series nodes and connect them to their drawn books
+    for (grouping, kind, selected) in [
+        (BookGrouping::Author, "Author", &filter.authors),
+        (BookGrouping::Series, "Series", &filter.series),
+    ] {
+        for (name, book_ids) in groups(data, grouping) {
+            let drawn: Vec<i32> = book_ids.into_iter().filter(|id| connected_books.contains(id)).collect();
+            if drawn.is_empty() {
+                continue;
+            }
+            let id = group_node_id(grouping, &name);
+            edges.extend(drawn.iter().map(|book_id| Edge {
+                from: id.clone(),
+                to: format!("b_{}", book_id),
+                ..Default::default()
+            }));
+            nodes.push(Node {
+                id,
+                label: name.clone(),
+                group: if selected.contains(&name) { format!("{}Highlighted", kind) } else { kind.to_string() },
+                title: format!("<b>{}: {}</b><br>{} books<br>Click to add or remove it from the filter", kind, name, drawn.len()),
+                shape: if grouping == BookGrouping::Author { "triangle" } else { "hexagon" }.to_string(),
+                color: group_color(grouping, &name),
+                ..Default::default()
+            });
+        }
+    }
+
+    (nodes, edges)
+}
+
+// "Facet: value" for a filter chip; technologies are shown by name.
+fn chip_label(chip: &FilterChip, options: &FilterOptions) -> String {
+    let value = match chip {
+        FilterChip::Technology(id) => options.technologies.iter()
+            .find(|t| t.id == *id)
+            .map_or_else(|| format!("#{}", id), |t| t.name.clone()),
+        FilterChip::Category(value) | FilterChip::Subcategory(value) | FilterChip::Author(value) | FilterChip::Series(value) => value.clone(),
+    };
+    format!("{}: {}", chip.facet(), value)
+}
+
+// Nodes and edges for the clustered view. Collapsed clusters are sized by
+// their book count and bundled edges by the number of books behind them.
+fn build_cluster_graph(view: &ClusterView) -> (Vec<Node>, Vec<Edge>) {
+    let toggle_hint = |expanded: bool| if expanded { "Click to collapse" } else { "Click to expand" };
+
+    let nodes = view.nodes.iter().map(|node| match node.kind {
+        ClusterNodeKind::Category => Node {
+            id: node.id.clone(),
+            label: if node.expanded {
+                format!("▾ {}", node.label)
+            } else {
+                format!("▸ {}\n{} technologies · {} books", node.label, node.technologies, node.books)
+            },
+            group: if node.highlighted { "CategoryHighlighted".to_string() } else { "Category".to_string() },
+            title: format!("<b>Category: {}</b><br>{} technologies, {} books<br>{}",
+                         node.label, node.technologies, node.books, toggle_hint(node.expanded)),
+            shape: "diamond".to_string(),
+            value: (!node.expanded).then_some(node.books),
+            ..Default::default()
+        },
+        ClusterNodeKind::Technology => Node {
+            id: node.id.clone(),
+            label: if node.expanded { node.label.clone() } else { format!("{} ({})", node.label, node.books) },
+            group: if node.highlighted { "TechnologyHighlighted".to_string() } else { "Technology".to_string() },
+            title: format!("<b>{}</b><br>{}<br>{} books<br>{}", node.label, node.detail, node.books, toggle_hint(node.expanded)),
+            shape: "dot".to_string(),
+            value: (!node.expanded).then_some(node.books),
+            ..Default::default()
+        },
+        ClusterNodeKind::Book => Node {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            group: if node.highlighted { "BookHighlighted".to_string() } else { "Book".to_string() },
+            title: format!("<b>{}</b><br>{}", node.label, node.detail),
+            shape: "box".to_string(),
+            ..Default::default()
+        },
+        ClusterNodeKind::MoreBooks => Node {
+            id: node.id.clone(),
+            label: node.label.clone(),
+            group: "MoreBooks".to_string(),
+            title: format!("{} more books are not shown", node.books),
+            shape: "box".to_string(),
+            ..Default::default()
+        },
+    }).collect();
+
+    let edges = view.edges.iter().map(|edge| Edge {
+        from: edge.from.clone(),
+        to: edge.to.clone(),
+        value: (edge.kind != ClusterEdgeKind::Member).then_some(edge.weight),
+        title: match edge.kind {
+            ClusterEdgeKind::Member => None,
+            ClusterEdgeKind::Link => Some(format!("Links of weight {} in all", edge.weight)),
+            ClusterEdgeKind::Shared => Some(format!("{} books link both", edge.weight)),
+        },
+        dashes: if edge.kind == ClusterEdgeKind::Shared { DASHED.to_vec() } else { Vec::new() },
+        ..Default::default()
+    }).collect();
+
+    (nodes, edges)
 }
 
+// `use_navigate` behind a pointer, so it can be stored and the page's
+// handlers stay `Copy`.
+type Navigate = Rc<dyn Fn(&str, NavigateOptions)>;
+
 #[component]
 pub fn TechGraphPage() -> impl IntoView {
-    let data_resource = create_local_resource(
-        || (),
-        |_| async {
-            logging::log!("Starting to fetch CSV data...");
-            let books = fetch_csv_data::<Book>("/data/books.csv").await;
-            let techs = fetch_csv_data::<Tech>("/data/technologies.csv").await;
-            let links = fetch_csv_data::<BookTechLink>("/data/book_tech_links.csv").await;
-            logging::log!("All CSV fetch attempts completed");
-            (books, techs, links)
+    let snapshot_action = create_server_action::<CreateGraphSnapshot>();
+    let revert_action = create_server_action::<RevertGraph>();
+    let (diff_request, set_diff_request) = create_signal::<DiffRequest>(None);
+
+    let live = subscribe_to_changes();
+
+    // Filter, projection, expanded clusters, focus and camera all live in the
+    // URL (see `graph_url_state`), so the address is always a shareable link.
+    let query = use_query_map();
+    let location = use_location();
+    let navigate: Navigate = Rc::new(use_navigate());
+    let navigate = store_value(navigate);
+    let state = create_memo(move |_| query.with(GraphUrlState::from_query));
+
+    // Writes a change to the URL. Anything that redraws the graph drops the
+    // camera, which only fits the layout it was taken on; camera moves and
+    // timeline steps alone replace the history entry instead of adding one,
+    // so playback does not fill the history.
+    let update_state = move |change: &dyn Fn(&mut GraphUrlState)| {
+        let current = state.get_untracked();
+        let mut next = current.clone();
+        change(&mut next);
+        let without_view = |s: &GraphUrlState| GraphUrlState { focus: None, viewport: None, ..s.clone() };
+        if without_view(&next) != without_view(&current) {
+            next.viewport = None;
+        }
+        if next == current {
+            return;
+        }
+        let replace = GraphUrlState { viewport: current.viewport, year: current.year, ..next.clone() } == current;
+        let path = format!("{}{}", location.pathname.get_untracked(), next.to_query().to_query_string());
+        navigate.with_value(|navigate| navigate(&path, NavigateOptions { replace, scroll: false, ..Default::default() }));
+    };
+
+    let filter = create_memo(move |_| state.with(|s| s.filter.clone()));
+    let projection = create_memo(move |_| state.with(GraphUrlState::effective_projection));
+    // What to expand: whatever was clicked open, plus the technologies and
+    // categories in the filter.
+    let expansion = create_memo(move |_| state.with(|s| {
+        let categories: BTreeSet<String> = s.open_categories.union(&s.filter.categories).cloned().collect();
+        let technologies: BTreeSet<i32> = s.open_technologies.union(&s.filter.technologies).copied().collect();
+        (categories.into_iter().collect::<Vec<_>>(), technologies.into_iter().collect::<Vec<_>>())
+    }));
+    let year = create_memo(move |_| state.with(|s| s.year));
+    let chart = create_memo(move |_| state.with(|s| s.chart));
+    let focus = Signal::derive(move || state.with(|s| s.focus.clone()));
+    let viewport = Signal::derive(move || state.with(|s| s.viewport));
+
+    // What the filter can be built from; reloaded with the graph.
+    let options_resource = create_resource(
+        move || (revert_action.version().get(), live.graph.get()),
+        |_| async move {
+            get_filter_options().await.map_err(|e| {
+                logging::error!("Failed to load filter options: {}", e);
+            })
+        },
+    );
+
+    // The graph as stored on the server (or the part the filter matches);
+    // reloaded after a revert (ours or anyone's). Only fetched when every
+    // node is shown.
+    let data_resource = create_resource(
+        move || (
+            revert_action.version().get(),
+            live.graph.get(),
+            projection.get(),
+            (projection.get() == Projection::Matches).then(|| filter.get()),
+        ),
+        |(_, _, projection, matches_of)| async move {
+            let data = match (projection, matches_of) {
+                (_, Some(filter)) => get_filtered_graph(filter).await,
+                (Projection::AllNodes, None) => get_graph().await,
+                _ => return Ok(None),
+            };
+            data.map(Some).map_err(|e| {
+                logging::error!("Failed to load graph data: {}", e);
+            })
+        },
+    );
+
+    // The server-side aggregation for the clustered view.
+    let cluster_resource = create_resource(
+        move || (
+            revert_action.version().get(),
+            live.graph.get(),
+            projection.get() == Projection::Clusters,
+            expansion.get(),
+            filter.get(),
+            year.get(),
+        ),
+        |(_, _, clustered, (categories, technologies), filter, year)| async move {
+            if !clustered {
+                return Ok(None);
+            }
+            get_graph_clusters(categories, technologies, filter, year).await.map(Some).map_err(|e| {
+                logging::error!("Failed to load graph clusters: {}", e);
+            })
+        },
+    );
+
+    // The whole graph for the matrix, chord and sunburst tabs; only fetched
+    // while one of them is shown.
+    let chart_resource = create_resource(
+        move || (revert_action.version().get(), live.graph.get(), chart.get() != GraphChart::Network),
+        |(_, _, charted)| async move {
+            if !charted {
+                return Ok(None);
+            }
+            get_graph().await.map(Some).map_err(|e| {
+                logging::error!("Failed to load graph data for the chart: {}", e);
+            })
+        },
+    );
+
+    // Publication years and first appearances for the timeline.
+    let timeline_resource = create_resource(
+        move || (revert_action.version().get(), live.graph.get()),
+        |_| async move {
+            get_technology_timeline().await.map_err(|e| {
+                logging::error!("Failed to load the timeline: {}", e);
+            })
         },
     );
 
-    let (selected_technology, set_selected_technology) = create_signal::<Option<i32>>(None);
-    let (selected_category, set_selected_category) = create_signal::<Option<String>>(None);
+    // The taxonomy tree with its roll-up counts, for the navigator.
+    let taxonomy_resource = create_resource(
+        move || (revert_action.version().get(), live.graph.get()),
+        |_| async move {
+            get_taxonomy().await.map_err(|e| {
+                logging::error!("Failed to load the taxonomy: {}", e);
+            })
+        },
+    );
+
+    // (graph on the "to" side, diff) for the requested comparison.
+    let diff_resource = create_resource(
+        move || diff_request.get(),
+        |request| async move {
+            let (from, to) = request?;
+            let diff = diff_graph_versions(from, to).await;
+            let data = match to {
+                Some(id) => get_graph_version(id).await,
+                None => get_graph().await,
+            };
+            match (data, diff) {
+                (Ok(data), Ok(diff)) => Some((data, diff)),
+                (Err(e), _) | (_, Err(e)) => {
+                    logging::error!("Failed to load graph diff: {}", e);
+                    None
+                }
+            }
+        },
+    );
+
+    let color_by = create_memo(move |_| state.with(|s| s.color_by));
+    let taxonomy_layer = create_memo(move |_| state.with(|s| s.taxonomy_layer));
+
+    // The fetched graph as of the timeline's year; the clusters are cut on the server.
+    let as_of_year = move |data: GraphData| match year.get() {
+        Some(year) => graph_until(&data, year),
+        None => data,
+    };
 
     let graph_data = create_memo(move |_| {
-        match data_resource.get() {
-            Some((books_res, techs_res, links_res)) => {
-                logging::log!("Data resource available, checking results...");
-                if let (Ok(books), Ok(techs), Ok(links)) = (books_res, techs_res, links_res) {
-                    logging::log!("All data loaded successfully: {} books, {} techs, {} links", 
-                                books.len(), techs.len(), links.len());
-                    let tech_map: HashMap<i32, Tech> = techs.iter().cloned().map(|t| (t.id, t)).collect();
-                    let book_map: HashMap<i32, Book> = books.iter().cloned().map(|b| (b.id, b)).collect();
-                    
-                    let mut nodes = Vec::new();
-                    let mut edges = Vec::new();
-                    
-                    let tech_filter = selected_technology.get();
-                    let category_filter = selected_category.get();
-
-                    // Create category nodes (large nodes)
-                    let categories: HashSet<String> = techs.iter().map(|t| t.category.clone()).collect();
-                    for category in &categories {
-                        let _is_selected = category_filter.as_ref() == Some(category);
-                        nodes.push(Node {
-                            id: format!("c_{}", category),
-                            label: category.clone(),
-                            group: "Category".to_string(),
-                            title: format!("<b>Category: {}</b><br>Click to filter by this category", category),
-                            shape: "diamond".to_string(),
-                        });
-                    }
-
-                    // Create technology nodes (medium nodes) and connect to categories
-                    for tech in &techs {
-                        let is_filtered = tech_filter == Some(tech.id) || 
-                                         category_filter.as_ref() == Some(&tech.category);
-                        
-                        nodes.push(Node {
-                            id: format!("t_{}", tech.id),
-                            label: tech.name.clone(),
-                            group: if is_filtered { "TechnologyHighlighted".to_string() } else { "Technology".to_string() },
-                            title: format!("<b>{}</b><br><i>{}</i><br>{}<br>Click to see related books", 
-                                         tech.name, tech.subcategory, tech.description),
-                            shape: "dot".to_string(),
-                        });
-                        
-                        // Connect technology to its category
-                        edges.push(Edge {
-                            from: format!("t_{}", tech.id),
-                            to: format!("c_{}", tech.category),
-                        });
-                    }
-
-                    // Create book nodes (small nodes) and connect to technologies
-                    let mut connected_books = HashSet::new();
-                    
-                    for link in &links {
-                        if let (Some(tech), Some(book)) = (tech_map.get(&link.tech_id), book_map.get(&link.book_id)) {
-                            let tech_is_filtered = tech_filter == Some(tech.id) || 
-                                                  category_filter.as_ref() == Some(&tech.category);
-                            
-                            // Always include the book, but highlight if connected to filtered tech
-                            if !connected_books.contains(&book.id) {
-                                connected_books.insert(book.id);
-                                nodes.push(Node {
-                                    id: format!("b_{}", book.id),
-                                    label: book.title.clone(),
-                                    group: if tech_is_filtered { "BookHighlighted".to_string() } else { "Book".to_string() },
-                                    title: format!("<b>{}</b><br>by {}<br>Series: {}", 
-                                                 book.title, book.author, 
-                                                 if book.series.is_empty() { "Standalone".to_string() } else { book.series.clone() }),
-                                    shape: "box".to_string(),
-                                });
-                            }
-                            
-                            // Connect book to technology (only show if no filter or tech is relevant)
-                            if (tech_filter.is_none() && category_filter.is_none()) || 
-                               tech_filter == Some(tech.id) || 
-                               category_filter.as_ref() == Some(&tech.category) {
-                                edges.push(Edge {
-                                    from: format!("b_{}", book.id),
-                                    to: format!("t_{}", tech.id),
-                                });
-                            }
-                        }
-                    }
+        let filter = filter.get();
+        let color_by = color_by.get();
+        let taxonomy_layer = taxonomy_layer.get();
 
-                    logging::log!("Generated graph with {} nodes and {} edges", nodes.len(), edges.len());
-                    (nodes, edges)
-                } else {
-                    logging::error!("Failed to load some data resources");
+        // A diff colours individual books and links, so it always shows every node.
+        let (nodes, edges) = if let Some(Some((data, diff))) = diff_resource.get().filter(|_| diff_request.get().is_some()) {
+            build_graph(&with_removed(data, &diff), &filter, Some(&DiffColors::new(&diff)), color_by, taxonomy_layer)
+        } else {
+            match (data_resource.get(), cluster_resource.get()) {
+                (Some(Ok(Some(data))), _) => {
+                    let data = as_of_year(data);
+                    logging::log!("Graph data loaded: {} books, {} techs, {} links",
+                                data.books.len(), data.technologies.len(), data.links.len());
+                    build_graph(&data, &filter, None, color_by, taxonomy_layer)
+                }
+                (_, Some(Ok(Some(view)))) => build_cluster_graph(&view),
+                (Some(Err(())), _) | (_, Some(Err(()))) => {
+                    logging::error!("Failed to load graph data");
+                    (Vec::new(), Vec::new())
+                }
+                _ => {
+                    logging::log!("Data resource not yet available");
                     (Vec::new(), Vec::new())
                 }
             }
-            None => {
-                logging::log!("Data resource not yet available");
-                (Vec::new(), Vec::new())
-            }
-        }
+        };
+        logging::log!("Generated graph with {} nodes and {} edges", nodes.len(), edges.len());
+        (nodes, edges)
     });
 
     let nodes = Signal::derive(move || graph_data.get().0);
     let edges = Signal::derive(move || graph_data.get().1);
 
+    let add_to_filter = move |chip: FilterChip| update_state(&|s| { chip.clone().insert_into(&mut s.filter); });
+
+    // Clicking a node focuses it. Clustered: clicking a category or
+    // technology also expands or collapses it (collapsing drops it from the
+    // filter too, which keeps it open). Otherwise clicking one (or an author
+    // or series) adds it to the filter or removes it, as its tooltip says.
+    let on_node_click = Callback::new(move |id: String| {
+        let clustered = projection.get_untracked() == Projection::Clusters && diff_request.get_untracked().is_none();
+        update_state(&|s| {
+            s.focus = Some(id.clone());
+            let (chip, opened) = if let Some(category) = id.strip_prefix("c_") {
+                (FilterChip::Category(category.to_string()), s.open_categories.contains(category))
+            } else if let Some(tech_id) = id.strip_prefix("t_").and_then(|id| id.parse::<i32>().ok()) {
+                (FilterChip::Technology(tech_id), s.open_technologies.contains(&tech_id))
+            } else if let Some(path) = path_of_node_id(&id).filter(|path| path.len() > 1) {
+                // Only subcategories are in the filter; finer groupings just take the focus.
+                match <[String; 2]>::try_from(path) {
+                    Ok([_, subcategory]) => (FilterChip::Subcategory(subcategory), false),
+                    Err(_) => return,
+                }
+            } else if let Some(author) = id.strip_prefix("a_") {
+                (FilterChip::Author(author.to_string()), false)
+            } else if let Some(series) = id.strip_prefix("s_") {
+                (FilterChip::Series(series.to_string()), false)
+            } else {
+                return;
+            };
+            let selected = chip.remove_from(&mut s.filter);
+            if !clustered {
+                if !selected {
+                    chip.insert_into(&mut s.filter);
+                }
+                return;
+            }
+            match chip {
+                FilterChip::Category(category) if !(opened || selected) => { s.open_categories.insert(category); }
+                FilterChip::Category(category) => { s.open_categories.remove(&category); }
+                FilterChip::Technology(tech_id) if !(opened || selected) => { s.open_technologies.insert(tech_id); }
+                FilterChip::Technology(tech_id) => { s.open_technologies.remove(&tech_id); }
+                _ => {}
+            }
+        });
+    });
+
+    let on_viewport_change = Callback::new(move |viewport: Viewport| update_state(&|s| s.viewport = Some(viewport)));
+
+    // Every change is already in the URL, so the page's own address is the link.
+    let (link_copied, set_link_copied) = create_signal(false);
+    let copy_link = move |_| {
+        #[cfg(feature = "hydrate")]
+        if let Ok(href) = window().location().href() {
+            let _ = window().navigator().clipboard().write_text(&href);
+        }
+        set_link_copied.set(true);
+        set_timeout(move || set_link_copied.set(false), std::time::Duration::from_secs(2));
+    };
+
+    let summary = move || {
+        let filter = filter.get();
+        let clusters = cluster_resource.get().and_then(Result::ok).flatten();
+        let matching = match (&clusters, data_resource.get().and_then(Result::ok).flatten()) {
+            (Some(view), _) => Some(view.matching_books),
+            (None, Some(data)) => Some(matching_books(&as_of_year(data), &filter).len()),
+            (None, None) => None,
+        };
+        let mut parts = Vec::new();
+        if let Some(view) = clusters {
+            parts.push(format!(
+                "Showing {} of {} nodes ({} books, {} technologies, {} links). Click a category or technology to expand it.",
+                view.nodes.len(), view.total_books + view.total_technologies,
+                view.total_books, view.total_technologies, view.total_links,
+            ));
+        }
+        if let Some(matching) = matching.filter(|_| !filter.is_empty()) {
+            parts.push(format!("{} books match the filter.", matching));
+        }
+        parts.join(" ")
+    };
+
+    // The focused node's label, while it is drawn.
+    let focused = move || {
+        let id = focus.get()?;
+        nodes.with(|nodes| nodes.iter().find(|n| n.id == id).map(|n| n.label.lines().next().unwrap_or_default().to_string()))
+    };
+
     view! {
         <div class="tech-graph-page">
             <h1>"Sci-Fi Technology Network"</h1>
-            <p>"Explore the relationships between books, technologies, and categories. Blue diamonds are categories, purple circles are technologies, and orange boxes are books. Select a technology or category to highlight related connections."</p>
-            
+            <p>"Explore the relationships between books, technologies, and categories. Blue diamonds are categories (pale ones, with subcategories shown, the subcategories and finer groupings below them), purple circles are technologies, and orange boxes are books; with every node shown, green triangles are authors and brown hexagons are series. Build a filter from technologies, categories, subcategories, authors and series to highlight the books that match, or pick a year on the timeline (or press Play) to watch the graph grow book by book. The tabs above the chart swap the network for a books × technologies matrix, a chord diagram of the categories that share books, or a sunburst of the taxonomy."</p>
+
             <Suspense fallback=move || view!{<p>"Loading data..."</p>}>
                 <ErrorBoundary fallback=|_| view!{<p>"Error loading graph data."</p>}>
-                    { move || data_resource.map(|(_, techs, _)| {
-                        let techs = techs.clone().unwrap_or_default();
-                        let categories: Vec<String> = techs.iter()
-                            .map(|t| t.category.clone())
-                            .collect::<HashSet<_>>()
-                            .into_iter()
+                    { move || {
+                        // Re-rendered with the filter, so each picker resets
+                        // and only offers what is not selected yet.
+                        let options = options_resource.get().and_then(Result::ok).unwrap_or_default();
+                        let current = filter.get();
+                        let shown = state.with(|s| s.projection);
+                        let coloured = color_by.get();
+                        let layered = taxonomy_layer.get();
+                        let techs: Vec<(i32, String)> = options.technologies.iter()
+                            .filter(|t| !current.technologies.contains(&t.id))
+                            .map(|t| (t.id, format!("{} ({})", t.name, t.category)))
                             .collect();
-                        
+                        let unselected = |values: &[String], selected: &BTreeSet<String>| -> Vec<String> {
+                            values.iter().filter(|v| !selected.contains(*v)).cloned().collect()
+                        };
+                        let categories = unselected(&options.categories, &current.categories);
+                        let subcategories = unselected(&options.subcategories, &current.subcategories);
+                        let authors = unselected(&options.authors, &current.authors);
+                        let series = unselected(&options.series, &current.series);
+
                         view! {
                             <div class="graph-controls card">
                                 <div class="control-group">
-                                    <label for="tech-filter">"Filter by Technology:"</label>
+                                    <label for="tech-filter">"Add a Technology:"</label>
                                     <select
                                         id="tech-filter"
                                         on:change=move |ev| {
-                                            let val = event_target_value(&ev);
-                                            if val == "none" {
-                                                set_selected_technology.set(None);
-                                            } else {
-                                                set_selected_technology.set(val.parse::<i32>().ok());
+                                            if let Ok(id) = event_target_value(&ev).parse::<i32>() {
+                                                add_to_filter(FilterChip::Technology(id));
                                             }
-                                            set_selected_category.set(None);
                                         }
                                     >
                                         <option value="none">"-- Select a Technology --"</option>
-                                        {techs.iter().map(|t| view!{ 
-                                            <option value=t.id>{format!("{} ({})", t.name, t.category)}</option> 
-                                        }).collect_view()}
+                                        {techs.into_iter().map(|(id, label)| view!{ <option value=id>{label}</option> }).collect_view()}
                                     </select>
                                 </div>
                                 <div class="control-group">
-                                    <label for="category-filter">"Filter by Category:"</label>
-                                     <select
+                                    <label for="category-filter">"Add a Category:"</label>
+                                    <select
                                         id="category-filter"
                                         on:change=move |ev| {
                                             let val = event_target_value(&ev);
-                                            if val == "none" {
-                                                set_selected_category.set(None);
-                                            } else {
-                                                set_selected_category.set(Some(val));
+                                            if val != "none" {
+                                                add_to_filter(FilterChip::Category(val));
                                             }
-                                            set_selected_technology.set(None);
                                         }
                                     >
                                         <option value="none">"-- Select a Category --"</option>
                                         {categories.into_iter().map(|c| view!{ <option value=c.clone()>{c}</option> }).collect_view()}
                                     </select>
                                 </div>
-                                <button on:click=move |_| {
-                                    set_selected_technology.set(None);
-                                    set_selected_category.set(None);
-                                }>
+                                <div class="control-group">
+                                    <label for="subcategory-filter">"Add a Subcategory:"</label>
+                                    <select
+                                        id="subcategory-filter"
+                                        on:change=move |ev| {
+                                            let val = event_target_value(&ev);
+                                            if val != "none" {
+                                                add_to_filter(FilterChip::Subcategory(val));
+                                            }
+                                        }
+                                    >
+                                        <option value="none">"-- Select a Subcategory --"</option>
+                                        {subcategories.into_iter().map(|s| view!{ <option value=s.clone()>{s}</option> }).collect_view()}
+                                    </select>
+                                </div>
+                                <div class="control-group">
+                                    <label for="author-filter">"Add an Author:"</label>
+                                    <input
+                                        id="author-filter"
+                                        list="author-options"
+                                        placeholder="Type an author"
+                                        on:change=move |ev| {
+                                            let val = event_target_value(&ev).trim().to_string();
+                                            if !val.is_empty() {
+                                                add_to_filter(FilterChip::Author(val));
+                                            }
+                                        }
+                                    />
+                                    <datalist id="author-options">
+                                        {authors.into_iter().map(|a| view!{ <option value=a/> }).collect_view()}
+                                    </datalist>
+                                </div>
+                                <div class="control-group">
+                                    <label for="series-filter">"Add a Series:"</label>
+                                    <input
+                                        id="series-filter"
+                                        list="series-options"
+                                        placeholder="Type a series"
+                                        on:change=move |ev| {
+                                            let val = event_target_value(&ev).trim().to_string();
+                                            if !val.is_empty() {
+                                                add_to_filter(FilterChip::Series(val));
+                                            }
+                                        }
+                                    />
+                                    <datalist id="series-options">
+                                        {series.into_iter().map(|s| view!{ <option value=s/> }).collect_view()}
+                                    </datalist>
+                                </div>
+                                <div class="control-group">
+                                    <label for="match-mode">"Books must match:"</label>
+                                    <select
+                                        id="match-mode"
+                                        on:change=move |ev| {
+                                            let mode = if event_target_value(&ev) == "all" { MatchMode::All } else { MatchMode::Any };
+                                            update_state(&|s| s.filter.mode = mode);
+                                        }
+                                    >
+                                        <option value="any" selected=current.mode == MatchMode::Any>"Any selection (OR)"</option>
+                                        <option value="all" selected=current.mode == MatchMode::All>"All selections (AND)"</option>
+                                    </select>
+                                </div>
+                                <div class="control-group">
+                                    <label for="projection">"Show:"</label>
+                                    <select
+                                        id="projection"
+                                        on:change=move |ev| {
+                                            let projection = match event_target_value(&ev).as_str() {
+                                                "all" => Projection::AllNodes,
+                                                "matches" => Projection::Matches,
+                                                _ => Projection::Clusters,
+                                            };
+                                            update_state(&|s| s.projection = projection);
+                                        }
+                                    >
+                                        <option value="clusters" selected=shown == Projection::Clusters>"Clusters"</option>
+                                        <option value="all" selected=shown == Projection::AllNodes>"All nodes"</option>
+                                        <option value="matches" selected=shown == Projection::Matches>"Only matches"</option>
+                                    </select>
+                                </div>
+                                <div class="control-group">
+                                    <label for="color-by">"Colour books by:"</label>
+                                    <select
+                                        id="color-by"
+                                        on:change=move |ev| {
+                                            let color_by = match event_target_value(&ev).as_str() {
+                                                "author" => Some(BookGrouping::Author),
+                                                "series" => Some(BookGrouping::Series),
+                                                _ => None,
+                                            };
+                                            update_state(&|s| s.color_by = color_by);
+                                        }
+                                    >
+                                        <option value="none" selected=coloured.is_none()>"Match status"</option>
+                                        <option value="author" selected=coloured == Some(BookGrouping::Author)>"Author"</option>
+                                        <option value="series" selected=coloured == Some(BookGrouping::Series)>"Series"</option>
+                                    </select>
+                                </div>
+                                <div class="control-group">
+                                    <label for="taxonomy-layer">
+                                        <input
+                                            id="taxonomy-layer"
+                                            type="checkbox"
+                                            prop:checked=layered
+                                            disabled=move || projection.get() == Projection::Clusters
+                                            on:change=move |ev| {
+                                                let layered = event_target_checked(&ev);
+                                                update_state(&|s| s.taxonomy_layer = layered);
+                                            }
+                                        />
+                                        " Show subcategories"
+                                    </label>
+                                </div>
+                                <button on:click=move |_| update_state(&|s| s.filter = GraphFilter::default())>
                                 "Clear Filter"
                                 </button>
+                                <button
+                                    disabled=move || projection.get() != Projection::Clusters
+                                    on:click=move |_| update_state(&|s| {
+                                        s.open_categories.clear();
+                                        s.open_technologies.clear();
+                                    })
+                                >
+                                "Collapse All"
+                                </button>
+                                <button on:click=copy_link>
+                                    {move || if link_copied.get() { "Link Copied" } else { "Copy Link" }}
+                                </button>
+                            </div>
+                            <div class="filter-chips">
+                                {chips(&current).into_iter().map(|chip| {
+                                    let label = chip_label(&chip, &options);
+                                    view! {
+                                        <span class="chip">
+                                            {label}
+                                            <button
+                                                type="button"
+                                                title="Remove from the filter"
+                                                on:click=move |_| update_state(&|s| { chip.remove_from(&mut s.filter); })
+                                            >"×"</button>
+                                        </span>
+                                    }
+                                }).collect_view()}
                             </div>
                         }
+                    }}
+                    {move || taxonomy_resource.get().and_then(Result::ok).map(|tree| view! {
+                        <TaxonomyNavigator
+                            tree
+                            on_pick=Callback::new(move |picked: Vec<FilterChip>| update_state(&|s| {
+                                for chip in &picked {
+                                    chip.clone().insert_into(&mut s.filter);
+                                }
+                            }))
+                        />
+                    })}
+                    {move || timeline_resource.get().and_then(Result::ok).map(|timeline| view! {
+                        <TimelinePanel
+                            timeline
+                            year=year.into()
+                            on_change=Callback::new(move |picked: Option<i32>| update_state(&|s| s.year = picked))
+                        />
+                    })}
+                    <p class="cluster-summary">{summary}</p>
+                    {move || focused().map(|label| view! {
+                        <p class="graph-focus">
+                            "Focused on " <b>{label}</b>
+                            <button type="button" title="Clear the focus" on:click=move |_| update_state(&|s| s.focus = None)>"×"</button>
+                        </p>
                     })}
                 </ErrorBoundary>
             </Suspense>
-            
-            <TechGraphView nodes=nodes edges=edges />
+
+            <GraphVersionsPanel
+                snapshot_action=snapshot_action
+                revert_action=revert_action
+                diff_request=diff_request
+                set_diff_request=set_diff_request
+                versions_changed=live.versions
+            />
+            {move || diff_request.get().and(diff_resource.get().flatten()).map(|(_, diff)| view! {
+                <p class="diff-legend">
+                    <span style=format!("color: {}", DIFF_ADDED_COLOR)>"■ added "</span>
+                    <span style=format!("color: {}", DIFF_REMOVED_COLOR)>"■ removed "</span>
+                    <span style=format!("color: {}", DIFF_CHANGED_COLOR)>"■ changed "</span>
+                    {diff_summary(&diff)}
+                </p>
+            })}
+
+            <div class="graph-tabs" role="tablist">
+                {GraphChart::ALL.into_iter().map(|tab| view! {
+                    <button
+                        type="button"
+                        role="tab"
+                        class:active=move || chart.get() == tab
+                        aria-selected=move || (chart.get() == tab).to_string()
+                        on:click=move |_| update_state(&|s| s.chart = tab)
+                    >
+                        {tab.label()}
+                    </button>
+                }).collect_view()}
+            </div>
+            {move || match chart.get() {
+                GraphChart::Network => view! {
+                    <p class="hint">
+                        "Book–technology edges are as wide as the link's weight: solid for a central premise, dashed for a background detail, dotted for a mention. Hover an edge for its citation."
+                    </p>
+                    <TechGraphView
+                        nodes=nodes
+                        edges=edges
+                        on_node_click=on_node_click
+                        focus=focus
+                        viewport=viewport
+                        on_viewport_change=on_viewport_change
+                    />
+                }.into_view(),
+                shown => view! {
+                    <Suspense fallback=move || view! { <p class="loading-indicator">"Loading the chart..."</p> }>
+                        {move || chart_resource.get().map(|result| match result {
+                            Ok(Some(data)) => {
+                                // As of the timeline's year, like the network.
+                                let data = as_of_year(data);
+                                match shown {
+                                    GraphChart::Matrix => {
+                                        // Highlights follow the filter without
+                                        // redrawing the matrix in its default order.
+                                        let matrix = adjacency_matrix(&data);
+                                        let data = store_value(data);
+                                        let matching = Signal::derive(move || {
+                                            let current = filter.get();
+                                            data.with_value(|data| matching_books(data, &current))
+                                        });
+                                        view! { <AdjacencyMatrixChart matrix matching/> }.into_view()
+                                    }
+                                    GraphChart::Chord => view! {
+                                        <ChordDiagram
+                                            cooccurrence=category_cooccurrence(&data)
+                                            selected=filter.get().categories
+                                            on_pick=Callback::new(add_to_filter)
+                                        />
+                                    }.into_view(),
+                                    _ => view! {
+                                        <SunburstChart
+                                            tree=taxonomy_tree(&data)
+                                            filter=filter.get()
+                                            on_pick=Callback::new(move |picked: Vec<FilterChip>| update_state(&|s| {
+                                                for chip in &picked {
+                                                    chip.clone().insert_into(&mut s.filter);
+                                                }
+                                            }))
+                                        />
+                                    }.into_view(),
+                                }
+                            }
+                            Ok(None) => ().into_view(),
+                            Err(()) => view! { <p class="field-error">"Error loading the chart."</p> }.into_view(),
+                        })}
+                    </Suspense>
+                }.into_view(),
+            }}
         </div>
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::graph_diff::diff_graphs;
+    use shared::ClusterExpansion;
+
+    #[test]
+    fn test_build_graph_colours_diff() {
+        let before = bundled_graph().unwrap();
+        let mut after = before.clone();
+        let removed = after.links.remove(0);
+        after.links[0].weight = after.links[0].weight % BookTechLink::MAX_WEIGHT + 1;
+        let reweighted = after.links[0].clone();
+
+        let diff = diff_graphs(&before, &after);
+        let (_, edges) = build_graph(&with_removed(after, &diff), &GraphFilter::default(), Some(&DiffColors::new(&diff)), None, false);
+
+        let edge_of = |link: &BookTechLink| edges.iter()
+            .find(|e| e.from == format!("b_{}", link.book_id) && e.to == format!("t_{}", link.tech_id))
+            .expect("link should be drawn");
+        assert_eq!(edge_of(&removed).color.as_deref(), Some(DIFF_REMOVED_COLOR), "removed links are still drawn");
+        assert_eq!(edge_of(&reweighted).color.as_deref(), Some(DIFF_CHANGED_COLOR));
+        assert_eq!(edge_of(&reweighted).value, Some(reweighted.weight as usize));
+        assert_eq!(edges.iter().filter(|e| e.color.is_some()).count(), 2);
+        assert!(diff_summary(&diff).ends_with("Links: +0 −1 ~1"));
+    }
+
+    #[test]
+    fn test_link_edges_show_kind_and_weight() {
+        let link = |kind, weight| BookTechLink { kind, weight, ..BookTechLink::new(1, 101) };
+        let central = link_edge(&BookTechLink { citation: Some("psychohistory".into()), ..link(LinkKind::CentralPremise, 9) });
+        assert_eq!((central.value, central.dashes.is_empty()), (Some(9), true));
+        assert_eq!(central.title.as_deref(), Some("<b>Central premise</b> · weight 9<br>“psychohistory”"));
+        assert_eq!(link_edge(&link(LinkKind::BackgroundDetail, 4)).dashes, DASHED);
+        assert_eq!(link_edge(&link(LinkKind::Mentioned, 1)).dashes, DOTTED);
+    }
+
+    #[test]
+    fn test_build_cluster_graph_sizes_collapsed_clusters_and_bundles_edges() {
+        let data = bundled_graph().unwrap();
+        let tech = data.technologies[0].clone();
+        let expansion = ClusterExpansion { categories: vec![tech.category.clone()], technologies: vec![] };
+        let filter = GraphFilter { technologies: [tech.id].into(), ..Default::default() };
+        let view = crate::graph_clusters::cluster_graph(&data, &expansion, &filter);
+        let (nodes, edges) = build_cluster_graph(&view);
+
+        assert_eq!(nodes.len(), view.nodes.len());
+        let category = nodes.iter().find(|n| n.id == format!("c_{}", tech.category)).unwrap();
+        assert_eq!(category.value, None, "an expanded category is not sized");
+        assert!(nodes.iter().any(|n| n.group == "Category" && n.value.is_some()));
+        let selected = nodes.iter().find(|n| n.id == format!("t_{}", tech.id)).unwrap();
+        assert_eq!(selected.group, "TechnologyHighlighted");
+
+        assert!(edges.iter().any(|e| !e.dashes.is_empty() && e.value.is_some_and(|v| v > 0)));
+        assert!(edges.iter().filter(|e| e.dashes.is_empty()).all(|e| e.value.is_none()));
+    }
+
+    #[test]
+    fn test_build_graph_highlights_filter_matches() {
+        let data = bundled_graph().unwrap();
+        let options = crate::graph_filter::filter_options(&data);
+        let book = &data.books[0];
+        let techs: Vec<i32> = data.links.iter().filter(|l| l.book_id == book.id).map(|l| l.tech_id).take(2).collect();
+        let filter = GraphFilter { technologies: techs.iter().copied().collect(), mode: MatchMode::All, ..Default::default() };
+        let (nodes, edges) = build_graph(&data, &filter, None, None, false);
+
+        let highlighted: BTreeSet<String> = nodes.iter().filter(|n| n.group == "BookHighlighted").map(|n| n.id.clone()).collect();
+        let expected: BTreeSet<String> = matching_books(&data, &filter).iter().map(|id| format!("b_{}", id)).collect();
+        assert!(highlighted.contains(&format!("b_{}", book.id)));
+        assert_eq!(highlighted, expected);
+        assert!(edges.iter().filter(|e| e.from.starts_with("b_")).all(|e| techs.iter().any(|t| e.to == format!("t_{}", t))));
+
+        // Authors alone highlight books without hiding any of their links.
+        let by_author = GraphFilter { authors: [book.author.clone()].into(), ..Default::default() };
+        let (_, edges) = build_graph(&data, &by_author, None, None, false);
+        assert_eq!(edges.iter().filter(|e| e.from.starts_with("b_")).count(), data.links.len());
+
+        let labels: Vec<String> = chips(&filter).iter().map(|chip| chip_label(chip, &options)).collect();
+        assert!(labels.iter().all(|l| l.starts_with("Technology: ") && !l.contains('#')));
+    }
+
+    #[test]
+    fn test_build_graph_links_authors_and_series_and_colours_books() {
+        let data = bundled_graph().unwrap();
+        let book = data.books.iter().find(|b| !b.series.is_empty()).unwrap();
+        let filter = GraphFilter { series: [book.series.clone()].into(), ..Default::default() };
+        let (nodes, edges) = build_graph(&data, &filter, None, Some(BookGrouping::Author), false);
+
+        let book_id = format!("b_{}", book.id);
+        let author_id = group_node_id(BookGrouping::Author, &book.author);
+        let series_id = group_node_id(BookGrouping::Series, &book.series);
+        assert!(edges.iter().any(|e| e.from == author_id && e.to == book_id));
+        assert!(edges.iter().any(|e| e.from == series_id && e.to == book_id));
+        let node = |id: &str| nodes.iter().find(|n| n.id == id).unwrap();
+        assert_eq!(node(&series_id).group, "SeriesHighlighted");
+        assert_eq!(node(&author_id).group, "Author");
+
+        // The book takes its author's colour; series are not coloured.
+        assert!(node(&book_id).color.is_some());
+        assert_eq!(node(&book_id).color, node(&author_id).color);
+        assert_eq!(node(&series_id).color, None);
+
+        // Every edge ends at a drawn node.
+        let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(edges.iter().all(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str())));
+    }
+
+    #[test]
+    fn test_build_graph_draws_taxonomy_layer() {
+        let data = bundled_graph().unwrap();
+        let (plain, _) = build_graph(&data, &GraphFilter::default(), None, None, false);
+        assert!(!plain.iter().any(|n| n.id.starts_with("x_")));
+
+        let paths = node_paths(&data.taxonomy);
+        let tech = data.technologies.iter().find(|t| technology_path(t, &paths).len() > 2).expect("a technology below a subcategory");
+        let path = technology_path(tech, &paths);
+        let filter = GraphFilter { technologies: [tech.id].into(), ..Default::default() };
+        let (nodes, edges) = build_graph(&data, &filter, None, None, true);
+
+        // The technology hangs off its deepest node, and every node above it is highlighted.
+        assert!(edges.iter().any(|e| e.from == format!("t_{}", tech.id) && e.to == taxonomy_node_id(&path)));
+        assert!(!edges.iter().any(|e| e.from == format!("t_{}", tech.id) && e.to == format!("c_{}", tech.category)));
+        let node = |id: &str| nodes.iter().find(|n| n.id == id).unwrap_or_else(|| panic!("{} is not drawn", id));
+        for depth in 2..=path.len() {
+            assert_eq!(node(&taxonomy_node_id(&path[..depth])).group, "SubcategoryHighlighted");
+        }
+        assert!(nodes.iter().any(|n| n.group == "Subcategory"));
+
+        let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+        assert!(edges.iter().all(|e| ids.contains(e.from.as_str()) && ids.contains(e.to.as_str())));
+    }
+}
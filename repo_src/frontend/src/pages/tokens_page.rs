@@ -0,0 +1,122 @@
+use leptos::*;
+use leptos_meta::*;
+use shared::{ApiToken, TokenScope};
+use crate::auth::TOKEN_NAME_MAX_LEN;
+use crate::server_fns::{list_api_tokens, CreateApiToken, RevokeApiToken};
+
+fn format_time(time: Option<chrono::NaiveDateTime>) -> String {
+    time.map(|t| t.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_else(|| "never".to_string())
+}
+
+#[component]
+pub fn TokensPage() -> impl IntoView {
+    let create_action = create_server_action::<CreateApiToken>();
+    let revoke_action = create_server_action::<RevokeApiToken>();
+
+    let tokens = create_resource(
+        move || (create_action.version().get(), revoke_action.version().get()),
+        |_| async move { list_api_tokens().await.map_err(|e| e.to_string()) },
+    );
+
+    let (name, set_name) = create_signal(String::new());
+    let (scope, set_scope) = create_signal(TokenScope::Read);
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        create_action.dispatch(CreateApiToken { name: name.get(), scope: scope.get() });
+        set_name.set(String::new());
+    };
+
+    let error = move || {
+        create_action.value().get().and_then(|r| r.err())
+            .or_else(|| revoke_action.value().get().and_then(|r| r.err()))
+            .map(|e| match e {
+                ServerFnError::WrappedServerError(e) => e.field_message("name").map(str::to_string).unwrap_or_else(|| e.to_string()),
+                other => other.to_string(),
+            })
+    };
+
+    let token_row = move |token: ApiToken| {
+        let id = token.id;
+        let revoked = token.revoked_at.is_some();
+        view! {
+            <tr class:token-revoked=revoked>
+                <td>{token.name}</td>
+                <td>{token.scope.as_str()}</td>
+                <td>{format_time(Some(token.created_at))}</td>
+                <td>{format_time(token.last_used_at)}</td>
+                <td>
+                    {if revoked {
+                        format!("revoked {}", format_time(token.revoked_at)).into_view()
+                    } else {
+                        view! {
+                            <button
+                                disabled=move || revoke_action.pending().get()
+                                on:click=move |_| revoke_action.dispatch(RevokeApiToken { id })
+                            >
+                                "Revoke"
+                            </button>
+                        }.into_view()
+                    }}
+                </td>
+            </tr>
+        }
+    };
+
+    view! {
+        <Title text="API tokens"/>
+
+        <h1>"API tokens"</h1>
+
+        <div class="card">
+            <p class="hint">
+                "Scripts authenticate with " <code>"Authorization: Bearer <token>"</code>
+                ". Read tokens can only call server functions that change nothing; write tokens act with your role."
+            </p>
+            <form class="control-group" on:submit=on_submit>
+                <label for="token-name">"Name:"</label>
+                <input
+                    type="text"
+                    id="token-name"
+                    prop:value=name
+                    on:input=move |ev| set_name.set(event_target_value(&ev))
+                    maxlength=TOKEN_NAME_MAX_LEN
+                />
+                <label for="token-scope">"Scope:"</label>
+                <select
+                    id="token-scope"
+                    on:change=move |ev| set_scope.set(TokenScope::parse(&event_target_value(&ev)).unwrap_or(TokenScope::Read))
+                >
+                    {TokenScope::ALL.into_iter().map(|s| view! { <option value=s.as_str()>{s.as_str()}</option> }).collect_view()}
+                </select>
+                <button type="submit" disabled=move || create_action.pending().get() || name.get().trim().is_empty()>
+                    "Create token"
+                </button>
+            </form>
+            {move || create_action.value().get().and_then(|r| r.ok()).map(|created| view! {
+                <p class="hint">"Copy this token now; it will not be shown again:"</p>
+                <pre class="token-secret">{created.secret}</pre>
+            })}
+            {move || error().map(|msg| view! { <p class="field-error">{msg}</p> })}
+        </div>
+
+        <div class="card">
+            <Suspense fallback=move || view! { <p class="loading-indicator">"Loading tokens..."</p> }>
+                {move || tokens.get().map(|result| match result {
+                    // Most likely not signed in (or signed in with a token).
+                    Err(e) => view! { <p class="field-error">{format!("Error loading tokens: {}", e)}</p> }.into_view(),
+                    Ok(tokens) if tokens.is_empty() => view! { <p>"No API tokens yet."</p> }.into_view(),
+                    Ok(tokens) => view! {
+                        <table class="token-table">
+                            <thead>
+                                <tr><th>"Name"</th><th>"Scope"</th><th>"Created"</th><th>"Last used"</th><th></th></tr>
+                            </thead>
+                            <tbody>{tokens.into_iter().map(token_row).collect_view()}</tbody>
+                        </table>
+                    }.into_view(),
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
@@ -0,0 +1,243 @@
+// Behavior every repository implementation must share. Each backend's test
+// module runs these against a fresh, empty store so the implementations
+// cannot drift apart.
+
+use chrono::{Duration, Utc};
+use shared::{Book, BookTechLink, GraphData, Role, TokenScope};
+
+use super::{
+    ApiTokenRepository, AuditFilter, Fixture, FixtureReport, GraphRepository, ItemRepository, Repositories, UserRepository,
+};
+use crate::dataset::bundled_graph;
+use crate::errors::AppError;
+
+pub async fn item_repository_contract(repo: &dyn ItemRepository) {
+    assert_eq!(repo.count_items().await.unwrap(), 0);
+
+    let first = repo.add_item("first".to_string(), "test").await.unwrap();
+    let second = repo.add_item("second".to_string(), "test").await.unwrap();
+    assert_ne!(first.id, second.id);
+    assert_eq!(repo.count_items().await.unwrap(), 2);
+
+    let items = repo.list_items().await.unwrap();
+    assert_eq!(items, vec![second.clone(), first.clone()], "items should be listed newest first");
+
+    repo.delete_item(first.id, "test").await.unwrap();
+    assert!(matches!(repo.delete_item(first.id, "test").await, Err(AppError::NotFound(_))));
+    assert_eq!(repo.list_items().await.unwrap(), vec![second]);
+}
+
+pub async fn graph_repository_contract(repo: &dyn GraphRepository) {
+    assert!(repo.load_graph().await.unwrap().books.is_empty());
+
+    let data = bundled_graph().unwrap();
+    repo.replace_graph(data.clone(), "test").await.unwrap();
+    let loaded = repo.load_graph().await.unwrap();
+    assert_eq!(loaded.books.len(), data.books.len());
+    assert_eq!(loaded.technologies.len(), data.technologies.len());
+    assert_eq!(loaded.links.len(), data.links.len());
+    assert!(loaded.books.windows(2).all(|w| w[0].id < w[1].id), "books should be sorted by id");
+
+    assert_eq!(loaded.links, data.links, "kinds, weights and citations are kept");
+
+    // A link is identified by its book and technology alone.
+    let link = loaded.links[0].clone();
+    let bare = BookTechLink::new(link.book_id, link.tech_id);
+    assert_ne!(bare, link);
+    assert!(matches!(repo.add_link(bare.clone(), "test").await, Err(AppError::Conflict(_))));
+    repo.remove_link(bare, "test").await.unwrap();
+    assert!(matches!(repo.remove_link(link.clone(), "test").await, Err(AppError::NotFound(_))));
+    repo.add_link(link.clone(), "test").await.unwrap();
+    assert_eq!(repo.load_graph().await.unwrap().links, loaded.links);
+
+    let dangling = BookTechLink::new(-1, link.tech_id);
+    assert!(matches!(repo.add_link(dangling, "test").await, Err(AppError::NotFound(_))));
+
+    // Replacing swaps everything, including links.
+    repo.replace_graph(Default::default(), "test").await.unwrap();
+    assert_eq!(repo.load_graph().await.unwrap(), Default::default());
+}
+
+pub async fn graph_versioning_contract(repo: &dyn GraphRepository) {
+    assert!(repo.list_versions().await.unwrap().is_empty());
+    assert!(matches!(repo.load_version(1).await, Err(AppError::NotFound(_))));
+
+    let original = bundled_graph().unwrap();
+    repo.replace_graph(original.clone(), "test").await.unwrap();
+    let v1 = repo.create_version("bundled".to_string(), "test").await.unwrap();
+
+    let link = original.links[0].clone();
+    repo.remove_link(link.clone(), "test").await.unwrap();
+    let v2 = repo.create_version("without first link".to_string(), "test").await.unwrap();
+    assert!(v2.id > v1.id);
+
+    let versions = repo.list_versions().await.unwrap();
+    assert_eq!(versions.iter().map(|v| v.id).collect::<Vec<_>>(), vec![v2.id, v1.id], "newest first");
+    assert_eq!(versions[1].label, "bundled");
+
+    // Snapshots are unaffected by later edits.
+    assert_eq!(repo.load_version(v1.id).await.unwrap(), original);
+    assert!(!repo.load_version(v2.id).await.unwrap().links.contains(&link));
+
+    assert!(matches!(repo.revert_to_version(999, "backup".to_string(), "test").await, Err(AppError::NotFound(_))));
+    assert_eq!(repo.list_versions().await.unwrap().len(), 2, "a failed revert stores no backup");
+
+    let backup = repo.revert_to_version(v1.id, "Before revert".to_string(), "test").await.unwrap();
+    assert_eq!(repo.load_graph().await.unwrap(), original);
+    // The backup is the graph the revert overwrote.
+    assert_eq!((backup.label.as_str(), repo.load_version(backup.id).await.unwrap()), ("Before revert", repo.load_version(v2.id).await.unwrap()));
+    assert_eq!(repo.list_versions().await.unwrap().len(), 3, "reverting must not drop versions");
+
+    let imported = repo.replace_graph_as_version(GraphData::default(), "imported".to_string(), "test").await.unwrap();
+    assert_eq!(repo.load_graph().await.unwrap(), GraphData::default());
+    assert_eq!(repo.load_version(imported.id).await.unwrap(), GraphData::default());
+}
+
+pub async fn fixture_contract(repos: &Repositories) {
+    let graph = bundled_graph().unwrap();
+    let fixture = Fixture { items: vec!["first".into(), "second".into(), "first".into()], graph: graph.clone() };
+    let report = repos.fixtures.apply_fixture("demo", &fixture, "test").await.unwrap();
+    assert_eq!(
+        report,
+        FixtureReport {
+            items: 2,
+            books: graph.books.len(),
+            technologies: graph.technologies.len(),
+            links: graph.links.len(),
+            taxonomy: graph.taxonomy.len(),
+        }
+    );
+    assert_eq!(repos.graph.load_graph().await.unwrap(), graph);
+
+    // Applying it again changes nothing.
+    assert_eq!(repos.fixtures.apply_fixture("demo", &fixture, "test").await.unwrap(), FixtureReport::default());
+    assert_eq!(repos.items.count_items().await.unwrap(), 2);
+
+    // Rows with the same id are brought in line with the fixture.
+    let renamed = Book { title: "Renamed".into(), ..graph.books[0].clone() };
+    let update = Fixture { graph: GraphData { books: vec![renamed.clone()], ..Default::default() }, ..Default::default() };
+    assert_eq!(repos.fixtures.apply_fixture("rename", &update, "test").await.unwrap().books, 1);
+    assert_eq!(repos.graph.load_graph().await.unwrap().books[0], renamed);
+    let reweighted = BookTechLink { weight: graph.links[0].weight % BookTechLink::MAX_WEIGHT + 1, ..graph.links[0].clone() };
+    let update = Fixture { graph: GraphData { links: vec![reweighted.clone()], ..Default::default() }, ..Default::default() };
+    assert_eq!(repos.fixtures.apply_fixture("reweight", &update, "test").await.unwrap().links, 1);
+    assert_eq!(repos.graph.load_graph().await.unwrap().links[0], reweighted);
+
+    // All or nothing: a dangling link keeps the fixture's items out too.
+    let broken = Fixture {
+        items: vec!["third".into()],
+        graph: GraphData { links: vec![BookTechLink::new(-1, graph.technologies[0].id)], ..Default::default() },
+    };
+    assert!(matches!(repos.fixtures.apply_fixture("broken", &broken, "test").await, Err(AppError::NotFound(_))));
+    assert_eq!(repos.items.count_items().await.unwrap(), 2);
+
+    // One audit entry per application that changed something.
+    let filter = AuditFilter { entity_type: Some("fixture".into()), limit: 10, ..Default::default() };
+    let entries = repos.audit.list_entries(&filter).await.unwrap();
+    assert_eq!(entries.iter().map(|e| e.entity_id.as_str()).collect::<Vec<_>>(), vec!["reweight", "rename", "demo"]);
+}
+
+pub async fn audit_contract(repos: &Repositories) {
+    let all = AuditFilter { limit: 100, ..Default::default() };
+    assert!(repos.audit.list_entries(&all).await.unwrap().is_empty());
+
+    let item = repos.items.add_item("audited".to_string(), "alice").await.unwrap();
+    repos.graph.replace_graph(bundled_graph().unwrap(), "system").await.unwrap();
+    let link = bundled_graph().unwrap().links[0].clone();
+    repos.graph.remove_link(link.clone(), "bob").await.unwrap();
+    repos.items.delete_item(item.id, "bob").await.unwrap();
+
+    // A failed mutation leaves no trace.
+    assert!(repos.items.delete_item(item.id, "mallory").await.is_err());
+    assert!(repos.graph.remove_link(link.clone(), "mallory").await.is_err());
+
+    let entries = repos.audit.list_entries(&all).await.unwrap();
+    let summary: Vec<(&str, &str, &str)> = entries.iter()
+        .map(|e| (e.entity_type.as_str(), e.action.as_str(), e.actor.as_str()))
+        .collect();
+    assert_eq!(summary, vec![
+        ("item", "delete", "bob"),
+        ("link", "delete", "bob"),
+        ("graph", "replace", "system"),
+        ("item", "create", "alice"),
+    ], "newest first");
+
+    let deleted_item = &entries[0];
+    assert_eq!(deleted_item.entity_id, item.id.to_string());
+    assert!(deleted_item.before.as_deref().unwrap().contains("audited"));
+    assert!(deleted_item.after.is_none());
+    assert!(entries[3].before.is_none() && entries[3].after.is_some());
+    assert!(entries[2].after.as_deref().unwrap().contains("\"links\""), "graph entries log sizes");
+
+    // Filters combine, and the limit applies after filtering.
+    let by_bob = AuditFilter { actor: Some("bob".to_string()), ..all.clone() };
+    assert_eq!(repos.audit.list_entries(&by_bob).await.unwrap().len(), 2);
+    let who_removed_link = AuditFilter {
+        entity_type: Some("link".to_string()),
+        entity_id: Some(format!("{}:{}", link.book_id, link.tech_id)),
+        action: Some("delete".to_string()),
+        ..all.clone()
+    };
+    let found = repos.audit.list_entries(&who_removed_link).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].actor, "bob");
+    let newest = AuditFilter { limit: 1, ..all };
+    assert_eq!(repos.audit.list_entries(&newest).await.unwrap(), vec![entries[0].clone()]);
+}
+
+pub async fn user_repository_contract(repo: &dyn UserRepository) {
+    assert!(repo.list_users().await.unwrap().is_empty());
+    assert!(repo.find_credentials("zoe").await.unwrap().is_none());
+
+    let zoe = repo.create_user("zoe", "hash-z", Role::Editor, "test").await.unwrap();
+    let adam = repo.create_user("adam", "hash-a", Role::Admin, "test").await.unwrap();
+    assert!(matches!(repo.create_user("zoe", "other", Role::Viewer, "test").await, Err(AppError::Conflict(_))));
+
+    assert_eq!(repo.list_users().await.unwrap(), vec![adam.clone(), zoe.clone()], "sorted by username");
+    assert_eq!(repo.find_credentials("zoe").await.unwrap(), Some((zoe.clone(), "hash-z".to_string())));
+
+    let now = Utc::now().naive_utc();
+    repo.create_session(zoe.id, "live", now + Duration::hours(1)).await.unwrap();
+    repo.create_session(zoe.id, "stale", now - Duration::seconds(1)).await.unwrap();
+    assert!(matches!(repo.create_session(9999, "orphan", now).await, Err(AppError::NotFound(_))));
+
+    assert_eq!(repo.session_user("live", now).await.unwrap(), Some(zoe));
+    assert_eq!(repo.session_user("stale", now).await.unwrap(), None, "expired sessions are ignored");
+    assert_eq!(repo.session_user("unknown", now).await.unwrap(), None);
+
+    repo.delete_session("live").await.unwrap();
+    repo.delete_session("live").await.unwrap();
+    assert_eq!(repo.session_user("live", now).await.unwrap(), None);
+}
+
+// Run after `user_repository_contract` on the same store (it relies on "zoe" and "adam" existing).
+pub async fn api_token_contract(users: &dyn UserRepository, tokens: &dyn ApiTokenRepository) {
+    let (zoe, _) = users.find_credentials("zoe").await.unwrap().unwrap();
+    let (adam, _) = users.find_credentials("adam").await.unwrap().unwrap();
+    let now = Utc::now().naive_utc();
+
+    let read = tokens.create_token(zoe.id, "ingest", "hash-read", TokenScope::Read, "zoe").await.unwrap();
+    let write = tokens.create_token(zoe.id, "sync", "hash-write", TokenScope::Write, "zoe").await.unwrap();
+    assert_eq!(read.last_used_at, None);
+    assert!(matches!(tokens.create_token(9999, "x", "hash-x", TokenScope::Read, "test").await, Err(AppError::NotFound(_))));
+
+    let listed = tokens.list_tokens(zoe.id).await.unwrap();
+    assert_eq!(listed.iter().map(|t| t.id).collect::<Vec<_>>(), vec![write.id, read.id], "newest first");
+    assert!(tokens.list_tokens(adam.id).await.unwrap().is_empty());
+
+    let (user, used) = tokens.authenticate_token("hash-read", now).await.unwrap().expect("token should authenticate");
+    assert_eq!(user, zoe);
+    assert_eq!(used.scope, TokenScope::Read);
+    assert!(used.last_used_at.is_some());
+    let stored = tokens.list_tokens(zoe.id).await.unwrap().into_iter().find(|t| t.id == read.id).unwrap();
+    assert!(stored.last_used_at.is_some(), "last use is persisted");
+    assert!(tokens.authenticate_token("hash-unknown", now).await.unwrap().is_none());
+
+    // Only the owner can revoke, and only once.
+    assert!(matches!(tokens.revoke_token(adam.id, read.id, "adam").await, Err(AppError::NotFound(_))));
+    let revoked = tokens.revoke_token(zoe.id, read.id, "zoe").await.unwrap();
+    assert!(revoked.revoked_at.is_some());
+    assert!(matches!(tokens.revoke_token(zoe.id, read.id, "zoe").await, Err(AppError::Conflict(_))));
+    assert!(tokens.authenticate_token("hash-read", now).await.unwrap().is_none());
+    assert!(tokens.authenticate_token("hash-write", now).await.unwrap().is_some());
+}
@@ -0,0 +1,530 @@
+// In-memory repositories. No SQL, no I/O: state lives behind a `RwLock` and is
+// lost when the value is dropped. Compiles for both the server and the WASM client.
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use shared::{ApiToken, AuditEntry, BookTechLink, GraphData, GraphVersion, Item, Role, TokenScope, User};
+use std::sync::{Arc, RwLock};
+
+use super::{
+    ApiTokenRepository, AuditFilter, AuditRecord, AuditRepository, Fixture, FixtureReport, FixtureRepository, GraphRepository,
+    ItemRepository, UserRepository,
+};
+use crate::errors::AppError;
+
+fn poisoned() -> AppError {
+    AppError::Storage("in-memory repository lock poisoned".to_string())
+}
+
+// Plays the part of the audit_log table. Repositories append to it while still
+// holding the lock on their own data, so readers never see a change without its entry.
+#[derive(Default)]
+pub struct InMemoryAuditLog {
+    entries: RwLock<Vec<AuditEntry>>,
+}
+
+impl InMemoryAuditLog {
+    fn record(&self, record: AuditRecord) -> Result<(), AppError> {
+        let mut entries = self.entries.write().map_err(|_| poisoned())?;
+        let id = entries.len() as i64 + 1;
+        entries.push(AuditEntry {
+            id,
+            entity_type: record.entity_type.to_string(),
+            entity_id: record.entity_id,
+            action: record.action.to_string(),
+            before: record.before,
+            after: record.after,
+            actor: record.actor,
+            created_at: Utc::now().naive_utc(),
+        });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditLog {
+    async fn list_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, AppError> {
+        let matches = |wanted: &Option<String>, value: &str| wanted.as_deref().is_none_or(|w| w == value);
+        let entries = self.entries.read().map_err(|_| poisoned())?;
+        Ok(entries
+            .iter()
+            .rev()
+            .filter(|e| {
+                matches(&filter.entity_type, &e.entity_type)
+                    && matches(&filter.entity_id, &e.entity_id)
+                    && matches(&filter.action, &e.action)
+                    && matches(&filter.actor, &e.actor)
+            })
+            .take(filter.limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Default)]
+struct ItemState {
+    items: Vec<Item>,
+    last_id: i64,
+}
+
+#[derive(Default)]
+pub struct InMemoryItemRepository {
+    state: RwLock<ItemState>,
+    audit: Arc<InMemoryAuditLog>,
+}
+
+impl InMemoryItemRepository {
+    pub fn with_audit_log(audit: Arc<InMemoryAuditLog>) -> Self {
+        Self { state: Default::default(), audit }
+    }
+}
+
+#[async_trait]
+impl ItemRepository for InMemoryItemRepository {
+    async fn list_items(&self) -> Result<Vec<Item>, AppError> {
+        let state = self.state.read().map_err(|_| poisoned())?;
+        let mut items = state.items.clone();
+        // Same order as the SQL implementation; ids break ties within one timestamp.
+        items.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(items)
+    }
+
+    async fn add_item(&self, text: String, actor: &str) -> Result<Item, AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        let item = Item { id: state.last_id + 1, text, created_at: Utc::now().naive_utc() };
+        self.audit.record(AuditRecord::new("item", item.id, "create", actor).after(&item))?;
+        state.last_id = item.id;
+        state.items.push(item.clone());
+        Ok(item)
+    }
+
+    async fn delete_item(&self, id: i64, actor: &str) -> Result<(), AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        let index = state.items.iter().position(|item| item.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Item with id {} not found for deletion", id)))?;
+        self.audit.record(AuditRecord::new("item", id, "delete", actor).before(&state.items[index]))?;
+        state.items.remove(index);
+        Ok(())
+    }
+
+    async fn count_items(&self) -> Result<i64, AppError> {
+        Ok(self.state.read().map_err(|_| poisoned())?.items.len() as i64)
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryGraphRepository {
+    data: RwLock<GraphData>,
+    versions: RwLock<Vec<(GraphVersion, GraphData)>>,
+    audit: Arc<InMemoryAuditLog>,
+}
+
+impl InMemoryGraphRepository {
+    pub fn with_data(data: GraphData) -> Self {
+        Self { data: RwLock::new(sorted(data)), ..Default::default() }
+    }
+
+    pub fn with_audit_log(audit: Arc<InMemoryAuditLog>) -> Self {
+        Self { audit, ..Default::default() }
+    }
+
+    // Shared by replace_graph and revert_to_version, with the data lock held;
+    // `record` gets the sizes filled in.
+    fn write_graph(&self, data: &mut GraphData, new_data: GraphData, record: AuditRecord) -> Result<(), AppError> {
+        let new_data = sorted(new_data);
+        self.audit.record(
+            record
+                .before(&AuditRecord::graph_sizes(data.books.len() as i64, data.technologies.len() as i64, data.links.len() as i64))
+                .after(&AuditRecord::graph_sizes(new_data.books.len() as i64, new_data.technologies.len() as i64, new_data.links.len() as i64)),
+        )?;
+        *data = new_data;
+        Ok(())
+    }
+
+    // Stores `data` as a new version, with the versions lock held.
+    fn push_version(&self, versions: &mut Vec<(GraphVersion, GraphData)>, label: String, data: GraphData, actor: &str) -> Result<GraphVersion, AppError> {
+        let version = GraphVersion {
+            id: versions.len() as i64 + 1,
+            label,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.audit.record(AuditRecord::new("graph_version", version.id, "create", actor).after(&version))?;
+        versions.push((version.clone(), data));
+        Ok(version)
+    }
+}
+
+fn sorted(mut data: GraphData) -> GraphData {
+    data.books.sort_by_key(|b| b.id);
+    data.technologies.sort_by_key(|t| t.id);
+    data.links.sort_by_key(|l| (l.book_id, l.tech_id));
+    data.taxonomy.sort_by_key(|n| n.id);
+    data
+}
+
+#[async_trait]
+impl GraphRepository for InMemoryGraphRepository {
+    async fn load_graph(&self) -> Result<GraphData, AppError> {
+        Ok(self.data.read().map_err(|_| poisoned())?.clone())
+    }
+
+    async fn replace_graph(&self, data: GraphData, actor: &str) -> Result<(), AppError> {
+        let mut current = self.data.write().map_err(|_| poisoned())?;
+        self.write_graph(&mut current, data, AuditRecord::new("graph", "current", "replace", actor))
+    }
+
+    async fn replace_graph_as_version(&self, data: GraphData, label: String, actor: &str) -> Result<GraphVersion, AppError> {
+        let mut current = self.data.write().map_err(|_| poisoned())?;
+        let mut versions = self.versions.write().map_err(|_| poisoned())?;
+        self.write_graph(&mut current, data, AuditRecord::new("graph", "current", "replace", actor))?;
+        self.push_version(&mut versions, label, current.clone(), actor)
+    }
+
+    async fn add_link(&self, link: BookTechLink, actor: &str) -> Result<(), AppError> {
+        let mut data = self.data.write().map_err(|_| poisoned())?;
+        if !data.books.iter().any(|b| b.id == link.book_id) {
+            return Err(AppError::NotFound(format!("Book with id {} not found", link.book_id)));
+        }
+        if !data.technologies.iter().any(|t| t.id == link.tech_id) {
+            return Err(AppError::NotFound(format!("Technology with id {} not found", link.tech_id)));
+        }
+        if data.links.iter().any(|l| same_link(l, &link)) {
+            return Err(AppError::Conflict(format!(
+                "Book {} is already linked to technology {}", link.book_id, link.tech_id
+            )));
+        }
+        self.audit.record(AuditRecord::new("link", AuditRecord::link_id(&link), "create", actor).after(&link))?;
+        data.links.push(link);
+        data.links.sort_by_key(|l| (l.book_id, l.tech_id));
+        Ok(())
+    }
+
+    async fn remove_link(&self, link: BookTechLink, actor: &str) -> Result<(), AppError> {
+        let mut data = self.data.write().map_err(|_| poisoned())?;
+        let Some(index) = data.links.iter().position(|l| same_link(l, &link)) else {
+            return Err(AppError::NotFound(format!(
+                "No link between book {} and technology {}", link.book_id, link.tech_id
+            )));
+        };
+        // The audit entry records the link as it was stored, kind and weight included.
+        self.audit.record(AuditRecord::new("link", AuditRecord::link_id(&link), "delete", actor).before(&data.links[index]))?;
+        data.links.remove(index);
+        Ok(())
+    }
+
+    async fn create_version(&self, label: String, actor: &str) -> Result<GraphVersion, AppError> {
+        // Locks are always taken data first, then versions.
+        let data = self.data.read().map_err(|_| poisoned())?;
+        let mut versions = self.versions.write().map_err(|_| poisoned())?;
+        self.push_version(&mut versions, label, data.clone(), actor)
+    }
+
+    async fn list_versions(&self) -> Result<Vec<GraphVersion>, AppError> {
+        let versions = self.versions.read().map_err(|_| poisoned())?;
+        Ok(versions.iter().rev().map(|(v, _)| v.clone()).collect())
+    }
+
+    async fn load_version(&self, id: i64) -> Result<GraphData, AppError> {
+        let versions = self.versions.read().map_err(|_| poisoned())?;
+        versions
+            .iter()
+            .find(|(v, _)| v.id == id)
+            .map(|(_, data)| data.clone())
+            .ok_or_else(|| AppError::NotFound(format!("Graph version {} not found", id)))
+    }
+
+    async fn revert_to_version(&self, id: i64, backup_label: String, actor: &str) -> Result<GraphVersion, AppError> {
+        let mut data = self.data.write().map_err(|_| poisoned())?;
+        let mut versions = self.versions.write().map_err(|_| poisoned())?;
+        let target = versions
+            .iter()
+            .find(|(v, _)| v.id == id)
+            .map(|(_, data)| data.clone())
+            .ok_or_else(|| AppError::NotFound(format!("Graph version {} not found", id)))?;
+        let backup = self.push_version(&mut versions, backup_label, data.clone(), actor)?;
+        self.write_graph(&mut data, target, AuditRecord::new("graph", "current", "revert", actor))?;
+        Ok(backup)
+    }
+}
+
+// Needs both stores: a fixture's items and graph rows go in together, under
+// both locks, or not at all.
+pub struct InMemoryFixtureRepository {
+    items: Arc<InMemoryItemRepository>,
+    graph: Arc<InMemoryGraphRepository>,
+    audit: Arc<InMemoryAuditLog>,
+}
+
+impl InMemoryFixtureRepository {
+    pub fn new(items: Arc<InMemoryItemRepository>, graph: Arc<InMemoryGraphRepository>, audit: Arc<InMemoryAuditLog>) -> Self {
+        Self { items, graph, audit }
+    }
+}
+
+// Inserts `row` or overwrites the row with the same id; true if anything changed.
+// Links are identified by their book and technology.
+fn same_link(a: &BookTechLink, b: &BookTechLink) -> bool {
+    (a.book_id, a.tech_id) == (b.book_id, b.tech_id)
+}
+
+fn upsert<T: Clone + PartialEq>(rows: &mut Vec<T>, row: &T, same_id: impl Fn(&T) -> bool) -> bool {
+    match rows.iter_mut().find(|existing| same_id(existing)) {
+        Some(existing) if existing == row => false,
+        Some(existing) => {
+            *existing = row.clone();
+            true
+        }
+        None => {
+            rows.push(row.clone());
+            true
+        }
+    }
+}
+
+#[async_trait]
+impl FixtureRepository for InMemoryFixtureRepository {
+    async fn apply_fixture(&self, name: &str, fixture: &Fixture, actor: &str) -> Result<FixtureReport, AppError> {
+        let mut items = self.items.state.write().map_err(|_| poisoned())?;
+        let mut data = self.graph.data.write().map_err(|_| poisoned())?;
+        // Work on a copy so a bad link leaves the stored graph untouched.
+        let mut new_data = data.clone();
+        let mut report = FixtureReport::default();
+
+        for node in &fixture.graph.taxonomy {
+            report.taxonomy += upsert(&mut new_data.taxonomy, node, |n| n.id == node.id) as usize;
+        }
+        for book in &fixture.graph.books {
+            report.books += upsert(&mut new_data.books, book, |b| b.id == book.id) as usize;
+        }
+        for tech in &fixture.graph.technologies {
+            report.technologies += upsert(&mut new_data.technologies, tech, |t| t.id == tech.id) as usize;
+        }
+        for link in &fixture.graph.links {
+            if !new_data.books.iter().any(|b| b.id == link.book_id) {
+                return Err(AppError::NotFound(format!("Book with id {} not found", link.book_id)));
+            }
+            if !new_data.technologies.iter().any(|t| t.id == link.tech_id) {
+                return Err(AppError::NotFound(format!("Technology with id {} not found", link.tech_id)));
+            }
+            report.links += upsert(&mut new_data.links, link, |l| same_link(l, link)) as usize;
+        }
+        let mut new_items: Vec<&String> = Vec::new();
+        for text in &fixture.items {
+            if !items.items.iter().any(|item| &item.text == text) && !new_items.contains(&text) {
+                new_items.push(text);
+            }
+        }
+        report.items = new_items.len();
+
+        if report != FixtureReport::default() {
+            self.audit.record(AuditRecord::new("fixture", name, "apply", actor).after(&report))?;
+        }
+        let now = Utc::now().naive_utc();
+        for text in new_items {
+            items.last_id += 1;
+            let id = items.last_id;
+            items.items.push(Item { id, text: text.clone(), created_at: now });
+        }
+        *data = sorted(new_data);
+        Ok(report)
+    }
+}
+
+#[derive(Default)]
+struct UserState {
+    // (user, password hash)
+    users: Vec<(User, String)>,
+    // token hash -> (user id, expires at)
+    sessions: Vec<(String, i64, NaiveDateTime)>,
+    // (token, token hash)
+    tokens: Vec<(ApiToken, String)>,
+}
+
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    state: RwLock<UserState>,
+    audit: Arc<InMemoryAuditLog>,
+}
+
+impl InMemoryUserRepository {
+    pub fn with_audit_log(audit: Arc<InMemoryAuditLog>) -> Self {
+        Self { state: Default::default(), audit }
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create_user(&self, username: &str, password_hash: &str, role: Role, actor: &str) -> Result<User, AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        if state.users.iter().any(|(u, _)| u.username == username) {
+            return Err(AppError::Conflict(format!("Username {} is already taken", username)));
+        }
+        let user = User {
+            id: state.users.len() as i64 + 1,
+            username: username.to_string(),
+            role,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.audit.record(AuditRecord::new("user", user.id, "create", actor).after(&user))?;
+        state.users.push((user.clone(), password_hash.to_string()));
+        Ok(user)
+    }
+
+    async fn find_credentials(&self, username: &str) -> Result<Option<(User, String)>, AppError> {
+        let state = self.state.read().map_err(|_| poisoned())?;
+        Ok(state.users.iter().find(|(u, _)| u.username == username).cloned())
+    }
+
+    async fn list_users(&self) -> Result<Vec<User>, AppError> {
+        let state = self.state.read().map_err(|_| poisoned())?;
+        let mut users: Vec<User> = state.users.iter().map(|(u, _)| u.clone()).collect();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users)
+    }
+
+    async fn create_session(&self, user_id: i64, token_hash: &str, expires_at: NaiveDateTime) -> Result<(), AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        if !state.users.iter().any(|(u, _)| u.id == user_id) {
+            return Err(AppError::NotFound(format!("User with id {} not found", user_id)));
+        }
+        state.sessions.push((token_hash.to_string(), user_id, expires_at));
+        Ok(())
+    }
+
+    async fn session_user(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<User>, AppError> {
+        let state = self.state.read().map_err(|_| poisoned())?;
+        Ok(state.sessions.iter()
+            .find(|(hash, _, expires_at)| hash == token_hash && *expires_at > now)
+            .and_then(|(_, user_id, _)| state.users.iter().find(|(u, _)| u.id == *user_id))
+            .map(|(u, _)| u.clone()))
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        state.sessions.retain(|(hash, _, _)| hash != token_hash);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApiTokenRepository for InMemoryUserRepository {
+    async fn create_token(&self, user_id: i64, name: &str, token_hash: &str, scope: TokenScope, actor: &str) -> Result<ApiToken, AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        if !state.users.iter().any(|(u, _)| u.id == user_id) {
+            return Err(AppError::NotFound(format!("User with id {} not found", user_id)));
+        }
+        let token = ApiToken {
+            id: state.tokens.len() as i64 + 1,
+            user_id,
+            name: name.to_string(),
+            scope,
+            created_at: Utc::now().naive_utc(),
+            last_used_at: None,
+            revoked_at: None,
+        };
+        self.audit.record(AuditRecord::new("api_token", token.id, "create", actor).after(&token))?;
+        state.tokens.push((token.clone(), token_hash.to_string()));
+        Ok(token)
+    }
+
+    async fn list_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>, AppError> {
+        let state = self.state.read().map_err(|_| poisoned())?;
+        Ok(state.tokens.iter().rev().filter(|(t, _)| t.user_id == user_id).map(|(t, _)| t.clone()).collect())
+    }
+
+    async fn revoke_token(&self, user_id: i64, token_id: i64, actor: &str) -> Result<ApiToken, AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        let (token, _) = state.tokens.iter_mut()
+            .find(|(t, _)| t.id == token_id && t.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound(format!("API token with id {} not found", token_id)))?;
+        if token.revoked_at.is_some() {
+            return Err(AppError::Conflict(format!("API token {} is already revoked", token_id)));
+        }
+        let before = token.clone();
+        token.revoked_at = Some(Utc::now().naive_utc());
+        self.audit.record(AuditRecord::new("api_token", token_id, "revoke", actor).before(&before).after(&*token))?;
+        Ok(token.clone())
+    }
+
+    async fn authenticate_token(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<(User, ApiToken)>, AppError> {
+        let mut state = self.state.write().map_err(|_| poisoned())?;
+        let Some((token, _)) = state.tokens.iter_mut().find(|(t, hash)| hash == token_hash && t.revoked_at.is_none()) else {
+            return Ok(None);
+        };
+        token.last_used_at = Some(now);
+        let token = token.clone();
+        Ok(state.users.iter().find(|(u, _)| u.id == token.user_id).map(|(u, _)| (u.clone(), token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::conformance;
+    use shared::{Book, Technology};
+
+    fn tiny_graph() -> GraphData {
+        GraphData {
+            books: vec![Book { id: 1, title: "Excession".into(), author: "Iain M. Banks".into(), series: "Culture Series".into(), year: Some(1996) }],
+            technologies: vec![Technology {
+                id: 101,
+                name: "Sentient AI (Minds)".into(),
+                category: "COMPUTING & INFORMATION".into(),
+                subcategory: "Artificial Intelligence".into(),
+                description: String::new(),
+                taxonomy_id: None,
+            }],
+            links: vec![],
+            taxonomy: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_item_repository_contract() {
+        conformance::item_repository_contract(&InMemoryItemRepository::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_graph_repository_contract() {
+        conformance::graph_repository_contract(&InMemoryGraphRepository::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_graph_versioning_contract() {
+        conformance::graph_versioning_contract(&InMemoryGraphRepository::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_fixture_contract() {
+        conformance::fixture_contract(&crate::repository::Repositories::in_memory()).await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_audit_contract() {
+        let repos = crate::repository::Repositories::in_memory();
+        conformance::audit_contract(&repos).await;
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_user_repository_contract() {
+        let repo = InMemoryUserRepository::default();
+        conformance::user_repository_contract(&repo).await;
+        conformance::api_token_contract(&repo, &repo).await;
+    }
+
+    #[tokio::test]
+    async fn test_graph_repository_link_errors() {
+        let repo = InMemoryGraphRepository::with_data(tiny_graph());
+        let link = BookTechLink::new(1, 101);
+
+        repo.add_link(link.clone(), "test").await.unwrap();
+        assert!(matches!(repo.add_link(link.clone(), "test").await, Err(AppError::Conflict(_))));
+        assert!(matches!(
+            repo.add_link(BookTechLink::new(99, 101), "test").await,
+            Err(AppError::NotFound(_))
+        ));
+
+        repo.remove_link(link.clone(), "test").await.unwrap();
+        assert!(matches!(repo.remove_link(link, "test").await, Err(AppError::NotFound(_))));
+    }
+}
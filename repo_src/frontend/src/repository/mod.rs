@@ -0,0 +1,252 @@
+// Storage abstraction for items and the tech graph.
+//
+// Server functions talk to these traits through the `Repositories` handle in
+// Leptos context instead of issuing SQL themselves. `SqliteItemRepository` /
+// `SqliteGraphRepository` back the real server; the in-memory versions need no
+// database at all, so they also compile for the WASM client and are what unit
+// tests and demos use. PostgreSQL support (`postgres.rs`, "postgres" feature)
+// is just another pair of implementations; new backends slot in the same way.
+//
+// Every mutating method takes the acting user's name and records an
+// `AuditRecord` atomically with the change (same SQL transaction, or under the
+// same lock in memory). `AuditRepository` reads the resulting log.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use chrono::NaiveDateTime;
+use shared::{ApiToken, AuditEntry, BookTechLink, GraphData, GraphVersion, Item, Role, TokenScope, User};
+use std::sync::Arc;
+
+use crate::errors::AppError;
+
+pub mod memory;
+#[cfg(feature = "ssr")]
+pub mod sqlite;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(test)]
+mod conformance;
+
+pub use memory::{InMemoryAuditLog, InMemoryFixtureRepository, InMemoryGraphRepository, InMemoryItemRepository, InMemoryUserRepository};
+#[cfg(feature = "ssr")]
+pub use sqlite::{SqliteAuditRepository, SqliteFixtureRepository, SqliteGraphRepository, SqliteItemRepository, SqliteUserRepository};
+#[cfg(feature = "postgres")]
+pub use postgres::{PgAuditRepository, PgFixtureRepository, PgGraphRepository, PgItemRepository, PgUserRepository};
+
+#[async_trait]
+pub trait ItemRepository: Send + Sync {
+    // Newest first.
+    async fn list_items(&self) -> Result<Vec<Item>, AppError>;
+    async fn add_item(&self, text: String, actor: &str) -> Result<Item, AppError>;
+    // `AppError::NotFound` if there is no item with this id.
+    async fn delete_item(&self, id: i64, actor: &str) -> Result<(), AppError>;
+    async fn count_items(&self) -> Result<i64, AppError>;
+}
+
+#[async_trait]
+pub trait GraphRepository: Send + Sync {
+    // Books, technologies and links, each sorted by id.
+    async fn load_graph(&self) -> Result<GraphData, AppError>;
+    // Atomically swaps the whole dataset (used for seeding and imports).
+    async fn replace_graph(&self, data: GraphData, actor: &str) -> Result<(), AppError>;
+    // `replace_graph` that also stores the new dataset as a version labelled
+    // `label`, in the same transaction.
+    async fn replace_graph_as_version(&self, data: GraphData, label: String, actor: &str) -> Result<GraphVersion, AppError>;
+    // `AppError::NotFound` for unknown book/technology ids, `AppError::Conflict` for duplicates.
+    async fn add_link(&self, link: BookTechLink, actor: &str) -> Result<(), AppError>;
+    // `AppError::NotFound` if the link does not exist.
+    async fn remove_link(&self, link: BookTechLink, actor: &str) -> Result<(), AppError>;
+
+    // Stores an immutable copy of the current dataset under a new version id.
+    async fn create_version(&self, label: String, actor: &str) -> Result<GraphVersion, AppError>;
+    // Newest first.
+    async fn list_versions(&self) -> Result<Vec<GraphVersion>, AppError>;
+    // The dataset as it was when the version was created. `AppError::NotFound` for unknown ids.
+    async fn load_version(&self, id: i64) -> Result<GraphData, AppError>;
+    // Stores the current dataset as a new version labelled `backup_label`, then
+    // makes version `id` the current dataset, in one transaction; returns the
+    // backup. Versions themselves are untouched, so reverting never loses
+    // history. `AppError::NotFound` for unknown ids, and then nothing changes.
+    async fn revert_to_version(&self, id: i64, backup_label: String, actor: &str) -> Result<GraphVersion, AppError>;
+}
+
+// Accounts and sessions. Hashing happens in `crate::auth`; this layer only
+// stores the resulting strings.
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    // `AppError::Conflict` if the username is taken.
+    async fn create_user(&self, username: &str, password_hash: &str, role: Role, actor: &str) -> Result<User, AppError>;
+    // The user and their password hash, for verifying a login.
+    async fn find_credentials(&self, username: &str) -> Result<Option<(User, String)>, AppError>;
+    // Ordered by username.
+    async fn list_users(&self) -> Result<Vec<User>, AppError>;
+    async fn create_session(&self, user_id: i64, token_hash: &str, expires_at: NaiveDateTime) -> Result<(), AppError>;
+    // The session's user, unless the session is unknown or expired at `now`.
+    async fn session_user(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<User>, AppError>;
+    // Deleting an unknown session is not an error (logging out twice is fine).
+    async fn delete_session(&self, token_hash: &str) -> Result<(), AppError>;
+}
+
+// Personal API tokens. As with sessions only the token's hash is stored.
+// Implemented by the user repositories, since tokens resolve to users.
+#[async_trait]
+pub trait ApiTokenRepository: Send + Sync {
+    async fn create_token(&self, user_id: i64, name: &str, token_hash: &str, scope: TokenScope, actor: &str) -> Result<ApiToken, AppError>;
+    // The user's tokens (revoked ones included), newest first.
+    async fn list_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>, AppError>;
+    // `AppError::NotFound` unless the token belongs to `user_id`;
+    // `AppError::Conflict` if it is already revoked.
+    async fn revoke_token(&self, user_id: i64, token_id: i64, actor: &str) -> Result<ApiToken, AppError>;
+    // The owner and token for an unrevoked token hash. Records `now` as the
+    // token's last use.
+    async fn authenticate_token(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<(User, ApiToken)>, AppError>;
+}
+
+// Seed data resolved from a fixture file (see `crate::fixtures`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fixture {
+    pub items: Vec<String>,
+    pub graph: GraphData,
+}
+
+// Rows a fixture inserted or changed; all zero when it was already applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FixtureReport {
+    pub items: usize,
+    pub books: usize,
+    pub technologies: usize,
+    pub links: usize,
+    pub taxonomy: usize,
+}
+
+impl FixtureReport {
+    pub fn graph_changed(&self) -> bool {
+        self.books + self.technologies + self.links + self.taxonomy > 0
+    }
+}
+
+// Applies a fixture in one transaction, keyed by natural key: items by text,
+// books and technologies by id (existing rows are updated to match), links by
+// their pair. Applying the same fixture twice changes nothing the second time.
+// Links must point at rows that exist once the fixture's own rows are in,
+// otherwise nothing is written (`AppError::NotFound`).
+#[async_trait]
+pub trait FixtureRepository: Send + Sync {
+    async fn apply_fixture(&self, name: &str, fixture: &Fixture, actor: &str) -> Result<FixtureReport, AppError>;
+}
+
+// Which audit entries to return; `None` fields match everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub action: Option<String>,
+    pub actor: Option<String>,
+    pub limit: i64,
+}
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    // Newest first, at most `filter.limit` entries.
+    async fn list_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, AppError>;
+}
+
+// An audit row about to be written alongside a mutation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub entity_type: &'static str,
+    pub entity_id: String,
+    pub action: &'static str,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub actor: String,
+}
+
+impl AuditRecord {
+    pub fn new(entity_type: &'static str, entity_id: impl ToString, action: &'static str, actor: &str) -> Self {
+        Self {
+            entity_type,
+            entity_id: entity_id.to_string(),
+            action,
+            before: None,
+            after: None,
+            actor: actor.to_string(),
+        }
+    }
+
+    pub fn before(mut self, value: &impl Serialize) -> Self {
+        self.before = serde_json::to_string(value).ok();
+        self
+    }
+
+    pub fn after(mut self, value: &impl Serialize) -> Self {
+        self.after = serde_json::to_string(value).ok();
+        self
+    }
+
+    // Whole-graph mutations log sizes rather than the full dataset; the
+    // dataset itself is what graph versions are for.
+    pub fn graph_sizes(books: i64, technologies: i64, links: i64) -> serde_json::Value {
+        serde_json::json!({ "books": books, "technologies": technologies, "links": links })
+    }
+
+    // Matches the `entity_id` used for links in the audit log.
+    pub fn link_id(link: &BookTechLink) -> String {
+        format!("{}:{}", link.book_id, link.tech_id)
+    }
+}
+
+// The set of repositories handed to server functions via `provide_context`.
+#[derive(Clone)]
+pub struct Repositories {
+    pub items: Arc<dyn ItemRepository>,
+    pub graph: Arc<dyn GraphRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub tokens: Arc<dyn ApiTokenRepository>,
+    pub fixtures: Arc<dyn FixtureRepository>,
+}
+
+impl Repositories {
+    pub fn in_memory() -> Self {
+        // One log shared by all repositories, like the audit_log table.
+        let log = Arc::new(InMemoryAuditLog::default());
+        // Tokens resolve to users, so both traits are served by one store.
+        let users = Arc::new(InMemoryUserRepository::with_audit_log(log.clone()));
+        // Fixtures write items and graph rows under both stores' locks.
+        let items = Arc::new(InMemoryItemRepository::with_audit_log(log.clone()));
+        let graph = Arc::new(InMemoryGraphRepository::with_audit_log(log.clone()));
+        Self {
+            fixtures: Arc::new(InMemoryFixtureRepository::new(items.clone(), graph.clone(), log.clone())),
+            items,
+            graph,
+            users: users.clone(),
+            tokens: users,
+            audit: log,
+        }
+    }
+
+    #[cfg(feature = "ssr")]
+    pub fn sqlite(pool: sqlx::SqlitePool) -> Self {
+        Self {
+            items: Arc::new(SqliteItemRepository::new(pool.clone())),
+            graph: Arc::new(SqliteGraphRepository::new(pool.clone())),
+            audit: Arc::new(SqliteAuditRepository::new(pool.clone())),
+            users: Arc::new(SqliteUserRepository::new(pool.clone())),
+            tokens: Arc::new(SqliteUserRepository::new(pool.clone())),
+            fixtures: Arc::new(SqliteFixtureRepository::new(pool)),
+        }
+    }
+
+    #[cfg(feature = "postgres")]
+    pub fn postgres(pool: sqlx::PgPool) -> Self {
+        Self {
+            items: Arc::new(PgItemRepository::new(pool.clone())),
+            graph: Arc::new(PgGraphRepository::new(pool.clone())),
+            audit: Arc::new(PgAuditRepository::new(pool.clone())),
+            users: Arc::new(PgUserRepository::new(pool.clone())),
+            tokens: Arc::new(PgUserRepository::new(pool.clone())),
+            fixtures: Arc::new(PgFixtureRepository::new(pool)),
+        }
+    }
+}
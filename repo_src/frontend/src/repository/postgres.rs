@@ -0,0 +1,811 @@
+// PostgreSQL-backed repositories. Only compiled with the "postgres" feature.
+// Behavior mirrors `sqlite.rs` exactly; only the SQL dialect differs.
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use shared::{
+    ApiToken, AuditEntry, Book, BookTechLink, GraphData, GraphVersion, Item, LinkKind, Role, TaxonomyNode, Technology, TokenScope, User,
+};
+use sqlx::{PgConnection, PgPool, QueryBuilder, Row};
+
+use super::{
+    ApiTokenRepository, AuditFilter, AuditRecord, AuditRepository, Fixture, FixtureReport, FixtureRepository, GraphRepository,
+    ItemRepository, UserRepository,
+};
+use crate::errors::AppError;
+use crate::taxonomy::parents_first;
+
+async fn insert_audit(conn: &mut PgConnection, record: AuditRecord) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO audit_log (entity_type, entity_id, action, before_json, after_json, actor, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+        .bind(record.entity_type)
+        .bind(record.entity_id)
+        .bind(record.action)
+        .bind(record.before)
+        .bind(record.after)
+        .bind(record.actor)
+        .bind(Utc::now().naive_utc())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn graph_sizes(conn: &mut PgConnection) -> Result<serde_json::Value, AppError> {
+    let (books, technologies, links): (i64, i64, i64) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM books), (SELECT COUNT(*) FROM technologies), (SELECT COUNT(*) FROM book_tech_links)",
+    )
+        .fetch_one(conn)
+        .await?;
+    Ok(AuditRecord::graph_sizes(books, technologies, links))
+}
+
+#[derive(Clone)]
+pub struct PgItemRepository {
+    pool: PgPool,
+}
+
+impl PgItemRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ItemRepository for PgItemRepository {
+    async fn list_items(&self) -> Result<Vec<Item>, AppError> {
+        let rows = sqlx::query("SELECT id, text, created_at FROM items ORDER BY created_at DESC, id DESC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| Item {
+            id: row.get("id"),
+            text: row.get("text"),
+            created_at: row.get("created_at"),
+        }).collect())
+    }
+
+    async fn add_item(&self, text: String, actor: &str) -> Result<Item, AppError> {
+        let mut tx = self.pool.begin().await?;
+        // TIMESTAMP keeps microseconds, so return what was stored rather than the input value.
+        let row = sqlx::query("INSERT INTO items (text, created_at) VALUES ($1, $2) RETURNING id, created_at")
+            .bind(&text)
+            .bind(Utc::now().naive_utc())
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let created_at: NaiveDateTime = row.get("created_at");
+        let item = Item { id: row.get("id"), text, created_at };
+        insert_audit(&mut tx, AuditRecord::new("item", item.id, "create", actor).after(&item)).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn delete_item(&self, id: i64, actor: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query("DELETE FROM items WHERE id = $1 RETURNING id, text, created_at")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Item with id {} not found for deletion", id)))?;
+
+        let deleted = Item { id: row.get("id"), text: row.get("text"), created_at: row.get("created_at") };
+        insert_audit(&mut tx, AuditRecord::new("item", id, "delete", actor).before(&deleted)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn count_items(&self) -> Result<i64, AppError> {
+        Ok(sqlx::query_scalar("SELECT COUNT(*) FROM items").fetch_one(&self.pool).await?)
+    }
+}
+
+#[derive(Clone)]
+pub struct PgGraphRepository {
+    pool: PgPool,
+}
+
+impl PgGraphRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+// Swaps the whole dataset inside the caller's transaction. Shared by
+// replace_graph and revert_to_version; `record` gets the sizes filled in.
+async fn write_graph(conn: &mut PgConnection, data: GraphData, record: AuditRecord) -> Result<(), AppError> {
+    let before = graph_sizes(conn).await?;
+
+    sqlx::query("DELETE FROM book_tech_links").execute(&mut *conn).await?;
+    sqlx::query("DELETE FROM books").execute(&mut *conn).await?;
+    sqlx::query("DELETE FROM technologies").execute(&mut *conn).await?;
+    sqlx::query("DELETE FROM taxonomy_nodes").execute(&mut *conn).await?;
+
+    for node in parents_first(&data.taxonomy) {
+        sqlx::query("INSERT INTO taxonomy_nodes (id, parent_id, name) VALUES ($1, $2, $3)")
+            .bind(node.id)
+            .bind(node.parent_id)
+            .bind(&node.name)
+            .execute(&mut *conn)
+            .await?;
+    }
+    for book in &data.books {
+        sqlx::query("INSERT INTO books (id, title, author, series, year) VALUES ($1, $2, $3, $4, $5)")
+            .bind(book.id)
+            .bind(&book.title)
+            .bind(&book.author)
+            .bind(&book.series)
+            .bind(book.year)
+            .execute(&mut *conn)
+            .await?;
+    }
+    for tech in &data.technologies {
+        sqlx::query(
+            "INSERT INTO technologies (id, name, category, subcategory, description, taxonomy_id) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+            .bind(tech.id)
+            .bind(&tech.name)
+            .bind(&tech.category)
+            .bind(&tech.subcategory)
+            .bind(&tech.description)
+            .bind(tech.taxonomy_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    for link in &data.links {
+        sqlx::query("INSERT INTO book_tech_links (book_id, tech_id, kind, weight, citation) VALUES ($1, $2, $3, $4, $5)")
+            .bind(link.book_id)
+            .bind(link.tech_id)
+            .bind(link.kind.as_str())
+            .bind(link.weight)
+            .bind(&link.citation)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    let after = graph_sizes(conn).await?;
+    insert_audit(conn, record.before(&before).after(&after)).await?;
+    Ok(())
+}
+
+// The current dataset, read through `conn` so a transaction sees its own state.
+async fn read_graph(conn: &mut PgConnection) -> Result<GraphData, AppError> {
+    let books = sqlx::query("SELECT id, title, author, series, year FROM books ORDER BY id")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|row| Book {
+            id: row.get("id"),
+            title: row.get("title"),
+            author: row.get("author"),
+            series: row.get("series"),
+            year: row.get("year"),
+        })
+        .collect();
+
+    let technologies = sqlx::query(
+        "SELECT id, name, category, subcategory, description, taxonomy_id FROM technologies ORDER BY id",
+    )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|row| Technology {
+            id: row.get("id"),
+            name: row.get("name"),
+            category: row.get("category"),
+            subcategory: row.get("subcategory"),
+            description: row.get("description"),
+            taxonomy_id: row.get("taxonomy_id"),
+        })
+        .collect();
+
+    let links = sqlx::query(
+        "SELECT book_id, tech_id, kind, weight, citation FROM book_tech_links ORDER BY book_id, tech_id",
+    )
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(link_from_row)
+        .collect::<Result<_, _>>()?;
+
+    let taxonomy = sqlx::query("SELECT id, parent_id, name FROM taxonomy_nodes ORDER BY id")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|row| TaxonomyNode { id: row.get("id"), parent_id: row.get("parent_id"), name: row.get("name") })
+        .collect();
+
+    Ok(GraphData { books, technologies, links, taxonomy })
+}
+
+// Holds off other writers to the graph tables until the transaction ends, so
+// what it reads is still current when it writes. Readers are not blocked.
+async fn lock_graph(conn: &mut PgConnection) -> Result<(), AppError> {
+    sqlx::query("LOCK TABLE taxonomy_nodes, books, technologies, book_tech_links IN SHARE ROW EXCLUSIVE MODE")
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+// Stores `data` as a new version inside the caller's transaction.
+async fn insert_version(conn: &mut PgConnection, label: String, data: &GraphData, actor: &str) -> Result<GraphVersion, AppError> {
+    let json = serde_json::to_string(data)
+        .map_err(|e| AppError::Storage(format!("failed to serialize graph snapshot: {}", e)))?;
+    let row = sqlx::query(
+        "INSERT INTO graph_versions (label, created_at, data) VALUES ($1, $2, $3) RETURNING id, created_at",
+    )
+        .bind(&label)
+        .bind(Utc::now().naive_utc())
+        .bind(&json)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    let version = GraphVersion { id: row.get("id"), label, created_at: row.get("created_at") };
+    insert_audit(conn, AuditRecord::new("graph_version", version.id, "create", actor).after(&version)).await?;
+    Ok(version)
+}
+
+async fn read_version(conn: &mut PgConnection, id: i64) -> Result<GraphData, AppError> {
+    let json: Option<String> = sqlx::query_scalar("SELECT data FROM graph_versions WHERE id = $1")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    let json = json.ok_or_else(|| AppError::NotFound(format!("Graph version {} not found", id)))?;
+    serde_json::from_str(&json)
+        .map_err(|e| AppError::Storage(format!("graph version {} is unreadable: {}", id, e)))
+}
+
+#[async_trait]
+impl GraphRepository for PgGraphRepository {
+    async fn load_graph(&self) -> Result<GraphData, AppError> {
+        read_graph(&mut *self.pool.acquire().await?).await
+    }
+
+    async fn replace_graph(&self, data: GraphData, actor: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        write_graph(&mut tx, data, AuditRecord::new("graph", "current", "replace", actor)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn replace_graph_as_version(&self, data: GraphData, label: String, actor: &str) -> Result<GraphVersion, AppError> {
+        let mut tx = self.pool.begin().await?;
+        write_graph(&mut tx, data, AuditRecord::new("graph", "current", "replace", actor)).await?;
+        // Read back, so the version is in the same order as any other snapshot.
+        let stored = read_graph(&mut tx).await?;
+        let version = insert_version(&mut tx, label, &stored, actor).await?;
+        tx.commit().await?;
+        Ok(version)
+    }
+
+    async fn add_link(&self, link: BookTechLink, actor: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let book_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM books WHERE id = $1)")
+            .bind(link.book_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !book_exists {
+            return Err(AppError::NotFound(format!("Book with id {} not found", link.book_id)));
+        }
+        let tech_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM technologies WHERE id = $1)")
+            .bind(link.tech_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !tech_exists {
+            return Err(AppError::NotFound(format!("Technology with id {} not found", link.tech_id)));
+        }
+
+        // The composite primary key turns duplicates into a unique violation -> AppError::Conflict.
+        sqlx::query("INSERT INTO book_tech_links (book_id, tech_id, kind, weight, citation) VALUES ($1, $2, $3, $4, $5)")
+            .bind(link.book_id)
+            .bind(link.tech_id)
+            .bind(link.kind.as_str())
+            .bind(link.weight)
+            .bind(&link.citation)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditRecord::new("link", AuditRecord::link_id(&link), "create", actor).after(&link)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn remove_link(&self, link: BookTechLink, actor: &str) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        // The audit entry records the link as it was stored, kind and weight included.
+        let removed = sqlx::query(
+            "DELETE FROM book_tech_links WHERE book_id = $1 AND tech_id = $2 RETURNING book_id, tech_id, kind, weight, citation",
+        )
+            .bind(link.book_id)
+            .bind(link.tech_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let Some(row) = removed else {
+            return Err(AppError::NotFound(format!(
+                "No link between book {} and technology {}", link.book_id, link.tech_id
+            )));
+        };
+        let removed = link_from_row(&row)?;
+        insert_audit(&mut tx, AuditRecord::new("link", AuditRecord::link_id(&removed), "delete", actor).before(&removed)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn create_version(&self, label: String, actor: &str) -> Result<GraphVersion, AppError> {
+        let mut tx = self.pool.begin().await?;
+        lock_graph(&mut tx).await?;
+        let data = read_graph(&mut tx).await?;
+        let version = insert_version(&mut tx, label, &data, actor).await?;
+        tx.commit().await?;
+        Ok(version)
+    }
+
+    async fn list_versions(&self) -> Result<Vec<GraphVersion>, AppError> {
+        let rows = sqlx::query("SELECT id, label, created_at FROM graph_versions ORDER BY id DESC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| GraphVersion {
+            id: row.get("id"),
+            label: row.get("label"),
+            created_at: row.get("created_at"),
+        }).collect())
+    }
+
+    async fn load_version(&self, id: i64) -> Result<GraphData, AppError> {
+        read_version(&mut *self.pool.acquire().await?, id).await
+    }
+
+    async fn revert_to_version(&self, id: i64, backup_label: String, actor: &str) -> Result<GraphVersion, AppError> {
+        let mut tx = self.pool.begin().await?;
+        lock_graph(&mut tx).await?;
+        let target = read_version(&mut tx, id).await?;
+        let current = read_graph(&mut tx).await?;
+        let backup = insert_version(&mut tx, backup_label, &current, actor).await?;
+        write_graph(&mut tx, target, AuditRecord::new("graph", "current", "revert", actor)).await?;
+        tx.commit().await?;
+        Ok(backup)
+    }
+}
+
+#[derive(Clone)]
+pub struct PgAuditRepository {
+    pool: PgPool,
+}
+
+impl PgAuditRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PgAuditRepository {
+    async fn list_entries(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, AppError> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, entity_type, entity_id, action, before_json, after_json, actor, created_at FROM audit_log WHERE 1 = 1",
+        );
+        for (column, value) in [
+            ("entity_type", &filter.entity_type),
+            ("entity_id", &filter.entity_id),
+            ("action", &filter.action),
+            ("actor", &filter.actor),
+        ] {
+            if let Some(value) = value {
+                query.push(format!(" AND {} = ", column)).push_bind(value.clone());
+            }
+        }
+        query.push(" ORDER BY id DESC LIMIT ").push_bind(filter.limit);
+
+        let rows = query.build().fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|row| AuditEntry {
+            id: row.get("id"),
+            entity_type: row.get("entity_type"),
+            entity_id: row.get("entity_id"),
+            action: row.get("action"),
+            before: row.get("before_json"),
+            after: row.get("after_json"),
+            actor: row.get("actor"),
+            created_at: row.get("created_at"),
+        }).collect())
+    }
+}
+
+#[derive(Clone)]
+pub struct PgFixtureRepository {
+    pool: PgPool,
+}
+
+impl PgFixtureRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FixtureRepository for PgFixtureRepository {
+    async fn apply_fixture(&self, name: &str, fixture: &Fixture, actor: &str) -> Result<FixtureReport, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut report = FixtureReport::default();
+
+        // Upserts only touch rows whose values differ, so rows_affected counts real changes.
+        for node in parents_first(&fixture.graph.taxonomy) {
+            report.taxonomy += sqlx::query(
+                "INSERT INTO taxonomy_nodes (id, parent_id, name) VALUES ($1, $2, $3) \
+                 ON CONFLICT (id) DO UPDATE SET parent_id = EXCLUDED.parent_id, name = EXCLUDED.name \
+                 WHERE taxonomy_nodes.parent_id IS DISTINCT FROM EXCLUDED.parent_id OR taxonomy_nodes.name IS DISTINCT FROM EXCLUDED.name",
+            )
+                .bind(node.id)
+                .bind(node.parent_id)
+                .bind(&node.name)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+        }
+        for book in &fixture.graph.books {
+            report.books += sqlx::query(
+                "INSERT INTO books (id, title, author, series, year) VALUES ($1, $2, $3, $4, $5) \
+                 ON CONFLICT (id) DO UPDATE SET title = EXCLUDED.title, author = EXCLUDED.author, series = EXCLUDED.series, year = EXCLUDED.year \
+                 WHERE books.title IS DISTINCT FROM EXCLUDED.title OR books.author IS DISTINCT FROM EXCLUDED.author OR books.series IS DISTINCT FROM EXCLUDED.series \
+                 OR books.year IS DISTINCT FROM EXCLUDED.year",
+            )
+                .bind(book.id)
+                .bind(&book.title)
+                .bind(&book.author)
+                .bind(&book.series)
+                .bind(book.year)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+        }
+        for tech in &fixture.graph.technologies {
+            report.technologies += sqlx::query(
+                "INSERT INTO technologies (id, name, category, subcategory, description, taxonomy_id) VALUES ($1, $2, $3, $4, $5, $6) \
+                 ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, category = EXCLUDED.category, \
+                 subcategory = EXCLUDED.subcategory, description = EXCLUDED.description, taxonomy_id = EXCLUDED.taxonomy_id \
+                 WHERE technologies.name IS DISTINCT FROM EXCLUDED.name OR technologies.category IS DISTINCT FROM EXCLUDED.category \
+                 OR technologies.subcategory IS DISTINCT FROM EXCLUDED.subcategory OR technologies.description IS DISTINCT FROM EXCLUDED.description \
+                 OR technologies.taxonomy_id IS DISTINCT FROM EXCLUDED.taxonomy_id",
+            )
+                .bind(tech.id)
+                .bind(&tech.name)
+                .bind(&tech.category)
+                .bind(&tech.subcategory)
+                .bind(&tech.description)
+                .bind(tech.taxonomy_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+        }
+        for link in &fixture.graph.links {
+            let (book_exists, tech_exists): (bool, bool) = sqlx::query_as(
+                "SELECT EXISTS(SELECT 1 FROM books WHERE id = $1), EXISTS(SELECT 1 FROM technologies WHERE id = $2)",
+            )
+                .bind(link.book_id)
+                .bind(link.tech_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if !book_exists {
+                return Err(AppError::NotFound(format!("Book with id {} not found", link.book_id)));
+            }
+            if !tech_exists {
+                return Err(AppError::NotFound(format!("Technology with id {} not found", link.tech_id)));
+            }
+            report.links += sqlx::query(
+                "INSERT INTO book_tech_links (book_id, tech_id, kind, weight, citation) VALUES ($1, $2, $3, $4, $5) \
+                 ON CONFLICT (book_id, tech_id) DO UPDATE SET kind = EXCLUDED.kind, weight = EXCLUDED.weight, citation = EXCLUDED.citation \
+                 WHERE book_tech_links.kind IS DISTINCT FROM EXCLUDED.kind OR book_tech_links.weight IS DISTINCT FROM EXCLUDED.weight \
+                 OR book_tech_links.citation IS DISTINCT FROM EXCLUDED.citation",
+            )
+                .bind(link.book_id)
+                .bind(link.tech_id)
+                .bind(link.kind.as_str())
+                .bind(link.weight)
+                .bind(&link.citation)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+        }
+        let now = Utc::now().naive_utc();
+        for text in &fixture.items {
+            report.items += sqlx::query(
+                "INSERT INTO items (text, created_at) SELECT $1, $2 WHERE NOT EXISTS (SELECT 1 FROM items WHERE text = $1)",
+            )
+                .bind(text)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as usize;
+        }
+
+        if report != FixtureReport::default() {
+            insert_audit(&mut tx, AuditRecord::new("fixture", name, "apply", actor).after(&report)).await?;
+        }
+        tx.commit().await?;
+        Ok(report)
+    }
+}
+
+// Kinds are stored as their snake_case names; the CHECK constraint keeps them valid.
+fn link_from_row(row: &sqlx::postgres::PgRow) -> Result<BookTechLink, AppError> {
+    let kind: String = row.get("kind");
+    Ok(BookTechLink {
+        book_id: row.get("book_id"),
+        tech_id: row.get("tech_id"),
+        kind: LinkKind::parse(&kind).ok_or_else(|| AppError::Storage(format!("unknown kind in book_tech_links table: {}", kind)))?,
+        weight: row.get("weight"),
+        citation: row.get("citation"),
+    })
+}
+
+fn user_from_row(row: &sqlx::postgres::PgRow) -> Result<User, AppError> {
+    let role: String = row.get("role");
+    Ok(User {
+        id: row.get("id"),
+        username: row.get("username"),
+        role: Role::parse(&role).ok_or_else(|| AppError::Storage(format!("unknown role in users table: {}", role)))?,
+        created_at: row.get("created_at"),
+    })
+}
+
+#[derive(Clone)]
+pub struct PgUserRepository {
+    pool: PgPool,
+}
+
+impl PgUserRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for PgUserRepository {
+    async fn create_user(&self, username: &str, password_hash: &str, role: Role, actor: &str) -> Result<User, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            "INSERT INTO users (username, password_hash, role, created_at) VALUES ($1, $2, $3, $4) RETURNING id, created_at",
+        )
+            .bind(username)
+            .bind(password_hash)
+            .bind(role.as_str())
+            .bind(Utc::now().naive_utc())
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let user = User { id: row.get("id"), username: username.to_string(), role, created_at: row.get("created_at") };
+        insert_audit(&mut tx, AuditRecord::new("user", user.id, "create", actor).after(&user)).await?;
+        tx.commit().await?;
+        Ok(user)
+    }
+
+    async fn find_credentials(&self, username: &str) -> Result<Option<(User, String)>, AppError> {
+        let row = sqlx::query("SELECT id, username, role, created_at, password_hash FROM users WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| Ok((user_from_row(&row)?, row.get("password_hash")))).transpose()
+    }
+
+    async fn list_users(&self) -> Result<Vec<User>, AppError> {
+        sqlx::query("SELECT id, username, role, created_at FROM users ORDER BY username")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(user_from_row)
+            .collect()
+    }
+
+    async fn create_session(&self, user_id: i64, token_hash: &str, expires_at: NaiveDateTime) -> Result<(), AppError> {
+        let user_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !user_exists {
+            return Err(AppError::NotFound(format!("User with id {} not found", user_id)));
+        }
+        sqlx::query("INSERT INTO sessions (token_hash, user_id, created_at, expires_at) VALUES ($1, $2, $3, $4)")
+            .bind(token_hash)
+            .bind(user_id)
+            .bind(Utc::now().naive_utc())
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn session_user(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<User>, AppError> {
+        let row = sqlx::query(
+            "SELECT u.id, u.username, u.role, u.created_at FROM sessions s JOIN users u ON u.id = s.user_id WHERE s.token_hash = $1 AND s.expires_at > $2",
+        )
+            .bind(token_hash)
+            .bind(now)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(user_from_row).transpose()
+    }
+
+    async fn delete_session(&self, token_hash: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM sessions WHERE token_hash = $1")
+            .bind(token_hash)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+const API_TOKEN_COLUMNS: &str = "id, user_id, name, scope, created_at, last_used_at, revoked_at";
+
+fn api_token_from_row(row: &sqlx::postgres::PgRow) -> Result<ApiToken, AppError> {
+    let scope: String = row.get("scope");
+    Ok(ApiToken {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        name: row.get("name"),
+        scope: TokenScope::parse(&scope).ok_or_else(|| AppError::Storage(format!("unknown scope in api_tokens table: {}", scope)))?,
+        created_at: row.get("created_at"),
+        last_used_at: row.get("last_used_at"),
+        revoked_at: row.get("revoked_at"),
+    })
+}
+
+#[async_trait]
+impl ApiTokenRepository for PgUserRepository {
+    async fn create_token(&self, user_id: i64, name: &str, token_hash: &str, scope: TokenScope, actor: &str) -> Result<ApiToken, AppError> {
+        let user_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !user_exists {
+            return Err(AppError::NotFound(format!("User with id {} not found", user_id)));
+        }
+
+        let created_at = Utc::now().naive_utc();
+        let mut tx = self.pool.begin().await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO api_tokens (user_id, name, token_hash, scope, created_at) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        )
+            .bind(user_id)
+            .bind(name)
+            .bind(token_hash)
+            .bind(scope.as_str())
+            .bind(created_at)
+            .fetch_one(&mut *tx)
+            .await?;
+        let token = ApiToken {
+            id,
+            user_id,
+            name: name.to_string(),
+            scope,
+            created_at,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        insert_audit(&mut tx, AuditRecord::new("api_token", id, "create", actor).after(&token)).await?;
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    async fn list_tokens(&self, user_id: i64) -> Result<Vec<ApiToken>, AppError> {
+        sqlx::query(&format!("SELECT {} FROM api_tokens WHERE user_id = $1 ORDER BY id DESC", API_TOKEN_COLUMNS))
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(api_token_from_row)
+            .collect()
+    }
+
+    async fn revoke_token(&self, user_id: i64, token_id: i64, actor: &str) -> Result<ApiToken, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(&format!("SELECT {} FROM api_tokens WHERE id = $1 AND user_id = $2", API_TOKEN_COLUMNS))
+            .bind(token_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("API token with id {} not found", token_id)))?;
+        let before = api_token_from_row(&row)?;
+        if before.revoked_at.is_some() {
+            return Err(AppError::Conflict(format!("API token {} is already revoked", token_id)));
+        }
+
+        let revoked = ApiToken { revoked_at: Some(Utc::now().naive_utc()), ..before.clone() };
+        sqlx::query("UPDATE api_tokens SET revoked_at = $1 WHERE id = $2")
+            .bind(revoked.revoked_at)
+            .bind(token_id)
+            .execute(&mut *tx)
+            .await?;
+        insert_audit(&mut tx, AuditRecord::new("api_token", token_id, "revoke", actor).before(&before).after(&revoked)).await?;
+        tx.commit().await?;
+        Ok(revoked)
+    }
+
+    async fn authenticate_token(&self, token_hash: &str, now: NaiveDateTime) -> Result<Option<(User, ApiToken)>, AppError> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM api_tokens WHERE token_hash = $1 AND revoked_at IS NULL",
+            API_TOKEN_COLUMNS
+        ))
+            .bind(token_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let token = ApiToken { last_used_at: Some(now), ..api_token_from_row(&row)? };
+
+        sqlx::query("UPDATE api_tokens SET last_used_at = $1 WHERE id = $2")
+            .bind(now)
+            .bind(token.id)
+            .execute(&self.pool)
+            .await?;
+        let user = sqlx::query("SELECT id, username, role, created_at FROM users WHERE id = $1")
+            .bind(token.user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(Some((user_from_row(&user)?, token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::setup_test_pg_with_migrations;
+    use crate::repository::conformance;
+
+    // These run only when TEST_POSTGRES_URL is set, e.g. via with_temp_postgres.sh at the repo root.
+    #[tokio::test]
+    async fn test_pg_item_repository_contract() {
+        let Some(pool) = setup_test_pg_with_migrations().await else {
+            eprintln!("TEST_POSTGRES_URL not set; skipping PostgreSQL repository test");
+            return;
+        };
+        conformance::item_repository_contract(&PgItemRepository::new(pool)).await;
+    }
+
+    #[tokio::test]
+    async fn test_pg_graph_repository_contract() {
+        let Some(pool) = setup_test_pg_with_migrations().await else {
+            eprintln!("TEST_POSTGRES_URL not set; skipping PostgreSQL repository test");
+            return;
+        };
+        conformance::graph_repository_contract(&PgGraphRepository::new(pool)).await;
+    }
+
+    #[tokio::test]
+    async fn test_pg_graph_versioning_contract() {
+        let Some(pool) = setup_test_pg_with_migrations().await else {
+            eprintln!("TEST_POSTGRES_URL not set; skipping PostgreSQL repository test");
+            return;
+        };
+        conformance::graph_versioning_contract(&PgGraphRepository::new(pool)).await;
+    }
+
+    #[tokio::test]
+    async fn test_pg_fixture_contract() {
+        let Some(pool) = setup_test_pg_with_migrations().await else {
+            eprintln!("TEST_POSTGRES_URL not set; skipping PostgreSQL repository test");
+            return;
+        };
+        conformance::fixture_contract(&crate::repository::Repositories::postgres(pool)).await;
+    }
+
+    #[tokio::test]
+    async fn test_pg_audit_contract() {
+        let Some(pool) = setup_test_pg_with_migrations().await else {
+            eprintln!("TEST_POSTGRES_URL not set; skipping PostgreSQL repository test");
+            return;
+        };
+        conformance::audit_contract(&crate::repository::Repositories::postgres(pool)).await;
+    }
+
+    #[tokio::test]
+    async fn test_pg_user_repository_contract() {
+        let Some(pool) = setup_test_pg_with_migrations().await else {
+            eprintln!("TEST_POSTGRES_URL not set; skipping PostgreSQL repository test");
+            return;
+        };
+        let repo = PgUserRepository::new(pool);
+        conformance::user_repository_contract(&repo).await;
+        conformance::api_token_contract(&repo, &repo).await;
+    }
+}
@@ -2,10 +2,132 @@ use leptos::*;
 // No explicit serde import needed here as #[server] handles it.
 // database functions are now in crate::database
 // shared::Item is used for return types/params.
-#[cfg(feature = "ssr")] // Only compile the database interactions on the server
-use crate::database::{add_item_db, delete_item_db, get_all_items_db};
-use shared::Item;
+#[cfg(feature = "ssr")] // Only compile the repository lookups on the server
+use crate::repository::{AuditFilter, Repositories};
+#[cfg(feature = "ssr")]
+use crate::auth::Caller;
+use crate::errors::AppError;
+use shared::{ApiToken, AuditEntry, BookGrouping, ClusterView, FilterOptions, GraphData, GraphDiff, GraphFilter, GraphVersion, Item, NewApiToken, QueryResult, Role, TaxonomyBranch, TechnologyFingerprint, Timeline, TokenScope, User};
+#[cfg(feature = "ssr")]
+use shared::ChangeEvent;
 
+// Maximum item length, mirrored by the `maxlength` attribute in ItemForm.
+pub const ITEM_TEXT_MAX_LEN: usize = 100;
+
+// Validation shared by the AddItem server function and the ItemForm component,
+// so the client can show the same field-level message before a round trip.
+pub fn validate_item_text(text: &str) -> Result<(), AppError> {
+    if text.trim().is_empty() {
+        return Err(AppError::validation("text", "Item text cannot be empty."));
+    }
+    if text.len() > ITEM_TEXT_MAX_LEN {
+        return Err(AppError::validation(
+            "text",
+            format!("Item text too long (max {} chars).", ITEM_TEXT_MAX_LEN),
+        ));
+    }
+    Ok(())
+}
+
+pub const SNAPSHOT_LABEL_MAX_LEN: usize = 100;
+
+pub fn validate_snapshot_label(label: &str) -> Result<(), AppError> {
+    if label.trim().is_empty() {
+        return Err(AppError::validation("label", "Snapshot label cannot be empty."));
+    }
+    if label.len() > SNAPSHOT_LABEL_MAX_LEN {
+        return Err(AppError::validation(
+            "label",
+            format!("Snapshot label too long (max {} chars).", SNAPSHOT_LABEL_MAX_LEN),
+        ));
+    }
+    Ok(())
+}
+
+// Logs the error, sets the matching HTTP status on the response (when running
+// inside an axum request) and wraps it for the client.
+#[cfg(feature = "ssr")]
+fn app_error(fn_name: &str, err: AppError) -> ServerFnError<AppError> {
+    leptos::logging::error!("Server function {} failed: {}", fn_name, err);
+    if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+        response.set_status(err.status_code());
+    }
+    ServerFnError::WrappedServerError(err)
+}
+
+// The backend provides SQLite-backed repositories with `leptos_routes_with_context`;
+// tests provide their own (SQLite on an isolated pool, or in-memory) the same way.
+#[cfg(feature = "ssr")]
+fn repositories(fn_name: &str) -> Result<Repositories, ServerFnError<AppError>> {
+    use_context::<Repositories>().ok_or_else(|| {
+        app_error(fn_name, AppError::Storage("repositories missing from request context".to_string()))
+    })
+}
+
+// Tells open pages about a change (see `crate::live`). The backend provides the
+// feed; without one (tests, CLI) events are simply dropped.
+#[cfg(feature = "ssr")]
+fn publish(event: ChangeEvent) {
+    if let Some(feed) = use_context::<crate::live::ChangeFeed>() {
+        feed.publish(event);
+    }
+}
+
+// The session token from the cookie on the request being handled.
+// leptos_axum provides the request `Parts` to server functions.
+#[cfg(feature = "ssr")]
+fn session_token() -> Option<String> {
+    let parts = use_context::<http::request::Parts>()?;
+    crate::auth::request_session_token(&parts).map(str::to_string)
+}
+
+// Who is making the request (see `crate::auth::request_caller`).
+#[cfg(feature = "ssr")]
+async fn current_caller(repos: &Repositories) -> Result<Option<Caller>, AppError> {
+    match use_context::<http::request::Parts>() {
+        Some(parts) => crate::auth::request_caller(repos, &parts).await,
+        None => Ok(None),
+    }
+}
+
+// Role check for server functions that only read.
+#[cfg(feature = "ssr")]
+async fn require_role(fn_name: &str, repos: &Repositories, role: Role) -> Result<User, ServerFnError<AppError>> {
+    authorize(fn_name, repos, role).await.map(|caller| caller.user)
+}
+
+// Role check used at the top of every mutating server function; read-scoped
+// API tokens are refused. The returned user's name is what the audit log
+// records as the actor.
+#[cfg(feature = "ssr")]
+async fn require_write_role(fn_name: &str, repos: &Repositories, role: Role) -> Result<User, ServerFnError<AppError>> {
+    let caller = authorize(fn_name, repos, role).await?;
+    caller.require_write_role(role).map_err(|e| app_error(fn_name, e))?;
+    Ok(caller.user)
+}
+
+#[cfg(feature = "ssr")]
+async fn authorize(fn_name: &str, repos: &Repositories, role: Role) -> Result<Caller, ServerFnError<AppError>> {
+    let caller = current_caller(repos).await
+        .map_err(|e| app_error(fn_name, e))?
+        .ok_or_else(|| app_error(fn_name, AppError::Unauthorized("please log in".to_string())))?;
+    caller.require_role(role).map_err(|e| app_error(fn_name, e))?;
+    Ok(caller)
+}
+
+// Token management needs a browser session, so a leaked token cannot mint more.
+#[cfg(feature = "ssr")]
+async fn require_session(fn_name: &str, repos: &Repositories) -> Result<User, ServerFnError<AppError>> {
+    let caller = authorize(fn_name, repos, Role::Viewer).await?;
+    if caller.token_scope.is_some() {
+        return Err(app_error(fn_name, AppError::Forbidden("API tokens cannot manage API tokens".to_string())));
+    }
+    Ok(caller.user)
+}
+
+// Every server function has a fixed endpoint name (the fourth `#[server]`
+// argument), so scripts using API tokens can POST to stable URLs such as
+// `/api/get_items` instead of the default hashed ones.
 
 // If GetItemsParams was previously defined and used:
 // use serde::{Deserialize, Serialize};
@@ -15,19 +137,14 @@ use shared::Item;
 // The first argument to `#[server]` is the name of the generated struct for this server function.
 // If it takes no arguments (like a simple GET), you can pass () when calling it,
 // or define an empty params struct. For simplicity, let's assume no explicit params struct.
-#[server(GetItems, "/api")]
-pub async fn get_items() -> Result<Vec<Item>, ServerFnError> {
+#[server(GetItems, "/api", "Url", "get_items")]
+pub async fn get_items() -> Result<Vec<Item>, ServerFnError<AppError>> {
     // This part of the code will only be compiled and run on the server
     #[cfg(feature = "ssr")]
     {
         // log::debug!("Executing get_items_server_fn on server");
-        match get_all_items_db().await {
-            Ok(items) => Ok(items),
-            Err(db_error_string) => {
-                leptos::logging::error!("Server function GetItems failed: {}", db_error_string);
-                Err(ServerFnError::ServerError(db_error_string))
-            }
-        }
+        let repos = repositories("GetItems")?;
+        repos.items.list_items().await.map_err(|e| app_error("GetItems", e))
     }
 
     // This part is for the client-side stub, it won't be executed for real.
@@ -41,26 +158,19 @@ pub async fn get_items() -> Result<Vec<Item>, ServerFnError> {
     }
 }
 
-// AddItem takes `text: String` as a parameter.
+// AddItem takes `text: String` as a parameter and returns the stored item.
 // The `#[server]` macro will generate a struct `AddItem { text: String }`.
-#[server(AddItem, "/api")]
-pub async fn add_item(text: String) -> Result<(), ServerFnError> {
+#[server(AddItem, "/api", "Url", "add_item")]
+pub async fn add_item(text: String) -> Result<Item, ServerFnError<AppError>> {
     #[cfg(feature = "ssr")]
     {
         // log::debug!("Executing add_item_server_fn on server with text: {}", text);
-        if text.trim().is_empty() {
-            return Err(ServerFnError::Args("Item text cannot be empty.".into()));
-        }
-        if text.len() > 100 {
-            return Err(ServerFnError::Args("Item text too long (max 100 chars).".into()));
-        }
-        match add_item_db(text).await {
-            Ok(_) => Ok(()),
-            Err(db_error_string) => {
-                leptos::logging::error!("Server function AddItem failed: {}", db_error_string);
-                Err(ServerFnError::ServerError(db_error_string))
-            }
-        }
+        let repos = repositories("AddItem")?;
+        let user = require_write_role("AddItem", &repos, Role::Editor).await?;
+        validate_item_text(&text).map_err(|e| app_error("AddItem", e))?;
+        let item = repos.items.add_item(text, &user.username).await.map_err(|e| app_error("AddItem", e))?;
+        publish(ChangeEvent::ItemAdded { item: item.clone() });
+        Ok(item)
     }
     #[cfg(not(feature = "ssr"))]
     { 
@@ -70,18 +180,16 @@ pub async fn add_item(text: String) -> Result<(), ServerFnError> {
 
 // DeleteItem takes `id: i64` as a parameter.
 // The `#[server]` macro will generate a struct `DeleteItem { id: i64 }`.
-#[server(DeleteItem, "/api")]
-pub async fn delete_item(id: i64) -> Result<(), ServerFnError> {
+#[server(DeleteItem, "/api", "Url", "delete_item")]
+pub async fn delete_item(id: i64) -> Result<(), ServerFnError<AppError>> {
     #[cfg(feature = "ssr")]
     {
         // log::debug!("Executing delete_item_server_fn on server with id: {}", id);
-        match delete_item_db(id).await {
-            Ok(_) => Ok(()),
-            Err(db_error_string) => {
-                leptos::logging::error!("Server function DeleteItem failed: {}", db_error_string);
-                Err(ServerFnError::ServerError(db_error_string))
-            }
-        }
+        let repos = repositories("DeleteItem")?;
+        let user = require_write_role("DeleteItem", &repos, Role::Editor).await?;
+        repos.items.delete_item(id, &user.username).await.map_err(|e| app_error("DeleteItem", e))?;
+        publish(ChangeEvent::ItemDeleted { id });
+        Ok(())
     }
     #[cfg(not(feature = "ssr"))]
     { 
@@ -89,6 +197,436 @@ pub async fn delete_item(id: i64) -> Result<(), ServerFnError> {
     }
 }
 
+// GetGraph returns the whole tech graph dataset (books, technologies, links) from storage.
+#[server(GetGraph, "/api", "Url", "get_graph")]
+pub async fn get_graph() -> Result<GraphData, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetGraph")?;
+        repos.graph.load_graph().await.map_err(|e| app_error("GetGraph", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_graph should only run on the server")
+    }
+}
+
+// GetGraphClusters returns the graph aggregated for display: categories
+// collapsed into counted cluster nodes unless listed in `categories`, and the
+// books of the technologies listed in `technologies`. Nodes picked out by
+// `filter` are flagged as highlighted. With `until`, only the graph as of that
+// year is clustered (see `graph_timeline::graph_until`).
+#[server(GetGraphClusters, "/api", "Url", "get_graph_clusters")]
+pub async fn get_graph_clusters(
+    #[server(default)] categories: Vec<String>,
+    #[server(default)] technologies: Vec<i32>,
+    #[server(default)] filter: GraphFilter,
+    #[server(default)] until: Option<i32>,
+) -> Result<ClusterView, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetGraphClusters")?;
+        let mut data = repos.graph.load_graph().await.map_err(|e| app_error("GetGraphClusters", e))?;
+        if let Some(year) = until {
+            data = crate::graph_timeline::graph_until(&data, year);
+        }
+        Ok(crate::graph_clusters::cluster_graph(&data, &shared::ClusterExpansion { categories, technologies }, &filter))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_graph_clusters should only run on the server")
+    }
+}
+
+// GetFilterOptions returns the technologies, categories, subcategories,
+// authors and series the graph filter can be built from.
+#[server(GetFilterOptions, "/api", "Url", "get_filter_options")]
+pub async fn get_filter_options() -> Result<FilterOptions, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetFilterOptions")?;
+        let data = repos.graph.load_graph().await.map_err(|e| app_error("GetFilterOptions", e))?;
+        Ok(crate::graph_filter::filter_options(&data))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_filter_options should only run on the server")
+    }
+}
+
+// GetFilteredGraph returns only the part of the graph `filter` matches (see
+// `graph_filter::extract_subgraph`); an empty filter returns the whole graph.
+#[server(GetFilteredGraph, "/api", "Url", "get_filtered_graph")]
+pub async fn get_filtered_graph(#[server(default)] filter: GraphFilter) -> Result<GraphData, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetFilteredGraph")?;
+        let data = repos.graph.load_graph().await.map_err(|e| app_error("GetFilteredGraph", e))?;
+        Ok(crate::graph_filter::extract_subgraph(&data, &filter))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_filtered_graph should only run on the server")
+    }
+}
+
+// GetTechnologyFingerprints returns, per author or series, the technologies
+// its books use most (see `graph_authors::fingerprints`).
+#[server(GetTechnologyFingerprints, "/api", "Url", "get_technology_fingerprints")]
+pub async fn get_technology_fingerprints(
+    #[server(default)] group_by: BookGrouping,
+) -> Result<Vec<TechnologyFingerprint>, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetTechnologyFingerprints")?;
+        let data = repos.graph.load_graph().await.map_err(|e| app_error("GetTechnologyFingerprints", e))?;
+        Ok(crate::graph_authors::fingerprints(&data, group_by, crate::graph_authors::DEFAULT_FINGERPRINT_SIZE))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_technology_fingerprints should only run on the server")
+    }
+}
+
+// GetTechnologyTimeline returns the range of publication years and the year
+// each technology first appeared in a book, for the timeline on the graph page.
+#[server(GetTechnologyTimeline, "/api", "Url", "get_technology_timeline")]
+pub async fn get_technology_timeline() -> Result<Timeline, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetTechnologyTimeline")?;
+        let data = repos.graph.load_graph().await.map_err(|e| app_error("GetTechnologyTimeline", e))?;
+        Ok(crate::graph_timeline::timeline(&data))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_technology_timeline should only run on the server")
+    }
+}
+
+// GetTaxonomy returns the technology taxonomy as a tree with technology and
+// book counts rolled up at every level, for the navigator on the graph page.
+#[server(GetTaxonomy, "/api", "Url", "get_taxonomy")]
+pub async fn get_taxonomy() -> Result<Vec<TaxonomyBranch>, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetTaxonomy")?;
+        let data = repos.graph.load_graph().await.map_err(|e| app_error("GetTaxonomy", e))?;
+        Ok(crate::taxonomy::taxonomy_tree(&data))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_taxonomy should only run on the server")
+    }
+}
+
+// RunGraphQuery runs a pattern query (see `graph_query`) against the current
+// graph. Syntax and semantic errors come back as a validation error on "query".
+#[server(RunGraphQuery, "/api", "Url", "run_graph_query")]
+pub async fn run_graph_query(query: String) -> Result<QueryResult, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("RunGraphQuery")?;
+        let data = repos.graph.load_graph().await.map_err(|e| app_error("RunGraphQuery", e))?;
+        // Evaluation can take up to the query's step budget, so it runs off
+        // the async workers.
+        tokio::task::spawn_blocking(move || crate::graph_query::run_query(&data, &query))
+            .await
+            .map_err(|e| app_error("RunGraphQuery", AppError::Storage(format!("query evaluation failed: {}", e))))?
+            .map_err(|e| app_error("RunGraphQuery", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("run_graph_query should only run on the server")
+    }
+}
+
+// --- Graph versioning ---
+
+// ListGraphVersions returns all stored snapshots, newest first.
+#[server(ListGraphVersions, "/api", "Url", "list_graph_versions")]
+pub async fn list_graph_versions() -> Result<Vec<GraphVersion>, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("ListGraphVersions")?;
+        repos.graph.list_versions().await.map_err(|e| app_error("ListGraphVersions", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("list_graph_versions should only run on the server")
+    }
+}
+
+// CreateGraphSnapshot stores the current graph as a new immutable version.
+#[server(CreateGraphSnapshot, "/api", "Url", "create_graph_snapshot")]
+pub async fn create_graph_snapshot(label: String) -> Result<GraphVersion, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("CreateGraphSnapshot")?;
+        let user = require_write_role("CreateGraphSnapshot", &repos, Role::Editor).await?;
+        validate_snapshot_label(&label).map_err(|e| app_error("CreateGraphSnapshot", e))?;
+        let version = repos.graph
+            .create_version(label.trim().to_string(), &user.username)
+            .await
+            .map_err(|e| app_error("CreateGraphSnapshot", e))?;
+        publish(ChangeEvent::GraphVersionCreated { version: version.clone() });
+        Ok(version)
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("create_graph_snapshot should only run on the server")
+    }
+}
+
+// GetGraphVersion returns the dataset as stored in one version.
+#[server(GetGraphVersion, "/api", "Url", "get_graph_version")]
+pub async fn get_graph_version(id: i64) -> Result<GraphData, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("GetGraphVersion")?;
+        repos.graph.load_version(id).await.map_err(|e| app_error("GetGraphVersion", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_graph_version should only run on the server")
+    }
+}
+
+// DiffGraphVersions compares version `from` with version `to`, or with the
+// current graph when `to` is omitted.
+#[server(DiffGraphVersions, "/api", "Url", "diff_graph_versions")]
+pub async fn diff_graph_versions(from: i64, to: Option<i64>) -> Result<GraphDiff, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("DiffGraphVersions")?;
+        let before = repos.graph.load_version(from).await.map_err(|e| app_error("DiffGraphVersions", e))?;
+        let after = match to {
+            Some(id) => repos.graph.load_version(id).await,
+            None => repos.graph.load_graph().await,
+        }
+        .map_err(|e| app_error("DiffGraphVersions", e))?;
+        Ok(crate::graph_diff::diff_graphs(&before, &after))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("diff_graph_versions should only run on the server")
+    }
+}
+
+// RevertGraph makes `version_id` the current graph. The graph as it was just
+// before the revert is snapshotted in the same transaction and that version
+// is returned, so a revert can itself be undone.
+#[server(RevertGraph, "/api", "Url", "revert_graph")]
+pub async fn revert_graph(version_id: i64) -> Result<GraphVersion, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("RevertGraph")?;
+        let user = require_write_role("RevertGraph", &repos, Role::Admin).await?;
+        let backup = repos.graph
+            .revert_to_version(version_id, format!("Before revert to #{}", version_id), &user.username)
+            .await
+            .map_err(|e| app_error("RevertGraph", e))?;
+        publish(ChangeEvent::GraphVersionCreated { version: backup.clone() });
+        publish(ChangeEvent::GraphChanged);
+        Ok(backup)
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("revert_graph should only run on the server")
+    }
+}
+
+// --- Audit log ---
+
+pub const AUDIT_LOG_DEFAULT_LIMIT: i64 = 100;
+pub const AUDIT_LOG_MAX_LIMIT: i64 = 500;
+
+// GetAuditLog returns audit entries, newest first. Every filter is optional
+// (empty strings count as unset, which is what the activity page's selects send).
+// The log contains before/after values, so it needs a signed-in user.
+#[server(GetAuditLog, "/api", "Url", "get_audit_log")]
+pub async fn get_audit_log(
+    entity_type: Option<String>,
+    entity_id: Option<String>,
+    action: Option<String>,
+    actor: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<AuditEntry>, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let non_empty = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let filter = AuditFilter {
+            entity_type: non_empty(entity_type),
+            entity_id: non_empty(entity_id),
+            action: non_empty(action),
+            actor: non_empty(actor),
+            limit: limit.unwrap_or(AUDIT_LOG_DEFAULT_LIMIT).clamp(1, AUDIT_LOG_MAX_LIMIT),
+        };
+        let repos = repositories("GetAuditLog")?;
+        require_role("GetAuditLog", &repos, Role::Viewer).await?;
+        repos.audit.list_entries(&filter).await.map_err(|e| app_error("GetAuditLog", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("get_audit_log should only run on the server")
+    }
+}
+
+// --- Accounts and sessions ---
+
+// Login checks the password and, on success, starts a session: a random token
+// goes to the browser in an HttpOnly cookie and only its hash is stored.
+// Unknown usernames and wrong passwords get the same message.
+#[server(Login, "/api", "Url", "login")]
+pub async fn login(username: String, password: String) -> Result<User, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        use crate::auth::{dummy_password_hash, hash_token, new_session_token, session_cookie, session_ttl, verify_password};
+
+        let repos = repositories("Login")?;
+        let credentials = repos.users.find_credentials(username.trim()).await.map_err(|e| app_error("Login", e))?;
+        let verified = match credentials {
+            Some((user, hash)) => verify_password(&password, &hash).then_some(user),
+            None => {
+                verify_password(&password, dummy_password_hash());
+                None
+            }
+        };
+        let Some(user) = verified else {
+            return Err(app_error("Login", AppError::Unauthorized("invalid username or password".to_string())));
+        };
+
+        let token = new_session_token();
+        let expires_at = chrono::Utc::now().naive_utc() + session_ttl();
+        repos.users.create_session(user.id, &hash_token(&token), expires_at).await.map_err(|e| app_error("Login", e))?;
+        set_cookie(&session_cookie(&token));
+        leptos::logging::log!("[DB LOG] User {} logged in", user.username);
+        Ok(user)
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("login should only run on the server")
+    }
+}
+
+// Logout ends the current session (if any) and clears the cookie.
+#[server(Logout, "/api", "Url", "logout")]
+pub async fn logout() -> Result<(), ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("Logout")?;
+        if let Some(token) = session_token() {
+            repos.users.delete_session(&crate::auth::hash_token(&token)).await.map_err(|e| app_error("Logout", e))?;
+        }
+        set_cookie(&crate::auth::clear_session_cookie());
+        Ok(())
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("logout should only run on the server")
+    }
+}
+
+// CurrentUser returns the signed-in user, or None for anonymous visitors.
+#[server(CurrentUser, "/api", "Url", "current_user")]
+pub async fn current_user_info() -> Result<Option<User>, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("CurrentUser")?;
+        let caller = current_caller(&repos).await.map_err(|e| app_error("CurrentUser", e))?;
+        Ok(caller.map(|caller| caller.user))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("current_user_info should only run on the server")
+    }
+}
+
+// CreateUser adds an account. Admins only; the first admin is created by the
+// backend at startup from ADMIN_USERNAME / ADMIN_PASSWORD.
+#[server(CreateUser, "/api", "Url", "create_user")]
+pub async fn create_user(username: String, password: String, role: Role) -> Result<User, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        // Only admins get to learn the username and password rules.
+        let repos = repositories("CreateUser")?;
+        let admin = require_write_role("CreateUser", &repos, Role::Admin).await?;
+        let username = username.trim().to_string();
+        crate::auth::validate_username(&username).map_err(|e| app_error("CreateUser", e))?;
+        crate::auth::validate_password(&password).map_err(|e| app_error("CreateUser", e))?;
+        let hash = crate::auth::hash_password(&password).map_err(|e| app_error("CreateUser", e))?;
+        repos.users.create_user(&username, &hash, role, &admin.username).await.map_err(|e| app_error("CreateUser", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("create_user should only run on the server")
+    }
+}
+
+// --- API tokens ---
+
+// CreateApiToken issues a personal token for the signed-in user. The secret is
+// returned this once; only its hash is stored.
+#[server(CreateApiToken, "/api", "Url", "create_api_token")]
+pub async fn create_api_token(name: String, scope: TokenScope) -> Result<NewApiToken, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("CreateApiToken")?;
+        let user = require_session("CreateApiToken", &repos).await?;
+        crate::auth::validate_token_name(&name).map_err(|e| app_error("CreateApiToken", e))?;
+        let secret = crate::auth::new_api_token();
+        let token = repos.tokens
+            .create_token(user.id, name.trim(), &crate::auth::hash_token(&secret), scope, &user.username)
+            .await
+            .map_err(|e| app_error("CreateApiToken", e))?;
+        Ok(NewApiToken { token, secret })
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("create_api_token should only run on the server")
+    }
+}
+
+// ListApiTokens returns the signed-in user's tokens, newest first.
+#[server(ListApiTokens, "/api", "Url", "list_api_tokens")]
+pub async fn list_api_tokens() -> Result<Vec<ApiToken>, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("ListApiTokens")?;
+        let user = require_session("ListApiTokens", &repos).await?;
+        repos.tokens.list_tokens(user.id).await.map_err(|e| app_error("ListApiTokens", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("list_api_tokens should only run on the server")
+    }
+}
+
+// RevokeApiToken disables one of the signed-in user's tokens immediately.
+#[server(RevokeApiToken, "/api", "Url", "revoke_api_token")]
+pub async fn revoke_api_token(id: i64) -> Result<ApiToken, ServerFnError<AppError>> {
+    #[cfg(feature = "ssr")]
+    {
+        let repos = repositories("RevokeApiToken")?;
+        let user = require_session("RevokeApiToken", &repos).await?;
+        repos.tokens.revoke_token(user.id, id, &user.username).await.map_err(|e| app_error("RevokeApiToken", e))
+    }
+    #[cfg(not(feature = "ssr"))]
+    {
+        unreachable!("revoke_api_token should only run on the server")
+    }
+}
+
+// Appends a Set-Cookie header to the response of the current server function call.
+#[cfg(feature = "ssr")]
+fn set_cookie(cookie: &str) {
+    if let Some(response) = use_context::<leptos_axum::ResponseOptions>() {
+        if let Ok(value) = http::HeaderValue::from_str(cookie) {
+            response.append_header(http::header::SET_COOKIE, value);
+        }
+    }
+}
+
 // Ensure the server_fn_type_aliases macro is called to generate the necessary type aliases
 // This should be done once, typically in lib.rs or main.rs if it's a binary-only crate.
 // However, cargo-leptos handles this under the hood when it sees #[server] macros.
@@ -109,62 +647,377 @@ pub async fn delete_item(id: i64) -> Result<(), ServerFnError> {
 #[cfg(all(test, feature = "ssr"))] // Ensure ssr features are active for tests
 mod tests {
     use super::*; // To access AddItem, GetItems, DeleteItem server functions
+    use crate::database::setup_test_db_with_migrations;
+    use crate::dataset::bundled_graph;
     use leptos::create_runtime;
 
+    // Creates a user with `role`, starts a session for them and provides request
+    // parts carrying the session cookie, as leptos_axum does for real requests.
+    async fn sign_in(repos: &Repositories, username: &str, role: Role) -> User {
+        let user = repos.users.create_user(username, "unused-hash", role, "test").await.unwrap();
+        let token = crate::auth::new_session_token();
+        let expires_at = chrono::Utc::now().naive_utc() + crate::auth::session_ttl();
+        repos.users.create_session(user.id, &crate::auth::hash_token(&token), expires_at).await.unwrap();
+        provide_cookie(&format!("{}={}", crate::auth::SESSION_COOKIE, token));
+        user
+    }
+
+    // What the backend's bearer middleware does for an `Authorization: Bearer` request.
+    async fn use_api_token(repos: &Repositories, secret: &str) {
+        let principal = crate::auth::authenticate_api_token(repos, secret).await.unwrap().expect("token should be valid");
+        let mut parts = http::Request::builder().body(()).unwrap().into_parts().0;
+        parts.extensions.insert(principal);
+        provide_context(parts);
+    }
+
+    fn provide_cookie(cookie: &str) {
+        let request = http::Request::builder().header(http::header::COOKIE, cookie).body(()).unwrap();
+        provide_context(request.into_parts().0);
+    }
+
     #[tokio::test]
     async fn test_add_get_delete_item_server_fns() {
-        // For simplicity in testing, let's run each operation in isolation 
-        // to avoid the global pool initialization issue
-        
-        // Create unique environment variable per test operation
-        let test_id = std::process::id();
-        let env_var_name = format!("TEST_DATABASE_URL_FOR_SERVER_FN_TESTS_{}", test_id);
-        std::env::set_var(&env_var_name, "sqlite::memory:");
-        std::env::set_var("TEST_DATABASE_URL_FOR_SERVER_FN_TESTS", "sqlite::memory:");
-
         let rt = create_runtime(); // Leptos runtime for server functions
+        // Each test gets its own migrated in-memory database, injected the same
+        // way the backend does it for real requests.
+        let pool = setup_test_db_with_migrations().await.expect("test DB setup failed");
+        let repos = Repositories::sqlite(pool);
+        provide_context(repos.clone());
+        sign_in(&repos, "editor", Role::Editor).await;
 
-        // Test 1: Add item
         let item_text = "Test item from server_fn".to_string();
-        
-        // Since we can't easily share state between in-memory DBs across separate calls,
-        // let's at least verify that the server functions don't crash when called
-        // The actual integration will be verified by the database tests
-        match add_item(item_text.clone()).await {
-            Ok(_) => println!("add_item succeeded"),
-            Err(e) => {
-                // For in-memory DB, we expect this to fail due to missing table
-                // but we want to verify the server function at least executes
-                println!("add_item failed as expected for in-memory DB: {:?}", e);
-                assert!(e.to_string().contains("no such table") || e.to_string().contains("Failed to add item"));
-            }
-        }
+        add_item(item_text.clone()).await.expect("add_item failed");
 
-        // Test 2: Get items  
-        match get_items().await {
-            Ok(items) => {
-                println!("get_items succeeded with {} items", items.len());
-            },
-            Err(e) => {
-                // Expected failure for in-memory DB without migrations
-                println!("get_items failed as expected: {:?}", e);
-                assert!(e.to_string().contains("no such table") || e.to_string().contains("Failed to fetch items"));
-            }
-        }
+        let items = get_items().await.expect("get_items failed");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].text, item_text);
+
+        delete_item(items[0].id).await.expect("delete_item failed");
+        assert!(get_items().await.expect("get_items failed").is_empty());
+
+        // Deleting it again reports a typed NotFound rather than a generic failure.
+        let err = delete_item(items[0].id).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::NotFound(_))), "got {:?}", err);
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_get_graph_from_in_memory_repositories() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        repos.graph.replace_graph(bundled_graph().unwrap(), "test").await.unwrap();
+        provide_context(repos);
 
-        // Test 3: Delete item (will fail but we verify it doesn't crash)
-        match delete_item(1).await {
-            Ok(_) => println!("delete_item succeeded"),
-            Err(e) => {
-                println!("delete_item failed as expected: {:?}", e);
-                assert!(e.to_string().contains("no such table") || e.to_string().contains("Failed to delete item"));
+        let graph = get_graph().await.expect("get_graph failed");
+        assert_eq!(graph.books.len(), bundled_graph().unwrap().books.len());
+        assert!(get_items().await.unwrap().is_empty());
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_graph_clusters_and_queries_server_fns() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        repos.graph.replace_graph(bundled_graph().unwrap(), "test").await.unwrap();
+        provide_context(repos);
+
+        let view = get_graph_clusters(vec![], vec![], GraphFilter::default(), None).await.expect("get_graph_clusters failed");
+        assert!(view.nodes.iter().all(|n| n.kind == shared::ClusterNodeKind::Category));
+
+        let timeline = get_technology_timeline().await.expect("get_technology_timeline failed");
+        let first_year = timeline.years[0];
+        let early = get_graph_clusters(vec![], vec![], GraphFilter::default(), Some(first_year)).await.unwrap();
+        assert!(early.total_books >= 1 && early.total_books < view.total_books);
+
+        let taxonomy = get_taxonomy().await.expect("get_taxonomy failed");
+        assert_eq!(taxonomy.iter().map(|c| c.technology_count).sum::<usize>(), bundled_graph().unwrap().technologies.len());
+
+        let options = get_filter_options().await.expect("get_filter_options failed");
+        let filter = GraphFilter { categories: [options.categories[0].clone()].into(), ..Default::default() };
+        let view = get_graph_clusters(vec![], vec![], filter.clone(), None).await.unwrap();
+        assert_eq!(view.nodes.iter().filter(|n| n.highlighted).count(), 1);
+        let subgraph = get_filtered_graph(filter).await.expect("get_filtered_graph failed");
+        assert_eq!(subgraph.books.len(), view.matching_books);
+        assert!(subgraph.technologies.iter().all(|t| t.category == options.categories[0]));
+
+        let authors = get_technology_fingerprints(BookGrouping::Author).await.expect("get_technology_fingerprints failed");
+        assert_eq!(authors.len(), options.authors.len());
+        assert!(authors.windows(2).all(|w| w[0].books >= w[1].books));
+
+        let result = run_graph_query("MATCH (t:Technology {id: 101}) RETURN t".to_string()).await.unwrap();
+        assert_eq!(result.rows.len(), 1);
+        let err = run_graph_query("MATCH (t:Technology".to_string()).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(ref e) if e.field_message("query").is_some()), "got {:?}", err);
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_server_fns_without_repositories_report_storage_error() {
+        let rt = create_runtime();
+
+        let err = get_items().await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Storage(_))), "got {:?}", err);
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_add_item_rejects_invalid_text_with_field_error() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        provide_context(repos.clone());
+        sign_in(&repos, "editor", Role::Editor).await;
+
+        let err = add_item("   ".to_string()).await.unwrap_err();
+        match err {
+            ServerFnError::WrappedServerError(app_err) => {
+                assert_eq!(app_err.field_message("text"), Some("Item text cannot be empty."));
             }
+            other => panic!("expected a validation error, got {:?}", other),
         }
-        
+
+        let too_long = "x".repeat(ITEM_TEXT_MAX_LEN + 1);
+        let err = add_item(too_long).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ServerFnError::WrappedServerError(AppError::Validation { ref field, .. }) if field == "text"
+        ));
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_diff_and_revert_server_fns() {
+        let rt = create_runtime();
+        let pool = setup_test_db_with_migrations().await.expect("test DB setup failed");
+        let repos = Repositories::sqlite(pool);
+        repos.graph.replace_graph(bundled_graph().unwrap(), "test").await.unwrap();
+        provide_context(repos.clone());
+        sign_in(&repos, "admin", Role::Admin).await;
+
+        let v1 = create_graph_snapshot("  initial import ".to_string()).await.expect("snapshot failed");
+        assert_eq!(v1.label, "initial import");
+
+        let link = bundled_graph().unwrap().links[0].clone();
+        repos.graph.remove_link(link.clone(), "test").await.unwrap();
+
+        let diff = diff_graph_versions(v1.id, None).await.expect("diff failed");
+        assert_eq!(diff.removed_links, vec![link.clone()]);
+        assert!(diff.added_links.is_empty() && diff.changed_books.is_empty());
+
+        let backup = revert_graph(v1.id).await.expect("revert failed");
+        assert_eq!(get_graph().await.unwrap(), bundled_graph().unwrap());
+        // The pre-revert state is kept as its own version.
+        assert!(!get_graph_version(backup.id).await.unwrap().links.contains(&link));
+        let ids: Vec<i64> = list_graph_versions().await.unwrap().iter().map(|v| v.id).collect();
+        assert_eq!(ids, vec![backup.id, v1.id]);
+        assert_eq!(diff_graph_versions(v1.id, Some(backup.id)).await.unwrap().removed_links, vec![link]);
+
+        let err = revert_graph(9999).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::NotFound(_))), "got {:?}", err);
+        let err = create_graph_snapshot(String::new()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ServerFnError::WrappedServerError(AppError::Validation { ref field, .. }) if field == "label"
+        ));
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_get_audit_log_filters_server_fn_mutations() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        provide_context(repos.clone());
+        sign_in(&repos, "editor", Role::Editor).await;
+
+        add_item("first".to_string()).await.unwrap();
+        add_item("second".to_string()).await.unwrap();
+        let first = get_items().await.unwrap().into_iter().find(|i| i.text == "first").unwrap();
+        delete_item(first.id).await.unwrap();
+
+        // The user's own creation is logged too (by the "test" actor).
+        let all = get_audit_log(None, None, None, Some("editor".into()), None).await.unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(get_audit_log(None, None, None, None, None).await.unwrap().len(), 4);
+
+        // Empty strings from the UI are treated as "any".
+        let deletes = get_audit_log(Some("item".into()), Some(String::new()), Some("delete".into()), Some(" ".into()), None)
+            .await.unwrap();
+        assert_eq!(deletes.len(), 1);
+        assert_eq!(deletes[0].entity_id, first.id.to_string());
+
+        assert_eq!(get_audit_log(None, None, None, None, Some(0)).await.unwrap().len(), 1, "limit is clamped to at least 1");
+        assert!(get_audit_log(None, None, None, Some("nobody".into()), None).await.unwrap().is_empty());
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_mutations_enforce_roles() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        repos.graph.replace_graph(bundled_graph().unwrap(), "test").await.unwrap();
+        provide_context(repos.clone());
+
+        // Anonymous: reads work, writes and the audit log need a login.
+        assert!(get_graph().await.is_ok());
+        let err = add_item("hello".to_string()).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Unauthorized(_))), "got {:?}", err);
+        assert!(matches!(get_audit_log(None, None, None, None, None).await, Err(ServerFnError::WrappedServerError(AppError::Unauthorized(_)))));
+        assert_eq!(current_user_info().await.unwrap(), None);
+        // Invalid input is refused for the caller, not described to them.
+        let unauthorized = |result: Result<_, ServerFnError<AppError>>| matches!(result, Err(ServerFnError::WrappedServerError(AppError::Unauthorized(_))));
+        assert!(unauthorized(add_item("   ".to_string()).await.map(|_| ())));
+        assert!(unauthorized(create_graph_snapshot(String::new()).await.map(|_| ())));
+        assert!(unauthorized(create_user("x".into(), "short".into(), Role::Viewer).await.map(|_| ())));
+
+        // An unknown session cookie is the same as no cookie.
+        provide_cookie(&format!("{}=forged", crate::auth::SESSION_COOKIE));
+        assert!(matches!(delete_item(1).await, Err(ServerFnError::WrappedServerError(AppError::Unauthorized(_)))));
+
+        let viewer = sign_in(&repos, "viewer", Role::Viewer).await;
+        assert_eq!(current_user_info().await.unwrap(), Some(viewer));
+        assert!(get_audit_log(None, None, None, None, None).await.is_ok());
+        let err = add_item("hello".to_string()).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Forbidden(_))), "got {:?}", err);
+
+        sign_in(&repos, "editor", Role::Editor).await;
+        add_item("hello".to_string()).await.unwrap();
+        let version = create_graph_snapshot("by editor".to_string()).await.unwrap();
+        assert!(matches!(revert_graph(version.id).await, Err(ServerFnError::WrappedServerError(AppError::Forbidden(_)))));
+        assert!(matches!(
+            create_user("newbie".into(), "password123".into(), Role::Viewer).await,
+            Err(ServerFnError::WrappedServerError(AppError::Forbidden(_)))
+        ));
+        // Non-admins can't probe the password rules either.
+        assert!(matches!(
+            create_user("newbie".into(), "short".into(), Role::Viewer).await,
+            Err(ServerFnError::WrappedServerError(AppError::Forbidden(_)))
+        ));
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_login_logout_and_create_user() {
+        let rt = create_runtime();
+        let pool = setup_test_db_with_migrations().await.expect("test DB setup failed");
+        let repos = Repositories::sqlite(pool);
+        provide_context(repos.clone());
+        let hash = crate::auth::hash_password("admin-password").unwrap();
+        repos.users.create_user("root", &hash, Role::Admin, "test").await.unwrap();
+
+        let response = leptos_axum::ResponseOptions::default();
+        provide_context(response.clone());
+        let err = login("root".into(), "wrong-password".into()).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Unauthorized(_))), "got {:?}", err);
+        assert!(matches!(login("nobody".into(), "admin-password".into()).await, Err(ServerFnError::WrappedServerError(AppError::Unauthorized(_)))));
+
+        let user = login("root".into(), "admin-password".into()).await.expect("login failed");
+        assert_eq!(user.role, Role::Admin);
+        let cookie = response.0.read().headers.get(http::header::SET_COOKIE).unwrap().to_str().unwrap().to_string();
+        assert!(cookie.contains("HttpOnly"));
+
+        // The browser sends the cookie back on the next request.
+        provide_cookie(cookie.split(';').next().unwrap());
+        assert_eq!(current_user_info().await.unwrap(), Some(user));
+
+        let err = create_user("x".into(), "password123".into(), Role::Editor).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Validation { ref field, .. }) if field == "username"));
+        let editor = create_user(" writer ".into(), "password123".into(), Role::Editor).await.unwrap();
+        assert_eq!(editor.username, "writer");
+        assert!(matches!(
+            create_user("writer".into(), "password123".into(), Role::Viewer).await,
+            Err(ServerFnError::WrappedServerError(AppError::Conflict(_)))
+        ));
+        let created = get_audit_log(Some("user".into()), None, Some("create".into()), Some("root".into()), None).await.unwrap();
+        assert_eq!(created.len(), 1);
+        assert!(!created[0].after.as_deref().unwrap_or_default().contains("password"), "hashes stay out of the audit log");
+
+        logout().await.unwrap();
+        assert_eq!(current_user_info().await.unwrap(), None, "the session is gone server-side");
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_api_tokens_are_scoped_and_revocable() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        provide_context(repos.clone());
+        let editor = sign_in(&repos, "editor", Role::Editor).await;
+
+        let err = create_api_token(" ".into(), TokenScope::Read).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Validation { ref field, .. }) if field == "name"));
+        let read = create_api_token("dashboards".into(), TokenScope::Read).await.unwrap();
+        let write = create_api_token("ingest".into(), TokenScope::Write).await.unwrap();
+        assert_ne!(read.secret, write.secret);
+        assert_eq!(list_api_tokens().await.unwrap().len(), 2);
+
+        // A read token can read, but not mutate or mint more tokens.
+        use_api_token(&repos, &read.secret).await;
+        assert_eq!(current_user_info().await.unwrap(), Some(editor.clone()));
+        assert!(get_audit_log(None, None, None, None, None).await.is_ok());
+        let err = add_item("from a script".into()).await.unwrap_err();
+        assert!(matches!(err, ServerFnError::WrappedServerError(AppError::Forbidden(_))), "got {:?}", err);
+        assert!(matches!(list_api_tokens().await, Err(ServerFnError::WrappedServerError(AppError::Forbidden(_)))));
+
+        // A write token acts as its owner, within the owner's role.
+        use_api_token(&repos, &write.secret).await;
+        add_item("from a script".into()).await.unwrap();
+        let created = get_audit_log(Some("item".into()), None, Some("create".into()), None, None).await.unwrap();
+        assert_eq!(created[0].actor, "editor");
+        assert!(matches!(revert_graph(1).await, Err(ServerFnError::WrappedServerError(AppError::Forbidden(_)))));
+
+        sign_in(&repos, "other-editor", Role::Editor).await;
+        assert!(matches!(revoke_api_token(read.token.id).await, Err(ServerFnError::WrappedServerError(AppError::NotFound(_)))));
+        provide_cookie(""); // back to no session
+        assert!(matches!(revoke_api_token(read.token.id).await, Err(ServerFnError::WrappedServerError(AppError::Unauthorized(_)))));
+        // Without a session even a bad name is simply unauthorized.
+        assert!(matches!(create_api_token(" ".into(), TokenScope::Read).await, Err(ServerFnError::WrappedServerError(AppError::Unauthorized(_)))));
+
+        let tokens = repos.tokens.list_tokens(editor.id).await.unwrap();
+        assert!(tokens.iter().all(|t| t.last_used_at.is_some()), "both tokens were used");
+        repos.tokens.revoke_token(editor.id, read.token.id, "editor").await.unwrap();
+        assert!(crate::auth::authenticate_api_token(&repos, &read.secret).await.unwrap().is_none());
+
+        rt.dispose();
+    }
+
+    #[tokio::test]
+    async fn test_mutations_publish_change_events() {
+        let rt = create_runtime();
+        let repos = Repositories::in_memory();
+        repos.graph.replace_graph(bundled_graph().unwrap(), "test").await.unwrap();
+        let feed = crate::live::ChangeFeed::default();
+        let mut events = feed.subscribe();
+        provide_context(repos.clone());
+        provide_context(feed);
+        sign_in(&repos, "admin", Role::Admin).await;
+
+        add_item("live".to_string()).await.unwrap();
+        let item = match events.recv().await.unwrap() {
+            ChangeEvent::ItemAdded { item } => item,
+            other => panic!("expected ItemAdded, got {:?}", other),
+        };
+        assert_eq!(item.text, "live");
+        delete_item(item.id).await.unwrap();
+        assert_eq!(events.recv().await.unwrap(), ChangeEvent::ItemDeleted { id: item.id });
+
+        let version = create_graph_snapshot("v1".to_string()).await.unwrap();
+        assert_eq!(events.recv().await.unwrap(), ChangeEvent::GraphVersionCreated { version: version.clone() });
+        let backup = revert_graph(version.id).await.unwrap();
+        assert_eq!(events.recv().await.unwrap(), ChangeEvent::GraphVersionCreated { version: backup });
+        assert_eq!(events.recv().await.unwrap(), ChangeEvent::GraphChanged);
+
+        // Failed calls publish nothing.
+        assert!(delete_item(item.id).await.is_err());
+        assert!(events.try_recv().is_err());
+
         rt.dispose();
-        
-        // Clean up env vars
-        std::env::remove_var(&env_var_name);
-        std::env::remove_var("TEST_DATABASE_URL_FOR_SERVER_FN_TESTS");
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,390 @@
+// Synthetic book/technology datasets for load and UI testing. The bundled
+// data is far too small to show how the graph page behaves at 10k+ nodes, so
+// this builds graphs of any size with the same shape as the real thing: a few
+// technologies appear in most books, most in only a handful, and each book
+// links to a small, heavy-tailed number of technologies.
+//
+// Generation is deterministic for a given config (including the seed), so a
+// load test can be reproduced exactly.
+#![cfg(feature = "ssr")]
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use shared::{Book, BookTechLink, GraphData, LinkKind, Technology};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::errors::AppError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntheticConfig {
+    pub books: usize,
+    pub technologies: usize,
+    pub categories: usize,
+    // Mean number of technologies linked from each book.
+    pub links_per_book: f64,
+    // Power-law exponent of the degree distribution: P(k) ~ k^-exponent.
+    // Real citation-like networks sit between 2 and 3; lower is more skewed.
+    pub exponent: f64,
+    pub seed: u64,
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            books: 10_000,
+            technologies: 2_000,
+            categories: 8,
+            links_per_book: 4.0,
+            exponent: 2.5,
+            seed: 42,
+        }
+    }
+}
+
+impl SyntheticConfig {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.books == 0 {
+            return Err(AppError::validation("books", "must be at least 1"));
+        }
+        if self.technologies == 0 {
+            return Err(AppError::validation("technologies", "must be at least 1"));
+        }
+        // Ids are i32 in the schema; technologies are numbered after the books.
+        if self.books.saturating_add(self.technologies) > i32::MAX as usize {
+            return Err(AppError::validation("books", "books and technologies together must fit in an i32 id"));
+        }
+        if self.categories == 0 || self.categories > self.technologies {
+            return Err(AppError::validation("categories", "must be between 1 and the number of technologies"));
+        }
+        if !(self.links_per_book >= 1.0 && self.links_per_book <= self.technologies as f64) {
+            return Err(AppError::validation("links_per_book", "must be between 1 and the number of technologies"));
+        }
+        if !(self.exponent > 1.0 && self.exponent.is_finite()) {
+            return Err(AppError::validation("exponent", "must be greater than 1"));
+        }
+        Ok(())
+    }
+}
+
+// Category names; past the end of the list they are numbered.
+const CATEGORIES: [&str; 12] = [
+    "COMPUTING & INFORMATION",
+    "PHYSICS & SPACETIME",
+    "BIOLOGY & GENETICS",
+    "MATERIALS & ENGINEERING",
+    "ENERGY & PROPULSION",
+    "MIND & CONSCIOUSNESS",
+    "SOCIETY & ECONOMICS",
+    "WARFARE & DEFENCE",
+    "MEDICINE & LONGEVITY",
+    "COMMUNICATION & LANGUAGE",
+    "ECOLOGY & TERRAFORMING",
+    "TIME & CAUSALITY",
+];
+
+const SUBCATEGORIES: [&str; 20] = [
+    "Artificial Intelligence",
+    "Simulation",
+    "Networking",
+    "Propulsion",
+    "Field Manipulation",
+    "Fundamental Physics",
+    "Bio-engineering",
+    "Cybernetics",
+    "Nano-engineering",
+    "Macro-Engineering",
+    "Materials Science",
+    "Robotics",
+    "Longevity",
+    "Consciousness",
+    "Energy & Weapons",
+    "Planetary Engineering",
+    "Information Theory",
+    "Data Analysis",
+    "Computing Hardware",
+    "Alien Life",
+];
+
+const TECH_ADJECTIVES: [&str; 24] = [
+    "Quantum", "Neural", "Orbital", "Synthetic", "Recursive", "Stellar", "Adaptive", "Distributed", "Hyperspatial",
+    "Molecular", "Gravitic", "Sentient", "Autonomous", "Holographic", "Cryogenic", "Photonic", "Symbiotic", "Temporal",
+    "Ambient", "Swarm", "Memetic", "Tachyon", "Fractal", "Ansible",
+];
+
+const TECH_NOUNS: [&str; 24] = [
+    "Drive", "Lattice", "Uplink", "Habitat", "Engine", "Shield", "Archive", "Mesh", "Forge", "Gate", "Reactor", "Implant",
+    "Array", "Swarm", "Vault", "Loom", "Beacon", "Cortex", "Foundry", "Relay", "Sail", "Membrane", "Oracle", "Scaffold",
+];
+
+const TITLE_ADJECTIVES: [&str; 20] = [
+    "Silent", "Burning", "Last", "Hollow", "Distant", "Broken", "Endless", "Iron", "Glass", "Drowned", "Shattered",
+    "Crimson", "Forgotten", "Quiet", "Ancillary", "Infinite", "Red", "Cold", "Bright", "Fallen",
+];
+
+const TITLE_NOUNS: [&str; 24] = [
+    "Stars", "Orbit", "Machine", "Empire", "Garden", "Signal", "Frontier", "Children", "Sky", "Station", "Protocol",
+    "Horizon", "Tide", "Ship", "Crown", "Sun", "Engine", "Memory", "Storm", "Choir", "Labyrinth", "City", "Archive",
+    "Wake",
+];
+
+const FIRST_NAMES: [&str; 20] = [
+    "Ada", "Yuki", "Kofi", "Marta", "Ravi", "Elena", "Tomas", "Amara", "Lin", "Oskar", "Nadia", "Ibrahim", "Sofia",
+    "Kenji", "Priya", "Mateo", "Ingrid", "Zhao", "Leila", "Jonah",
+];
+
+const LAST_NAMES: [&str; 20] = [
+    "Okafor", "Lindqvist", "Nakamura", "Castellanos", "Reyes", "Achterberg", "Mbeki", "Varga", "Chen", "Novak",
+    "Haddad", "Sørensen", "Abara", "Kowalski", "Iyer", "Moreau", "Tanaka", "Eriksen", "Adeyemi", "Wolfe",
+];
+
+const SERIES_KINDS: [&str; 5] = ["Cycle", "Saga", "Trilogy", "Sequence", "Chronicles"];
+
+// Builds a dataset that passes `validate_graph`. Books are numbered from 1
+// and technologies after them, so the two id ranges never overlap (as in the
+// bundled data).
+pub fn generate_graph(config: &SyntheticConfig) -> Result<GraphData, AppError> {
+    config.validate()?;
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let technologies = generate_technologies(config, &mut rng);
+    let books = generate_books(config, &mut rng);
+
+    // Popularity follows a Zipf law over a random ranking of technologies:
+    // weight ~ rank^(-1/(exponent-1)), which gives their degrees a power-law
+    // tail with the configured exponent.
+    let zipf = 1.0 / (config.exponent - 1.0);
+    let mut ranking: Vec<usize> = (0..technologies.len()).collect();
+    ranking.shuffle(&mut rng);
+    let mut weights = vec![0.0; technologies.len()];
+    for (rank, &index) in ranking.iter().enumerate() {
+        weights[index] = ((rank + 1) as f64).powf(-zipf);
+    }
+    let popularity = WeightedIndex::new(&weights).expect("weights are positive and finite");
+
+    let mut links = Vec::new();
+    for book in &books {
+        let degree = book_degree(config, &mut rng);
+        for index in pick_distinct(&popularity, &weights, degree, &mut rng) {
+            let (kind, weight) = link_prominence(&mut rng);
+            links.push(BookTechLink { kind, weight, ..BookTechLink::new(book.id, technologies[index].id) });
+        }
+    }
+
+    // No stored taxonomy: the generated categories and subcategories are the whole hierarchy.
+    Ok(GraphData { books, technologies, links, taxonomy: Vec::new() })
+}
+
+// One link in five is the book's premise and one in five a mention; weights
+// fall in a band for each kind.
+fn link_prominence(rng: &mut StdRng) -> (LinkKind, i32) {
+    match rng.gen_range(0..5) {
+        0 => (LinkKind::CentralPremise, rng.gen_range(7..=BookTechLink::MAX_WEIGHT)),
+        1 => (LinkKind::Mentioned, rng.gen_range(1..=2)),
+        _ => (LinkKind::BackgroundDetail, rng.gen_range(3..=6)),
+    }
+}
+
+// A heavy-tailed number of links for one book: a Pareto draw with the same
+// exponent, scaled so the mean comes out near `links_per_book`, clamped to
+// [1, technologies].
+fn book_degree(config: &SyntheticConfig, rng: &mut StdRng) -> usize {
+    let shape = config.exponent - 1.0;
+    // With shape <= 1 the mean is infinite; fall back to the mean as the minimum.
+    let minimum = if shape > 1.0 { config.links_per_book * (shape - 1.0) / shape } else { config.links_per_book };
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let degree = (minimum * u.powf(-1.0 / shape)).round();
+    (degree as usize).clamp(1, config.technologies)
+}
+
+// Draws per index wanted before rejection sampling gives up. With an exponent
+// near 1 the tail weights are vanishingly small (or 0), so the last few
+// distinct indices may never come up.
+const REJECTION_DRAWS: usize = 8;
+
+// `count` distinct indices drawn by weight. Rejection is cheap while
+// `count` is small next to the population and the weights are not too
+// skewed; otherwise fall back to a single weighted pass over all of it.
+fn pick_distinct(popularity: &WeightedIndex<f64>, weights: &[f64], count: usize, rng: &mut StdRng) -> BTreeSet<usize> {
+    if count * 4 <= weights.len() {
+        let mut picked = BTreeSet::new();
+        for _ in 0..count * REJECTION_DRAWS {
+            picked.insert(popularity.sample(rng));
+            if picked.len() == count {
+                return picked;
+            }
+        }
+    }
+    let indices: Vec<usize> = (0..weights.len()).collect();
+    indices
+        .choose_multiple_weighted(rng, count, |&i| weights[i])
+        .expect("weights are non-negative and finite")
+        .copied()
+        .collect()
+}
+
+fn generate_technologies(config: &SyntheticConfig, rng: &mut StdRng) -> Vec<Technology> {
+    let categories: Vec<String> = (0..config.categories)
+        .map(|i| CATEGORIES.get(i).map(|c| c.to_string()).unwrap_or_else(|| format!("CATEGORY {}", i + 1)))
+        .collect();
+    // Each category draws on its own handful of subcategories.
+    let subcategories: Vec<Vec<&str>> = (0..config.categories)
+        .map(|_| SUBCATEGORIES.choose_multiple(rng, 3).copied().collect())
+        .collect();
+
+    let mut names = HashSet::new();
+    (0..config.technologies)
+        .map(|i| {
+            // Every category gets at least one technology; the rest are random.
+            let category = if i < config.categories { i } else { rng.gen_range(0..config.categories) };
+            let subcategory = *subcategories[category].choose(rng).expect("three subcategories per category");
+            let base = format!("{} {}", TECH_ADJECTIVES.choose(rng).unwrap(), TECH_NOUNS.choose(rng).unwrap());
+            let name = unique(&mut names, base, "Mk");
+            Technology {
+                id: (config.books + i + 1) as i32,
+                description: format!("Synthetic {} technology: a {} studied in {}.", subcategory.to_lowercase(), name.to_lowercase(), categories[category].to_lowercase()),
+                name,
+                category: categories[category].clone(),
+                subcategory: subcategory.to_string(),
+                taxonomy_id: None,
+            }
+        })
+        .collect()
+}
+
+fn generate_books(config: &SyntheticConfig, rng: &mut StdRng) -> Vec<Book> {
+    // About five books per author; prolific authors write far more than that,
+    // and about half of the authors write a series.
+    let mut author_names = HashSet::new();
+    let authors: Vec<(String, Option<String>)> = (0..config.books.div_ceil(5))
+        .map(|_| {
+            let initial = rng.gen_range(b'A'..=b'Z') as char;
+            let base = format!("{} {}. {}", FIRST_NAMES.choose(rng).unwrap(), initial, LAST_NAMES.choose(rng).unwrap());
+            let name = unique(&mut author_names, base, "");
+            let series = rng.gen_bool(0.5).then(|| {
+                format!("{} {} {}", TITLE_ADJECTIVES.choose(rng).unwrap(), TITLE_NOUNS.choose(rng).unwrap(), SERIES_KINDS.choose(rng).unwrap())
+            });
+            (name, series)
+        })
+        .collect();
+    let weights: Vec<f64> = (1..=authors.len()).map(|rank| 1.0 / rank as f64).collect();
+    let prolific = WeightedIndex::new(&weights).expect("weights are positive and finite");
+
+    (0..config.books)
+        .map(|i| {
+            let (author, series) = &authors[prolific.sample(rng)];
+            let title = match rng.gen_range(0..4) {
+                0 => format!("The {} {}", TITLE_ADJECTIVES.choose(rng).unwrap(), TITLE_NOUNS.choose(rng).unwrap()),
+                1 => format!("{} of the {}", TITLE_NOUNS.choose(rng).unwrap(), TITLE_NOUNS.choose(rng).unwrap()),
+                2 => format!("{} {}", TITLE_ADJECTIVES.choose(rng).unwrap(), TITLE_NOUNS.choose(rng).unwrap()),
+                _ => format!("The {}", TITLE_NOUNS.choose(rng).unwrap()),
+            };
+            Book {
+                id: (i + 1) as i32,
+                title,
+                author: author.clone(),
+                series: series.clone().filter(|_| rng.gen_bool(0.7)).unwrap_or_default(),
+                // Squared, so recent decades have more books, as in the real list.
+                year: Some(2025 - (rng.gen::<f64>().powi(2) * 100.0) as i32),
+            }
+        })
+        .collect()
+}
+
+// `base`, or `base` with the first free number appended ("Quantum Drive Mk 2").
+fn unique(taken: &mut HashSet<String>, base: String, marker: &str) -> String {
+    let mut name = base.clone();
+    let mut n = 1;
+    while !taken.insert(name.clone()) {
+        n += 1;
+        name = if marker.is_empty() { format!("{} {}", base, n) } else { format!("{} {} {}", base, marker, n) };
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::validate_graph;
+    use std::collections::HashMap;
+
+    fn small() -> SyntheticConfig {
+        SyntheticConfig { books: 2_000, technologies: 400, categories: 6, ..Default::default() }
+    }
+
+    #[test]
+    fn test_generated_graph_has_the_requested_shape() {
+        let config = small();
+        let data = generate_graph(&config).unwrap();
+        assert_eq!(data.books.len(), config.books);
+        assert_eq!(data.technologies.len(), config.technologies);
+        assert!(validate_graph(&data).is_empty(), "got {:?}", validate_graph(&data));
+
+        let categories: HashSet<&str> = data.technologies.iter().map(|t| t.category.as_str()).collect();
+        assert_eq!(categories.len(), config.categories);
+
+        let mean = data.links.len() as f64 / data.books.len() as f64;
+        assert!((2.0..8.0).contains(&mean), "mean links per book was {}", mean);
+    }
+
+    #[test]
+    fn test_technology_degrees_are_heavy_tailed() {
+        let data = generate_graph(&small()).unwrap();
+        let mut degree: HashMap<i32, usize> = HashMap::new();
+        for link in &data.links {
+            *degree.entry(link.tech_id).or_default() += 1;
+        }
+        let mut degrees: Vec<usize> = data.technologies.iter().map(|t| degree.get(&t.id).copied().unwrap_or(0)).collect();
+        degrees.sort_unstable();
+        let median = degrees[degrees.len() / 2].max(1);
+        let max = *degrees.last().unwrap();
+        assert!(max > 20 * median, "max degree {} vs median {}", max, median);
+    }
+
+    #[test]
+    fn test_generation_is_deterministic_per_seed() {
+        let config = SyntheticConfig { books: 300, technologies: 50, ..Default::default() };
+        assert_eq!(generate_graph(&config).unwrap(), generate_graph(&config).unwrap());
+        let reseeded = SyntheticConfig { seed: 7, ..config.clone() };
+        assert_ne!(generate_graph(&config).unwrap(), generate_graph(&reseeded).unwrap());
+    }
+
+    #[test]
+    fn test_tiny_graphs_link_every_book() {
+        // More links requested than rejection sampling handles well.
+        let config = SyntheticConfig { books: 5, technologies: 3, categories: 3, links_per_book: 3.0, ..Default::default() };
+        let data = generate_graph(&config).unwrap();
+        assert!(validate_graph(&data).is_empty());
+        let linked: HashSet<i32> = data.links.iter().map(|l| l.book_id).collect();
+        assert_eq!(linked.len(), 5);
+    }
+
+    #[test]
+    fn test_exponents_just_above_one_still_finish() {
+        // Tail weights underflow to 0 here, so rejection alone never finds
+        // enough distinct technologies for the long-tailed books.
+        for exponent in [1.001, 1.1, 1.3] {
+            let config = SyntheticConfig { books: 50, technologies: 400, exponent, ..Default::default() };
+            let data = generate_graph(&config).unwrap();
+            assert!(validate_graph(&data).is_empty(), "exponent {}", exponent);
+            let linked: HashSet<i32> = data.links.iter().map(|l| l.book_id).collect();
+            assert_eq!(linked.len(), 50, "exponent {}", exponent);
+        }
+    }
+
+    #[test]
+    fn test_invalid_configs_name_the_field() {
+        let cases = [
+            (SyntheticConfig { books: 0, ..Default::default() }, "books"),
+            (SyntheticConfig { categories: 0, ..Default::default() }, "categories"),
+            (SyntheticConfig { technologies: 4, categories: 5, links_per_book: 2.0, ..Default::default() }, "categories"),
+            (SyntheticConfig { links_per_book: 0.5, ..Default::default() }, "links_per_book"),
+            (SyntheticConfig { exponent: 1.0, ..Default::default() }, "exponent"),
+        ];
+        for (config, field) in cases {
+            let err = generate_graph(&config).unwrap_err();
+            assert!(err.field_message(field).is_some(), "{:?} gave {:?}", config, err);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+// The technology taxonomy: category → subcategory → any finer groupings →
+// technology. It is stored as `GraphData::taxonomy` nodes that technologies
+// point into with `taxonomy_id`; a technology without one (and so every
+// technology of a dataset from before the taxonomy) sits under its category
+// and subcategory. Builds the roll-up tree behind the navigator (GetTaxonomy)
+// and the subcategory layer of the tech graph page; compiles for the client too.
+
+use shared::{GraphData, TaxonomyBranch, TaxonomyLeaf, TaxonomyNode, Technology};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// Joins the names of a path in node ids, e.g. "x_PHYSICS & SPACETIME › Propulsion".
+pub const PATH_SEPARATOR: &str = " › ";
+
+// Graph node id for a taxonomy node. Categories keep the `c_` ids of the
+// category nodes; everything below them is `x_` and the path.
+pub fn taxonomy_node_id(path: &[String]) -> String {
+    match path {
+        [category] => format!("c_{}", category),
+        _ => format!("x_{}", path.join(PATH_SEPARATOR)),
+    }
+}
+
+// The path back out of a `taxonomy_node_id`.
+pub fn path_of_node_id(id: &str) -> Option<Vec<String>> {
+    if let Some(category) = id.strip_prefix("c_") {
+        return Some(vec![category.to_string()]);
+    }
+    id.strip_prefix("x_").map(|path| path.split(PATH_SEPARATOR).map(str::to_string).collect())
+}
+
+// Names from the root down to `id`, or None when the chain of parents is
+// broken or loops (which `dataset::validate_graph` reports).
+fn path_to(id: i32, nodes: &HashMap<i32, &TaxonomyNode>) -> Option<Vec<String>> {
+    let mut path = Vec::new();
+    let mut current = nodes.get(&id)?;
+    loop {
+        path.push(current.name.clone());
+        if path.len() > nodes.len() {
+            return None;
+        }
+        match current.parent_id {
+            None => break,
+            Some(parent) => current = nodes.get(&parent)?,
+        }
+    }
+    path.reverse();
+    Some(path)
+}
+
+// The path of every well-formed taxonomy node, by id.
+pub fn node_paths(taxonomy: &[TaxonomyNode]) -> HashMap<i32, Vec<String>> {
+    let nodes: HashMap<i32, &TaxonomyNode> = taxonomy.iter().map(|n| (n.id, n)).collect();
+    taxonomy.iter().filter_map(|n| Some((n.id, path_to(n.id, &nodes)?))).collect()
+}
+
+// The nodes ordered so every parent comes before its children, for inserting
+// them under the parent_id foreign key; broken chains go last.
+pub fn parents_first(taxonomy: &[TaxonomyNode]) -> Vec<&TaxonomyNode> {
+    let paths = node_paths(taxonomy);
+    let mut nodes: Vec<&TaxonomyNode> = taxonomy.iter().collect();
+    nodes.sort_by_key(|n| (paths.get(&n.id).map_or(usize::MAX, Vec::len), n.id));
+    nodes
+}
+
+// Where a technology sits: its taxonomy node's path, or else its category and
+// subcategory.
+pub fn technology_path(tech: &Technology, paths: &HashMap<i32, Vec<String>>) -> Vec<String> {
+    if let Some(path) = tech.taxonomy_id.and_then(|id| paths.get(&id)) {
+        return path.clone();
+    }
+    [&tech.category, &tech.subcategory].into_iter().filter(|s| !s.is_empty()).cloned().collect()
+}
+
+// Problems with the stored taxonomy, one line each, for `validate_graph`.
+pub fn taxonomy_problems(data: &GraphData) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut ids = HashSet::new();
+    for node in &data.taxonomy {
+        if !ids.insert(node.id) {
+            problems.push(format!("duplicate taxonomy node id {}", node.id));
+        }
+        if node.name.trim().is_empty() {
+            problems.push(format!("taxonomy node {} has an empty name", node.id));
+        }
+    }
+    let paths = node_paths(&data.taxonomy);
+    for node in &data.taxonomy {
+        if !paths.contains_key(&node.id) {
+            problems.push(format!("taxonomy node {} has an unknown parent or is its own ancestor", node.id));
+        }
+    }
+    for tech in &data.technologies {
+        let Some(id) = tech.taxonomy_id else { continue };
+        let Some(path) = paths.get(&id) else {
+            problems.push(format!("technology {} points at unknown taxonomy node {}", tech.id, id));
+            continue;
+        };
+        let agrees = path[0] == tech.category && (tech.subcategory.is_empty() || path.get(1) == Some(&tech.subcategory));
+        if !agrees {
+            problems.push(format!(
+                "technology {} is filed under {} but has category {:?} and subcategory {:?}",
+                tech.id, path.join(PATH_SEPARATOR), tech.category, tech.subcategory
+            ));
+        }
+    }
+    problems
+}
+
+#[derive(Default)]
+struct Builder {
+    children: BTreeMap<String, Builder>,
+    technologies: Vec<TaxonomyLeaf>,
+    tech_ids: Vec<i32>,
+}
+
+impl Builder {
+    fn at(&mut self, path: &[String]) -> &mut Builder {
+        path.iter().fold(self, |node, name| node.children.entry(name.clone()).or_default())
+    }
+
+    // The branch and the distinct books of its whole subtree.
+    fn finish(self, path: Vec<String>, books_of: &HashMap<i32, HashSet<i32>>) -> (TaxonomyBranch, HashSet<i32>) {
+        let mut books: HashSet<i32> = self.tech_ids.iter().filter_map(|id| books_of.get(id)).flatten().copied().collect();
+        let mut technology_count = self.technologies.len();
+        let children = self
+            .children
+            .into_iter()
+            .map(|(name, child)| {
+                let mut child_path = path.clone();
+                child_path.push(name);
+                let (branch, child_books) = child.finish(child_path, books_of);
+                technology_count += branch.technology_count;
+                books.extend(child_books);
+                branch
+            })
+            .collect();
+        let mut technologies = self.technologies;
+        technologies.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        let branch = TaxonomyBranch { path, children, technologies, technology_count, book_count: books.len() };
+        (branch, books)
+    }
+}
+
+// The taxonomy as a tree of categories, in name order, with every stored node
+// (even an empty one) and every technology that has a category.
+pub fn taxonomy_tree(data: &GraphData) -> Vec<TaxonomyBranch> {
+    let paths = node_paths(&data.taxonomy);
+    let mut books_of: HashMap<i32, HashSet<i32>> = HashMap::new();
+    for link in &data.links {
+        books_of.entry(link.tech_id).or_default().insert(link.book_id);
+    }
+
+    let mut root = Builder::default();
+    for path in paths.values() {
+        root.at(path);
+    }
+    for tech in &data.technologies {
+        let path = technology_path(tech, &paths);
+        if path.is_empty() {
+            continue;
+        }
+        let node = root.at(&path);
+        node.technologies.push(TaxonomyLeaf {
+            id: tech.id,
+            name: tech.name.clone(),
+            books: books_of.get(&tech.id).map_or(0, HashSet::len),
+        });
+        node.tech_ids.push(tech.id);
+    }
+    root.children.into_iter().map(|(name, node)| node.finish(vec![name], &books_of).0).collect()
+}
+
+// Ids of every technology in the branch and below it.
+pub fn branch_technologies(branch: &TaxonomyBranch) -> Vec<i32> {
+    let mut ids: Vec<i32> = branch.technologies.iter().map(|t| t.id).collect();
+    ids.extend(branch.children.iter().flat_map(branch_technologies));
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::bundled_graph;
+    use crate::test_data::{test_book, test_graph, test_tech};
+
+    fn tiny() -> GraphData {
+        // Tech 12 predates the taxonomy and is placed by its strings.
+        let technologies = [(10, Some(3)), (11, Some(2)), (12, None)]
+            .map(|(id, taxonomy_id)| Technology { category: "PHYSICS".into(), subcategory: "Propulsion".into(), taxonomy_id, ..test_tech(id) });
+        let mut data = test_graph([1, 2, 3].map(test_book), technologies, &[(1, 10), (2, 10), (2, 11), (3, 12)]);
+        data.taxonomy = [(1, None, "PHYSICS"), (2, Some(1), "Propulsion"), (3, Some(2), "Faster than light"), (4, Some(1), "Weapons")]
+            .map(|(id, parent_id, name)| TaxonomyNode { id, parent_id, name: name.into() })
+            .into();
+        data
+    }
+
+    #[test]
+    fn test_tree_rolls_up_counts() {
+        let tree = taxonomy_tree(&tiny());
+        assert_eq!(tree.len(), 1);
+        let physics = &tree[0];
+        assert_eq!((physics.name(), physics.technology_count, physics.book_count), ("PHYSICS", 3, 3));
+        assert_eq!(physics.children.iter().map(TaxonomyBranch::name).collect::<Vec<_>>(), ["Propulsion", "Weapons"]);
+
+        let propulsion = &physics.children[0];
+        assert_eq!(propulsion.technologies.iter().map(|t| (t.id, t.books)).collect::<Vec<_>>(), [(11, 1), (12, 1)]);
+        let ftl = &propulsion.children[0];
+        assert_eq!(ftl.path, ["PHYSICS", "Propulsion", "Faster than light"]);
+        assert_eq!((ftl.technology_count, ftl.book_count), (1, 2));
+        // Book 2 uses two technologies of Propulsion but counts once.
+        assert_eq!((propulsion.technology_count, propulsion.book_count), (3, 3));
+        // Stored nodes show up even when empty.
+        assert_eq!(physics.children[1].technology_count, 0);
+
+        let mut ids = branch_technologies(propulsion);
+        ids.sort();
+        assert_eq!(ids, [10, 11, 12]);
+    }
+
+    #[test]
+    fn test_node_ids_and_problems() {
+        let path = vec!["PHYSICS".to_string(), "Propulsion".to_string()];
+        assert_eq!(taxonomy_node_id(&path[..1]), "c_PHYSICS");
+        assert_eq!(path_of_node_id(&taxonomy_node_id(&path)), Some(path));
+        assert_eq!(path_of_node_id("t_101"), None);
+
+        assert!(taxonomy_problems(&tiny()).is_empty());
+        // A loop cuts off every node below it, and the technologies filed there.
+        let mut looped = tiny();
+        looped.taxonomy[0].parent_id = Some(3);
+        assert_eq!(taxonomy_problems(&looped).len(), 6, "got {:?}", taxonomy_problems(&looped));
+
+        let mut data = tiny();
+        data.technologies[1].subcategory = "Weapons".into();
+        data.technologies[2].taxonomy_id = Some(99);
+        let problems = taxonomy_problems(&data);
+        assert_eq!(problems.len(), 2, "got {:?}", problems);
+        assert!(problems[0].contains("filed under PHYSICS › Propulsion"));
+        assert!(problems[1].contains("unknown taxonomy node 99"));
+
+        let bundled = bundled_graph().unwrap();
+        assert!(taxonomy_problems(&bundled).is_empty());
+        assert!(taxonomy_tree(&bundled).iter().any(|category| category.children.iter().any(|sub| !sub.children.is_empty())),
+            "the bundled taxonomy goes deeper than subcategories");
+    }
+}
@@ -0,0 +1,33 @@
+// Builders for the small graphs unit tests are written against. Each fills in
+// plain defaults, so a test only spells out the fields it is about:
+// `Book { year: Some(1990), ..test_book(1) }`.
+
+use shared::{Book, BookTechLink, GraphData, Technology};
+
+// "Book <id>" by "A", standalone and undated.
+pub fn test_book(id: i32) -> Book {
+    Book { id, title: format!("Book {}", id), author: "A".into(), series: String::new(), year: None }
+}
+
+// "Tech <id>" in category "C", subcategory "S", outside any deeper taxonomy.
+pub fn test_tech(id: i32) -> Technology {
+    Technology {
+        id,
+        name: format!("Tech {}", id),
+        category: "C".into(),
+        subcategory: "S".into(),
+        description: String::new(),
+        taxonomy_id: None,
+    }
+}
+
+// A graph of the given books and technologies, linked by (book id, tech id)
+// pairs with default links, and without a stored taxonomy.
+pub fn test_graph(books: impl IntoIterator<Item = Book>, technologies: impl IntoIterator<Item = Technology>, links: &[(i32, i32)]) -> GraphData {
+    GraphData {
+        books: books.into_iter().collect(),
+        technologies: technologies.into_iter().collect(),
+        links: links.iter().map(|&(book_id, tech_id)| BookTechLink::new(book_id, tech_id)).collect(),
+        taxonomy: Vec::new(),
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDateTime;
+use std::collections::BTreeSet;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Item {
@@ -19,4 +20,546 @@ pub struct Item {
 //     pub text: String,
 //     #[serde(with = "chrono::serde::ts_milliseconds")] // Example for serde with chrono
 //     pub created_at: DateTime<Utc>,
-// } 
\ No newline at end of file
+// } 
+// --- Tech graph dataset ---
+// Mirrors the CSV files in `frontend/public/data/` and the graph tables
+// created by `0002_create_graph_tables.sql`.
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Book {
+    pub id: i32,
+    pub title: String,
+    pub author: String,
+    pub series: String, // Empty string for standalone novels
+    // Year of first publication, if known. Absent from older CSV files and
+    // graph snapshots, which still load.
+    #[serde(default)]
+    pub year: Option<i32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Technology {
+    pub id: i32,
+    pub name: String,
+    pub category: String,
+    pub subcategory: String,
+    pub description: String,
+    // The taxonomy node the technology sits under, when the taxonomy goes
+    // deeper than its category and subcategory (which it must still agree with).
+    #[serde(default)]
+    pub taxonomy_id: Option<i32>,
+}
+
+// A node of the technology taxonomy (table `taxonomy_nodes`, 0008 migration):
+// categories at the root, subcategories below them, then any finer groupings.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct TaxonomyNode {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub name: String,
+}
+
+// How a technology figures in a book, the most prominent first.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    // The book is built around it.
+    CentralPremise,
+    // Part of the setting without driving the story.
+    #[default]
+    BackgroundDetail,
+    // Named in passing.
+    Mentioned,
+}
+
+impl LinkKind {
+    pub const ALL: [LinkKind; 3] = [LinkKind::CentralPremise, LinkKind::BackgroundDetail, LinkKind::Mentioned];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::CentralPremise => "central_premise",
+            LinkKind::BackgroundDetail => "background_detail",
+            LinkKind::Mentioned => "mentioned",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<LinkKind> {
+        LinkKind::ALL.into_iter().find(|k| k.as_str() == s)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkKind::CentralPremise => "Central premise",
+            LinkKind::BackgroundDetail => "Background detail",
+            LinkKind::Mentioned => "Mentioned",
+        }
+    }
+}
+
+// A book featuring a technology (table `book_tech_links`). Links from before
+// kinds and weights (0009 migration) read as background details of weight 1,
+// so weighted counts over them are the plain counts they used to be.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct BookTechLink {
+    pub book_id: i32,
+    pub tech_id: i32,
+    #[serde(default)]
+    pub kind: LinkKind,
+    // Prominence, from 1 (barely there) to MAX_WEIGHT.
+    #[serde(default = "BookTechLink::default_weight")]
+    pub weight: i32,
+    // A quote or page reference backing the link.
+    #[serde(default)]
+    pub citation: Option<String>,
+}
+
+impl BookTechLink {
+    pub const MAX_WEIGHT: i32 = 10;
+
+    // A background detail of weight 1 without a citation.
+    pub fn new(book_id: i32, tech_id: i32) -> Self {
+        Self { book_id, tech_id, kind: LinkKind::default(), weight: Self::default_weight(), citation: None }
+    }
+
+    fn default_weight() -> i32 {
+        1
+    }
+
+    // What is wrong with the link's own fields, if anything.
+    pub fn problem(&self) -> Option<String> {
+        if !(1..=Self::MAX_WEIGHT).contains(&self.weight) {
+            return Some(format!("weight must be between 1 and {}, got {}", Self::MAX_WEIGHT, self.weight));
+        }
+        self.citation.as_ref().filter(|c| c.trim().is_empty()).map(|_| "citation must not be blank; leave it out instead".to_string())
+    }
+}
+
+// The whole dataset as loaded from storage, in id order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct GraphData {
+    pub books: Vec<Book>,
+    pub technologies: Vec<Technology>,
+    pub links: Vec<BookTechLink>,
+    // Empty for datasets from before the taxonomy, which is then read off the
+    // technologies' categories and subcategories.
+    #[serde(default)]
+    pub taxonomy: Vec<TaxonomyNode>,
+}
+
+// --- Graph versioning ---
+// Immutable snapshots of `GraphData` (table `graph_versions`, 0003 migration).
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GraphVersion {
+    pub id: i64,
+    pub label: String,
+    pub created_at: NaiveDateTime,
+}
+
+// A record present on both sides of a diff whose fields differ.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Changed<T> {
+    pub before: T,
+    pub after: T,
+}
+
+// What it takes to get from one graph version to another. Every list is sorted by id.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GraphDiff {
+    pub added_books: Vec<Book>,
+    pub removed_books: Vec<Book>,
+    pub changed_books: Vec<Changed<Book>>,
+    pub added_technologies: Vec<Technology>,
+    pub removed_technologies: Vec<Technology>,
+    pub changed_technologies: Vec<Changed<Technology>>,
+    pub added_links: Vec<BookTechLink>,
+    pub removed_links: Vec<BookTechLink>,
+    // Same book and technology, different kind, weight or citation.
+    #[serde(default)]
+    pub changed_links: Vec<Changed<BookTechLink>>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == GraphDiff::default()
+    }
+}
+
+// --- Graph filters ---
+// The tech graph page's filter: any number of technologies, categories,
+// subcategories, authors and series (see `frontend::graph_filter`).
+
+// Whether a book must meet every selected technology, category and
+// subcategory, or just one of them.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    #[default]
+    Any,
+    All,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct GraphFilter {
+    pub technologies: BTreeSet<i32>,
+    pub categories: BTreeSet<String>,
+    pub subcategories: BTreeSet<String>,
+    pub authors: BTreeSet<String>,
+    pub series: BTreeSet<String>,
+    pub mode: MatchMode,
+}
+
+impl GraphFilter {
+    // Nothing selected (the mode alone selects nothing).
+    pub fn is_empty(&self) -> bool {
+        self.technologies.is_empty()
+            && self.categories.is_empty()
+            && self.subcategories.is_empty()
+            && self.authors.is_empty()
+            && self.series.is_empty()
+    }
+}
+
+// The values the filter can be built from, each sorted and distinct.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FilterOptions {
+    // By name.
+    pub technologies: Vec<Technology>,
+    pub categories: Vec<String>,
+    pub subcategories: Vec<String>,
+    pub authors: Vec<String>,
+    // Standalone books (empty series) are not a series.
+    pub series: Vec<String>,
+}
+
+// --- Authors and series ---
+// Books grouped by who wrote them or the series they belong to: shown as
+// their own nodes on the tech graph, used to colour books, and summarised as
+// technology fingerprints.
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BookGrouping {
+    #[default]
+    Author,
+    Series,
+}
+
+impl BookGrouping {
+    // The book's author or series; standalone novels have an empty series.
+    pub fn key<'a>(&self, book: &'a Book) -> &'a str {
+        match self {
+            BookGrouping::Author => &book.author,
+            BookGrouping::Series => &book.series,
+        }
+    }
+}
+
+// How many of a group's books use a technology, and how prominently: the
+// summed weights of their links to it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TechnologyUse {
+    pub id: i32,
+    pub name: String,
+    pub category: String,
+    pub books: usize,
+    pub weight: i32,
+}
+
+// The technologies an author (or series) uses most, most prominent first.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TechnologyFingerprint {
+    pub name: String,
+    pub books: usize,
+    pub technologies: Vec<TechnologyUse>,
+    // Technologies used by any of the books, including those not listed.
+    pub distinct_technologies: usize,
+}
+
+// --- Timeline ---
+// Books by year of first publication: the graph as it stood at the end of a
+// year, and the year each technology first appeared in a book.
+
+// The earliest dated book featuring a technology.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FirstAppearance {
+    pub id: i32,
+    pub name: String,
+    pub category: String,
+    pub year: i32,
+    pub book_id: i32,
+    pub book_title: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Timeline {
+    // Every year a book was published in, oldest first.
+    pub years: Vec<i32>,
+    // Oldest first; technologies used only by undated books are left out.
+    pub first_appearances: Vec<FirstAppearance>,
+    pub undated_books: usize,
+}
+
+// --- Taxonomy ---
+// The category → subcategory → ... → technology hierarchy with roll-up counts,
+// for the tree navigator and the subcategory layer of the tech graph.
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaxonomyLeaf {
+    pub id: i32,
+    pub name: String,
+    pub books: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaxonomyBranch {
+    // Names from the category down to this node.
+    pub path: Vec<String>,
+    pub children: Vec<TaxonomyBranch>,
+    // Technologies placed directly under this node.
+    pub technologies: Vec<TaxonomyLeaf>,
+    // Rolled up over the whole subtree; books are counted once each.
+    pub technology_count: usize,
+    pub book_count: usize,
+}
+
+impl TaxonomyBranch {
+    pub fn name(&self) -> &str {
+        self.path.last().map(String::as_str).unwrap_or_default()
+    }
+}
+
+// --- Clustered graph ---
+// A level-of-detail view of the graph computed on the server, so large graphs
+// never reach the browser whole. Categories are collapsed into one node each
+// unless expanded; expanded technologies show (some of) their books.
+
+// Which clusters to open. Technologies can be expanded without their category.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClusterExpansion {
+    pub categories: Vec<String>,
+    pub technologies: Vec<i32>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterNodeKind {
+    Category,
+    Technology,
+    Book,
+    // The books of an expanded technology past the display limit.
+    MoreBooks,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClusterNode {
+    // `c_<category>`, `t_<id>`, `b_<id>` or `m_<tech id>`, as on the full graph page.
+    pub id: String,
+    pub kind: ClusterNodeKind,
+    pub label: String,
+    // The category of a category or technology node; empty otherwise.
+    pub category: String,
+    // Subcategory and description of a technology; author and series of a book.
+    pub detail: String,
+    // Technologies and distinct books inside (or, for a book, linked to) this node.
+    pub technologies: usize,
+    pub books: usize,
+    pub expanded: bool,
+    // Picked out by the filter: a technology it selects, a category holding
+    // one, or a book it matches.
+    pub highlighted: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterEdgeKind {
+    // A technology to its category, or an expanded technology to its "more" node.
+    Member,
+    // A visible book to a technology, or to the collapsed category holding it,
+    // weighing the summed weights of the links it stands for.
+    Link,
+    // Bundled: the number of hidden books linking to both ends.
+    Shared,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClusterEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: ClusterEdgeKind,
+    pub weight: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ClusterView {
+    pub nodes: Vec<ClusterNode>,
+    pub edges: Vec<ClusterEdge>,
+    pub total_books: usize,
+    pub total_technologies: usize,
+    pub total_links: usize,
+    // Books matching the filter, visible or not; 0 without a filter.
+    pub matching_books: usize,
+}
+
+// --- Graph queries ---
+// Results of a pattern query (see `frontend::graph_query`).
+
+// One matched node. Categories are nodes too, named after the category.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueryValue {
+    Book(Book),
+    Technology(Technology),
+    Category { name: String },
+}
+
+impl QueryValue {
+    // The node id used by the graph page: `b_<id>`, `t_<id>` or `c_<name>`.
+    pub fn node_id(&self) -> String {
+        match self {
+            QueryValue::Book(book) => format!("b_{}", book.id),
+            QueryValue::Technology(tech) => format!("t_{}", tech.id),
+            QueryValue::Category { name } => format!("c_{}", name),
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            QueryValue::Book(book) => &book.title,
+            QueryValue::Technology(tech) => &tech.name,
+            QueryValue::Category { name } => name,
+        }
+    }
+}
+
+// An edge of the matched subgraph, between two `QueryValue::node_id`s.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QueryEdge {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QueryResult {
+    // The RETURN variables, one column each.
+    pub columns: Vec<String>,
+    // Distinct rows, in match order.
+    pub rows: Vec<Vec<QueryValue>>,
+    // More rows matched than the LIMIT (or the server's cap) allowed.
+    pub truncated: bool,
+    // Every node and edge matched by the returned rows' patterns.
+    pub nodes: Vec<QueryValue>,
+    pub edges: Vec<QueryEdge>,
+}
+
+// --- Audit log ---
+// One row of `audit_log` (0004 migration). `before` / `after` are JSON
+// documents of the entity around the mutation; `None` for creates / deletes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub actor: String,
+    pub created_at: NaiveDateTime,
+}
+
+// --- Accounts ---
+
+// Roles are ordered: each one can do everything the previous one can.
+// viewer: read-only, editor: change items and the graph, admin: also manage users and revert the graph.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::Viewer, Role::Editor, Role::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|r| r.as_str() == s)
+    }
+}
+
+// An account as seen by the app. The password hash never leaves the repository layer.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+    pub role: Role,
+    pub created_at: NaiveDateTime,
+}
+
+// What an API token may do: `Read` only reaches server functions that change
+// nothing; `Write` may also mutate, within the owner's role.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    Read,
+    Write,
+}
+
+impl TokenScope {
+    pub const ALL: [TokenScope; 2] = [TokenScope::Read, TokenScope::Write];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::Read => "read",
+            TokenScope::Write => "write",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<TokenScope> {
+        TokenScope::ALL.into_iter().find(|scope| scope.as_str() == s)
+    }
+}
+
+// A personal API token as listed to its owner. The secret is shown once, at creation.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiToken {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub scope: TokenScope,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+// Returned by CreateApiToken: the stored token plus its secret value.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NewApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+// --- Live updates ---
+
+// Pushed to every open page over the server-sent events stream whenever data
+// changes, whoever made the change.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChangeEvent {
+    ItemAdded { item: Item },
+    ItemDeleted { id: i64 },
+    // Books, technologies or links changed (e.g. a revert).
+    GraphChanged,
+    GraphVersionCreated { version: GraphVersion },
+    // The subscriber may have missed events and should reload everything.
+    Resync,
+}
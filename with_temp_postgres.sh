@@ -0,0 +1,33 @@
+#!/bin/bash
+# Runs a command against a throwaway PostgreSQL cluster.
+#
+#   ./with_temp_postgres.sh cargo test -p frontend --no-default-features --features postgres
+#
+# initdb creates a cluster in a temp directory, pg_ctl starts it on a free-ish
+# port, TEST_POSTGRES_URL is exported for the command, and the cluster is
+# stopped and deleted afterwards (also on failure). Needs initdb/pg_ctl on PATH
+# and must not run as root (initdb refuses).
+
+set -euo pipefail
+
+if [ $# -eq 0 ]; then
+    echo "Usage: $0 <command> [args...]"
+    exit 2
+fi
+
+PGDATA_DIR="$(mktemp -d -t temp-postgres-XXXXXX)"
+PGPORT="${PGPORT:-$((20000 + RANDOM % 10000))}"
+
+cleanup() {
+    pg_ctl -D "$PGDATA_DIR" -m immediate stop >/dev/null 2>&1 || true
+    rm -rf "$PGDATA_DIR"
+}
+trap cleanup EXIT
+
+echo "🐘 Creating temporary PostgreSQL cluster in $PGDATA_DIR (port $PGPORT)..."
+initdb -D "$PGDATA_DIR" -U postgres --auth=trust >/dev/null
+pg_ctl -D "$PGDATA_DIR" -o "-p $PGPORT -k $PGDATA_DIR -c listen_addresses=127.0.0.1" -l "$PGDATA_DIR/server.log" -w start >/dev/null
+echo "✅ PostgreSQL is up"
+
+export TEST_POSTGRES_URL="postgres://postgres@127.0.0.1:$PGPORT/postgres"
+"$@"